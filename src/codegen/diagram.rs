@@ -0,0 +1,630 @@
+//! Entity-relationship diagram generation
+//!
+//! Renders entities (with their fields) and links (labelled with the link type
+//! and the forward/reverse route names) as Mermaid, Graphviz DOT or a
+//! standalone SVG. DOT and SVG can group entities into one cluster per storage
+//! backend.
+
+use std::collections::BTreeMap;
+
+use crate::codegen::introspect::{EntityMeta, LinkMeta, ProjectIntrospection};
+use crate::codegen::typescript::{split_generic_pair, strip_wrapper};
+
+/// Entity snake_case name → storage backend, used to cluster entities
+pub type Backends = BTreeMap<String, String>;
+
+// ── Public API ────────────────────────────────────────────────────────
+
+/// Render a Mermaid `erDiagram`.
+pub fn mermaid(project: &ProjectIntrospection) -> String {
+    let mut out = String::from("erDiagram\n");
+
+    for entity in &project.entities {
+        out.push_str(&format!("    {} {{\n", entity.pascal_name));
+        for attr in attributes(entity) {
+            let key = if attr.name == "id" { " PK" } else { "" };
+            let comment = if attr.optional { " \"optional\"" } else { "" };
+            out.push_str(&format!(
+                "        {} {}{}{}\n",
+                attr.ty, attr.name, key, comment
+            ));
+        }
+        out.push_str("    }\n");
+    }
+
+    for (link, source, target) in links(project) {
+        out.push_str(&format!(
+            "    {} }}o--o{{ {} : \"{}\"\n",
+            source.pascal_name,
+            target.pascal_name,
+            link_label(link).replace('"', "'")
+        ));
+    }
+
+    out
+}
+
+/// Render a Graphviz DOT digraph, optionally clustered by storage backend.
+pub fn dot(project: &ProjectIntrospection, backends: Option<&Backends>) -> String {
+    let mut out = String::from(
+        "digraph entities {\n  \
+         graph [rankdir=LR, fontname=\"Helvetica\"];\n  \
+         node [shape=plaintext, fontname=\"Helvetica\"];\n  \
+         edge [fontname=\"Helvetica\", fontsize=10];\n\n",
+    );
+
+    for (backend, entities) in groups(project, backends) {
+        let indent = if backend.is_some() { "    " } else { "  " };
+        if let Some(backend) = &backend {
+            out.push_str(&format!(
+                "  subgraph \"cluster_{}\" {{\n    label=\"{}\";\n    style=dashed;\n\n",
+                backend, backend
+            ));
+        }
+        for entity in entities {
+            out.push_str(&format!(
+                "{}{} [label=<\n{}  <table border=\"0\" cellborder=\"1\" cellspacing=\"0\" cellpadding=\"4\">\n\
+                 {}    <tr><td bgcolor=\"lightgrey\"><b>{}</b></td></tr>\n",
+                indent, entity.snake_name, indent, indent, entity.pascal_name
+            ));
+            for attr in attributes(entity) {
+                out.push_str(&format!(
+                    "{}    <tr><td align=\"left\">{}</td></tr>\n",
+                    indent,
+                    xml_escape(&attr.display())
+                ));
+            }
+            out.push_str(&format!("{}  </table>>];\n", indent));
+        }
+        if backend.is_some() {
+            out.push_str("  }\n");
+        }
+        out.push('\n');
+    }
+
+    for (link, source, target) in links(project) {
+        out.push_str(&format!(
+            "  {} -> {} [label=\"{}\"];\n",
+            source.snake_name,
+            target.snake_name,
+            link_label(link).replace('"', "\\\"")
+        ));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// Render a standalone SVG with a simple grid layout, optionally clustered by
+/// storage backend. Needs no Graphviz installation.
+pub fn svg(project: &ProjectIntrospection, backends: Option<&Backends>) -> String {
+    let layout = Layout::compute(project, backends);
+    let mut body = String::new();
+
+    for cluster in &layout.clusters {
+        body.push_str(&format!(
+            "  <rect x=\"{:.0}\" y=\"{:.0}\" width=\"{:.0}\" height=\"{:.0}\" fill=\"none\" stroke=\"#888\" stroke-dasharray=\"6 4\"/>\n  \
+             <text x=\"{:.0}\" y=\"{:.0}\" font-weight=\"bold\" fill=\"#555\">{}</text>\n",
+            cluster.x,
+            cluster.y,
+            cluster.width,
+            cluster.height,
+            cluster.x + PAD,
+            cluster.y + ROW_H,
+            xml_escape(&cluster.label)
+        ));
+    }
+
+    let mut pair_count: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for (link, source, target) in links(project) {
+        let (Some(from), Some(to)) = (
+            layout.boxes.get(&source.snake_name),
+            layout.boxes.get(&target.snake_name),
+        ) else {
+            continue;
+        };
+        let nth = pair_count
+            .entry((source.snake_name.clone(), target.snake_name.clone()))
+            .or_insert(0);
+        let label_offset = *nth as f64 * ROW_H;
+        *nth += 1;
+        let label = xml_escape(&link_label(link));
+
+        if source.snake_name == target.snake_name {
+            // Self-link: loop on the right edge of the box
+            let x = from.x + from.width;
+            let y = from.y + HEADER_H / 2.0;
+            body.push_str(&format!(
+                "  <path d=\"M {x:.0} {y:.0} C {cx:.0} {y1:.0}, {cx:.0} {y2:.0}, {x:.0} {y3:.0}\" fill=\"none\" stroke=\"#333\" marker-end=\"url(#arrow)\"/>\n  \
+                 <text x=\"{tx:.0}\" y=\"{ty:.0}\" class=\"label\">{label}</text>\n",
+                cx = x + layout.gap_x / 2.0,
+                y1 = y - ROW_H,
+                y2 = y + 2.0 * ROW_H,
+                y3 = y + ROW_H,
+                tx = x + layout.gap_x / 2.0 + 4.0,
+                ty = y + ROW_H / 2.0 + label_offset,
+            ));
+            continue;
+        }
+
+        let (x1, y1) = from.border_towards(to.center());
+        let (x2, y2) = to.border_towards(from.center());
+        body.push_str(&format!(
+            "  <line x1=\"{:.0}\" y1=\"{:.0}\" x2=\"{:.0}\" y2=\"{:.0}\" stroke=\"#333\" marker-end=\"url(#arrow)\"/>\n  \
+             <text x=\"{:.0}\" y=\"{:.0}\" class=\"label\" text-anchor=\"middle\">{}</text>\n",
+            x1,
+            y1,
+            x2,
+            y2,
+            (x1 + x2) / 2.0,
+            (y1 + y2) / 2.0 - 4.0 + label_offset,
+            label
+        ));
+    }
+
+    for entity in &project.entities {
+        let Some(b) = layout.boxes.get(&entity.snake_name) else {
+            continue;
+        };
+        body.push_str(&format!(
+            "  <rect x=\"{:.0}\" y=\"{:.0}\" width=\"{:.0}\" height=\"{:.0}\" fill=\"#fff\" stroke=\"#333\"/>\n  \
+             <rect x=\"{:.0}\" y=\"{:.0}\" width=\"{:.0}\" height=\"{:.0}\" fill=\"#ddd\" stroke=\"#333\"/>\n  \
+             <text x=\"{:.0}\" y=\"{:.0}\" font-weight=\"bold\" text-anchor=\"middle\">{}</text>\n",
+            b.x,
+            b.y,
+            b.width,
+            b.height,
+            b.x,
+            b.y,
+            b.width,
+            HEADER_H,
+            b.x + b.width / 2.0,
+            b.y + HEADER_H - 7.0,
+            xml_escape(&entity.pascal_name)
+        ));
+        for (i, attr) in attributes(entity).iter().enumerate() {
+            body.push_str(&format!(
+                "  <text x=\"{:.0}\" y=\"{:.0}\">{}</text>\n",
+                b.x + PAD,
+                b.y + HEADER_H + ROW_H * (i as f64 + 1.0) - 4.0,
+                xml_escape(&attr.display())
+            ));
+        }
+    }
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\" \
+         font-family=\"monospace\" font-size=\"12\">\n  \
+         <defs>\n    \
+         <marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\">\n      \
+         <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#333\"/>\n    \
+         </marker>\n    \
+         <style>.label {{ font-size: 10px; fill: #333; paint-order: stroke; stroke: #fff; stroke-width: 3px; }}</style>\n  \
+         </defs>\n  \
+         <rect width=\"100%\" height=\"100%\" fill=\"#fff\"/>\n{body}</svg>\n",
+        w = layout.width,
+        h = layout.height,
+        body = body
+    )
+}
+
+// ── Model ─────────────────────────────────────────────────────────────
+
+/// A displayed entity attribute
+struct Attribute {
+    name: String,
+    ty: String,
+    optional: bool,
+}
+
+impl Attribute {
+    fn display(&self) -> String {
+        format!(
+            "{}: {}{}",
+            self.name,
+            self.ty,
+            if self.optional { "?" } else { "" }
+        )
+    }
+}
+
+/// `id`, custom fields, then the timestamps every entity carries
+fn attributes(entity: &EntityMeta) -> Vec<Attribute> {
+    let builtin = |name: &str, ty: &str| Attribute {
+        name: name.to_string(),
+        ty: ty.to_string(),
+        optional: false,
+    };
+    let mut attrs = vec![builtin("id", "uuid")];
+    for field in &entity.fields {
+        if ["id", "created_at", "updated_at"].contains(&field.name.as_str()) {
+            continue;
+        }
+        let trimmed = field.rust_type.trim();
+        let (inner, optional) = match strip_wrapper(trimmed, "Option<", ">") {
+            Some(inner) => (inner, true),
+            None => (trimmed, false),
+        };
+        attrs.push(Attribute {
+            name: field.name.clone(),
+            ty: short_type(inner),
+            optional,
+        });
+    }
+    attrs.push(builtin("created_at", "datetime"));
+    attrs.push(builtin("updated_at", "datetime"));
+    attrs
+}
+
+/// Compact, diagram-friendly type name (valid as a Mermaid attribute type)
+pub fn short_type(rust_type: &str) -> String {
+    let trimmed = rust_type.trim();
+
+    if let Some(inner) = strip_wrapper(trimmed, "Option<", ">") {
+        return short_type(inner);
+    }
+    if let Some(inner) = strip_wrapper(trimmed, "Vec<", ">") {
+        return format!("{}[]", short_type(inner));
+    }
+    if let Some(inner) = strip_wrapper(trimmed, "HashMap<", ">")
+        && split_generic_pair(inner).is_some()
+    {
+        return "map".to_string();
+    }
+
+    match trimmed {
+        "String" | "&str" => "string".to_string(),
+        "Uuid" | "uuid::Uuid" => "uuid".to_string(),
+        "f32" | "f64" => "float".to_string(),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "u8" | "u16" | "u32" | "u64" | "u128" | "isize"
+        | "usize" => "int".to_string(),
+        "bool" => "bool".to_string(),
+        "DateTime<Utc>" | "chrono::DateTime<Utc>" | "NaiveDateTime" => "datetime".to_string(),
+        "Value" | "serde_json::Value" | "JsonValue" => "json".to_string(),
+        other => {
+            let name = other.rsplit("::").next().unwrap_or(other);
+            name.chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect()
+        }
+    }
+}
+
+fn link_label(link: &LinkMeta) -> String {
+    format!(
+        "{} ({} / {})",
+        link.link_type, link.forward_route, link.reverse_route
+    )
+}
+
+/// Links whose two ends are entities of the project
+fn links(project: &ProjectIntrospection) -> Vec<(&LinkMeta, &EntityMeta, &EntityMeta)> {
+    let find = |name: &str| project.entities.iter().find(|e| e.snake_name == name);
+    project
+        .links
+        .iter()
+        .filter_map(|link| Some((link, find(&link.source)?, find(&link.target)?)))
+        .collect()
+}
+
+/// Entities grouped by backend (sorted), or a single unnamed group
+fn groups<'a>(
+    project: &'a ProjectIntrospection,
+    backends: Option<&Backends>,
+) -> Vec<(Option<String>, Vec<&'a EntityMeta>)> {
+    let Some(backends) = backends else {
+        return vec![(None, project.entities.iter().collect())];
+    };
+    let mut grouped: BTreeMap<String, Vec<&EntityMeta>> = BTreeMap::new();
+    for entity in &project.entities {
+        let backend = backends
+            .get(&entity.snake_name)
+            .cloned()
+            .unwrap_or_else(|| "in-memory".to_string());
+        grouped.entry(backend).or_default().push(entity);
+    }
+    grouped
+        .into_iter()
+        .map(|(backend, entities)| (Some(backend), entities))
+        .collect()
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ── SVG layout ────────────────────────────────────────────────────────
+
+const CHAR_W: f64 = 7.2;
+const LABEL_CHAR_W: f64 = 6.0;
+const ROW_H: f64 = 18.0;
+const HEADER_H: f64 = 24.0;
+const PAD: f64 = 10.0;
+const MIN_GAP_X: f64 = 80.0;
+const GAP_Y: f64 = 60.0;
+const MARGIN: f64 = 20.0;
+
+struct Rect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Rect {
+    fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Point where the segment from the center towards `(tx, ty)` leaves the box
+    fn border_towards(&self, (tx, ty): (f64, f64)) -> (f64, f64) {
+        let (cx, cy) = self.center();
+        let (dx, dy) = (tx - cx, ty - cy);
+        if dx == 0.0 && dy == 0.0 {
+            return (cx, cy);
+        }
+        let sx = if dx != 0.0 {
+            (self.width / 2.0) / dx.abs()
+        } else {
+            f64::INFINITY
+        };
+        let sy = if dy != 0.0 {
+            (self.height / 2.0) / dy.abs()
+        } else {
+            f64::INFINITY
+        };
+        let s = sx.min(sy);
+        (cx + dx * s, cy + dy * s)
+    }
+}
+
+struct Cluster {
+    label: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+struct Layout {
+    boxes: BTreeMap<String, Rect>,
+    clusters: Vec<Cluster>,
+    /// Horizontal gap between boxes, wide enough for the longest link label
+    gap_x: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Layout {
+    /// Lay entities out on a grid, one band of rows per cluster
+    fn compute(project: &ProjectIntrospection, backends: Option<&Backends>) -> Self {
+        let columns = (project.entities.len() as f64).sqrt().ceil().max(1.0) as usize;
+        let gap_x = links(project)
+            .iter()
+            .map(|(link, _, _)| link_label(link).chars().count() as f64 * LABEL_CHAR_W + 2.0 * PAD)
+            .fold(MIN_GAP_X, f64::max);
+        let mut boxes = BTreeMap::new();
+        let mut clusters = Vec::new();
+        let mut width: f64 = 0.0;
+        let mut y = MARGIN;
+
+        for (backend, entities) in groups(project, backends) {
+            let inset = if backend.is_some() { PAD } else { 0.0 };
+            let band_top = y;
+            if backend.is_some() {
+                y += ROW_H + PAD;
+            }
+            let mut band_right: f64 = MARGIN;
+
+            for row in entities.chunks(columns) {
+                let mut x = MARGIN + inset;
+                let mut row_height: f64 = 0.0;
+                for entity in row {
+                    let attrs = attributes(entity);
+                    let longest = attrs
+                        .iter()
+                        .map(|a| a.display().chars().count())
+                        .chain(std::iter::once(entity.pascal_name.chars().count()))
+                        .max()
+                        .unwrap_or(0);
+                    let rect = Rect {
+                        x,
+                        y,
+                        width: longest as f64 * CHAR_W + 2.0 * PAD,
+                        height: HEADER_H + attrs.len() as f64 * ROW_H + PAD / 2.0,
+                    };
+                    x += rect.width + gap_x;
+                    band_right = band_right.max(rect.x + rect.width);
+                    row_height = row_height.max(rect.height);
+                    boxes.insert(entity.snake_name.clone(), rect);
+                }
+                y += row_height + GAP_Y;
+            }
+
+            if let Some(label) = backend {
+                let bottom = y - GAP_Y + PAD;
+                clusters.push(Cluster {
+                    label,
+                    x: MARGIN,
+                    y: band_top,
+                    width: band_right + PAD - MARGIN,
+                    height: bottom - band_top,
+                });
+                y = bottom + GAP_Y / 2.0;
+            }
+            width = width.max(band_right + inset);
+        }
+
+        Layout {
+            boxes,
+            clusters,
+            gap_x,
+            // Room for self-link loops and their labels on the right
+            width: width + gap_x + MARGIN,
+            height: y - GAP_Y / 2.0 + MARGIN,
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::introspect::FieldMeta;
+
+    fn field(name: &str, rust_type: &str) -> FieldMeta {
+        FieldMeta {
+            name: name.to_string(),
+            rust_type: rust_type.to_string(),
+        }
+    }
+
+    fn entity(pascal: &str, snake: &str, fields: Vec<FieldMeta>) -> EntityMeta {
+        EntityMeta {
+            pascal_name: pascal.to_string(),
+            snake_name: snake.to_string(),
+            plural: format!("{}s", snake),
+            indexed_fields: vec![],
+            fields,
+            routes: vec![],
+        }
+    }
+
+    fn shop() -> ProjectIntrospection {
+        ProjectIntrospection {
+            entities: vec![
+                entity(
+                    "Product",
+                    "product",
+                    vec![
+                        field("name", "String"),
+                        field("price", "f64"),
+                        field("tags", "Vec<String>"),
+                        field("description", "Option<String>"),
+                    ],
+                ),
+                entity("Review", "review", vec![field("rating", "i32")]),
+            ],
+            links: vec![LinkMeta {
+                link_type: "has_review".to_string(),
+                source: "product".to_string(),
+                target: "review".to_string(),
+                forward_route: "reviews".to_string(),
+                reverse_route: "product".to_string(),
+            }],
+        }
+    }
+
+    fn backends() -> Backends {
+        BTreeMap::from([
+            ("product".to_string(), "postgres".to_string()),
+            ("review".to_string(), "mongodb".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_short_type() {
+        assert_eq!(short_type("String"), "string");
+        assert_eq!(short_type("f64"), "float");
+        assert_eq!(short_type("u64"), "int");
+        assert_eq!(short_type("Vec<String>"), "string[]");
+        assert_eq!(short_type("Option<Uuid>"), "uuid");
+        assert_eq!(short_type("HashMap<String, f64>"), "map");
+        assert_eq!(short_type("serde_json::Value"), "json");
+        assert_eq!(short_type("crate::money::Money"), "Money");
+    }
+
+    #[test]
+    fn test_mermaid() {
+        let out = mermaid(&shop());
+        assert!(out.starts_with("erDiagram\n"));
+        assert!(out.contains("    Product {\n        uuid id PK\n        string name\n"));
+        assert!(out.contains("        string[] tags\n"));
+        assert!(out.contains("        string description \"optional\"\n"));
+        assert!(out.contains("        datetime updated_at\n"));
+        assert!(out.contains("    Product }o--o{ Review : \"has_review (reviews / product)\"\n"));
+    }
+
+    #[test]
+    fn test_links_to_unknown_entities_are_skipped() {
+        let mut project = shop();
+        project.links[0].target = "user".to_string();
+        assert!(!mermaid(&project).contains("}o--o{"));
+        assert!(!dot(&project, None).contains("->"));
+    }
+
+    #[test]
+    fn test_dot_without_clusters() {
+        let out = dot(&shop(), None);
+        assert!(out.starts_with("digraph entities {"));
+        assert!(out.contains("  product [label=<"));
+        assert!(out.contains("<b>Product</b>"));
+        assert!(out.contains("<td align=\"left\">description: string?</td>"));
+        assert!(out.contains("  product -> review [label=\"has_review (reviews / product)\"];"));
+        assert!(!out.contains("subgraph"));
+    }
+
+    #[test]
+    fn test_dot_clusters_by_backend() {
+        let out = dot(&shop(), Some(&backends()));
+        let mongodb = out.find("subgraph \"cluster_mongodb\"").unwrap();
+        let postgres = out.find("subgraph \"cluster_postgres\"").unwrap();
+        assert!(mongodb < postgres);
+        assert!(out.contains("    label=\"postgres\";"));
+        assert!(out[postgres..].contains("    product [label=<"));
+    }
+
+    #[test]
+    fn test_svg() {
+        let out = svg(&shop(), None);
+        assert!(out.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(out.trim_end().ends_with("</svg>"));
+        assert!(out.contains(">Product</text>"));
+        assert!(out.contains(">description: string?</text>"));
+        assert!(out.contains(">has_review (reviews / product)</text>"));
+        assert!(out.contains("<line "));
+        assert!(!out.contains("stroke-dasharray"));
+    }
+
+    #[test]
+    fn test_svg_clusters_and_self_links() {
+        let mut project = shop();
+        project.links.push(LinkMeta {
+            link_type: "related".to_string(),
+            source: "product".to_string(),
+            target: "product".to_string(),
+            forward_route: "related".to_string(),
+            reverse_route: "related_to".to_string(),
+        });
+        let out = svg(&project, Some(&backends()));
+        assert_eq!(out.matches("stroke-dasharray").count(), 2);
+        assert!(out.contains(">postgres</text>"));
+        assert!(out.contains("<path d=\"M "));
+    }
+
+    #[test]
+    fn test_svg_escapes_text() {
+        let project = ProjectIntrospection {
+            entities: vec![entity("Doc", "doc", vec![field("meta", "Vec<Foo<Bar>>")])],
+            links: vec![],
+        };
+        let out = svg(&project, None);
+        assert!(!out.contains("Foo<"));
+    }
+
+    #[test]
+    fn test_border_towards() {
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 50.0,
+        };
+        assert_eq!(rect.border_towards((200.0, 25.0)), (100.0, 25.0));
+        assert_eq!(rect.border_towards((50.0, -100.0)), (50.0, 0.0));
+    }
+}
@@ -0,0 +1,671 @@
+//! API reference documentation generation
+//!
+//! Generates one page per entity (fields, routes, link traversals, auth
+//! policies, event flows) plus an index, as Markdown or standalone HTML.
+//! Pages are built as a small block model first so both formats share the
+//! same content.
+
+use std::collections::BTreeMap;
+
+use regex::Regex;
+
+use crate::codegen::introspect::{
+    AuthMeta, EntityMeta, EventFlowMeta, OPERATIONS, ProjectIntrospection,
+};
+use crate::codegen::typescript::rust_type_to_ts;
+
+/// Prefix the REST routes are served under (same as the generated clients)
+const API_PREFIX: &str = "/api";
+
+/// Everything the reference is generated from
+pub struct DocsInput<'a> {
+    /// Project name, used as the title of the index
+    pub title: &'a str,
+    pub project: &'a ProjectIntrospection,
+    pub auth: &'a AuthMeta,
+    pub flows: &'a [EventFlowMeta],
+    /// Entity snake_case name → storage backend
+    pub backends: &'a BTreeMap<String, String>,
+}
+
+/// Output format of the reference
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocsFormat {
+    Markdown,
+    Html,
+}
+
+impl DocsFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            DocsFormat::Markdown => "md",
+            DocsFormat::Html => "html",
+        }
+    }
+
+    /// File name of the index page
+    pub fn index_file(self) -> &'static str {
+        match self {
+            DocsFormat::Markdown => "README.md",
+            DocsFormat::Html => "index.html",
+        }
+    }
+}
+
+// ── Public API ────────────────────────────────────────────────────────
+
+/// Generate every page of the reference: `(file name, content)` pairs, index first.
+pub fn generate(input: &DocsInput, format: DocsFormat) -> Vec<(String, String)> {
+    let mut pages = vec![(
+        format.index_file().to_string(),
+        render(
+            &format!("{} API reference", input.title),
+            &index_blocks(input),
+            format,
+            false,
+        ),
+    )];
+    for entity in &input.project.entities {
+        pages.push((
+            format!("{}.{}", entity.snake_name, format.extension()),
+            render(
+                &entity.pascal_name,
+                &entity_blocks(entity, input),
+                format,
+                true,
+            ),
+        ));
+    }
+    pages
+}
+
+// ── Block model ───────────────────────────────────────────────────────
+
+/// Text in blocks may contain `` `code` `` spans and `[text](@page)` links to
+/// other pages of the reference.
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    Table {
+        headers: Vec<&'static str>,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+fn table(headers: Vec<&'static str>, rows: Vec<Vec<String>>) -> Block {
+    Block::Table { headers, rows }
+}
+
+fn code(s: &str) -> String {
+    format!("`{}`", s)
+}
+
+fn page_link(entity: &EntityMeta) -> String {
+    format!("[{}](@{})", entity.pascal_name, entity.snake_name)
+}
+
+// ── Page content ──────────────────────────────────────────────────────
+
+fn index_blocks(input: &DocsInput) -> Vec<Block> {
+    let project = input.project;
+    let mut blocks = vec![Block::Heading("Entities".to_string())];
+
+    blocks.push(table(
+        vec!["Entity", "Collection", "Fields", "Storage"],
+        project
+            .entities
+            .iter()
+            .map(|e| {
+                vec![
+                    page_link(e),
+                    code(&format!("{}/{}", API_PREFIX, e.plural)),
+                    e.fields.len().to_string(),
+                    backend(input, e),
+                ]
+            })
+            .collect(),
+    ));
+
+    if !project.links.is_empty() {
+        blocks.push(Block::Heading("Links".to_string()));
+        blocks.push(table(
+            vec!["Link", "Source", "Target", "Forward route", "Reverse route"],
+            project
+                .links
+                .iter()
+                .map(|l| {
+                    vec![
+                        code(&l.link_type),
+                        entity_ref(project, &l.source),
+                        entity_ref(project, &l.target),
+                        code(&l.forward_route),
+                        code(&l.reverse_route),
+                    ]
+                })
+                .collect(),
+        ));
+    }
+
+    blocks.push(Block::Heading("Authentication".to_string()));
+    blocks.push(Block::Paragraph(match &input.auth.provider {
+        Some(provider) => format!(
+            "Provider {}, default policy {} (`config/auth.yaml`).",
+            code(provider),
+            code(
+                input
+                    .auth
+                    .default_policy
+                    .as_deref()
+                    .unwrap_or("authenticated")
+            )
+        ),
+        None => "No `config/auth.yaml`: policies come from `config/links.yaml` only.".to_string(),
+    }));
+
+    if !input.flows.is_empty() {
+        blocks.push(Block::Heading("Event flows".to_string()));
+        blocks.push(table(
+            vec!["Flow", "Trigger", "Pipeline"],
+            input
+                .flows
+                .iter()
+                .map(|f| vec![code(&f.name), code(&f.trigger), pipeline(f)])
+                .collect(),
+        ));
+    }
+
+    blocks
+}
+
+fn entity_blocks(entity: &EntityMeta, input: &DocsInput) -> Vec<Block> {
+    let project = input.project;
+    let mut blocks = vec![Block::Paragraph(format!(
+        "Collection {} · storage {}",
+        code(&format!("{}/{}", API_PREFIX, entity.plural)),
+        backend(input, entity)
+    ))];
+
+    // Fields
+    blocks.push(Block::Heading("Fields".to_string()));
+    let builtin = |name: &str, rust: &str, note: &str| {
+        vec![
+            code(name),
+            code(rust),
+            code(&rust_type_to_ts(rust)),
+            note.to_string(),
+        ]
+    };
+    let mut rows = vec![builtin("id", "Uuid", "Primary key, set by the server")];
+    for field in &entity.fields {
+        if ["id", "created_at", "updated_at"].contains(&field.name.as_str()) {
+            continue;
+        }
+        let mut notes = Vec::new();
+        if field.rust_type.trim().starts_with("Option<") {
+            notes.push("Optional");
+        }
+        if entity.indexed_fields.contains(&field.name) {
+            notes.push("Indexed");
+        }
+        rows.push(vec![
+            code(&field.name),
+            code(&field.rust_type),
+            code(&rust_type_to_ts(&field.rust_type)),
+            notes.join(", "),
+        ]);
+    }
+    rows.push(builtin("created_at", "DateTime<Utc>", "Set by the server"));
+    rows.push(builtin("updated_at", "DateTime<Utc>", "Set by the server"));
+    blocks.push(table(
+        vec!["Field", "Rust type", "JSON type", "Notes"],
+        rows,
+    ));
+
+    // Routes
+    blocks.push(Block::Heading("Routes".to_string()));
+    if entity.routes.is_empty() {
+        blocks.push(Block::Paragraph(
+            "No routes found in `descriptor.rs`.".to_string(),
+        ));
+    } else {
+        blocks.push(table(
+            vec!["Method", "Path", "Operation", "Policy"],
+            entity
+                .routes
+                .iter()
+                .map(|route| {
+                    let operation = route_operation(&route.method, &route.path);
+                    vec![
+                        code(&route.method),
+                        code(&format!("{}{}", API_PREFIX, route.path)),
+                        operation.to_string(),
+                        policy_cell(input.auth, entity, operation),
+                    ]
+                })
+                .collect(),
+        ));
+    }
+
+    // Link traversals, outgoing then incoming
+    let mut link_rows = Vec::new();
+    for link in project
+        .links
+        .iter()
+        .filter(|l| l.source == entity.snake_name)
+    {
+        link_rows.push(vec![
+            code(&link.link_type),
+            code(&format!(
+                "GET {}/{}/{{id}}/{}",
+                API_PREFIX, entity.plural, link.forward_route
+            )),
+            entity_ref(project, &link.target),
+        ]);
+    }
+    for link in project
+        .links
+        .iter()
+        .filter(|l| l.target == entity.snake_name)
+    {
+        link_rows.push(vec![
+            code(&link.link_type),
+            code(&format!(
+                "GET {}/{}/{{id}}/{}",
+                API_PREFIX, entity.plural, link.reverse_route
+            )),
+            entity_ref(project, &link.source),
+        ]);
+    }
+    blocks.push(Block::Heading("Links".to_string()));
+    if link_rows.is_empty() {
+        blocks.push(Block::Paragraph("No links.".to_string()));
+    } else {
+        blocks.push(table(vec!["Link", "Traversal", "Returns"], link_rows));
+    }
+
+    // Authorization
+    blocks.push(Block::Heading("Authorization".to_string()));
+    blocks.push(table(
+        vec!["Operation", "Policy"],
+        OPERATIONS
+            .iter()
+            .map(|op| vec![op.to_string(), policy_cell(input.auth, entity, op)])
+            .collect(),
+    ));
+
+    // Events
+    blocks.push(Block::Heading("Events".to_string()));
+    let flows: Vec<Vec<String>> = input
+        .flows
+        .iter()
+        .filter_map(|flow| {
+            let events = flow.events_for(&entity.snake_name);
+            (!events.is_empty()).then(|| {
+                vec![
+                    code(&flow.name),
+                    events.join(", "),
+                    code(&flow.trigger),
+                    pipeline(flow),
+                ]
+            })
+        })
+        .collect();
+    if flows.is_empty() {
+        blocks.push(Block::Paragraph(
+            "No event flows are triggered by this entity.".to_string(),
+        ));
+    } else {
+        blocks.push(table(vec!["Flow", "On", "Trigger", "Pipeline"], flows));
+    }
+
+    blocks
+}
+
+/// CRUD operation served by a route
+fn route_operation(method: &str, path: &str) -> &'static str {
+    match method {
+        "GET" if path.ends_with('}') => "get",
+        "GET" => "list",
+        "POST" => "create",
+        "PUT" | "PATCH" => "update",
+        "DELETE" => "delete",
+        _ => "other",
+    }
+}
+
+fn policy_cell(auth: &AuthMeta, entity: &EntityMeta, operation: &str) -> String {
+    match auth.policy(&entity.snake_name, operation) {
+        Some((policy, source)) => format!("{} ({})", code(&policy), source),
+        None => "—".to_string(),
+    }
+}
+
+fn pipeline(flow: &EventFlowMeta) -> String {
+    let steps = if flow.steps.is_empty() {
+        "—".to_string()
+    } else {
+        flow.steps.join(" → ")
+    };
+    if flow.sinks.is_empty() {
+        steps
+    } else {
+        format!("{} ({})", steps, flow.sinks.join(", "))
+    }
+}
+
+fn backend(input: &DocsInput, entity: &EntityMeta) -> String {
+    code(
+        input
+            .backends
+            .get(&entity.snake_name)
+            .map(String::as_str)
+            .unwrap_or("in-memory"),
+    )
+}
+
+/// Link to an entity page, or plain code for an entity outside the project
+fn entity_ref(project: &ProjectIntrospection, name: &str) -> String {
+    project
+        .entities
+        .iter()
+        .find(|e| e.snake_name == name)
+        .map(page_link)
+        .unwrap_or_else(|| code(name))
+}
+
+// ── Rendering ─────────────────────────────────────────────────────────
+
+fn render(title: &str, blocks: &[Block], format: DocsFormat, back_link: bool) -> String {
+    match format {
+        DocsFormat::Markdown => render_markdown(title, blocks, back_link),
+        DocsFormat::Html => render_html(title, blocks, back_link),
+    }
+}
+
+fn render_markdown(title: &str, blocks: &[Block], back_link: bool) -> String {
+    let mut out =
+        String::from("<!-- Auto-generated by `this generate docs` — do not edit manually. -->\n\n");
+    out.push_str(&format!("# {}\n\n", title));
+    if back_link {
+        out.push_str("[← API reference](README.md)\n\n");
+    }
+    for block in blocks {
+        match block {
+            Block::Heading(text) => out.push_str(&format!("## {}\n\n", text)),
+            Block::Paragraph(text) => out.push_str(&format!("{}\n\n", markdown_inline(text))),
+            Block::Table { headers, rows } => {
+                out.push_str(&format!("| {} |\n", headers.join(" | ")));
+                out.push_str(&format!(
+                    "|{}\n",
+                    headers.iter().map(|_| "---|").collect::<String>()
+                ));
+                for row in rows {
+                    let cells: Vec<String> = row
+                        .iter()
+                        .map(|cell| markdown_inline(cell).replace('|', "\\|"))
+                        .collect();
+                    out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+                out.push('\n');
+            }
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+fn markdown_inline(text: &str) -> String {
+    page_link_re().replace_all(text, "[$1]($2.md)").into_owned()
+}
+
+fn render_html(title: &str, blocks: &[Block], back_link: bool) -> String {
+    let mut body = String::new();
+    if back_link {
+        body.push_str("  <p><a href=\"index.html\">← API reference</a></p>\n");
+    }
+    for block in blocks {
+        match block {
+            Block::Heading(text) => body.push_str(&format!("  <h2>{}</h2>\n", html_inline(text))),
+            Block::Paragraph(text) => body.push_str(&format!("  <p>{}</p>\n", html_inline(text))),
+            Block::Table { headers, rows } => {
+                body.push_str("  <table>\n    <tr>");
+                for header in headers {
+                    body.push_str(&format!("<th>{}</th>", html_escape(header)));
+                }
+                body.push_str("</tr>\n");
+                for row in rows {
+                    body.push_str("    <tr>");
+                    for cell in row {
+                        body.push_str(&format!("<td>{}</td>", html_inline(cell)));
+                    }
+                    body.push_str("</tr>\n");
+                }
+                body.push_str("  </table>\n");
+            }
+        }
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<!-- Auto-generated by `this generate docs` — do not edit manually. -->
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>{title}</title>
+  <style>
+    body {{ font-family: system-ui, sans-serif; max-width: 960px; margin: 2rem auto; padding: 0 1rem; color: #222; }}
+    table {{ border-collapse: collapse; margin-bottom: 1.5rem; }}
+    th, td {{ border: 1px solid #ccc; padding: 0.3rem 0.6rem; text-align: left; }}
+    th {{ background: #f3f3f3; }}
+    code {{ background: #f6f6f6; padding: 0 0.2rem; }}
+  </style>
+</head>
+<body>
+  <h1>{title}</h1>
+{body}</body>
+</html>
+"#,
+        title = html_escape(title),
+        body = body
+    )
+}
+
+fn html_inline(text: &str) -> String {
+    let escaped = html_escape(text);
+    let code_re = Regex::new(r"`([^`]+)`").unwrap();
+    let with_code = code_re.replace_all(&escaped, "<code>$1</code>");
+    page_link_re()
+        .replace_all(&with_code, "<a href=\"$2.html\">$1</a>")
+        .into_owned()
+}
+
+fn page_link_re() -> Regex {
+    Regex::new(r"\[([^\]]+)\]\(@(\w+)\)").unwrap()
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::introspect::{FieldMeta, LinkMeta, RouteMeta};
+
+    fn product() -> EntityMeta {
+        EntityMeta {
+            pascal_name: "Product".to_string(),
+            snake_name: "product".to_string(),
+            plural: "products".to_string(),
+            indexed_fields: vec!["sku".to_string()],
+            fields: vec![
+                FieldMeta {
+                    name: "sku".to_string(),
+                    rust_type: "String".to_string(),
+                },
+                FieldMeta {
+                    name: "notes".to_string(),
+                    rust_type: "Option<String>".to_string(),
+                },
+            ],
+            routes: vec![
+                RouteMeta {
+                    method: "GET".to_string(),
+                    path: "/products".to_string(),
+                },
+                RouteMeta {
+                    method: "DELETE".to_string(),
+                    path: "/products/{id}".to_string(),
+                },
+            ],
+        }
+    }
+
+    fn review() -> EntityMeta {
+        EntityMeta {
+            pascal_name: "Review".to_string(),
+            snake_name: "review".to_string(),
+            plural: "reviews".to_string(),
+            indexed_fields: vec![],
+            fields: vec![],
+            routes: vec![],
+        }
+    }
+
+    fn project() -> ProjectIntrospection {
+        ProjectIntrospection {
+            entities: vec![product(), review()],
+            links: vec![LinkMeta {
+                link_type: "has_review".to_string(),
+                source: "product".to_string(),
+                target: "review".to_string(),
+                forward_route: "reviews".to_string(),
+                reverse_route: "product".to_string(),
+            }],
+        }
+    }
+
+    fn auth() -> AuthMeta {
+        AuthMeta {
+            provider: Some("wami".to_string()),
+            default_policy: Some("authenticated".to_string()),
+            overrides: BTreeMap::from([(
+                "product".to_string(),
+                BTreeMap::from([("delete".to_string(), "admin_only".to_string())]),
+            )]),
+            entity_auth: BTreeMap::from([(
+                "product".to_string(),
+                BTreeMap::from([("list".to_string(), "public".to_string())]),
+            )]),
+        }
+    }
+
+    fn flows() -> Vec<EventFlowMeta> {
+        vec![EventFlowMeta {
+            name: "notify".to_string(),
+            trigger: "entity.created.product".to_string(),
+            steps: vec!["filter".to_string(), "deliver".to_string()],
+            sinks: vec!["in-app".to_string()],
+        }]
+    }
+
+    fn pages(format: DocsFormat) -> Vec<(String, String)> {
+        let project = project();
+        let auth = auth();
+        let flows = flows();
+        let backends = BTreeMap::from([("product".to_string(), "postgres".to_string())]);
+        generate(
+            &DocsInput {
+                title: "shop",
+                project: &project,
+                auth: &auth,
+                flows: &flows,
+                backends: &backends,
+            },
+            format,
+        )
+    }
+
+    fn page<'a>(pages: &'a [(String, String)], name: &str) -> &'a str {
+        &pages.iter().find(|(n, _)| n == name).unwrap().1
+    }
+
+    #[test]
+    fn test_markdown_pages() {
+        let pages = pages(DocsFormat::Markdown);
+        let names: Vec<&str> = pages.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["README.md", "product.md", "review.md"]);
+
+        let index = page(&pages, "README.md");
+        assert!(index.contains("# shop API reference"));
+        assert!(index.contains("| [Product](product.md) | `/api/products` | 2 | `postgres` |"));
+        assert!(index.contains(
+            "| `has_review` | [Product](product.md) | [Review](review.md) | `reviews` | `product` |"
+        ));
+        assert!(index.contains("Provider `wami`, default policy `authenticated`"));
+        assert!(
+            index.contains("| `notify` | `entity.created.product` | filter → deliver (in-app) |")
+        );
+    }
+
+    #[test]
+    fn test_markdown_entity_page() {
+        let pages = pages(DocsFormat::Markdown);
+        let product = page(&pages, "product.md");
+
+        assert!(product.contains("# Product"));
+        assert!(product.contains("| `sku` | `String` | `string` | Indexed |"));
+        assert!(product.contains("| `notes` | `Option<String>` | `string \\| null` | Optional |"));
+        assert!(product.contains("| `GET` | `/api/products` | list | `public` (links.yaml) |"));
+        assert!(
+            product.contains(
+                "| `DELETE` | `/api/products/{id}` | delete | `admin_only` (auth.yaml) |"
+            )
+        );
+        assert!(
+            product.contains(
+                "| `has_review` | `GET /api/products/{id}/reviews` | [Review](review.md) |"
+            )
+        );
+        assert!(product.contains("| update | `authenticated` (auth.yaml default_policy) |"));
+        assert!(product.contains("| `notify` | created | `entity.created.product` |"));
+
+        let review = page(&pages, "review.md");
+        assert!(review.contains(
+            "| `has_review` | `GET /api/reviews/{id}/product` | [Product](product.md) |"
+        ));
+        assert!(review.contains("No routes found in `descriptor.rs`."));
+        assert!(review.contains("No event flows are triggered by this entity."));
+    }
+
+    #[test]
+    fn test_html_pages() {
+        let pages = pages(DocsFormat::Html);
+        let names: Vec<&str> = pages.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["index.html", "product.html", "review.html"]);
+
+        let index = page(&pages, "index.html");
+        assert!(index.starts_with("<!DOCTYPE html>"));
+        assert!(index.contains("<td><a href=\"product.html\">Product</a></td>"));
+
+        let product = page(&pages, "product.html");
+        assert!(product.contains("<a href=\"index.html\">← API reference</a>"));
+        assert!(product.contains("<td><code>Option&lt;String&gt;</code></td>"));
+        assert!(product.contains("<td><code>admin_only</code> (auth.yaml)</td>"));
+    }
+
+    #[test]
+    fn test_route_operation() {
+        assert_eq!(route_operation("GET", "/orders"), "list");
+        assert_eq!(route_operation("GET", "/orders/{id}"), "get");
+        assert_eq!(route_operation("POST", "/orders"), "create");
+        assert_eq!(route_operation("PUT", "/orders/{id}"), "update");
+        assert_eq!(route_operation("DELETE", "/orders/{id}"), "delete");
+    }
+}
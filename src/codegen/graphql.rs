@@ -0,0 +1,670 @@
+//! GraphQL schema and typed operations generation
+//!
+//! Generates a `schema.graphql` SDL file and a self-contained `operations.ts`
+//! file from project introspection data. The operations use native `fetch()`
+//! against the `/graphql` endpoint; React hooks are optional.
+
+use crate::codegen::introspect::{EntityMeta, FieldMeta, LinkMeta, ProjectIntrospection};
+use crate::codegen::typescript::{
+    AUTO_FIELDS, InterfaceKind, generate_interface, split_generic_pair, strip_wrapper,
+};
+use crate::utils::naming;
+
+// ── Public API ────────────────────────────────────────────────────────
+
+/// Generate the SDL schema (`schema.graphql`) from project introspection.
+pub fn generate_sdl(project: &ProjectIntrospection) -> String {
+    let mut out = String::new();
+
+    out.push_str(
+        "# Auto-generated by `this generate graphql` — do not edit manually.\n\
+         # Re-run `this generate graphql` to regenerate after adding entities or links.\n\n",
+    );
+    out.push_str("scalar DateTime\nscalar JSON\n\n");
+
+    // Object types, with link fields on both ends of each link
+    for entity in &project.entities {
+        out.push_str(&generate_object_type(entity, project));
+        out.push('\n');
+    }
+
+    // Connections for every entity reachable through a link field
+    for entity in &project.entities {
+        if connection_needed(entity, project) {
+            out.push_str(&generate_connection_type(entity));
+            out.push('\n');
+        }
+    }
+
+    // Input types
+    for entity in &project.entities {
+        out.push_str(&generate_input_type(entity, InterfaceKind::Create));
+        out.push_str(&generate_input_type(entity, InterfaceKind::Update));
+    }
+
+    // Root types
+    out.push_str("type Query {\n");
+    for entity in &project.entities {
+        out.push_str(&format!(
+            "  {}: [{}!]!\n  {}(id: ID!): {}\n",
+            entity.plural, entity.pascal_name, entity.snake_name, entity.pascal_name
+        ));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str("type Mutation {\n");
+    for entity in &project.entities {
+        let pascal = &entity.pascal_name;
+        out.push_str(&format!(
+            "  create{pascal}(input: {create}!): {pascal}!\n  \
+             update{pascal}(id: ID!, input: {update}!): {pascal}!\n  \
+             delete{pascal}(id: ID!): Boolean!\n",
+            pascal = pascal,
+            create = input_type_name(entity, InterfaceKind::Create),
+            update = input_type_name(entity, InterfaceKind::Update),
+        ));
+    }
+    out.push_str("}\n");
+
+    out
+}
+
+/// Generate typed TypeScript operations (`operations.ts`) from project introspection.
+/// With `hooks`, a React hook is also emitted for every query.
+pub fn generate_operations(project: &ProjectIntrospection, hooks: bool) -> String {
+    let mut out = String::new();
+
+    out.push_str(&generate_header(hooks));
+    out.push('\n');
+    out.push_str(&generate_request_helper());
+    out.push('\n');
+    if hooks {
+        out.push_str(&generate_hook_helper());
+        out.push('\n');
+    }
+
+    for entity in &project.entities {
+        out.push_str(&generate_entity_operations(entity, hooks));
+        out.push('\n');
+    }
+
+    let links: Vec<&LinkMeta> = project
+        .links
+        .iter()
+        .filter(|link| link_is_resolvable(link, &project.entities))
+        .collect();
+    if !links.is_empty() {
+        out.push_str("// ── Link queries ──────────────────────────────────────────────────\n\n");
+        for link in links {
+            out.push_str(&generate_link_operation(link, &project.entities, hooks));
+        }
+    }
+
+    out
+}
+
+// ── Type mapping ──────────────────────────────────────────────────────
+
+/// Convert a Rust type string to its GraphQL equivalent, including nullability:
+/// everything is non-null (`!`) except `Option<T>`.
+pub fn rust_type_to_graphql(rust_type: &str) -> String {
+    let trimmed = rust_type.trim();
+    match strip_wrapper(trimmed, "Option<", ">") {
+        Some(inner) => graphql_base_type(inner),
+        None => format!("{}!", graphql_base_type(trimmed)),
+    }
+}
+
+/// GraphQL type without the outer non-null marker.
+fn graphql_base_type(rust_type: &str) -> String {
+    let trimmed = rust_type.trim();
+
+    if let Some(inner) = strip_wrapper(trimmed, "Option<", ">") {
+        return graphql_base_type(inner);
+    }
+    if let Some(inner) = strip_wrapper(trimmed, "Vec<", ">") {
+        return format!("[{}]", rust_type_to_graphql(inner));
+    }
+    if let Some(inner) = strip_wrapper(trimmed, "HashMap<", ">")
+        && split_generic_pair(inner).is_some()
+    {
+        return "JSON".to_string();
+    }
+
+    match trimmed {
+        "String" | "&str" => "String".to_string(),
+        "Uuid" | "uuid::Uuid" => "ID".to_string(),
+        // GraphQL `Int` is 32-bit signed; wider integers travel as `Float`
+        "i8" | "i16" | "i32" | "u8" | "u16" => "Int".to_string(),
+        "f32" | "f64" | "i64" | "i128" | "u32" | "u64" | "u128" | "isize" | "usize" => {
+            "Float".to_string()
+        }
+        "bool" => "Boolean".to_string(),
+        "DateTime<Utc>" | "chrono::DateTime<Utc>" | "NaiveDateTime" => "DateTime".to_string(),
+        // Unknown types have no SDL definition: expose them as opaque JSON
+        _ => "JSON".to_string(),
+    }
+}
+
+// ── SDL sections ──────────────────────────────────────────────────────
+
+fn generate_object_type(entity: &EntityMeta, project: &ProjectIntrospection) -> String {
+    let mut out = format!("type {} {{\n  id: ID!\n", entity.pascal_name);
+
+    for field in data_fields(&entity.fields) {
+        out.push_str(&format!(
+            "  {}: {}\n",
+            field.name,
+            rust_type_to_graphql(&field.rust_type)
+        ));
+    }
+    out.push_str("  created_at: DateTime!\n  updated_at: DateTime!\n");
+
+    for (name, other) in link_fields(entity, project) {
+        out.push_str(&format!("  {}: {}Connection!\n", name, other.pascal_name));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn generate_connection_type(entity: &EntityMeta) -> String {
+    format!(
+        "type {pascal}Connection {{\n  nodes: [{pascal}!]!\n  total: Int!\n}}\n",
+        pascal = entity.pascal_name
+    )
+}
+
+fn generate_input_type(entity: &EntityMeta, kind: InterfaceKind) -> String {
+    let fields: Vec<&FieldMeta> = data_fields(&entity.fields).collect();
+    // An input type without fields is invalid SDL: those mutations take JSON
+    if fields.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("input {} {{\n", input_type_name(entity, kind));
+    for field in fields {
+        let ty = rust_type_to_graphql(&field.rust_type);
+        let ty = match kind {
+            InterfaceKind::Update => ty.trim_end_matches('!').to_string(),
+            _ => ty,
+        };
+        out.push_str(&format!("  {}: {}\n", field.name, ty));
+    }
+    out.push_str("}\n\n");
+    out
+}
+
+fn input_type_name(entity: &EntityMeta, kind: InterfaceKind) -> String {
+    if data_fields(&entity.fields).next().is_none() {
+        return "JSON".to_string();
+    }
+    match kind {
+        InterfaceKind::Update => format!("Update{}Input", entity.pascal_name),
+        _ => format!("Create{}Input", entity.pascal_name),
+    }
+}
+
+/// Custom fields, without the ones every entity already exposes
+fn data_fields(fields: &[FieldMeta]) -> impl Iterator<Item = &FieldMeta> {
+    fields
+        .iter()
+        .filter(|f| !AUTO_FIELDS.contains(&f.name.as_str()))
+}
+
+/// Link fields of an entity: forward routes where it is the source, reverse
+/// routes where it is the target. Names clashing with a data field are skipped.
+fn link_fields<'a>(
+    entity: &EntityMeta,
+    project: &'a ProjectIntrospection,
+) -> Vec<(&'a str, &'a EntityMeta)> {
+    let find = |name: &str| project.entities.iter().find(|e| e.snake_name == name);
+    let mut fields: Vec<(&str, &EntityMeta)> = Vec::new();
+
+    for link in &project.links {
+        if link.source == entity.snake_name
+            && let Some(target) = find(&link.target)
+        {
+            fields.push((&link.forward_route, target));
+        }
+        if link.target == entity.snake_name
+            && let Some(source) = find(&link.source)
+        {
+            fields.push((&link.reverse_route, source));
+        }
+    }
+
+    let taken =
+        |name: &str| AUTO_FIELDS.contains(&name) || entity.fields.iter().any(|f| f.name == name);
+    let mut seen: Vec<&str> = Vec::new();
+    fields.retain(|(name, _)| {
+        let keep = !name.is_empty() && !taken(name) && !seen.contains(name);
+        seen.push(name);
+        keep
+    });
+    fields
+}
+
+fn connection_needed(entity: &EntityMeta, project: &ProjectIntrospection) -> bool {
+    project.entities.iter().any(|e| {
+        link_fields(e, project)
+            .iter()
+            .any(|(_, other)| other.snake_name == entity.snake_name)
+    })
+}
+
+fn link_is_resolvable(link: &LinkMeta, entities: &[EntityMeta]) -> bool {
+    let known = |name: &str| entities.iter().any(|e| e.snake_name == name);
+    known(&link.source) && known(&link.target) && !link.forward_route.is_empty()
+}
+
+// ── TypeScript sections ───────────────────────────────────────────────
+
+fn generate_header(hooks: bool) -> String {
+    let mut out = String::from(
+        "// Auto-generated by `this generate graphql` — do not edit manually.\n\
+         // Re-run `this generate graphql` to regenerate after adding entities or links.\n\n",
+    );
+    if hooks {
+        out.push_str("import { useCallback, useEffect, useState } from 'react';\n\n");
+    }
+    out.push_str("const GRAPHQL_URL = `${import.meta.env.VITE_API_URL || ''}/graphql`;\n");
+    out
+}
+
+fn generate_request_helper() -> String {
+    r#"export interface Connection<T> {
+  nodes: T[];
+  total: number;
+}
+
+export async function gqlRequest<T>(query: string, variables?: Record<string, unknown>): Promise<T> {
+  const res = await fetch(GRAPHQL_URL, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ query, variables }),
+  });
+  if (!res.ok) {
+    throw new Error(`GraphQL error ${res.status}: ${res.statusText}`);
+  }
+  const body = (await res.json()) as { data?: T; errors?: { message: string }[] };
+  if (body.errors?.length) {
+    throw new Error(body.errors.map((e) => e.message).join('; '));
+  }
+  return body.data as T;
+}
+"#
+    .to_string()
+}
+
+fn generate_hook_helper() -> String {
+    r#"export interface QueryState<T> {
+  data: T | undefined;
+  error: Error | undefined;
+  loading: boolean;
+  refetch: () => void;
+}
+
+function useGraphQLQuery<T>(fetcher: () => Promise<T>, deps: unknown[]): QueryState<T> {
+  const [data, setData] = useState<T>();
+  const [error, setError] = useState<Error>();
+  const [loading, setLoading] = useState(true);
+  const [version, setVersion] = useState(0);
+
+  useEffect(() => {
+    let cancelled = false;
+    setLoading(true);
+    fetcher()
+      .then((result) => {
+        if (!cancelled) {
+          setData(result);
+          setError(undefined);
+        }
+      })
+      .catch((err: unknown) => {
+        if (!cancelled) setError(err instanceof Error ? err : new Error(String(err)));
+      })
+      .finally(() => {
+        if (!cancelled) setLoading(false);
+      });
+    return () => {
+      cancelled = true;
+    };
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [...deps, version]);
+
+  const refetch = useCallback(() => setVersion((v) => v + 1), []);
+  return { data, error, loading, refetch };
+}
+"#
+    .to_string()
+}
+
+/// Selection set constant name, e.g. `PRODUCT_FIELDS`
+fn fields_const(entity: &EntityMeta) -> String {
+    format!("{}_FIELDS", entity.snake_name.to_uppercase())
+}
+
+fn generate_entity_operations(entity: &EntityMeta, hooks: bool) -> String {
+    let pascal = &entity.pascal_name;
+    let plural = &entity.plural;
+    let snake = &entity.snake_name;
+    let fn_list = naming::to_pascal_case(plural);
+    let fn_single = naming::to_pascal_case(snake);
+    let upper_single = snake.to_uppercase();
+    let upper_plural = plural.to_uppercase();
+    let fields = fields_const(entity);
+
+    let mut out = format!(
+        "// ── {} ────────────────────────────────────────────────\n\n",
+        pascal
+    );
+
+    out.push_str(&generate_interface(
+        pascal,
+        &entity.fields,
+        InterfaceKind::Full,
+    ));
+    out.push('\n');
+    out.push_str(&generate_interface(
+        &format!("Create{}", pascal),
+        &entity.fields,
+        InterfaceKind::Create,
+    ));
+    out.push('\n');
+    out.push_str(&generate_interface(
+        &format!("Update{}", pascal),
+        &entity.fields,
+        InterfaceKind::Update,
+    ));
+    out.push('\n');
+
+    let selection: Vec<&str> = std::iter::once("id")
+        .chain(data_fields(&entity.fields).map(|f| f.name.as_str()))
+        .chain(["created_at", "updated_at"])
+        .collect();
+    out.push_str(&format!(
+        "const {} = '{}';\n\n",
+        fields,
+        selection.join(" ")
+    ));
+
+    out.push_str(&format!(
+        r#"export const LIST_{upper_plural} = `query List{fn_list} {{ {plural} {{ ${{{fields}}} }} }}`;
+export const GET_{upper_single} = `query Get{fn_single}($id: ID!) {{ {snake}(id: $id) {{ ${{{fields}}} }} }}`;
+export const CREATE_{upper_single} = `mutation Create{fn_single}($input: {create}!) {{ create{pascal}(input: $input) {{ ${{{fields}}} }} }}`;
+export const UPDATE_{upper_single} = `mutation Update{fn_single}($id: ID!, $input: {update}!) {{ update{pascal}(id: $id, input: $input) {{ ${{{fields}}} }} }}`;
+export const DELETE_{upper_single} = `mutation Delete{fn_single}($id: ID!) {{ delete{pascal}(id: $id) }}`;
+
+export async function list{fn_list}(): Promise<{pascal}[]> {{
+  const data = await gqlRequest<{{ {plural}: {pascal}[] }}>(LIST_{upper_plural});
+  return data.{plural};
+}}
+
+export async function get{fn_single}(id: string): Promise<{pascal} | null> {{
+  const data = await gqlRequest<{{ {snake}: {pascal} | null }}>(GET_{upper_single}, {{ id }});
+  return data.{snake};
+}}
+
+export async function create{fn_single}(input: Create{pascal}): Promise<{pascal}> {{
+  const data = await gqlRequest<{{ create{pascal}: {pascal} }}>(CREATE_{upper_single}, {{ input }});
+  return data.create{pascal};
+}}
+
+export async function update{fn_single}(id: string, input: Update{pascal}): Promise<{pascal}> {{
+  const data = await gqlRequest<{{ update{pascal}: {pascal} }}>(UPDATE_{upper_single}, {{ id, input }});
+  return data.update{pascal};
+}}
+
+export async function delete{fn_single}(id: string): Promise<boolean> {{
+  const data = await gqlRequest<{{ delete{pascal}: boolean }}>(DELETE_{upper_single}, {{ id }});
+  return data.delete{pascal};
+}}
+"#,
+        create = input_type_name(entity, InterfaceKind::Create),
+        update = input_type_name(entity, InterfaceKind::Update),
+    ));
+
+    if hooks {
+        out.push_str(&format!(
+            r#"
+export function use{fn_list}(): QueryState<{pascal}[]> {{
+  return useGraphQLQuery(list{fn_list}, []);
+}}
+
+export function use{fn_single}(id: string): QueryState<{pascal} | null> {{
+  return useGraphQLQuery(() => get{fn_single}(id), [id]);
+}}
+"#
+        ));
+    }
+
+    out
+}
+
+fn generate_link_operation(link: &LinkMeta, entities: &[EntityMeta], hooks: bool) -> String {
+    let source_pascal = naming::to_pascal_case(&link.source);
+    let forward_pascal = naming::to_pascal_case(&link.forward_route);
+    let target = entities
+        .iter()
+        .find(|e| e.snake_name == link.target)
+        .expect("link target checked by link_is_resolvable");
+    let target_pascal = &target.pascal_name;
+    let source = &link.source;
+    let forward_route = &link.forward_route;
+    let constant = format!(
+        "GET_{}_{}",
+        link.source.to_uppercase(),
+        link.forward_route.to_uppercase()
+    );
+    let fields = fields_const(target);
+
+    let mut out = format!(
+        r#"export const {constant} = `query Get{source_pascal}{forward_pascal}($id: ID!) {{ {source}(id: $id) {{ {forward_route} {{ nodes {{ ${{{fields}}} }} total }} }} }}`;
+
+export async function get{source_pascal}{forward_pascal}({source}Id: string): Promise<Connection<{target_pascal}>> {{
+  const data = await gqlRequest<{{ {source}: {{ {forward_route}: Connection<{target_pascal}> }} | null }}>({constant}, {{ id: {source}Id }});
+  return data.{source}?.{forward_route} ?? {{ nodes: [], total: 0 }};
+}}
+"#
+    );
+
+    if hooks {
+        out.push_str(&format!(
+            r#"
+export function use{source_pascal}{forward_pascal}({source}Id: string): QueryState<Connection<{target_pascal}>> {{
+  return useGraphQLQuery(() => get{source_pascal}{forward_pascal}({source}Id), [{source}Id]);
+}}
+"#
+        ));
+    }
+
+    out.push('\n');
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, rust_type: &str) -> FieldMeta {
+        FieldMeta {
+            name: name.to_string(),
+            rust_type: rust_type.to_string(),
+        }
+    }
+
+    fn entity(pascal: &str, snake: &str, plural: &str, fields: Vec<FieldMeta>) -> EntityMeta {
+        EntityMeta {
+            pascal_name: pascal.to_string(),
+            snake_name: snake.to_string(),
+            plural: plural.to_string(),
+            indexed_fields: vec![],
+            fields,
+            routes: vec![],
+        }
+    }
+
+    fn shop() -> ProjectIntrospection {
+        ProjectIntrospection {
+            entities: vec![
+                entity(
+                    "Product",
+                    "product",
+                    "products",
+                    vec![
+                        field("name", "String"),
+                        field("price", "f64"),
+                        field("description", "Option<String>"),
+                    ],
+                ),
+                entity("Review", "review", "reviews", vec![field("rating", "i32")]),
+            ],
+            links: vec![LinkMeta {
+                link_type: "has_review".to_string(),
+                source: "product".to_string(),
+                target: "review".to_string(),
+                forward_route: "reviews".to_string(),
+                reverse_route: "product".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_rust_type_to_graphql() {
+        assert_eq!(rust_type_to_graphql("String"), "String!");
+        assert_eq!(rust_type_to_graphql("Uuid"), "ID!");
+        assert_eq!(rust_type_to_graphql("i32"), "Int!");
+        assert_eq!(rust_type_to_graphql("i64"), "Float!");
+        assert_eq!(rust_type_to_graphql("f64"), "Float!");
+        assert_eq!(rust_type_to_graphql("bool"), "Boolean!");
+        assert_eq!(rust_type_to_graphql("DateTime<Utc>"), "DateTime!");
+        assert_eq!(rust_type_to_graphql("Option<String>"), "String");
+        assert_eq!(rust_type_to_graphql("Vec<String>"), "[String!]!");
+        assert_eq!(rust_type_to_graphql("Option<Vec<Option<f64>>>"), "[Float]");
+        assert_eq!(rust_type_to_graphql("HashMap<String, f64>"), "JSON!");
+        assert_eq!(rust_type_to_graphql("serde_json::Value"), "JSON!");
+        assert_eq!(rust_type_to_graphql("MyCustomType"), "JSON!");
+    }
+
+    #[test]
+    fn test_sdl_types_inputs_and_roots() {
+        let sdl = generate_sdl(&shop());
+
+        assert!(sdl.contains("scalar DateTime"));
+        assert!(sdl.contains(
+            "type Product {\n  id: ID!\n  name: String!\n  price: Float!\n  description: String\n"
+        ));
+        assert!(sdl.contains("input CreateProductInput {\n  name: String!\n"));
+        assert!(sdl.contains("input UpdateProductInput {\n  name: String\n  price: Float\n"));
+        assert!(sdl.contains("  products: [Product!]!\n  product(id: ID!): Product\n"));
+        assert!(sdl.contains("  createProduct(input: CreateProductInput!): Product!\n"));
+        assert!(sdl.contains("  updateProduct(id: ID!, input: UpdateProductInput!): Product!\n"));
+        assert!(sdl.contains("  deleteProduct(id: ID!): Boolean!\n"));
+    }
+
+    #[test]
+    fn test_sdl_link_connections_on_both_ends() {
+        let sdl = generate_sdl(&shop());
+
+        assert!(sdl.contains("  reviews: ReviewConnection!\n"));
+        assert!(sdl.contains("  product: ProductConnection!\n"));
+        assert!(sdl.contains("type ReviewConnection {\n  nodes: [Review!]!\n  total: Int!\n}"));
+        assert!(sdl.contains("type ProductConnection {"));
+    }
+
+    #[test]
+    fn test_sdl_skips_links_to_unknown_entities_and_clashing_names() {
+        let mut project = shop();
+        project.links.push(LinkMeta {
+            link_type: "owner".to_string(),
+            source: "product".to_string(),
+            target: "user".to_string(),
+            forward_route: "owners".to_string(),
+            reverse_route: "products".to_string(),
+        });
+        project.links.push(LinkMeta {
+            link_type: "named".to_string(),
+            source: "review".to_string(),
+            target: "product".to_string(),
+            forward_route: "rating".to_string(),
+            reverse_route: "name".to_string(),
+        });
+
+        let sdl = generate_sdl(&project);
+        assert!(!sdl.contains("owners"));
+        assert!(!sdl.contains("UserConnection"));
+        assert!(!sdl.contains("  rating: ProductConnection!"));
+        assert!(!sdl.contains("  name: ReviewConnection!"));
+    }
+
+    #[test]
+    fn test_sdl_entity_without_fields_uses_json_inputs() {
+        let project = ProjectIntrospection {
+            entities: vec![entity("Tag", "tag", "tags", vec![])],
+            links: vec![],
+        };
+        let sdl = generate_sdl(&project);
+        assert!(!sdl.contains("input CreateTagInput"));
+        assert!(sdl.contains("  createTag(input: JSON!): Tag!\n"));
+        assert!(!sdl.contains("Connection"));
+    }
+
+    #[test]
+    fn test_operations_are_typed() {
+        let ts = generate_operations(&shop(), false);
+
+        assert!(ts.contains("// Auto-generated by `this generate graphql`"));
+        assert!(ts.contains("export async function gqlRequest<T>"));
+        assert!(ts.contains("export interface Product {"));
+        assert!(ts.contains("export interface CreateProduct {"));
+        assert!(
+            ts.contains(
+                "const PRODUCT_FIELDS = 'id name price description created_at updated_at';"
+            )
+        );
+        assert!(ts.contains(
+            "export const LIST_PRODUCTS = `query ListProducts { products { ${PRODUCT_FIELDS} } }`;"
+        ));
+        assert!(ts.contains("export async function listProducts(): Promise<Product[]>"));
+        assert!(
+            ts.contains("export async function getProduct(id: string): Promise<Product | null>")
+        );
+        assert!(ts.contains(
+            "export async function createProduct(input: CreateProduct): Promise<Product>"
+        ));
+        assert!(ts.contains("mutation CreateProduct($input: CreateProductInput!)"));
+        assert!(ts.contains("export async function deleteProduct(id: string): Promise<boolean>"));
+        assert!(ts.contains(
+            "export async function getProductReviews(productId: string): Promise<Connection<Review>>"
+        ));
+        assert!(
+            ts.contains("{ product(id: $id) { reviews { nodes { ${REVIEW_FIELDS} } total } } }")
+        );
+        assert!(!ts.contains("from 'react'"));
+        assert!(!ts.contains("useProducts"));
+    }
+
+    #[test]
+    fn test_operations_with_hooks() {
+        let ts = generate_operations(&shop(), true);
+
+        assert!(ts.contains("import { useCallback, useEffect, useState } from 'react';"));
+        assert!(ts.contains("function useGraphQLQuery<T>"));
+        assert!(ts.contains("export function useProducts(): QueryState<Product[]>"));
+        assert!(ts.contains("export function useProduct(id: string): QueryState<Product | null>"));
+        assert!(ts.contains(
+            "export function useProductReviews(productId: string): QueryState<Connection<Review>>"
+        ));
+    }
+
+    #[test]
+    fn test_field_constants_precede_link_queries() {
+        let ts = generate_operations(&shop(), false);
+        let declared = ts.find("const REVIEW_FIELDS").unwrap();
+        let used = ts.find("export const GET_PRODUCT_REVIEWS").unwrap();
+        assert!(declared < used);
+    }
+}
@@ -3,6 +3,7 @@
 
 use anyhow::{Context, Result};
 use regex::Regex;
+use std::collections::BTreeMap;
 use std::path::Path;
 
 // ── Metadata structs ──────────────────────────────────────────────────
@@ -182,6 +183,83 @@ fn parse_fields(raw: &str) -> Vec<FieldMeta> {
         .collect()
 }
 
+// ── Validation rules ──────────────────────────────────────────────────
+
+/// A validator of an `impl_data_entity_validated!` `validate:` block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationRule {
+    Required,
+    Optional,
+    Positive,
+    /// `in_list("a", "b")`
+    InList(Vec<String>),
+    /// Any other validator, kept by name
+    Other(String),
+}
+
+/// Validators of a validated entity, per field, for create and update payloads.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityValidation {
+    pub create: BTreeMap<String, Vec<ValidationRule>>,
+    pub update: BTreeMap<String, Vec<ValidationRule>>,
+}
+
+/// Parse the `validate: { create: {...}, update: {...} }` block of a model.rs.
+/// Returns `None` for entities declared with plain `impl_data_entity!`.
+pub fn parse_entity_validation(path: &Path) -> Result<Option<EntityValidation>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read: {}", path.display()))?;
+
+    Ok(parse_entity_validation_content(&content))
+}
+
+fn parse_entity_validation_content(content: &str) -> Option<EntityValidation> {
+    if !content.contains("impl_data_entity_validated!") {
+        return None;
+    }
+    // The `filters:` block has the same create/update shape: stop before it
+    let start = content.find("validate:")?;
+    let block = &content[start..];
+    let block = &block[..block.find("filters:").unwrap_or(block.len())];
+
+    let section = |name: &str| {
+        let re = Regex::new(&format!(r"\b{}\s*:\s*\{{([^}}]*)\}}", name)).unwrap();
+        re.captures(block)
+            .map(|c| parse_validation_fields(&c[1]))
+            .unwrap_or_default()
+    };
+
+    Some(EntityValidation {
+        create: section("create"),
+        update: section("update"),
+    })
+}
+
+/// Parse lines like `price: [required positive],`
+fn parse_validation_fields(raw: &str) -> BTreeMap<String, Vec<ValidationRule>> {
+    let field_re = Regex::new(r"(\w+)\s*:\s*\[([^\]]*)\]").unwrap();
+    let rule_re = Regex::new(r"(\w+)(?:\(([^)]*)\))?").unwrap();
+
+    field_re
+        .captures_iter(raw)
+        .map(|field| {
+            let rules = rule_re
+                .captures_iter(&field[2])
+                .map(|rule| match &rule[1] {
+                    "required" => ValidationRule::Required,
+                    "optional" => ValidationRule::Optional,
+                    "positive" => ValidationRule::Positive,
+                    "in_list" => ValidationRule::InList(parse_string_list(
+                        rule.get(2).map_or("", |m| m.as_str()),
+                    )),
+                    other => ValidationRule::Other(other.to_string()),
+                })
+                .collect();
+            (field[1].to_string(), rules)
+        })
+        .collect()
+}
+
 // ── Descriptor parser ─────────────────────────────────────────────────
 
 /// Parse a descriptor.rs to extract the plural form and REST routes.
@@ -245,6 +323,214 @@ struct LinkEntry {
     reverse_route_name: String,
 }
 
+// ── Auth policies ─────────────────────────────────────────────────────
+
+/// CRUD operations an entity policy applies to
+pub const OPERATIONS: &[&str] = &["list", "get", "create", "update", "delete"];
+
+/// Authorization settings from `config/links.yaml` (`entities[].auth`) and
+/// `config/auth.yaml` (`provider`, `default_policy`, `entities` overrides).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthMeta {
+    /// `provider` of auth.yaml, when the file exists
+    pub provider: Option<String>,
+    /// `default_policy` of auth.yaml
+    pub default_policy: Option<String>,
+    /// entity → operation (including `read`/`write`) → policy, from auth.yaml
+    pub overrides: BTreeMap<String, BTreeMap<String, String>>,
+    /// entity → operation → policy, from links.yaml
+    pub entity_auth: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl AuthMeta {
+    /// Effective policy of an operation and the file it comes from.
+    /// auth.yaml overrides (exact operation, then `read`/`write`) win over
+    /// links.yaml, which wins over auth.yaml's `default_policy`.
+    pub fn policy(&self, entity: &str, operation: &str) -> Option<(String, &'static str)> {
+        let shorthand = match operation {
+            "list" | "get" => "read",
+            _ => "write",
+        };
+        if let Some(ops) = self.overrides.get(entity)
+            && let Some(policy) = ops.get(operation).or_else(|| ops.get(shorthand))
+        {
+            return Some((policy.clone(), "auth.yaml"));
+        }
+        if let Some(policy) = self
+            .entity_auth
+            .get(entity)
+            .and_then(|ops| ops.get(operation))
+        {
+            return Some((policy.clone(), "links.yaml"));
+        }
+        self.default_policy
+            .clone()
+            .map(|policy| (policy, "auth.yaml default_policy"))
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct LinksEntitiesYaml {
+    #[serde(default)]
+    entities: Vec<EntityEntry>,
+}
+
+#[derive(serde::Deserialize)]
+struct EntityEntry {
+    singular: String,
+    #[serde(default)]
+    auth: BTreeMap<String, String>,
+}
+
+/// Read the auth policies of a project at `api_root`. Missing files are empty.
+pub fn parse_auth(api_root: &Path) -> Result<AuthMeta> {
+    let mut auth = AuthMeta::default();
+
+    let links_path = api_root.join("config/links.yaml");
+    if links_path.exists() {
+        let content = std::fs::read_to_string(&links_path)
+            .with_context(|| format!("Failed to read: {}", links_path.display()))?;
+        let yaml: LinksEntitiesYaml =
+            serde_yaml::from_str(&content).with_context(|| "Failed to parse links.yaml")?;
+        for entity in yaml.entities {
+            if !entity.auth.is_empty() {
+                auth.entity_auth.insert(entity.singular, entity.auth);
+            }
+        }
+    }
+
+    let auth_path = api_root.join("config/auth.yaml");
+    if auth_path.exists() {
+        let content = std::fs::read_to_string(&auth_path)
+            .with_context(|| format!("Failed to read: {}", auth_path.display()))?;
+        let yaml: serde_yaml::Value =
+            serde_yaml::from_str(&content).with_context(|| "Failed to parse auth.yaml")?;
+        let string = |key: &str| yaml.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        auth.provider = string("provider");
+        auth.default_policy = string("default_policy");
+        for (entity, ops) in yaml
+            .get("entities")
+            .and_then(|e| e.as_mapping())
+            .into_iter()
+            .flatten()
+        {
+            let (Some(entity), Some(ops)) = (entity.as_str(), ops.as_mapping()) else {
+                continue;
+            };
+            let ops: BTreeMap<String, String> = ops
+                .iter()
+                .filter_map(|(op, policy)| {
+                    Some((op.as_str()?.to_string(), policy.as_str()?.to_string()))
+                })
+                .collect();
+            auth.overrides.insert(entity.to_string(), ops);
+        }
+    }
+
+    Ok(auth)
+}
+
+// ── Event flows ───────────────────────────────────────────────────────
+
+/// An event flow declared in `config/events.yaml`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventFlowMeta {
+    /// Flow name
+    pub name: String,
+    /// Trigger pattern, e.g. `entity.created.order` or `entity.*.*`
+    pub trigger: String,
+    /// Step types in order (`filter`, `deliver`, ...)
+    pub steps: Vec<String>,
+    /// Sinks the flow delivers to
+    pub sinks: Vec<String>,
+}
+
+impl EventFlowMeta {
+    /// Entity events (`created`, `updated`, `deleted`) of `entity` that fire this flow
+    pub fn events_for(&self, entity: &str) -> Vec<&'static str> {
+        let pattern = format!("^{}$", regex::escape(&self.trigger).replace(r"\*", ".*"));
+        let Ok(re) = Regex::new(&pattern) else {
+            return Vec::new();
+        };
+        ["created", "updated", "deleted"]
+            .into_iter()
+            .filter(|event| re.is_match(&format!("entity.{}.{}", event, entity)))
+            .collect()
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct EventsYaml {
+    #[serde(default)]
+    event_flows: Vec<FlowEntry>,
+}
+
+#[derive(serde::Deserialize)]
+struct FlowEntry {
+    name: String,
+    trigger: String,
+    #[serde(default)]
+    steps: Vec<StepEntry>,
+}
+
+#[derive(serde::Deserialize)]
+struct StepEntry {
+    #[serde(rename = "type")]
+    step_type: String,
+    #[serde(default)]
+    sink: Option<String>,
+}
+
+/// Read the event flows of `config/events.yaml`, if any.
+pub fn parse_event_flows(api_root: &Path) -> Result<Vec<EventFlowMeta>> {
+    let events_path = api_root.join("config/events.yaml");
+    if !events_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&events_path)
+        .with_context(|| format!("Failed to read: {}", events_path.display()))?;
+    let yaml: EventsYaml =
+        serde_yaml::from_str(&content).with_context(|| "Failed to parse events.yaml")?;
+
+    Ok(yaml
+        .event_flows
+        .into_iter()
+        .map(|flow| EventFlowMeta {
+            name: flow.name,
+            trigger: flow.trigger,
+            sinks: flow.steps.iter().filter_map(|s| s.sink.clone()).collect(),
+            steps: flow.steps.into_iter().map(|s| s.step_type).collect(),
+        })
+        .collect())
+}
+
+// ── Store backend detection ───────────────────────────────────────────
+
+/// Storage backend of an entity, read from `src/entities/<name>/store.rs`:
+/// the `this::storage::*DataService` it wraps, else `in-memory`.
+pub fn detect_store_backend(api_root: &Path, entity: &str) -> String {
+    let store_path = api_root.join("src/entities").join(entity).join("store.rs");
+    std::fs::read_to_string(store_path)
+        .map(|content| parse_store_backend(&content).to_string())
+        .unwrap_or_else(|_| "in-memory".to_string())
+}
+
+fn parse_store_backend(content: &str) -> &'static str {
+    const SERVICES: &[(&str, &str)] = &[
+        ("PostgresDataService", "postgres"),
+        ("MongoDataService", "mongodb"),
+        ("Neo4jDataService", "neo4j"),
+        ("ScyllaDataService", "scylladb"),
+        ("MysqlDataService", "mysql"),
+        ("LmdbDataService", "lmdb"),
+    ];
+    SERVICES
+        .iter()
+        .find(|(service, _)| content.contains(service))
+        .map(|(_, backend)| *backend)
+        .unwrap_or("in-memory")
+}
+
 /// Parse config/links.yaml into LinkMeta entries.
 pub fn parse_links_yaml(path: &Path) -> Result<Vec<LinkMeta>> {
     let content = std::fs::read_to_string(path)
@@ -663,4 +949,152 @@ impl_data_entity!(
         assert_eq!(fields[0].rust_type, "String");
         assert_eq!(fields[2].rust_type, "Option<String>");
     }
+
+    // ── Store backend tests ───────────────────────────────────────
+
+    #[test]
+    fn test_parse_store_backend() {
+        assert_eq!(
+            parse_store_backend("use this::storage::PostgresDataService;\n"),
+            "postgres"
+        );
+        assert_eq!(
+            parse_store_backend("use this::storage::LmdbDataService;\n"),
+            "lmdb"
+        );
+        assert_eq!(
+            parse_store_backend("pub struct InMemoryProductStore {}"),
+            "in-memory"
+        );
+    }
+
+    #[test]
+    fn test_detect_store_backend_missing_store() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(detect_store_backend(tmp.path(), "product"), "in-memory");
+    }
+
+    // ── Auth tests ────────────────────────────────────────────────
+
+    #[test]
+    fn test_parse_auth_precedence() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::write(
+            root.join("config/links.yaml"),
+            "entities:\n  - singular: order\n    plural: orders\n    auth:\n      list: public\n      get: public\n      create: authenticated\n      update: authenticated\n      delete: authenticated\nlinks: []\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("config/auth.yaml"),
+            "provider: wami\ndefault_policy: authenticated\nentities:\n  order:\n    delete: admin_only\n    write: owner\n",
+        )
+        .unwrap();
+
+        let auth = parse_auth(root).unwrap();
+        assert_eq!(auth.provider.as_deref(), Some("wami"));
+        assert_eq!(
+            auth.policy("order", "delete"),
+            Some(("admin_only".to_string(), "auth.yaml"))
+        );
+        assert_eq!(
+            auth.policy("order", "update"),
+            Some(("owner".to_string(), "auth.yaml"))
+        );
+        assert_eq!(
+            auth.policy("order", "list"),
+            Some(("public".to_string(), "links.yaml"))
+        );
+        assert_eq!(
+            auth.policy("invoice", "get"),
+            Some(("authenticated".to_string(), "auth.yaml default_policy"))
+        );
+    }
+
+    #[test]
+    fn test_parse_auth_without_files() {
+        let tmp = TempDir::new().unwrap();
+        let auth = parse_auth(tmp.path()).unwrap();
+        assert_eq!(auth, AuthMeta::default());
+        assert_eq!(auth.policy("order", "get"), None);
+    }
+
+    // ── Event flow tests ──────────────────────────────────────────
+
+    #[test]
+    fn test_parse_event_flows_and_match_triggers() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::write(
+            root.join("config/events.yaml"),
+            "event_sinks: []\nevent_flows:\n  - name: notify\n    trigger: entity.created.order\n    steps:\n      - type: filter\n        condition: \"true\"\n      - type: deliver\n        sink: in-app\n  - name: audit\n    trigger: \"entity.*\"\n    steps: []\n",
+        )
+        .unwrap();
+
+        let flows = parse_event_flows(root).unwrap();
+        assert_eq!(flows.len(), 2);
+        assert_eq!(flows[0].steps, vec!["filter", "deliver"]);
+        assert_eq!(flows[0].sinks, vec!["in-app"]);
+        assert_eq!(flows[0].events_for("order"), vec!["created"]);
+        assert!(flows[0].events_for("invoice").is_empty());
+        assert_eq!(
+            flows[1].events_for("invoice"),
+            vec!["created", "updated", "deleted"]
+        );
+    }
+
+    // ── Validation tests ──────────────────────────────────────────
+
+    #[test]
+    fn test_parse_entity_validation() {
+        let content = r#"
+impl_data_entity_validated!(
+    Product,
+    "product",
+    ["name"],
+    {
+        price: f64,
+    },
+    validate: {
+        create: {
+            price: [required positive],
+            status: [required in_list("active", "inactive")],
+        },
+        update: {
+            price: [optional positive],
+        },
+    },
+    filters: {
+        create: {
+            price: [round_decimals(2)],
+        },
+    }
+);
+"#;
+        let validation = parse_entity_validation_content(content).unwrap();
+        assert_eq!(
+            validation.create["price"],
+            vec![ValidationRule::Required, ValidationRule::Positive]
+        );
+        assert_eq!(
+            validation.create["status"],
+            vec![
+                ValidationRule::Required,
+                ValidationRule::InList(vec!["active".to_string(), "inactive".to_string()])
+            ]
+        );
+        assert_eq!(
+            validation.update["price"],
+            vec![ValidationRule::Optional, ValidationRule::Positive]
+        );
+        assert_eq!(validation.update.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_entity_validation_plain_entity() {
+        let content = "impl_data_entity!(Tag, \"tag\", [], { label: String, });";
+        assert_eq!(parse_entity_validation_content(content), None);
+    }
 }
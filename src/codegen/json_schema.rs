@@ -0,0 +1,656 @@
+//! JSON Schema generation
+//!
+//! Generates JSON Schemas (draft 2020-12) for each entity — the entity itself
+//! plus its Create/Update payloads, with the constraints of
+//! `impl_data_entity_validated!` — and for the project config files, so
+//! editors can validate `this.yaml`, `links.yaml`, `events.yaml` and `auth.yaml`.
+
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use serde_json::{Map, Value, json};
+
+use crate::codegen::introspect::{
+    EntityMeta, EntityValidation, FieldMeta, OPERATIONS, ProjectIntrospection, ValidationRule,
+};
+use crate::codegen::typescript::{AUTO_FIELDS, InterfaceKind, split_generic_pair, strip_wrapper};
+use crate::config::TargetType;
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Sink types accepted in `events.yaml`
+const SINK_TYPES: &[&str] = &["in_app", "webhook", "push", "websocket", "counter"];
+
+/// Step operators accepted in `events.yaml` flows
+const FLOW_OPERATORS: &[&str] = &[
+    "filter",
+    "map",
+    "batch",
+    "deduplicate",
+    "rate_limit",
+    "fan_out",
+    "resolve",
+    "deliver",
+];
+
+/// Config files a schema is generated for, with the schema file name
+pub const CONFIG_SCHEMAS: &[(&str, &str)] = &[
+    ("this.yaml", "this.schema.json"),
+    ("links.yaml", "links.schema.json"),
+    ("events.yaml", "events.schema.json"),
+    ("auth.yaml", "auth.schema.json"),
+];
+
+// ── Public API ────────────────────────────────────────────────────────
+
+/// Generate the entity schemas: `(relative path, content)` pairs, three per entity.
+/// `validations` maps an entity snake_case name to its `validate:` block.
+pub fn generate_entities(
+    project: &ProjectIntrospection,
+    validations: &BTreeMap<String, EntityValidation>,
+) -> Vec<(String, String)> {
+    let mut files = Vec::new();
+    for entity in &project.entities {
+        let validation = validations.get(&entity.snake_name);
+        for (kind, suffix) in [
+            (InterfaceKind::Full, ""),
+            (InterfaceKind::Create, ".create"),
+            (InterfaceKind::Update, ".update"),
+        ] {
+            files.push((
+                format!("entities/{}{}.schema.json", entity.snake_name, suffix),
+                to_json(&entity_schema(entity, validation, kind)),
+            ));
+        }
+    }
+    files
+}
+
+/// Generate the config file schemas: `(relative path, content)` pairs.
+pub fn generate_config() -> Vec<(String, String)> {
+    CONFIG_SCHEMAS
+        .iter()
+        .map(|(file, schema_file)| {
+            let schema = match *file {
+                "this.yaml" => this_yaml_schema(),
+                "links.yaml" => links_yaml_schema(),
+                "events.yaml" => events_yaml_schema(),
+                _ => auth_yaml_schema(),
+            };
+            (format!("config/{}", schema_file), to_json(&schema))
+        })
+        .collect()
+}
+
+// ── Entity schemas ────────────────────────────────────────────────────
+
+/// Schema of an entity (`Full`) or of its create/update payload.
+pub fn entity_schema(
+    entity: &EntityMeta,
+    validation: Option<&EntityValidation>,
+    kind: InterfaceKind,
+) -> Value {
+    let rules = validation.map(|v| match kind {
+        InterfaceKind::Update => &v.update,
+        _ => &v.create,
+    });
+    let rules_for = |name: &str| -> &[ValidationRule] {
+        rules
+            .and_then(|r| r.get(name))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    };
+
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    if kind == InterfaceKind::Full {
+        properties.insert(
+            "id".to_string(),
+            json!({ "type": "string", "format": "uuid" }),
+        );
+        required.push("id".to_string());
+    }
+
+    for field in &entity.fields {
+        if kind != InterfaceKind::Full && AUTO_FIELDS.contains(&field.name.as_str()) {
+            continue;
+        }
+        let field_rules = rules_for(&field.name);
+        properties.insert(field.name.clone(), field_schema(field, field_rules));
+
+        let is_required = match kind {
+            InterfaceKind::Update => false,
+            _ => !is_option(field) || field_rules.contains(&ValidationRule::Required),
+        };
+        if is_required {
+            required.push(field.name.clone());
+        }
+    }
+
+    // Validators may target built-in fields (e.g. `status`) that the model does not declare
+    if kind != InterfaceKind::Full
+        && let Some(rules) = rules
+    {
+        for (name, field_rules) in rules {
+            if properties.contains_key(name) || AUTO_FIELDS.contains(&name.as_str()) {
+                continue;
+            }
+            let mut schema = Map::new();
+            apply_rules(&mut schema, field_rules);
+            if schema.contains_key("enum") {
+                schema.insert("type".to_string(), json!("string"));
+            }
+            properties.insert(name.clone(), Value::Object(schema));
+            if kind == InterfaceKind::Create && field_rules.contains(&ValidationRule::Required) {
+                required.push(name.clone());
+            }
+        }
+    }
+
+    if kind == InterfaceKind::Full {
+        for name in ["created_at", "updated_at"] {
+            properties.insert(
+                name.to_string(),
+                json!({ "type": "string", "format": "date-time" }),
+            );
+            required.push(name.to_string());
+        }
+    }
+
+    let title = match kind {
+        InterfaceKind::Full => entity.pascal_name.clone(),
+        InterfaceKind::Create => format!("Create{}", entity.pascal_name),
+        InterfaceKind::Update => format!("Update{}", entity.pascal_name),
+    };
+
+    let mut schema = Map::new();
+    schema.insert("$schema".to_string(), json!(SCHEMA_DIALECT));
+    schema.insert("title".to_string(), json!(title));
+    schema.insert("type".to_string(), json!("object"));
+    schema.insert("properties".to_string(), Value::Object(properties));
+    if !required.is_empty() {
+        schema.insert("required".to_string(), json!(required));
+    }
+    Value::Object(schema)
+}
+
+fn field_schema(field: &FieldMeta, rules: &[ValidationRule]) -> Value {
+    let mut schema = rust_type_to_schema(&field.rust_type);
+    if let Value::Object(map) = &mut schema {
+        apply_rules(map, rules);
+    }
+    schema
+}
+
+/// Translate validators into JSON Schema keywords. `required`/`optional` are
+/// handled by the object's `required` list, unknown validators are ignored.
+fn apply_rules(schema: &mut Map<String, Value>, rules: &[ValidationRule]) {
+    for rule in rules {
+        match rule {
+            ValidationRule::Positive => {
+                schema.insert("exclusiveMinimum".to_string(), json!(0));
+            }
+            ValidationRule::InList(values) => {
+                schema.insert("enum".to_string(), json!(values));
+            }
+            ValidationRule::Required | ValidationRule::Optional | ValidationRule::Other(_) => {}
+        }
+    }
+}
+
+fn is_option(field: &FieldMeta) -> bool {
+    field.rust_type.trim().starts_with("Option<")
+}
+
+/// Convert a Rust type string to a JSON Schema. Unknown types accept any value.
+pub fn rust_type_to_schema(rust_type: &str) -> Value {
+    let trimmed = rust_type.trim();
+
+    if let Some(inner) = strip_wrapper(trimmed, "Option<", ">") {
+        let mut schema = rust_type_to_schema(inner);
+        return match schema.get("type").cloned() {
+            Some(Value::String(ty)) => {
+                schema["type"] = json!([ty, "null"]);
+                schema
+            }
+            _ if schema.as_object().is_some_and(Map::is_empty) => schema,
+            _ => json!({ "anyOf": [schema, { "type": "null" }] }),
+        };
+    }
+
+    if let Some(inner) = strip_wrapper(trimmed, "Vec<", ">") {
+        return json!({ "type": "array", "items": rust_type_to_schema(inner) });
+    }
+
+    for map in ["HashMap<", "BTreeMap<"] {
+        if let Some(inner) = strip_wrapper(trimmed, map, ">")
+            && let Some((_, v)) = split_generic_pair(inner)
+        {
+            return json!({ "type": "object", "additionalProperties": rust_type_to_schema(v) });
+        }
+    }
+
+    match trimmed {
+        "String" | "&str" => json!({ "type": "string" }),
+        "Uuid" | "uuid::Uuid" => json!({ "type": "string", "format": "uuid" }),
+        "f32" | "f64" => json!({ "type": "number" }),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => json!({ "type": "integer" }),
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => {
+            json!({ "type": "integer", "minimum": 0 })
+        }
+        "bool" => json!({ "type": "boolean" }),
+        "DateTime<Utc>" | "chrono::DateTime<Utc>" | "NaiveDateTime" => {
+            json!({ "type": "string", "format": "date-time" })
+        }
+        _ => json!({}),
+    }
+}
+
+// ── Config schemas ────────────────────────────────────────────────────
+
+fn config_schema(title: &str, body: Value) -> Value {
+    let mut schema = Map::new();
+    schema.insert("$schema".to_string(), json!(SCHEMA_DIALECT));
+    schema.insert("title".to_string(), json!(title));
+    if let Value::Object(body) = body {
+        schema.extend(body);
+    }
+    Value::Object(schema)
+}
+
+/// An auth policy: built-in, role-based, resolver or composed statement
+fn policy_schema() -> Value {
+    json!({
+        "type": "string",
+        "description": "public, authenticated, owner, admin_only, service_only, role:<role>, resolver:<name>, or an all:/any:/not: statement",
+        "examples": ["public", "authenticated", "owner", "admin_only", "role:editor"]
+    })
+}
+
+/// Per-operation policies, optionally with the `read`/`write` shorthands
+fn operations_schema(shorthands: bool) -> Value {
+    let mut ops: Vec<&str> = OPERATIONS.to_vec();
+    if shorthands {
+        ops.extend(["read", "write"]);
+    }
+    let properties: Map<String, Value> = ops
+        .into_iter()
+        .map(|op| (op.to_string(), policy_schema()))
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false
+    })
+}
+
+fn this_yaml_schema() -> Value {
+    let target_types: Vec<String> = TargetType::value_variants()
+        .iter()
+        .map(|t| t.to_string())
+        .collect();
+    config_schema(
+        "this.yaml",
+        json!({
+            "type": "object",
+            "required": ["name", "api"],
+            "properties": {
+                "name": { "type": "string", "description": "Workspace name" },
+                "api": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "default": "api" },
+                        "port": { "type": "integer", "minimum": 1, "maximum": 65535, "default": 3000 }
+                    }
+                },
+                "targets": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["target_type", "path"],
+                        "properties": {
+                            "target_type": { "enum": target_types },
+                            "framework": { "type": "string", "examples": ["react", "vue", "svelte"] },
+                            "runtime": { "type": "string", "examples": ["tauri", "capacitor"] },
+                            "path": { "type": "string" }
+                        }
+                    }
+                },
+                "templates": {
+                    "type": "string",
+                    "description": "Directory of template overrides, relative to the workspace root"
+                }
+            }
+        }),
+    )
+}
+
+fn links_yaml_schema() -> Value {
+    config_schema(
+        "links.yaml",
+        json!({
+            "type": "object",
+            "properties": {
+                "entities": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["singular", "plural"],
+                        "properties": {
+                            "singular": { "type": "string" },
+                            "plural": { "type": "string" },
+                            "auth": operations_schema(false)
+                        }
+                    }
+                },
+                "links": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": [
+                            "link_type",
+                            "source_type",
+                            "target_type",
+                            "forward_route_name",
+                            "reverse_route_name"
+                        ],
+                        "properties": {
+                            "link_type": { "type": "string" },
+                            "source_type": { "type": "string" },
+                            "target_type": { "type": "string" },
+                            "forward_route_name": { "type": "string" },
+                            "reverse_route_name": { "type": "string" },
+                            "description": { "type": "string" },
+                            "auth": operations_schema(false)
+                        }
+                    }
+                },
+                "validation_rules": {
+                    "type": "object",
+                    "description": "Allowed source/target pairs per link type",
+                    "additionalProperties": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["source", "targets"],
+                            "properties": {
+                                "source": { "type": "string" },
+                                "targets": { "type": "array", "items": { "type": "string" } }
+                            }
+                        }
+                    }
+                }
+            }
+        }),
+    )
+}
+
+fn events_yaml_schema() -> Value {
+    config_schema(
+        "events.yaml",
+        json!({
+            "type": "object",
+            "properties": {
+                "event_sinks": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "type"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "type": { "enum": SINK_TYPES },
+                            "url": { "type": "string", "format": "uri" }
+                        }
+                    }
+                },
+                "event_flows": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "trigger"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "trigger": {
+                                "type": "string",
+                                "description": "entity.<created|updated|deleted>.<type>, `*` matches anything",
+                                "examples": ["entity.created.order", "entity.*.*"]
+                            },
+                            "steps": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "required": ["type"],
+                                    "properties": {
+                                        "type": { "enum": FLOW_OPERATORS },
+                                        "condition": { "type": "string" },
+                                        "sink": { "type": "string" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }),
+    )
+}
+
+fn auth_yaml_schema() -> Value {
+    config_schema(
+        "auth.yaml",
+        json!({
+            "type": "object",
+            "properties": {
+                "provider": { "enum": ["wami", "oidc", "none"] },
+                "default_policy": policy_schema(),
+                "wami": {
+                    "type": "object",
+                    "properties": {
+                        "mode": { "enum": ["bootstrap", "embedded", "sts"] },
+                        "public_key": { "type": "string" },
+                        "token_ttl_secs": { "type": "integer", "minimum": 1 },
+                        "refresh_ttl_secs": { "type": "integer", "minimum": 1 }
+                    }
+                },
+                "entities": {
+                    "type": "object",
+                    "description": "Per-entity policy overrides",
+                    "additionalProperties": operations_schema(true)
+                },
+                "tenant": {
+                    "type": "object",
+                    "properties": {
+                        "enabled": { "type": "boolean" },
+                        "claim_field": { "type": "string" },
+                        "auto_provision": { "type": "boolean" }
+                    }
+                },
+                "gdpr": {
+                    "type": "object",
+                    "properties": {
+                        "erasure_cascade": { "type": "boolean" },
+                        "audit_sink": { "type": "string" }
+                    }
+                },
+                "events": {
+                    "type": "object",
+                    "properties": {
+                        "tenant_isolation": { "type": "boolean" },
+                        "cognitive_bridge": { "type": "boolean" },
+                        "cognitive_notifications": { "type": "boolean" }
+                    }
+                }
+            }
+        }),
+    )
+}
+
+fn to_json(schema: &Value) -> String {
+    let mut out = serde_json::to_string_pretty(schema).expect("JSON values always serialize");
+    out.push('\n');
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, rust_type: &str) -> FieldMeta {
+        FieldMeta {
+            name: name.to_string(),
+            rust_type: rust_type.to_string(),
+        }
+    }
+
+    fn product() -> EntityMeta {
+        EntityMeta {
+            pascal_name: "Product".to_string(),
+            snake_name: "product".to_string(),
+            plural: "products".to_string(),
+            indexed_fields: vec![],
+            fields: vec![field("price", "f64"), field("notes", "Option<String>")],
+            routes: vec![],
+        }
+    }
+
+    fn validation() -> EntityValidation {
+        EntityValidation {
+            create: BTreeMap::from([
+                (
+                    "price".to_string(),
+                    vec![ValidationRule::Required, ValidationRule::Positive],
+                ),
+                (
+                    "status".to_string(),
+                    vec![
+                        ValidationRule::Required,
+                        ValidationRule::InList(vec!["active".to_string(), "inactive".to_string()]),
+                    ],
+                ),
+            ]),
+            update: BTreeMap::from([(
+                "price".to_string(),
+                vec![ValidationRule::Optional, ValidationRule::Positive],
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_rust_type_to_schema() {
+        assert_eq!(rust_type_to_schema("String"), json!({ "type": "string" }));
+        assert_eq!(
+            rust_type_to_schema("u32"),
+            json!({ "type": "integer", "minimum": 0 })
+        );
+        assert_eq!(
+            rust_type_to_schema("Option<Uuid>"),
+            json!({ "type": ["string", "null"], "format": "uuid" })
+        );
+        assert_eq!(
+            rust_type_to_schema("Vec<bool>"),
+            json!({ "type": "array", "items": { "type": "boolean" } })
+        );
+        assert_eq!(
+            rust_type_to_schema("HashMap<String, i64>"),
+            json!({ "type": "object", "additionalProperties": { "type": "integer" } })
+        );
+        assert_eq!(rust_type_to_schema("serde_json::Value"), json!({}));
+        assert_eq!(rust_type_to_schema("Option<Value>"), json!({}));
+    }
+
+    #[test]
+    fn test_entity_schema_full() {
+        let schema = entity_schema(&product(), None, InterfaceKind::Full);
+        assert_eq!(schema["$schema"], SCHEMA_DIALECT);
+        assert_eq!(schema["title"], "Product");
+        assert_eq!(schema["properties"]["id"]["format"], "uuid");
+        assert_eq!(
+            schema["properties"]["notes"]["type"],
+            json!(["string", "null"])
+        );
+        assert_eq!(
+            schema["required"],
+            json!(["id", "price", "created_at", "updated_at"])
+        );
+    }
+
+    #[test]
+    fn test_entity_schema_create_with_validation() {
+        let validation = validation();
+        let schema = entity_schema(&product(), Some(&validation), InterfaceKind::Create);
+        assert_eq!(schema["title"], "CreateProduct");
+        assert!(schema["properties"].get("id").is_none());
+        assert_eq!(schema["properties"]["price"]["exclusiveMinimum"], 0);
+        assert_eq!(
+            schema["properties"]["status"],
+            json!({ "type": "string", "enum": ["active", "inactive"] })
+        );
+        assert_eq!(schema["required"], json!(["price", "status"]));
+    }
+
+    #[test]
+    fn test_entity_schema_update_has_no_required() {
+        let validation = validation();
+        let schema = entity_schema(&product(), Some(&validation), InterfaceKind::Update);
+        assert_eq!(schema["title"], "UpdateProduct");
+        assert!(schema.get("required").is_none());
+        assert_eq!(schema["properties"]["price"]["exclusiveMinimum"], 0);
+        assert!(schema["properties"].get("status").is_none());
+    }
+
+    #[test]
+    fn test_generate_entities_file_names() {
+        let project = ProjectIntrospection {
+            entities: vec![product()],
+            links: vec![],
+        };
+        let files = generate_entities(&project, &BTreeMap::new());
+        let names: Vec<&str> = files.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "entities/product.schema.json",
+                "entities/product.create.schema.json",
+                "entities/product.update.schema.json"
+            ]
+        );
+        let parsed: Value = serde_json::from_str(&files[0].1).unwrap();
+        assert_eq!(parsed["title"], "Product");
+    }
+
+    #[test]
+    fn test_config_schemas() {
+        let files = generate_config();
+        assert_eq!(files.len(), 4);
+        let schema = |name: &str| -> Value {
+            let (_, content) = files.iter().find(|(n, _)| n.ends_with(name)).unwrap();
+            serde_json::from_str(content).unwrap()
+        };
+
+        let this = schema("this.schema.json");
+        assert_eq!(
+            this["properties"]["targets"]["items"]["properties"]["target_type"]["enum"],
+            json!(["webapp", "website", "desktop", "ios", "android"])
+        );
+        let events = schema("events.schema.json");
+        assert_eq!(
+            events["properties"]["event_sinks"]["items"]["properties"]["type"]["enum"][0],
+            "in_app"
+        );
+        let auth = schema("auth.schema.json");
+        assert!(
+            auth["properties"]["entities"]["additionalProperties"]["properties"]
+                .get("write")
+                .is_some()
+        );
+        let links = schema("links.schema.json");
+        assert!(
+            links["properties"]["entities"]["items"]["properties"]["auth"]["properties"]
+                .get("write")
+                .is_none()
+        );
+    }
+}
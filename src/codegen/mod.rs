@@ -1,2 +1,7 @@
+pub mod diagram;
+pub mod docs;
+pub mod graphql;
 pub mod introspect;
+pub mod json_schema;
+pub mod proto;
 pub mod typescript;
@@ -0,0 +1,394 @@
+//! Protobuf package generation
+//!
+//! Generates a proto3 file from project introspection data, with one service
+//! per entity (CRUD plus link traversal), so gRPC clients can be generated with
+//! protoc or buf without starting the server.
+
+use crate::codegen::introspect::{EntityMeta, FieldMeta, LinkMeta, ProjectIntrospection};
+use crate::codegen::typescript::{AUTO_FIELDS, split_generic_pair, strip_wrapper};
+use crate::utils::naming;
+
+// ── Public API ────────────────────────────────────────────────────────
+
+/// Proto package name for a project, e.g. `my-shop` → `my_shop.v1`
+pub fn package_name(project_name: &str) -> String {
+    format!(
+        "{}.v1",
+        naming::to_snake_case(project_name).replace('-', "_")
+    )
+}
+
+/// Generate a complete proto3 file from project introspection.
+pub fn generate(project: &ProjectIntrospection, package: &str) -> String {
+    let mut out = String::new();
+
+    out.push_str(
+        "// Auto-generated by `this generate proto` — do not edit manually.\n\
+         // Re-run `this generate proto` to regenerate after adding entities or links.\n\n",
+    );
+    out.push_str("syntax = \"proto3\";\n\n");
+    out.push_str(&format!("package {};\n", package));
+    if uses_struct_types(project) {
+        out.push_str("\nimport \"google/protobuf/struct.proto\";\n");
+    }
+    out.push('\n');
+
+    for entity in &project.entities {
+        out.push_str(&generate_entity_section(entity, project));
+        out.push('\n');
+    }
+
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+// ── Type mapping ──────────────────────────────────────────────────────
+
+/// Convert a Rust type string to a proto3 field type, including its label
+/// (`optional`, `repeated` or a `map<K, V>`).
+pub fn rust_type_to_proto(rust_type: &str) -> String {
+    let trimmed = rust_type.trim();
+
+    if let Some(inner) = strip_wrapper(trimmed, "Option<", ">") {
+        let inner_proto = rust_type_to_proto(inner);
+        // repeated and map fields already have presence semantics
+        if inner_proto.starts_with("repeated ") || inner_proto.starts_with("map<") {
+            return inner_proto;
+        }
+        if is_scalar(&inner_proto) {
+            return format!("optional {}", inner_proto);
+        }
+        return inner_proto;
+    }
+
+    if let Some(inner) = strip_wrapper(trimmed, "Vec<", ">") {
+        let inner = strip_wrapper(inner.trim(), "Option<", ">").unwrap_or(inner);
+        let inner_proto = rust_type_to_proto(inner);
+        if inner_proto.starts_with("repeated ") || inner_proto.starts_with("map<") {
+            return "google.protobuf.ListValue".to_string();
+        }
+        return format!("repeated {}", inner_proto);
+    }
+
+    if let Some(inner) = strip_wrapper(trimmed, "HashMap<", ">")
+        && let Some((k, v)) = split_generic_pair(inner)
+    {
+        let key = rust_type_to_proto(k);
+        let value = rust_type_to_proto(v);
+        // Map keys must be integral or string; values cannot be labelled
+        let valid_key = is_scalar(&key) && !matches!(key.as_str(), "double" | "float" | "bytes");
+        let valid_value = !value.contains(' ') && !value.starts_with("map<");
+        if valid_key && valid_value {
+            return format!("map<{}, {}>", key, value);
+        }
+        return "google.protobuf.Struct".to_string();
+    }
+
+    match trimmed {
+        "String" | "&str" | "Uuid" | "uuid::Uuid" => "string".to_string(),
+        "f64" => "double".to_string(),
+        "f32" => "float".to_string(),
+        "i8" | "i16" | "i32" => "int32".to_string(),
+        "i64" | "isize" => "int64".to_string(),
+        "u8" | "u16" | "u32" => "uint32".to_string(),
+        "u64" | "usize" => "uint64".to_string(),
+        // No 128-bit integers in protobuf: carried as decimal strings
+        "i128" | "u128" => "string".to_string(),
+        "bool" => "bool".to_string(),
+        "DateTime<Utc>" | "chrono::DateTime<Utc>" | "NaiveDateTime" => "string".to_string(), // RFC 3339
+        _ => "google.protobuf.Value".to_string(),
+    }
+}
+
+fn is_scalar(proto_type: &str) -> bool {
+    matches!(
+        proto_type,
+        "string" | "bytes" | "bool" | "double" | "float" | "int32" | "int64" | "uint32" | "uint64"
+    )
+}
+
+fn uses_struct_types(project: &ProjectIntrospection) -> bool {
+    project
+        .entities
+        .iter()
+        .flat_map(|e| &e.fields)
+        .any(|f| rust_type_to_proto(&f.rust_type).contains("google.protobuf."))
+}
+
+// ── Code generation sections ──────────────────────────────────────────
+
+/// Custom fields, without the ones every entity already exposes
+fn data_fields(fields: &[FieldMeta]) -> impl Iterator<Item = &FieldMeta> {
+    fields
+        .iter()
+        .filter(|f| !AUTO_FIELDS.contains(&f.name.as_str()))
+}
+
+fn generate_entity_section(entity: &EntityMeta, project: &ProjectIntrospection) -> String {
+    let pascal = &entity.pascal_name;
+    let plural_pascal = naming::to_pascal_case(&entity.plural);
+    let fields: Vec<&FieldMeta> = data_fields(&entity.fields).collect();
+    let links: Vec<(&LinkMeta, &EntityMeta)> = project
+        .links
+        .iter()
+        .filter(|l| l.source == entity.snake_name && !l.forward_route.is_empty())
+        .filter_map(|l| {
+            project
+                .entities
+                .iter()
+                .find(|e| e.snake_name == l.target)
+                .map(|target| (l, target))
+        })
+        .collect();
+
+    let mut out = format!(
+        "// ── {} ────────────────────────────────────────────────\n\n",
+        pascal
+    );
+
+    // Entity message
+    out.push_str(&format!("message {} {{\n  string id = 1;\n", pascal));
+    let mut tag = 2;
+    for field in &fields {
+        out.push_str(&format!(
+            "  {} {} = {};\n",
+            rust_type_to_proto(&field.rust_type),
+            field.name,
+            tag
+        ));
+        tag += 1;
+    }
+    out.push_str(&format!(
+        "  string created_at = {};\n  string updated_at = {};\n}}\n\n",
+        tag,
+        tag + 1
+    ));
+
+    // Requests and responses
+    out.push_str(&format!(
+        "message Get{pascal}Request {{\n  string id = 1;\n}}\n\n\
+         message List{plural_pascal}Request {{\n  uint32 limit = 1;\n  uint32 offset = 2;\n}}\n\n\
+         message List{plural_pascal}Response {{\n  repeated {pascal} items = 1;\n  uint32 total = 2;\n}}\n\n"
+    ));
+
+    out.push_str(&format!("message Create{}Request {{\n", pascal));
+    for (i, field) in fields.iter().enumerate() {
+        out.push_str(&format!(
+            "  {} {} = {};\n",
+            rust_type_to_proto(&field.rust_type),
+            field.name,
+            i + 1
+        ));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str(&format!(
+        "message Update{}Request {{\n  string id = 1;\n",
+        pascal
+    ));
+    for (i, field) in fields.iter().enumerate() {
+        let ty = rust_type_to_proto(&field.rust_type);
+        // Every update field is optional: unset fields are left unchanged
+        let ty = if is_scalar(&ty) {
+            format!("optional {}", ty)
+        } else {
+            ty
+        };
+        out.push_str(&format!("  {} {} = {};\n", ty, field.name, i + 2));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str(&format!(
+        "message Delete{pascal}Request {{\n  string id = 1;\n}}\n\n\
+         message Delete{pascal}Response {{\n  bool deleted = 1;\n}}\n\n"
+    ));
+
+    for (link, target) in &links {
+        let route = naming::to_pascal_case(&link.forward_route);
+        out.push_str(&format!(
+            "message List{pascal}{route}Request {{\n  string {source}_id = 1;\n  uint32 limit = 2;\n  uint32 offset = 3;\n}}\n\n\
+             message List{pascal}{route}Response {{\n  repeated {target} items = 1;\n  uint32 total = 2;\n}}\n\n",
+            source = link.source,
+            target = target.pascal_name,
+        ));
+    }
+
+    // Service
+    out.push_str(&format!(
+        "service {pascal}Service {{\n  \
+         rpc Get{pascal}(Get{pascal}Request) returns ({pascal});\n  \
+         rpc List{plural_pascal}(List{plural_pascal}Request) returns (List{plural_pascal}Response);\n  \
+         rpc Create{pascal}(Create{pascal}Request) returns ({pascal});\n  \
+         rpc Update{pascal}(Update{pascal}Request) returns ({pascal});\n  \
+         rpc Delete{pascal}(Delete{pascal}Request) returns (Delete{pascal}Response);\n"
+    ));
+    for (link, _) in &links {
+        let route = naming::to_pascal_case(&link.forward_route);
+        out.push_str(&format!(
+            "  rpc List{pascal}{route}(List{pascal}{route}Request) returns (List{pascal}{route}Response);\n"
+        ));
+    }
+    out.push_str("}\n");
+
+    out
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, rust_type: &str) -> FieldMeta {
+        FieldMeta {
+            name: name.to_string(),
+            rust_type: rust_type.to_string(),
+        }
+    }
+
+    fn entity(pascal: &str, snake: &str, plural: &str, fields: Vec<FieldMeta>) -> EntityMeta {
+        EntityMeta {
+            pascal_name: pascal.to_string(),
+            snake_name: snake.to_string(),
+            plural: plural.to_string(),
+            indexed_fields: vec![],
+            fields,
+            routes: vec![],
+        }
+    }
+
+    fn shop() -> ProjectIntrospection {
+        ProjectIntrospection {
+            entities: vec![
+                entity(
+                    "Product",
+                    "product",
+                    "products",
+                    vec![
+                        field("name", "String"),
+                        field("price", "f64"),
+                        field("description", "Option<String>"),
+                    ],
+                ),
+                entity("Review", "review", "reviews", vec![field("rating", "i32")]),
+            ],
+            links: vec![LinkMeta {
+                link_type: "has_review".to_string(),
+                source: "product".to_string(),
+                target: "review".to_string(),
+                forward_route: "reviews".to_string(),
+                reverse_route: "product".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("my-shop"), "my_shop.v1");
+        assert_eq!(package_name("shop"), "shop.v1");
+    }
+
+    #[test]
+    fn test_rust_type_to_proto() {
+        assert_eq!(rust_type_to_proto("String"), "string");
+        assert_eq!(rust_type_to_proto("Uuid"), "string");
+        assert_eq!(rust_type_to_proto("f64"), "double");
+        assert_eq!(rust_type_to_proto("f32"), "float");
+        assert_eq!(rust_type_to_proto("i32"), "int32");
+        assert_eq!(rust_type_to_proto("i64"), "int64");
+        assert_eq!(rust_type_to_proto("u64"), "uint64");
+        assert_eq!(rust_type_to_proto("bool"), "bool");
+        assert_eq!(rust_type_to_proto("DateTime<Utc>"), "string");
+        assert_eq!(rust_type_to_proto("Option<String>"), "optional string");
+        assert_eq!(rust_type_to_proto("Vec<String>"), "repeated string");
+        assert_eq!(rust_type_to_proto("Option<Vec<f64>>"), "repeated double");
+        assert_eq!(rust_type_to_proto("Vec<Option<i32>>"), "repeated int32");
+        assert_eq!(
+            rust_type_to_proto("HashMap<String, f64>"),
+            "map<string, double>"
+        );
+        assert_eq!(
+            rust_type_to_proto("HashMap<String, Vec<f64>>"),
+            "google.protobuf.Struct"
+        );
+        assert_eq!(
+            rust_type_to_proto("Vec<Vec<i32>>"),
+            "google.protobuf.ListValue"
+        );
+        assert_eq!(
+            rust_type_to_proto("serde_json::Value"),
+            "google.protobuf.Value"
+        );
+        assert_eq!(
+            rust_type_to_proto("Option<MyCustomType>"),
+            "google.protobuf.Value"
+        );
+    }
+
+    #[test]
+    fn test_generate_messages_and_service() {
+        let proto = generate(&shop(), "shop.v1");
+
+        assert!(proto.contains("syntax = \"proto3\";"));
+        assert!(proto.contains("package shop.v1;"));
+        assert!(!proto.contains("import \"google/protobuf/struct.proto\";"));
+        assert!(proto.contains(
+            "message Product {\n  string id = 1;\n  string name = 2;\n  double price = 3;\n  optional string description = 4;\n  string created_at = 5;\n  string updated_at = 6;\n}"
+        ));
+        assert!(proto.contains("message CreateProductRequest {\n  string name = 1;\n"));
+        assert!(proto.contains(
+            "message UpdateProductRequest {\n  string id = 1;\n  optional string name = 2;\n  optional double price = 3;\n  optional string description = 4;\n}"
+        ));
+        assert!(proto.contains("message ListProductsResponse {\n  repeated Product items = 1;"));
+        assert!(proto.contains("service ProductService {"));
+        assert!(proto.contains("  rpc GetProduct(GetProductRequest) returns (Product);"));
+        assert!(
+            proto.contains(
+                "  rpc ListProducts(ListProductsRequest) returns (ListProductsResponse);"
+            )
+        );
+        assert!(proto.contains(
+            "  rpc DeleteProduct(DeleteProductRequest) returns (DeleteProductResponse);"
+        ));
+        assert!(proto.contains("service ReviewService {"));
+    }
+
+    #[test]
+    fn test_generate_link_rpcs_on_source_service() {
+        let proto = generate(&shop(), "shop.v1");
+
+        assert!(proto.contains("message ListProductReviewsRequest {\n  string product_id = 1;\n"));
+        assert!(
+            proto.contains("message ListProductReviewsResponse {\n  repeated Review items = 1;")
+        );
+        assert!(proto.contains(
+            "  rpc ListProductReviews(ListProductReviewsRequest) returns (ListProductReviewsResponse);"
+        ));
+    }
+
+    #[test]
+    fn test_generate_skips_links_to_unknown_entities() {
+        let mut project = shop();
+        project.links[0].target = "user".to_string();
+        let proto = generate(&project, "shop.v1");
+        assert!(!proto.contains("ListProductReviews"));
+    }
+
+    #[test]
+    fn test_generate_imports_struct_proto_when_needed() {
+        let project = ProjectIntrospection {
+            entities: vec![entity(
+                "Setting",
+                "setting",
+                "settings",
+                vec![field("value", "serde_json::Value")],
+            )],
+            links: vec![],
+        };
+        let proto = generate(&project, "cfg.v1");
+        assert!(proto.contains("import \"google/protobuf/struct.proto\";"));
+        assert!(proto.contains("  google.protobuf.Value value = 2;"));
+        assert!(proto.ends_with("}\n"));
+    }
+}
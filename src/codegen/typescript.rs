@@ -1,28 +1,40 @@
 //! TypeScript API client code generation
 //!
 //! Generates a self-contained `api-client.ts` file from project introspection data.
-//! The generated file uses native `fetch()` with no external dependencies.
+//! The generated file uses native `fetch()` through a configurable `createClient()`
+//! runtime; zod schemas and TanStack Query hooks are optional extras (see [`ClientOptions`]).
 
-use crate::codegen::introspect::{EntityMeta, LinkMeta, ProjectIntrospection};
+use crate::codegen::introspect::{EntityMeta, FieldMeta, LinkMeta, ProjectIntrospection};
 use crate::utils::naming;
 
+/// Runtime features and optional extras of the generated client
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClientOptions {
+    /// Send bearer tokens and refresh them on 401 (the project has `config/auth.yaml`)
+    pub auth: bool,
+    /// Emit a zod schema per entity payload and validate responses with them
+    pub zod: bool,
+    /// Emit TanStack Query hooks with cache keys per entity and link
+    pub react_query: bool,
+}
+
 // ── Public API ────────────────────────────────────────────────────────
 
 /// Generate a complete TypeScript API client file from project introspection.
-pub fn generate(project: &ProjectIntrospection) -> String {
+pub fn generate(project: &ProjectIntrospection, options: &ClientOptions) -> String {
     let mut out = String::new();
 
     // Header
-    out.push_str(&generate_header());
+    out.push_str(&generate_header(options));
     out.push('\n');
 
-    // Helper function
-    out.push_str(&generate_fetch_helper());
+    // Client runtime
+    out.push_str(&generate_runtime(options));
     out.push('\n');
 
     // Interfaces + CRUD for each entity
     for entity in &project.entities {
-        out.push_str(&generate_entity_section(entity));
+        out.push_str(&generate_entity_section(entity, project, options));
         out.push('\n');
     }
 
@@ -30,7 +42,7 @@ pub fn generate(project: &ProjectIntrospection) -> String {
     if !project.links.is_empty() {
         out.push_str("// ── Link functions ────────────────────────────────────────────────\n\n");
         for link in &project.links {
-            out.push_str(&generate_link_function(link, &project.entities));
+            out.push_str(&generate_link_function(link, &project.entities, options));
         }
     }
 
@@ -77,8 +89,41 @@ pub fn rust_type_to_ts(rust_type: &str) -> String {
     }
 }
 
+/// Convert a Rust type string to a zod schema expression.
+/// Unknown types are trusted as-is with `z.custom<T>()`, like `rust_type_to_ts`.
+pub fn rust_type_to_zod(rust_type: &str) -> String {
+    let trimmed = rust_type.trim();
+
+    if let Some(inner) = strip_wrapper(trimmed, "Option<", ">") {
+        return format!("{}.nullable()", rust_type_to_zod(inner));
+    }
+
+    if let Some(inner) = strip_wrapper(trimmed, "Vec<", ">") {
+        return format!("z.array({})", rust_type_to_zod(inner));
+    }
+
+    // JSON object keys are always strings
+    if let Some(inner) = strip_wrapper(trimmed, "HashMap<", ">")
+        && let Some((_, v)) = split_generic_pair(inner)
+    {
+        return format!("z.record(z.string(), {})", rust_type_to_zod(v));
+    }
+
+    match trimmed {
+        "String" | "&str" => "z.string()".to_string(),
+        "Uuid" | "uuid::Uuid" => "z.string().uuid()".to_string(),
+        "f32" | "f64" => "z.number()".to_string(),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "u8" | "u16" | "u32" | "u64" | "u128" | "isize"
+        | "usize" => "z.number().int()".to_string(),
+        "bool" => "z.boolean()".to_string(),
+        "DateTime<Utc>" | "chrono::DateTime<Utc>" | "NaiveDateTime" => "z.string()".to_string(),
+        "Value" | "serde_json::Value" | "JsonValue" => "z.unknown()".to_string(),
+        _ => format!("z.custom<{}>()", trimmed),
+    }
+}
+
 /// Strip a wrapper type, e.g. `strip_wrapper("Option<String>", "Option<", ">")` → Some("String")
-fn strip_wrapper<'a>(s: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
+pub(crate) fn strip_wrapper<'a>(s: &'a str, prefix: &str, suffix: &str) -> Option<&'a str> {
     if s.starts_with(prefix) && s.ends_with(suffix) {
         Some(&s[prefix.len()..s.len() - suffix.len()])
     } else {
@@ -87,7 +132,7 @@ fn strip_wrapper<'a>(s: &'a str, prefix: &str, suffix: &str) -> Option<&'a str>
 }
 
 /// Split `K, V` inside a generic, respecting nested angle brackets.
-fn split_generic_pair(s: &str) -> Option<(&str, &str)> {
+pub(crate) fn split_generic_pair(s: &str) -> Option<(&str, &str)> {
     let mut depth = 0;
     for (i, c) in s.char_indices() {
         match c {
@@ -104,32 +149,222 @@ fn split_generic_pair(s: &str) -> Option<(&str, &str)> {
 
 // ── Code generation sections ──────────────────────────────────────────
 
-fn generate_header() -> String {
-    r#"// Auto-generated by `this generate client` — do not edit manually.
-// Re-run `this generate client` to regenerate after adding entities or links.
+fn generate_header(options: &ClientOptions) -> String {
+    let mut out = String::from(
+        "// Auto-generated by `this generate client` — do not edit manually.\n\
+         // Re-run `this generate client` to regenerate after adding entities or links.\n",
+    );
+    if options.zod || options.react_query {
+        out.push('\n');
+    }
+    if options.zod {
+        out.push_str("import { z } from 'zod';\n");
+    }
+    if options.react_query {
+        out.push_str(
+            "import { type QueryClient, useMutation, useQuery, useQueryClient } from '@tanstack/react-query';\n",
+        );
+    }
+    out
+}
+
+/// Client runtime: `ApiError`, `createClient()`, the default client and `fetchJson`.
+/// With `auth`, requests carry a bearer token and a 401 triggers one `/auth/refresh`.
+fn generate_runtime(options: &ClientOptions) -> String {
+    let mut out = String::from(
+        r#"type MaybePromise<T> = T | Promise<T>;
+
+/** Error thrown for every non-2xx response */
+export class ApiError extends Error {
+  readonly status: number;
+  readonly statusText: string;
+  /** Parsed JSON body, or raw text */
+  readonly body: unknown;
+
+  constructor(status: number, statusText: string, body: unknown) {
+    super(`API error ${status}: ${statusText}`);
+    this.name = 'ApiError';
+    this.status = status;
+    this.statusText = statusText;
+    this.body = body;
+  }
+}
+"#,
+    );
+
+    if options.auth {
+        out.push_str(
+            r#"
+/** Tokens returned by `POST /auth/refresh` */
+export interface AuthTokens {
+  access_token: string;
+  refresh_token?: string;
+  expires_in?: number;
+}
+"#,
+        );
+    }
+
+    out.push_str(
+        r#"
+export interface ClientConfig {
+  /** API origin (default: VITE_API_URL when bundled by Vite, else same origin) */
+  baseUrl?: string;
+"#,
+    );
+    if options.auth {
+        out.push_str(
+            r#"  /** Access token sent as `Authorization: Bearer` */
+  getToken?: () => MaybePromise<string | null | undefined>;
+  /** Refresh token sent to `POST /auth/refresh` after a 401 */
+  getRefreshToken?: () => MaybePromise<string | null | undefined>;
+  /** Receives the tokens issued by `/auth/refresh`, to store them */
+  onTokenRefresh?: (tokens: AuthTokens) => MaybePromise<void>;
+"#,
+        );
+    }
+    out.push_str(
+        r#"  /** fetch implementation (default: the global fetch) */
+  fetch?: typeof fetch;
+  /** Called with every ApiError before it is thrown */
+  onError?: (error: ApiError) => void;
+}
+
+export interface ApiClient {
+  request<T>(path: string, init?: RequestInit): Promise<T>;
+}
 
-const API_BASE = import.meta.env.VITE_API_URL || '';
-"#
-    .to_string()
+export interface RequestOptions {
+  /** Abort the request */
+  signal?: AbortSignal;
+  /** Client to send the request with (default: the one set by `configureClient`) */
+  client?: ApiClient;
 }
 
-fn generate_fetch_helper() -> String {
-    r#"async function fetchJson<T>(path: string, options?: RequestInit): Promise<T> {
-  const res = await fetch(`${API_BASE}${path}`, {
-    headers: { 'Content-Type': 'application/json', ...options?.headers },
-    ...options,
-  });
-  if (!res.ok) {
-    throw new Error(`API error ${res.status}: ${res.statusText}`);
+function defaultBaseUrl(): string {
+  return (import.meta as { env?: Record<string, string | undefined> }).env?.VITE_API_URL ?? '';
+}
+
+async function readBody(res: Response): Promise<unknown> {
+  const text = await res.text();
+  try {
+    return JSON.parse(text);
+  } catch {
+    return text;
   }
-  if (res.status === 204) return undefined as unknown as T;
-  return res.json();
 }
-"#
-    .to_string()
+
+export function createClient(config: ClientConfig = {}): ApiClient {
+  const baseUrl = config.baseUrl ?? defaultBaseUrl();
+  const fetchImpl = config.fetch ?? ((input: RequestInfo | URL, init?: RequestInit) => fetch(input, init));
+"#,
+    );
+
+    if options.auth {
+        out.push_str(
+            r#"
+  let refreshing: Promise<string | null> | undefined;
+
+  /** Refresh the access token, once for concurrent 401s */
+  function refresh(): Promise<string | null> {
+    if (!refreshing) {
+      refreshing = (async () => {
+        try {
+          const refreshToken = await config.getRefreshToken?.();
+          const res = await fetchImpl(`${baseUrl}/auth/refresh`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(refreshToken ? { refresh_token: refreshToken } : {}),
+          });
+          if (!res.ok) return null;
+          const tokens = (await res.json()) as AuthTokens;
+          await config.onTokenRefresh?.(tokens);
+          return tokens.access_token;
+        } catch {
+          return null;
+        } finally {
+          refreshing = undefined;
+        }
+      })();
+    }
+    return refreshing;
+  }
+
+  /** `token` is set when retrying with a refreshed token */
+  async function request<T>(path: string, init: RequestInit = {}, token?: string): Promise<T> {
+    const headers = new Headers(init.headers);
+    if (!headers.has('Content-Type')) headers.set('Content-Type', 'application/json');
+    const bearer = token ?? (await config.getToken?.());
+    if (bearer) headers.set('Authorization', `Bearer ${bearer}`);
+
+    const res = await fetchImpl(`${baseUrl}${path}`, { ...init, headers });
+    if (res.status === 401 && token === undefined) {
+      const refreshed = await refresh();
+      if (refreshed) return request<T>(path, init, refreshed);
+    }
+"#,
+        );
+    } else {
+        out.push_str(
+            r#"
+  async function request<T>(path: string, init: RequestInit = {}): Promise<T> {
+    const headers = new Headers(init.headers);
+    if (!headers.has('Content-Type')) headers.set('Content-Type', 'application/json');
+
+    const res = await fetchImpl(`${baseUrl}${path}`, { ...init, headers });
+"#,
+        );
+    }
+
+    out.push_str(
+        r#"    if (!res.ok) {
+      const error = new ApiError(res.status, res.statusText, await readBody(res));
+      config.onError?.(error);
+      throw error;
+    }
+    if (res.status === 204) return undefined as T;
+    return (await res.json()) as T;
+  }
+
+  return { request };
 }
 
-fn generate_entity_section(entity: &EntityMeta) -> String {
+let defaultClient = createClient();
+
+/** Configure the client used by the generated functions and hooks */
+export function configureClient(config: ClientConfig): void {
+  defaultClient = createClient(config);
+}
+
+async function fetchJson<T>(path: string, options: RequestOptions & RequestInit = {}): Promise<T> {
+  const { client = defaultClient, ...init } = options;
+  return client.request<T>(path, init);
+}
+"#,
+    );
+
+    if options.zod {
+        out.push_str(
+            r#"
+/** Fetch JSON and validate it against a zod schema */
+async function fetchParsed<T>(
+  schema: z.ZodType<T>,
+  path: string,
+  options?: RequestOptions & RequestInit,
+): Promise<T> {
+  return schema.parse(await fetchJson<unknown>(path, options));
+}
+"#,
+        );
+    }
+    out
+}
+
+fn generate_entity_section(
+    entity: &EntityMeta,
+    project: &ProjectIntrospection,
+    options: &ClientOptions,
+) -> String {
     let mut out = String::new();
     let pascal = &entity.pascal_name;
 
@@ -138,11 +373,12 @@ fn generate_entity_section(entity: &EntityMeta) -> String {
         pascal
     ));
 
-    // Main interface
-    out.push_str(&generate_interface(
+    // Main interface, with the fields every REST response carries
+    out.push_str(&interface_with_builtins(
         pascal,
         &entity.fields,
         InterfaceKind::Full,
+        BUILTIN_FIELDS,
     ));
     out.push('\n');
 
@@ -162,26 +398,97 @@ fn generate_entity_section(entity: &EntityMeta) -> String {
     ));
     out.push('\n');
 
+    // Runtime validators, typed against the interfaces above
+    if options.zod {
+        for (name, kind) in [
+            (pascal.clone(), InterfaceKind::Full),
+            (format!("Create{}", pascal), InterfaceKind::Create),
+            (format!("Update{}", pascal), InterfaceKind::Update),
+        ] {
+            out.push_str(&generate_zod_schema(&name, &entity.fields, kind));
+            out.push('\n');
+        }
+    }
+
     // CRUD functions
-    out.push_str(&generate_crud_functions(entity));
+    out.push_str(&generate_crud_functions(entity, options));
+
+    // Query keys and hooks
+    if options.react_query {
+        out.push('\n');
+        out.push_str(&generate_query_hooks(entity, project));
+    }
 
     out
 }
 
-#[derive(PartialEq)]
-enum InterfaceKind {
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum InterfaceKind {
     Full,
     Create,
     Update,
 }
 
 /// Fields to exclude from Create interfaces (auto-generated by the backend)
-const AUTO_FIELDS: &[&str] = &["id", "created_at", "updated_at"];
+pub(crate) const AUTO_FIELDS: &[&str] = &["id", "created_at", "updated_at"];
+
+/// A field `impl_data_entity!` adds to every entity
+struct BuiltinField {
+    name: &'static str,
+    ts_type: &'static str,
+    zod: &'static str,
+    /// Absent from some responses
+    optional: bool,
+}
+
+/// Built-in fields besides `id` and the timestamps, declared on full entities
+/// of the REST client: zod strips keys its schema does not list.
+const BUILTIN_FIELDS: &[BuiltinField] = &[
+    BuiltinField {
+        name: "name",
+        ts_type: "string",
+        zod: "z.string()",
+        optional: false,
+    },
+    BuiltinField {
+        name: "entity_type",
+        ts_type: "string",
+        zod: "z.string()",
+        optional: false,
+    },
+    BuiltinField {
+        name: "status",
+        ts_type: "string",
+        zod: "z.string()",
+        optional: false,
+    },
+    BuiltinField {
+        name: "deleted_at",
+        ts_type: "string | null",
+        zod: "z.string().nullish()",
+        optional: true,
+    },
+];
+
+/// Built-in fields the entity does not declare itself
+fn undeclared_builtins<'a>(
+    builtins: &'a [BuiltinField],
+    fields: &'a [FieldMeta],
+) -> impl Iterator<Item = &'a BuiltinField> {
+    builtins
+        .iter()
+        .filter(|b| !fields.iter().any(|f| f.name == b.name))
+}
 
-fn generate_interface(
+pub(crate) fn generate_interface(name: &str, fields: &[FieldMeta], kind: InterfaceKind) -> String {
+    interface_with_builtins(name, fields, kind, &[])
+}
+
+fn interface_with_builtins(
     name: &str,
-    fields: &[crate::codegen::introspect::FieldMeta],
+    fields: &[FieldMeta],
     kind: InterfaceKind,
+    builtins: &[BuiltinField],
 ) -> String {
     let mut out = format!("export interface {} {{\n", name);
 
@@ -208,13 +515,69 @@ fn generate_interface(
         // Add timestamp fields
         out.push_str("  created_at: string;\n");
         out.push_str("  updated_at: string;\n");
+        for builtin in undeclared_builtins(builtins, fields) {
+            let optional = if builtin.optional { "?" } else { "" };
+            out.push_str(&format!(
+                "  {}{}: {};\n",
+                builtin.name, optional, builtin.ts_type
+            ));
+        }
     }
 
     out.push_str("}\n");
     out
 }
 
-fn generate_crud_functions(entity: &EntityMeta) -> String {
+/// zod schema mirroring the entity interfaces: `{name}Schema`, typed as `z.ZodType<{name}>`
+/// so the compiler keeps the schema and the interface in sync.
+fn generate_zod_schema(name: &str, fields: &[FieldMeta], kind: InterfaceKind) -> String {
+    let mut out = format!(
+        "export const {name}Schema: z.ZodType<{name}> = z.object({{\n",
+        name = name
+    );
+
+    if kind == InterfaceKind::Full {
+        out.push_str("  id: z.string(),\n");
+    }
+
+    for field in fields {
+        if kind == InterfaceKind::Create && AUTO_FIELDS.contains(&field.name.as_str()) {
+            continue;
+        }
+
+        let schema = rust_type_to_zod(&field.rust_type);
+        let optional = if kind == InterfaceKind::Update {
+            ".optional()"
+        } else {
+            ""
+        };
+        out.push_str(&format!("  {}: {}{},\n", field.name, schema, optional));
+    }
+
+    if kind == InterfaceKind::Full {
+        out.push_str("  created_at: z.string(),\n");
+        out.push_str("  updated_at: z.string(),\n");
+        for builtin in undeclared_builtins(BUILTIN_FIELDS, fields) {
+            out.push_str(&format!("  {}: {},\n", builtin.name, builtin.zod));
+        }
+    }
+
+    out.push_str("});\n");
+    out
+}
+
+/// Opening of a fetch call returning `T` (`many`: `T[]`): a plain `fetchJson`,
+/// or `fetchParsed` with the entity schema when zod is enabled.
+fn fetch_call(pascal: &str, many: bool, options: &ClientOptions) -> String {
+    match (options.zod, many) {
+        (true, false) => format!("fetchParsed({}Schema, ", pascal),
+        (true, true) => format!("fetchParsed(z.array({}Schema), ", pascal),
+        (false, false) => format!("fetchJson<{}>(", pascal),
+        (false, true) => format!("fetchJson<{}[]>(", pascal),
+    }
+}
+
+fn generate_crud_functions(entity: &EntityMeta, options: &ClientOptions) -> String {
     let pascal = &entity.pascal_name;
     let plural = &entity.plural;
     let snake = &entity.snake_name;
@@ -223,30 +586,37 @@ fn generate_crud_functions(entity: &EntityMeta) -> String {
     let fn_single = naming::to_pascal_case(snake);
 
     format!(
-        r#"export async function list{fn_list}(): Promise<{pascal}[]> {{
-  return fetchJson<{pascal}[]>('/api/{plural}');
+        r#"export async function list{fn_list}(options?: RequestOptions): Promise<{pascal}[]> {{
+  return {fetch_many}'/api/{plural}', options);
 }}
 
-export async function get{fn_single}(id: string): Promise<{pascal}> {{
-  return fetchJson<{pascal}>(`/api/{plural}/${{id}}`);
+export async function get{fn_single}(id: string, options?: RequestOptions): Promise<{pascal}> {{
+  return {fetch_one}`/api/{plural}/${{id}}`, options);
 }}
 
-export async function create{fn_single}(data: Create{pascal}): Promise<{pascal}> {{
-  return fetchJson<{pascal}>('/api/{plural}', {{
+export async function create{fn_single}(data: Create{pascal}, options?: RequestOptions): Promise<{pascal}> {{
+  return {fetch_one}'/api/{plural}', {{
+    ...options,
     method: 'POST',
     body: JSON.stringify(data),
   }});
 }}
 
-export async function update{fn_single}(id: string, data: Update{pascal}): Promise<{pascal}> {{
-  return fetchJson<{pascal}>(`/api/{plural}/${{id}}`, {{
+export async function update{fn_single}(
+  id: string,
+  data: Update{pascal},
+  options?: RequestOptions,
+): Promise<{pascal}> {{
+  return {fetch_one}`/api/{plural}/${{id}}`, {{
+    ...options,
     method: 'PUT',
     body: JSON.stringify(data),
   }});
 }}
 
-export async function delete{fn_single}(id: string): Promise<void> {{
+export async function delete{fn_single}(id: string, options?: RequestOptions): Promise<void> {{
   return fetchJson<void>(`/api/{plural}/${{id}}`, {{
+    ...options,
     method: 'DELETE',
   }});
 }}
@@ -255,18 +625,138 @@ export async function delete{fn_single}(id: string): Promise<void> {{
         fn_single = fn_single,
         pascal = pascal,
         plural = plural,
+        fetch_one = fetch_call(pascal, false, options),
+        fetch_many = fetch_call(pascal, true, options),
     )
 }
 
-fn generate_link_function(link: &LinkMeta, entities: &[EntityMeta]) -> String {
+/// Query key factory and TanStack Query hooks of an entity.
+///
+/// Keys are nested under `[plural]`, and link lists under `[source plural, route]`,
+/// so a mutation invalidates the entity's queries and every link list that
+/// returns it.
+fn generate_query_hooks(entity: &EntityMeta, project: &ProjectIntrospection) -> String {
+    let pascal = &entity.pascal_name;
+    let keys = format!("{}Keys", naming::to_camel_case(&entity.snake_name));
+    let fn_list = naming::to_pascal_case(&entity.plural);
+    let fn_single = naming::to_pascal_case(&entity.snake_name);
+
+    // Query keys: one per outgoing link
+    let mut out = format!(
+        "export const {keys} = {{\n  all: ['{plural}'] as const,\n  list: () => [...{keys}.all, 'list'] as const,\n  detail: (id: string) => [...{keys}.all, 'detail', id] as const,\n",
+        keys = keys,
+        plural = entity.plural
+    );
+    for link in outgoing_links(entity, project) {
+        out.push_str(&format!(
+            "  {route_key}: ({source}Id: string) => [...{keys}.all, '{route}', {source}Id] as const,\n",
+            route_key = naming::to_camel_case(&link.forward_route),
+            source = link.source,
+            keys = keys,
+            route = link.forward_route,
+        ));
+    }
+    out.push_str("};\n\n");
+
+    // Invalidation: own queries, then link lists of known sources returning this entity
+    out.push_str(&format!(
+        "/** Invalidate cached {pascal} queries and the link lists that return them */\nfunction invalidate{fn_single}(queryClient: QueryClient) {{\n  return Promise.all([\n    queryClient.invalidateQueries({{ queryKey: {keys}.all }}),\n",
+        pascal = pascal,
+        fn_single = fn_single,
+        keys = keys,
+    ));
+    for link in project.links.iter().filter(|l| {
+        l.target == entity.snake_name && project.entities.iter().any(|e| e.snake_name == l.source)
+    }) {
+        out.push_str(&format!(
+            "    queryClient.invalidateQueries({{ queryKey: [...{source}Keys.all, '{route}'] }}),\n",
+            source = naming::to_camel_case(&link.source),
+            route = link.forward_route,
+        ));
+    }
+    out.push_str("  ]);\n}\n\n");
+
+    out.push_str(&format!(
+        r#"export function use{fn_list}() {{
+  return useQuery({{
+    queryKey: {keys}.list(),
+    queryFn: ({{ signal }}) => list{fn_list}({{ signal }}),
+  }});
+}}
+
+export function use{fn_single}(id: string) {{
+  return useQuery({{
+    queryKey: {keys}.detail(id),
+    queryFn: ({{ signal }}) => get{fn_single}(id, {{ signal }}),
+  }});
+}}
+
+export function useCreate{fn_single}() {{
+  const queryClient = useQueryClient();
+  return useMutation({{
+    mutationFn: (data: Create{pascal}) => create{fn_single}(data),
+    onSuccess: () => invalidate{fn_single}(queryClient),
+  }});
+}}
+
+export function useUpdate{fn_single}() {{
+  const queryClient = useQueryClient();
+  return useMutation({{
+    mutationFn: ({{ id, data }}: {{ id: string; data: Update{pascal} }}) => update{fn_single}(id, data),
+    onSuccess: () => invalidate{fn_single}(queryClient),
+  }});
+}}
+
+export function useDelete{fn_single}() {{
+  const queryClient = useQueryClient();
+  return useMutation({{
+    mutationFn: (id: string) => delete{fn_single}(id),
+    onSuccess: () => invalidate{fn_single}(queryClient),
+  }});
+}}
+"#,
+        fn_list = fn_list,
+        fn_single = fn_single,
+        pascal = pascal,
+        keys = keys,
+    ));
+
+    out
+}
+
+fn outgoing_links<'a>(
+    entity: &'a EntityMeta,
+    project: &'a ProjectIntrospection,
+) -> impl Iterator<Item = &'a LinkMeta> {
+    project
+        .links
+        .iter()
+        .filter(move |l| l.source == entity.snake_name)
+}
+
+fn generate_link_function(
+    link: &LinkMeta,
+    entities: &[EntityMeta],
+    options: &ClientOptions,
+) -> String {
     let source_pascal = naming::to_pascal_case(&link.source);
     let source_plural = find_plural(&link.source, entities);
     let target_pascal = naming::to_pascal_case(&link.target);
     let forward_pascal = naming::to_pascal_case(&link.forward_route);
-
-    format!(
-        r#"export async function get{source_pascal}{forward_pascal}({source}Id: string): Promise<{target_pascal}[]> {{
-  return fetchJson<{target_pascal}[]>(`/api/{source_plural}/${{{source}Id}}/{forward_route}`);
+    let known = |name: &str| entities.iter().any(|e| e.snake_name == name);
+
+    // Only entities of the project have a schema
+    let target_options = ClientOptions {
+        zod: options.zod && known(&link.target),
+        ..*options
+    };
+
+    let mut out = format!(
+        r#"export async function get{source_pascal}{forward_pascal}(
+  {source}Id: string,
+  options?: RequestOptions,
+): Promise<{target_pascal}[]> {{
+  return {fetch_many}`/api/{source_plural}/${{{source}Id}}/{forward_route}`, options);
 }}
 "#,
         source_pascal = source_pascal,
@@ -275,11 +765,34 @@ fn generate_link_function(link: &LinkMeta, entities: &[EntityMeta]) -> String {
         target_pascal = target_pascal,
         source_plural = source_plural,
         forward_route = link.forward_route,
-    )
+        fetch_many = fetch_call(&target_pascal, true, &target_options),
+    );
+
+    // The cache key lives in the source entity's key factory
+    if options.react_query && known(&link.source) {
+        out.push_str(&format!(
+            r#"
+export function use{source_pascal}{forward_pascal}({source}Id: string) {{
+  return useQuery({{
+    queryKey: {source_camel}Keys.{route_key}({source}Id),
+    queryFn: ({{ signal }}) => get{source_pascal}{forward_pascal}({source}Id, {{ signal }}),
+  }});
+}}
+
+"#,
+            source_pascal = source_pascal,
+            forward_pascal = forward_pascal,
+            source = link.source,
+            source_camel = naming::to_camel_case(&link.source),
+            route_key = naming::to_camel_case(&link.forward_route),
+        ));
+    }
+
+    out
 }
 
 /// Find the plural form for an entity by snake_name, falling back to name + "s"
-fn find_plural(snake_name: &str, entities: &[EntityMeta]) -> String {
+pub(crate) fn find_plural(snake_name: &str, entities: &[EntityMeta]) -> String {
     entities
         .iter()
         .find(|e| e.snake_name == snake_name)
@@ -429,18 +942,23 @@ mod tests {
     #[test]
     fn test_generate_crud_functions() {
         let entity = make_product_entity();
-        let crud = generate_crud_functions(&entity);
+        let crud = generate_crud_functions(&entity, &ClientOptions::default());
 
-        assert!(crud.contains("export async function listProducts(): Promise<Product[]>"));
-        assert!(crud.contains("fetchJson<Product[]>('/api/products')"));
-        assert!(crud.contains("export async function getProduct(id: string): Promise<Product>"));
         assert!(crud.contains(
-            "export async function createProduct(data: CreateProduct): Promise<Product>"
+            "export async function listProducts(options?: RequestOptions): Promise<Product[]>"
+        ));
+        assert!(crud.contains("fetchJson<Product[]>('/api/products', options)"));
+        assert!(crud.contains(
+            "export async function getProduct(id: string, options?: RequestOptions): Promise<Product>"
         ));
         assert!(crud.contains(
-            "export async function updateProduct(id: string, data: UpdateProduct): Promise<Product>"
+            "export async function createProduct(data: CreateProduct, options?: RequestOptions): Promise<Product>"
         ));
-        assert!(crud.contains("export async function deleteProduct(id: string): Promise<void>"));
+        assert!(crud.contains("  data: UpdateProduct,\n  options?: RequestOptions,\n"));
+        assert!(crud.contains(
+            "export async function deleteProduct(id: string, options?: RequestOptions): Promise<void>"
+        ));
+        assert!(crud.contains("    ...options,\n    method: 'POST',"));
         assert!(crud.contains("method: 'POST'"));
         assert!(crud.contains("method: 'PUT'"));
         assert!(crud.contains("method: 'DELETE'"));
@@ -468,11 +986,12 @@ mod tests {
             forward_route: "reviews".to_string(),
             reverse_route: "product".to_string(),
         };
-        let func = generate_link_function(&link, &entities);
-        assert!(func.contains(
-            "export async function getProductReviews(productId: string): Promise<Review[]>"
-        ));
-        assert!(func.contains("fetchJson<Review[]>(`/api/products/${productId}/reviews`)"));
+        let func = generate_link_function(&link, &entities, &ClientOptions::default());
+        assert!(func.contains("export async function getProductReviews(\n  productId: string,"));
+        assert!(func.contains("): Promise<Review[]> {"));
+        assert!(
+            func.contains("fetchJson<Review[]>(`/api/products/${productId}/reviews`, options)")
+        );
     }
 
     // ── Full generation test ──────────────────────────────────────
@@ -490,13 +1009,17 @@ mod tests {
             }],
         };
 
-        let output = generate(&project);
+        let output = generate(&project, &ClientOptions::default());
 
         // Header
         assert!(output.contains("// Auto-generated by `this generate client`"));
-        assert!(output.contains("const API_BASE = import.meta.env.VITE_API_URL || '';"));
 
-        // Helper
+        // Runtime
+        assert!(output.contains("export class ApiError extends Error {"));
+        assert!(
+            output.contains("export function createClient(config: ClientConfig = {}): ApiClient {")
+        );
+        assert!(output.contains("export function configureClient(config: ClientConfig): void {"));
         assert!(output.contains("async function fetchJson<T>"));
 
         // Interfaces
@@ -522,7 +1045,7 @@ mod tests {
             links: vec![],
         };
 
-        let output = generate(&project);
+        let output = generate(&project, &ClientOptions::default());
         assert!(!output.contains("Link functions"));
     }
 
@@ -546,10 +1069,227 @@ mod tests {
             links: vec![],
         };
 
-        let output = generate(&project);
+        let output = generate(&project, &ClientOptions::default());
         assert!(output.contains("export interface Product {"));
         assert!(output.contains("export interface Order {"));
         assert!(output.contains("export async function listProducts"));
         assert!(output.contains("export async function listOrders"));
     }
+
+    // ── Runtime tests ─────────────────────────────────────────────
+
+    #[test]
+    fn test_generate_runtime_without_auth() {
+        let runtime = generate_runtime(&ClientOptions::default());
+        assert!(runtime.contains("  baseUrl?: string;"));
+        assert!(runtime.contains("  fetch?: typeof fetch;"));
+        assert!(runtime.contains("  onError?: (error: ApiError) => void;"));
+        assert!(runtime.contains("  signal?: AbortSignal;"));
+        assert!(!runtime.contains("getToken"));
+        assert!(!runtime.contains("Authorization"));
+        assert!(!runtime.contains("/auth/refresh"));
+    }
+
+    #[test]
+    fn test_generate_runtime_with_auth() {
+        let options = ClientOptions {
+            auth: true,
+            ..Default::default()
+        };
+        let runtime = generate_runtime(&options);
+        assert!(runtime.contains("export interface AuthTokens {"));
+        assert!(runtime.contains("  getToken?: () => MaybePromise<string | null | undefined>;"));
+        assert!(runtime.contains("  onTokenRefresh?: (tokens: AuthTokens) => MaybePromise<void>;"));
+        assert!(runtime.contains("headers.set('Authorization', `Bearer ${bearer}`);"));
+        assert!(runtime.contains("`${baseUrl}/auth/refresh`"));
+        assert!(runtime.contains("res.status === 401"));
+    }
+
+    // ── zod / React Query tests ───────────────────────────────────
+
+    fn make_linked_project() -> ProjectIntrospection {
+        ProjectIntrospection {
+            entities: vec![
+                make_product_entity(),
+                EntityMeta {
+                    pascal_name: "Review".to_string(),
+                    snake_name: "review".to_string(),
+                    plural: "reviews".to_string(),
+                    indexed_fields: vec![],
+                    fields: vec![FieldMeta {
+                        name: "rating".to_string(),
+                        rust_type: "u8".to_string(),
+                    }],
+                    routes: vec![],
+                },
+            ],
+            links: vec![LinkMeta {
+                link_type: "has_review".to_string(),
+                source: "product".to_string(),
+                target: "review".to_string(),
+                forward_route: "reviews".to_string(),
+                reverse_route: "product".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_rust_type_to_zod() {
+        assert_eq!(rust_type_to_zod("String"), "z.string()");
+        assert_eq!(rust_type_to_zod("Uuid"), "z.string().uuid()");
+        assert_eq!(rust_type_to_zod("f64"), "z.number()");
+        assert_eq!(rust_type_to_zod("i32"), "z.number().int()");
+        assert_eq!(rust_type_to_zod("Option<bool>"), "z.boolean().nullable()");
+        assert_eq!(rust_type_to_zod("Vec<String>"), "z.array(z.string())");
+        assert_eq!(
+            rust_type_to_zod("HashMap<String, f64>"),
+            "z.record(z.string(), z.number())"
+        );
+        assert_eq!(rust_type_to_zod("serde_json::Value"), "z.unknown()");
+        assert_eq!(rust_type_to_zod("Money"), "z.custom<Money>()");
+    }
+
+    #[test]
+    fn test_generate_with_zod() {
+        let options = ClientOptions {
+            zod: true,
+            ..Default::default()
+        };
+        let output = generate(&make_linked_project(), &options);
+
+        assert!(output.contains("import { z } from 'zod';"));
+        assert!(!output.contains("@tanstack/react-query"));
+        assert!(output.contains("async function fetchParsed<T>(\n  schema: z.ZodType<T>,"));
+        assert!(output.contains("export const ProductSchema: z.ZodType<Product> = z.object({"));
+        assert!(output.contains("  description: z.string().nullable(),"));
+        assert!(output.contains("  description: z.string().nullable().optional(),"));
+        assert!(output.contains("export const CreateReviewSchema: z.ZodType<CreateReview>"));
+        assert!(
+            output
+                .contains("return fetchParsed(z.array(ProductSchema), '/api/products', options);")
+        );
+        assert!(
+            output.contains("return fetchParsed(ProductSchema, `/api/products/${id}`, options);")
+        );
+        assert!(output.contains("return fetchJson<void>(`/api/products/${id}`"));
+        assert!(output.contains(
+            "return fetchParsed(z.array(ReviewSchema), `/api/products/${productId}/reviews`, options);"
+        ));
+    }
+
+    #[test]
+    fn test_generate_full_entity_declares_builtin_fields() {
+        let options = ClientOptions {
+            zod: true,
+            ..Default::default()
+        };
+        let output = generate(&make_linked_project(), &options);
+        let block = |start: &str| {
+            let from = output.find(start).unwrap();
+            let len = output[from..].find("}").unwrap();
+            output[from..from + len].to_string()
+        };
+
+        let review = block("export interface Review {");
+        assert!(review.contains("  name: string;\n  entity_type: string;\n  status: string;\n"));
+        assert!(review.contains("  deleted_at?: string | null;\n"));
+        let schema = block("export const ReviewSchema");
+        assert!(schema.contains("  name: z.string(),\n"));
+        assert!(schema.contains("  status: z.string(),\n"));
+        assert!(schema.contains("  deleted_at: z.string().nullish(),\n"));
+        assert!(!block("export const CreateReviewSchema").contains("status"));
+
+        // A declared field is not repeated
+        assert_eq!(
+            block("export const ProductSchema")
+                .matches("  name:")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    #[ignore] // Installs zod and tsx with npm — requires Node.js + network access
+    fn test_zod_schema_parse_keeps_builtin_fields() {
+        let tmp = tempfile::tempdir().unwrap();
+        let options = ClientOptions {
+            zod: true,
+            ..Default::default()
+        };
+        std::fs::write(
+            tmp.path().join("client.ts"),
+            generate(&make_linked_project(), &options),
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.path().join("check.ts"),
+            "import { ReviewSchema } from './client';\n\
+             const review = ReviewSchema.parse({ id: 'r1', rating: 5, created_at: 'c', \
+             updated_at: 'u', name: 'Great', entity_type: 'review', status: 'active' });\n\
+             if (review.name !== 'Great' || review.status !== 'active') process.exit(1);\n",
+        )
+        .unwrap();
+
+        let run = |program: &str, args: &[&str]| {
+            std::process::Command::new(program)
+                .args(args)
+                .current_dir(tmp.path())
+                .status()
+                .unwrap()
+                .success()
+        };
+        assert!(run("npm", &["install", "--silent", "zod@3", "tsx"]));
+        assert!(run("npx", &["tsx", "check.ts"]));
+    }
+
+    #[test]
+    fn test_generate_with_react_query() {
+        let options = ClientOptions {
+            react_query: true,
+            ..Default::default()
+        };
+        let output = generate(&make_linked_project(), &options);
+
+        assert!(output.contains(
+            "import { type QueryClient, useMutation, useQuery, useQueryClient } from '@tanstack/react-query';"
+        ));
+        assert!(!output.contains("from 'zod'"));
+        assert!(output.contains("export const productKeys = {"));
+        assert!(output.contains(
+            "  reviews: (productId: string) => [...productKeys.all, 'reviews', productId] as const,"
+        ));
+        assert!(output.contains("export function useProducts() {"));
+        assert!(output.contains("export function useProduct(id: string) {"));
+        assert!(output.contains("    queryFn: ({ signal }) => getProduct(id, { signal }),"));
+        assert!(output.contains("    mutationFn: (id: string) => deleteProduct(id),"));
+        assert!(output.contains("export function useUpdateReview() {"));
+        assert!(output.contains("    onSuccess: () => invalidateReview(queryClient),"));
+        // Review mutations also refresh the product → reviews link lists
+        assert!(output.contains(
+            "    queryClient.invalidateQueries({ queryKey: [...productKeys.all, 'reviews'] }),"
+        ));
+        assert!(output.contains("export function useProductReviews(productId: string) {"));
+        assert!(output.contains("    queryKey: productKeys.reviews(productId),"));
+    }
+
+    #[test]
+    fn test_generate_link_hook_skipped_for_unknown_source() {
+        let link = LinkMeta {
+            link_type: "owns".to_string(),
+            source: "user".to_string(),
+            target: "product".to_string(),
+            forward_route: "products".to_string(),
+            reverse_route: "owner".to_string(),
+        };
+        let options = ClientOptions {
+            zod: true,
+            react_query: true,
+            ..Default::default()
+        };
+        let func = generate_link_function(&link, &[make_product_entity()], &options);
+        assert!(func.contains(
+            "fetchParsed(z.array(ProductSchema), `/api/users/${userId}/products`, options)"
+        ));
+        assert!(!func.contains("useUserProducts"));
+    }
 }
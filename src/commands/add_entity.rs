@@ -5,8 +5,9 @@ use colored::Colorize;
 
 use super::AddEntityArgs;
 use crate::templates::TemplateEngine;
+use crate::templates::packs::{self, Hook};
 use crate::utils::file_writer::FileWriter;
-use crate::utils::{markers, naming, output, project};
+use crate::utils::{ast, markers, naming, output, project};
 
 /// Parsed field definition
 #[derive(Debug, Clone, serde::Serialize)]
@@ -141,7 +142,7 @@ pub(crate) fn run_in(
     writer.create_dir_all(&entity_dir)?;
 
     // Prepare template context
-    let engine = TemplateEngine::new()?;
+    let mut engine = TemplateEngine::for_project(&project_root)?;
     let mut context = tera::Context::new();
     context.insert("entity_name", &entity_name);
     context.insert("entity_pascal", &entity_pascal);
@@ -168,6 +169,18 @@ pub(crate) fn run_in(
         _ => "entity/store.rs",
     };
 
+    // Files contributed by template packs; those landing in the entity
+    // directory become submodules of the entity
+    let packs = packs::discover(&project_root)?;
+    let pack_files = packs::render_files(&packs, Hook::Entity, &mut engine, &context)?;
+    let entity_rel_dir = format!("src/entities/{}/", &entity_name);
+    let pack_modules: Vec<&str> = pack_files
+        .iter()
+        .filter_map(|f| f.path.strip_prefix(&entity_rel_dir))
+        .filter_map(|name| name.strip_suffix(".rs"))
+        .filter(|name| !name.contains('/') && *name != "mod")
+        .collect();
+
     let entity_files: &[(&str, &str)] = &[
         (template_name, "model.rs"),
         (store_template, "store.rs"),
@@ -177,9 +190,12 @@ pub(crate) fn run_in(
     ];
 
     for (tpl, filename) in entity_files {
-        let rendered = engine
+        let mut rendered = engine
             .render(tpl, &context)
             .with_context(|| format!("Failed to render template: {}", tpl))?;
+        if *filename == "mod.rs" {
+            rendered = add_submodules(&rendered, &pack_modules);
+        }
         let file_path = entity_dir.join(filename);
         writer.write_file(&file_path, &rendered)?;
         if !writer.is_dry_run() {
@@ -203,8 +219,7 @@ pub(crate) fn run_in(
     let entities_mod_path = project_root.join("src/entities/mod.rs");
     let mod_declaration = format!("pub mod {};", &entity_name);
 
-    if entities_mod_path.exists() {
-        let content = std::fs::read_to_string(&entities_mod_path)?;
+    if let Ok(content) = writer.read_file(&entities_mod_path) {
         if !content.contains(&mod_declaration) {
             let new_content = if content.trim().is_empty() {
                 format!("{}\n", &mod_declaration)
@@ -248,6 +263,16 @@ pub(crate) fn run_in(
     // Update config/links.yaml (add entity config)
     update_links_yaml(&project_root, &entity_name, &entity_plural, writer)?;
 
+    packs::apply(
+        &project_root,
+        &packs,
+        Hook::Entity,
+        &pack_files,
+        &mut engine,
+        &context,
+        writer,
+    )?;
+
     if !writer.is_dry_run() {
         output::print_success(&format!("Entity '{}' created!", &entity_name));
         println!();
@@ -360,12 +385,32 @@ pub(crate) fn run_in(
     Ok(())
 }
 
+/// Declare pack-generated files as submodules of the entity's `mod.rs`,
+/// after its last `pub mod` line
+fn add_submodules(mod_rs: &str, modules: &[&str]) -> String {
+    let mut lines: Vec<String> = mod_rs.lines().map(str::to_string).collect();
+    let mut idx = lines
+        .iter()
+        .rposition(|l| l.starts_with("pub mod "))
+        .map_or(0, |i| i + 1);
+    for module in modules {
+        let declaration = format!("pub mod {};", module);
+        if !lines.contains(&declaration) {
+            lines.insert(idx, declaration);
+            idx += 1;
+        }
+    }
+    let mut result = lines.join("\n");
+    if mod_rs.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
 /// Update src/stores.rs to add the new entity's store fields and initialization.
 ///
-/// Uses marker-based insertion for idempotent updates:
-/// - `[this:store_fields]` — struct fields
-/// - `[this:store_init_vars]` — variable initialization
-/// - `[this:store_init_fields]` — struct init fields
+/// The `*Stores` struct and its constructors are located by syntax; the
+/// `[this:store_*]` markers, when present, still pick the insertion point.
 fn update_stores_rs(
     project_root: &Path,
     entity_name: &str,
@@ -380,29 +425,51 @@ fn update_stores_rs(
         return Ok(());
     }
 
-    let content =
-        std::fs::read_to_string(&stores_path).with_context(|| "Failed to read src/stores.rs")?;
+    let content = writer.read_file(&stores_path)?;
 
-    // Check markers exist
-    if !content.contains("[this:store_fields]") {
+    if !ast::has_stores_struct(&content) {
         output::print_warn(
-            "src/stores.rs has no [this:store_fields] marker — skipping stores registration.\n\
-             Hint: regenerate your project with `this init` to get marker-based templates.",
+            "src/stores.rs has no `*Stores` struct — skipping stores registration.\n\
+             Hint: regenerate your project with `this init` to get the stores template.",
         );
         return Ok(());
     }
 
     // Idempotence check
-    let field_needle = format!("{}_store:", entity_plural);
-    if markers::has_line_after_marker(&content, "[this:store_fields]", &field_needle) {
+    let store_field = format!("{}_store", entity_plural);
+    if ast::has_store_field(&content, &store_field) {
         output::print_info(&format!(
             "stores.rs already contains {} — skipping",
-            field_needle
+            store_field
         ));
         return Ok(());
     }
 
-    // 1. Add store fields after [this:store_fields] (same for any backend — trait objects)
+    let updated =
+        stores_rs_with_entity(&content, entity_name, entity_pascal, entity_plural, backend)?;
+    writer.update_file(&stores_path, &content, &updated)?;
+
+    if !writer.is_dry_run() {
+        output::print_info(&format!(
+            "Updated src/stores.rs (added {} store, backend: {})",
+            entity_name, backend
+        ));
+    }
+
+    Ok(())
+}
+
+/// Add an entity's store fields, initialization and imports to stores.rs content.
+///
+/// Falls back to the `[this:store_*]` markers when the content does not parse.
+pub(crate) fn stores_rs_with_entity(
+    content: &str,
+    entity_name: &str,
+    entity_pascal: &str,
+    entity_plural: &str,
+    backend: &str,
+) -> Result<String> {
+    // 1. Add store fields to the struct (same for any backend — trait objects)
     let store_field = format!(
         "pub {plural}_store: Arc<dyn {pascal}Store>,",
         plural = entity_plural,
@@ -412,8 +479,7 @@ fn update_stores_rs(
         "pub {plural}_entity: Arc<dyn EntityStore>,",
         plural = entity_plural
     );
-    let mut updated = markers::insert_after_marker(&content, "[this:store_fields]", &store_field)?;
-    updated = markers::insert_after_marker(&updated, &store_field, &entity_field)?;
+    let mut updated = ast::add_store_fields(content, &[store_field, entity_field])?;
 
     match backend {
         "postgres" => {
@@ -543,22 +609,17 @@ fn update_stores_rs(
                 plural = entity_plural,
                 pascal = entity_pascal
             );
-            updated = markers::insert_after_marker(
-                &updated,
-                "[this:store_init_vars]",
-                &inmemory_init_var,
-            )?;
-
             let init_store_field =
                 format!("{plural}_store: {plural}.clone(),", plural = entity_plural);
             let init_entity_field = format!("{plural}_entity: {plural},", plural = entity_plural);
-            updated = markers::insert_after_marker(
+            updated = ast::add_constructor_init(
                 &updated,
+                "new_in_memory",
+                &[inmemory_init_var],
+                &[init_store_field, init_entity_field],
+                "[this:store_init_vars]",
                 "[this:store_init_fields]",
-                &init_store_field,
             )?;
-            updated =
-                markers::insert_after_marker(&updated, &init_store_field, &init_entity_field)?;
 
             let inmemory_import = format!(
                 "use crate::entities::{name}::{{InMemory{pascal}Store, {pascal}Store}};",
@@ -569,19 +630,10 @@ fn update_stores_rs(
         }
     }
 
-    writer.update_file(&stores_path, &content, &updated)?;
-
-    if !writer.is_dry_run() {
-        output::print_info(&format!(
-            "Updated src/stores.rs (added {} store, backend: {})",
-            entity_name, backend
-        ));
-    }
-
-    Ok(())
+    Ok(updated)
 }
 
-/// Ensure stores.rs has a backend-specific constructor with markers,
+/// Ensure stores.rs has a backend-specific constructor,
 /// and add the entity's init inside it.
 ///
 /// - `marker_prefix`: short name for markers, e.g. "pg", "mongo", "neo4j"
@@ -608,19 +660,16 @@ fn ensure_backend_constructor(
     let vars_marker = format!("[this:store_{}_init_vars]", marker_prefix);
     let fields_marker = format!("[this:store_{}_init_fields]", marker_prefix);
 
-    // If the markers don't exist yet, add the constructor
-    if !updated.contains(&vars_marker) {
-        let last_closing = updated.rfind("\n}").ok_or_else(|| {
-            anyhow::anyhow!("Cannot find closing brace of impl block in stores.rs")
-        })?;
-
+    // If the constructor doesn't exist yet, add it
+    let fn_name = format!("new_{}", backend_name);
+    if !ast::has_constructor(&updated, &fn_name) {
         let constructor = format!(
             r#"
     /// Create stores backed by {backend_display}.
     ///
     /// Requires the `{feature}` feature.
     {cfg}
-    pub fn new_{fn_name}({params}) -> Self {{
+    pub fn {fn_name}({params}) -> Self {{
         // {vars_mk}
 
         Self {{
@@ -631,29 +680,30 @@ fn ensure_backend_constructor(
             backend_display = store_prefix,
             feature = backend_name,
             cfg = cfg_attr,
-            fn_name = backend_name,
+            fn_name = fn_name,
             params = constructor_params,
             vars_mk = vars_marker,
             fields_mk = fields_marker,
         );
-        updated.insert_str(last_closing, &constructor);
+        updated = ast::add_constructor(&updated, &constructor)?;
     }
 
-    // Add init var
+    // Add init var and fields
     let init_var = format!(
         "let {plural} = Arc::new({new_expr});",
         plural = entity_plural,
         new_expr = store_new_expr.replace("{pascal}", entity_pascal),
     );
-    updated = markers::insert_after_marker(&updated, &vars_marker, &init_var)?;
-
-    // Add init fields
     let init_store_field = format!("{plural}_store: {plural}.clone(),", plural = entity_plural);
     let init_entity_field = format!("{plural}_entity: {plural},", plural = entity_plural);
-    updated = markers::insert_after_marker(&updated, &fields_marker, &init_store_field)?;
-    updated = markers::insert_after_marker(&updated, &init_store_field, &init_entity_field)?;
-
-    Ok(updated)
+    ast::add_constructor_init(
+        &updated,
+        &fn_name,
+        &[init_var],
+        &[init_store_field, init_entity_field],
+        &vars_marker,
+        &fields_marker,
+    )
 }
 
 /// Generate a SQL migration file for a SQL-backed entity (postgres or mysql).
@@ -704,13 +754,14 @@ fn generate_sql_migration(
     Ok(())
 }
 
-/// Update src/module.rs to register the new entity in all 4 marker sections.
+/// Update src/module.rs to register the new entity in the `Module` impl:
+/// - `entity_types` — entity type string in vec![]
+/// - `register_entities` — descriptor registration
+/// - `get_entity_fetcher` — match arm
+/// - `get_entity_creator` — match arm
 ///
-/// Uses marker-based insertion for idempotent updates:
-/// - `[this:entity_types]` — entity type string in vec![]
-/// - `[this:register_entities]` — descriptor registration
-/// - `[this:entity_fetcher]` — match arm for get_entity_fetcher
-/// - `[this:entity_creator]` — match arm for get_entity_creator
+/// Methods are located by syntax; the `[this:...]` markers, when present,
+/// still pick the insertion point.
 fn update_module_rs(
     project_root: &Path,
     entity_name: &str,
@@ -724,21 +775,18 @@ fn update_module_rs(
         return Ok(());
     }
 
-    let content =
-        std::fs::read_to_string(&module_path).with_context(|| "Failed to read src/module.rs")?;
+    let content = writer.read_file(&module_path)?;
 
-    // Check markers exist
-    if !content.contains("[this:entity_types]") {
+    if !ast::has_module_impl(&content) {
         output::print_warn(
-            "src/module.rs has no [this:entity_types] marker — skipping module registration.\n\
-             Hint: regenerate your project with `this init` to get marker-based templates.",
+            "src/module.rs has no `impl Module for ...` block — skipping module registration.\n\
+             Hint: regenerate your project with `this init` to get the module template.",
         );
         return Ok(());
     }
 
     // Idempotence check
-    let type_needle = format!("\"{}\"", entity_name);
-    if markers::has_line_after_marker(&content, "[this:entity_types]", &type_needle) {
+    if ast::has_entity_type(&content, entity_name) {
         output::print_info(&format!(
             "module.rs already contains \"{}\" — skipping",
             entity_name
@@ -746,52 +794,60 @@ fn update_module_rs(
         return Ok(());
     }
 
-    // 1. Add entity type after [this:entity_types]
-    let entity_type_line = format!("\"{}\",", entity_name);
-    let mut updated =
-        markers::insert_after_marker(&content, "[this:entity_types]", &entity_type_line)?;
-
-    // 2. Add descriptor registration after [this:register_entities]
-    // Change _registry to registry since it's now used
-    updated = updated.replace(
-        "_registry: &mut EntityRegistry",
-        "registry: &mut EntityRegistry",
-    );
-    let register_line = format!(
-        "registry.register(Box::new({pascal}Descriptor::new_with_creator(self.stores.{plural}_store.clone(), self.stores.{plural}_entity.clone())));",
-        pascal = entity_pascal,
-        plural = entity_plural
-    );
-    updated = markers::insert_after_marker(&updated, "[this:register_entities]", &register_line)?;
+    let updated = module_rs_with_entity(&content, entity_name, entity_pascal, entity_plural)?;
+    writer.update_file(&module_path, &content, &updated)?;
 
-    // 3. Add match arm for entity_fetcher after [this:entity_fetcher]
-    // Change _entity_type to entity_type since it's now used
-    updated = updated.replace(
-        "fn get_entity_fetcher(&self, _entity_type: &str)",
-        "fn get_entity_fetcher(&self, entity_type: &str)",
-    );
-    updated = updated.replace("match _entity_type {", "match entity_type {");
-    let fetcher_line = format!(
-        "\"{name}\" => Some(self.stores.{plural}_entity.clone()),",
-        name = entity_name,
-        plural = entity_plural
-    );
-    updated = markers::insert_after_marker(&updated, "[this:entity_fetcher]", &fetcher_line)?;
+    if !writer.is_dry_run() {
+        output::print_info(&format!(
+            "Updated src/module.rs (registered {} entity)",
+            entity_name
+        ));
+    }
 
-    // 4. Add match arm for entity_creator after [this:entity_creator]
-    // Change _entity_type to entity_type since it's now used
-    updated = updated.replace(
-        "fn get_entity_creator(&self, _entity_type: &str)",
-        "fn get_entity_creator(&self, entity_type: &str)",
-    );
-    let creator_line = format!(
+    Ok(())
+}
+
+/// Register an entity in the `Module` impl of module.rs content.
+pub(crate) fn module_rs_with_entity(
+    content: &str,
+    entity_name: &str,
+    entity_pascal: &str,
+    entity_plural: &str,
+) -> Result<String> {
+    // 1. Add entity type to entity_types()
+    let entity_type_line = format!("\"{}\",", entity_name);
+    let mut updated = ast::add_entity_type(content, &entity_type_line)?;
+
+    // 2. Add descriptor registration to register_entities()
+    updated = ast::add_registration(&updated, |registry| {
+        format!(
+            "{registry}.register(Box::new({pascal}Descriptor::new_with_creator(self.stores.{plural}_store.clone(), self.stores.{plural}_entity.clone())));",
+            registry = registry,
+            pascal = entity_pascal,
+            plural = entity_plural
+        )
+    })?;
+
+    // 3. Add match arms to get_entity_fetcher() and get_entity_creator()
+    let arm = format!(
         "\"{name}\" => Some(self.stores.{plural}_entity.clone()),",
         name = entity_name,
         plural = entity_plural
     );
-    updated = markers::insert_after_marker(&updated, "[this:entity_creator]", &creator_line)?;
+    updated = ast::add_match_arm(
+        &updated,
+        "get_entity_fetcher",
+        &arm,
+        "[this:entity_fetcher]",
+    )?;
+    updated = ast::add_match_arm(
+        &updated,
+        "get_entity_creator",
+        &arm,
+        "[this:entity_creator]",
+    )?;
 
-    // 5. Add imports
+    // 4. Add imports
     let descriptor_import = format!(
         "use crate::entities::{name}::descriptor::{pascal}Descriptor;",
         name = entity_name,
@@ -799,16 +855,7 @@ fn update_module_rs(
     );
     updated = markers::add_import(&updated, &descriptor_import);
 
-    writer.update_file(&module_path, &content, &updated)?;
-
-    if !writer.is_dry_run() {
-        output::print_info(&format!(
-            "Updated src/module.rs (registered {} entity)",
-            entity_name
-        ));
-    }
-
-    Ok(())
+    Ok(updated)
 }
 
 /// Update config/links.yaml to add the entity config if not already present.
@@ -824,8 +871,7 @@ fn update_links_yaml(
         return Ok(());
     }
 
-    let yaml_content =
-        std::fs::read_to_string(&links_path).with_context(|| "Failed to read config/links.yaml")?;
+    let yaml_content = writer.read_file(&links_path)?;
     let mut config: super::add_link::LinksConfig =
         serde_yaml::from_str(&yaml_content).with_context(|| "Failed to parse links.yaml")?;
 
@@ -1516,6 +1562,44 @@ impl Module for TestModule {{
         assert_file_contains(&project, "src/module.rs", "\"order\"");
     }
 
+    #[test]
+    fn test_add_entity_twice_in_one_transaction_builds_on_staged_files() {
+        let tmp = TempDir::new().unwrap();
+        let project = setup_entity_project(&tmp, "staged");
+        let writer = crate::mcp::handlers::McpFileWriter::dry_run();
+
+        run_in(default_args("product"), &writer, &project).unwrap();
+        run_in(default_args("order"), &writer, &project).unwrap();
+
+        let changes = writer.changes();
+        let content = |file: &str| {
+            changes
+                .iter()
+                .find(|c| c["path"].as_str().unwrap().ends_with(file))
+                .and_then(|c| c["content"].as_str())
+                .unwrap_or_else(|| panic!("{} should be proposed", file))
+                .to_string()
+        };
+        let entities_mod = content("src/entities/mod.rs");
+        assert!(
+            entities_mod.contains("pub mod product;") && entities_mod.contains("pub mod order;")
+        );
+        for file in ["src/stores.rs", "src/module.rs"] {
+            let text = content(file);
+            assert!(
+                text.contains("Product") && text.contains("Order"),
+                "{}",
+                text
+            );
+        }
+        let links = content("config/links.yaml");
+        assert!(
+            links.contains("product") && links.contains("order"),
+            "{}",
+            links
+        );
+    }
+
     #[test]
     fn test_add_entity_no_stores_rs() {
         let tmp = TempDir::new().unwrap();
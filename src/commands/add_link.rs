@@ -2,6 +2,8 @@ use anyhow::{Context, Result, bail};
 use colored::Colorize;
 
 use super::AddLinkArgs;
+use crate::templates::TemplateEngine;
+use crate::templates::packs::{self, Hook};
 use crate::utils::file_writer::FileWriter;
 use crate::utils::{naming, output, project};
 
@@ -207,6 +209,32 @@ pub(crate) fn run_in(
         serde_yaml::to_string(&config).with_context(|| "Failed to serialize links.yaml")?;
     writer.update_file(&links_path, &yaml_content, &new_yaml)?;
 
+    // Files and insertions contributed by template packs
+    let packs = packs::discover(&project_root)?;
+    if !packs.is_empty() {
+        let mut engine = TemplateEngine::for_project(&project_root)?;
+        let mut context = tera::Context::new();
+        context.insert("source", &source);
+        context.insert("target", &target);
+        context.insert("source_pascal", &naming::to_pascal_case(&source));
+        context.insert("target_pascal", &naming::to_pascal_case(&target));
+        context.insert("source_plural", &source_plural);
+        context.insert("target_plural", &target_plural);
+        context.insert("link_type", &link_type);
+        context.insert("forward", &forward);
+        context.insert("reverse", &reverse);
+        let files = packs::render_files(&packs, Hook::Link, &mut engine, &context)?;
+        packs::apply(
+            &project_root,
+            &packs,
+            Hook::Link,
+            &files,
+            &mut engine,
+            &context,
+            writer,
+        )?;
+    }
+
     output::print_info(&format!("Link type: {}", &link_type));
     output::print_info(&format!(
         "Forward route: {} (on /{}/{{id}}/{})",
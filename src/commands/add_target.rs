@@ -80,7 +80,7 @@ fn run_webapp(args: AddTargetArgs, writer: &dyn FileWriter, cwd: &std::path::Pat
     writer.create_dir_all(&target_path.join("public"))?;
 
     // 5. Render and write templates
-    let engine = TemplateEngine::new()?;
+    let engine = TemplateEngine::for_project(&workspace_root)?;
     let mut ctx = tera::Context::new();
     ctx.insert("framework", framework.as_str());
     ctx.insert("api_port", &api_port);
@@ -110,7 +110,7 @@ fn run_webapp(args: AddTargetArgs, writer: &dyn FileWriter, cwd: &std::path::Pat
         runtime: None,
         path: target_dir_name.to_string(),
     });
-    config::save_workspace_config(&this_yaml_path, &config)?;
+    config::save_workspace_config(&this_yaml_path, &config, writer)?;
     output::print_info("Updated this.yaml with webapp target");
 
     // 7. Print next steps
@@ -186,7 +186,7 @@ fn run_desktop(args: AddTargetArgs, writer: &dyn FileWriter, cwd: &std::path::Pa
     writer.create_dir_all(&tauri_path.join("capabilities"))?;
 
     // 6. Render and write templates
-    let engine = TemplateEngine::new()?;
+    let engine = TemplateEngine::for_project(&workspace_root)?;
     let mut ctx = tera::Context::new();
     ctx.insert("project_name", project_name);
     ctx.insert("project_name_snake", &project_name_snake);
@@ -219,7 +219,7 @@ fn run_desktop(args: AddTargetArgs, writer: &dyn FileWriter, cwd: &std::path::Pa
         runtime: Some("tauri".to_string()),
         path: target_dir_name.to_string(),
     });
-    config::save_workspace_config(&this_yaml_path, &config)?;
+    config::save_workspace_config(&this_yaml_path, &config, writer)?;
     output::print_info("Updated this.yaml with desktop target");
 
     // 8. Print next steps
@@ -310,7 +310,7 @@ fn run_mobile(
     writer.create_dir_all(&target_path)?;
 
     // 6. Render and write templates
-    let engine = TemplateEngine::new()?;
+    let engine = TemplateEngine::for_project(&workspace_root)?;
     let mut ctx = tera::Context::new();
     ctx.insert("project_name", project_name);
     ctx.insert(
@@ -342,7 +342,7 @@ fn run_mobile(
         runtime: Some("capacitor".to_string()),
         path: target_dir_name.to_string(),
     });
-    config::save_workspace_config(&this_yaml_path, &config)?;
+    config::save_workspace_config(&this_yaml_path, &config, writer)?;
     output::print_info(&format!("Updated this.yaml with {} target", platform));
 
     // 8. Print next steps
@@ -483,6 +483,26 @@ mod tests {
         assert_eq!(config.targets[0].path, "front");
     }
 
+    #[test]
+    fn test_add_target_webapp_dry_run_leaves_this_yaml() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_workspace(&tmp, "dry_yaml_test");
+        let before = std::fs::read_to_string(ws.join("this.yaml")).unwrap();
+        let writer = DryRunWriter::new();
+        let args = AddTargetArgs {
+            target_type: TargetType::Webapp,
+            framework: "react".to_string(),
+            name: None,
+        };
+        run_in(args, &writer, &ws).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(ws.join("this.yaml")).unwrap(),
+            before
+        );
+        assert!(writer.files_updated().contains(&ws.join("this.yaml")));
+    }
+
     #[test]
     fn test_add_target_webapp_duplicate_error() {
         let tmp = TempDir::new().unwrap();
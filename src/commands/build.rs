@@ -212,7 +212,7 @@ fn run_docker(
 ) -> Result<()> {
     output::print_step("Generating Dockerfile...");
 
-    let engine = TemplateEngine::new()?;
+    let engine = TemplateEngine::for_project(workspace_root)?;
     let mut context = tera::Context::new();
     context.insert("project_name", &config.name);
     context.insert("api_path", &config.api.path);
@@ -467,6 +467,7 @@ mod tests {
                 runtime: None,
                 path: "front".to_string(),
             }],
+            templates: None,
         };
         assert!(find_webapp_target(&config).is_some());
     }
@@ -480,6 +481,7 @@ mod tests {
                 port: 3000,
             },
             targets: vec![],
+            templates: None,
         };
         assert!(find_webapp_target(&config).is_none());
     }
@@ -514,6 +516,7 @@ mod tests {
                 port: 3000,
             },
             targets,
+            templates: None,
         }
     }
 
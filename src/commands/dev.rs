@@ -9,7 +9,7 @@ use anyhow::{Context, Result, bail};
 use colored::Colorize;
 
 use super::DevArgs;
-use crate::commands::info;
+use crate::commands::{generate, info};
 use crate::config::{self, TargetType};
 use crate::utils::project;
 use crate::utils::{naming, output};
@@ -60,6 +60,21 @@ pub fn run(args: DevArgs) -> Result<()> {
         detect_rust_watcher()
     };
 
+    // 5b. The webapp's API client is regenerated on entity/link changes.
+    // Client sync is optional: a setup problem must not stop the API server.
+    let client_args = match webapp {
+        Some(_) if !args.api_only && !args.no_client => {
+            match generate::dev_client_args(&workspace_root, &ws_config) {
+                Ok(client_args) => Some(client_args),
+                Err(e) => {
+                    output::print_warn(&format!("API client sync disabled: {:#}", e));
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
     // 6. Print dev banner
     print_banner(port, &watcher, webapp, args.api_only);
 
@@ -162,6 +177,17 @@ pub fn run(args: DevArgs) -> Result<()> {
         }
     }
 
+    // 9b. Keep the webapp's API client in sync with entities and links
+    let client_thread = client_args.map(|client_args| {
+        let root = workspace_root.clone();
+        let r5 = running.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = generate::watch_client(&client_args, &root, &r5) {
+                output::print_warn(&format!("API client watcher stopped: {:#}", e));
+            }
+        })
+    });
+
     // 10. Wait loop — check children and Ctrl+C
     while running.load(Ordering::SeqCst) {
         // Check if API exited
@@ -229,6 +255,9 @@ pub fn run(args: DevArgs) -> Result<()> {
     if let Some(t) = front_stderr_thread {
         let _ = t.join();
     }
+    if let Some(t) = client_thread {
+        let _ = t.join();
+    }
 
     output::print_success("Development servers stopped");
     Ok(())
@@ -1,11 +1,19 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use colored::Colorize;
 use serde::Serialize;
 
+use super::{DoctorArgs, DoctorFormat};
 use crate::config;
-use crate::utils::{markers, naming, project};
+use crate::utils::file_writer::FileWriter;
+use crate::utils::{ast, markers, naming, output, project};
+
+mod auth;
+mod drift;
+mod rules;
+
+pub(crate) use drift::sync_with_templates;
 
 /// Result of a single diagnostic check
 #[derive(Debug)]
@@ -19,42 +27,161 @@ enum DiagnosticLevel {
 #[derive(Debug, Serialize)]
 pub struct SerializableDiagnostic {
     pub level: String,
+    /// Stable id of the check that produced this finding (e.g. `check_links`)
+    pub rule: String,
     pub category: String,
     pub message: String,
+    /// File the finding points at, relative to the current directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 1-based line in `file`, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// What `this doctor --fix` would do about it, if anything
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
 }
 
 #[derive(Debug)]
 struct DiagnosticResult {
     level: DiagnosticLevel,
+    /// Stable rule id, set by `run_checks` from the check that produced it
+    rule: String,
     category: String,
     message: String,
+    file: Option<PathBuf>,
+    line: Option<usize>,
+    fix: Option<FixAction>,
+}
+
+/// Stable ids of the built-in checks, with a short description for SARIF
+pub const RULES: &[(&str, &str)] = &[
+    (
+        "check_workspace",
+        "this.yaml, API directory and targets are consistent",
+    ),
+    (
+        "check_cargo_toml",
+        "Cargo.toml declares the this-rs dependency",
+    ),
+    (
+        "check_entities",
+        "Entity directories match src/entities/mod.rs",
+    ),
+    (
+        "check_module_registration",
+        "Entities are registered in src/module.rs",
+    ),
+    (
+        "check_stores_configuration",
+        "Entities have stores in src/stores.rs",
+    ),
+    (
+        "check_markers",
+        "Generator markers are present in module.rs and stores.rs",
+    ),
+    (
+        "check_links",
+        "links.yaml only references existing entities",
+    ),
+    ("check_websocket", "WebSocket feature and main.rs agree"),
+    ("check_grpc", "gRPC feature and main.rs agree"),
+    ("check_graphql", "GraphQL feature and main.rs agree"),
+    ("check_events", "events.yaml flows reference existing sinks"),
+    (
+        "check_template_drift",
+        "module.rs and stores.rs match the installed CLI's templates",
+    ),
+    (
+        "check_auth",
+        "auth.yaml policies parse, target known entities and resolvers",
+    ),
+    ("check_custom_rules", ".this/doctor.yaml is valid"),
+];
+
+/// Automatic remediation that `this doctor --fix` can apply for a diagnostic
+#[derive(Debug, Clone, PartialEq)]
+enum FixAction {
+    /// Re-insert a generator marker comment that was deleted
+    RestoreMarker {
+        file: &'static str,
+        marker: &'static str,
+    },
+    /// Declare an entity directory in src/entities/mod.rs (then register it)
+    DeclareEntity { entity: String },
+    /// Register an entity in the src/module.rs marker sections
+    RegisterEntity { entity: String },
+    /// Add an entity's store fields and initialization to src/stores.rs
+    AddStore { entity: String },
+    /// Remove a link whose source or target entity does not exist
+    RemoveLink {
+        link_type: String,
+        source_type: String,
+        target_type: String,
+    },
+    /// Remove a validation rule that references unknown entities
+    RemoveValidationRule { link_type: String, source: String },
+    /// Copy an item (e.g. `fn new_lmdb`) missing from a generator-owned file
+    /// from the current template
+    AddScaffolding { file: &'static str, item: String },
+    /// Re-insert a marker that newer templates have but the file lacks
+    RestoreTemplateMarker { file: &'static str, marker: String },
 }
 
 impl DiagnosticResult {
     fn pass(category: &str, message: &str) -> Self {
         Self {
             level: DiagnosticLevel::Pass,
+            rule: String::new(),
             category: category.to_string(),
             message: message.to_string(),
+            file: None,
+            line: None,
+            fix: None,
         }
     }
 
     fn warn(category: &str, message: &str) -> Self {
         Self {
             level: DiagnosticLevel::Warn,
+            rule: String::new(),
             category: category.to_string(),
             message: message.to_string(),
+            file: None,
+            line: None,
+            fix: None,
         }
     }
 
     fn error(category: &str, message: &str) -> Self {
         Self {
             level: DiagnosticLevel::Error,
+            rule: String::new(),
             category: category.to_string(),
             message: message.to_string(),
+            file: None,
+            line: None,
+            fix: None,
         }
     }
 
+    fn for_rule(mut self, rule: &str) -> Self {
+        self.rule = rule.to_string();
+        self
+    }
+
+    /// Point the finding at a file (and line, when known)
+    fn at(mut self, file: &Path, line: Option<usize>) -> Self {
+        self.file = Some(file.to_path_buf());
+        self.line = line;
+        self
+    }
+
+    fn with_fix(mut self, fix: FixAction) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
     fn icon(&self) -> &str {
         match self.level {
             DiagnosticLevel::Pass => "✅",
@@ -69,7 +196,17 @@ impl DiagnosticResult {
             DiagnosticLevel::Warn => self.message.yellow().to_string(),
             DiagnosticLevel::Error => self.message.red().to_string(),
         };
-        println!("  {} {} — {}", self.icon(), self.category.bold(), msg);
+        if self.fix.is_some() {
+            println!(
+                "  {} {} — {} {}",
+                self.icon(),
+                self.category.bold(),
+                msg,
+                "[fixable]".dimmed()
+            );
+        } else {
+            println!("  {} {} — {}", self.icon(), self.category.bold(), msg);
+        }
     }
 
     fn level_str(&self) -> &str {
@@ -83,12 +220,262 @@ impl DiagnosticResult {
     fn to_serializable(&self) -> SerializableDiagnostic {
         SerializableDiagnostic {
             level: self.level_str().to_string(),
+            rule: self.rule.to_string(),
             category: self.category.clone(),
             message: self.message.clone(),
+            file: self.file.as_deref().map(relative_to_cwd),
+            line: self.line,
+            fix: self.fix.as_ref().map(FixAction::description),
+        }
+    }
+}
+
+impl FixAction {
+    /// File the fix edits, relative to the project root
+    fn file(&self) -> &'static str {
+        match self {
+            FixAction::RestoreMarker { file, .. }
+            | FixAction::AddScaffolding { file, .. }
+            | FixAction::RestoreTemplateMarker { file, .. } => file,
+            FixAction::DeclareEntity { .. } => "src/entities/mod.rs",
+            FixAction::RegisterEntity { .. } => "src/module.rs",
+            FixAction::AddStore { .. } => "src/stores.rs",
+            FixAction::RemoveLink { .. } | FixAction::RemoveValidationRule { .. } => {
+                "config/links.yaml"
+            }
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            FixAction::RestoreMarker { file, marker } => {
+                format!("Restore '// {}' marker in {}", marker, file)
+            }
+            FixAction::DeclareEntity { entity } => {
+                format!("Declare 'pub mod {};' in src/entities/mod.rs", entity)
+            }
+            FixAction::RegisterEntity { entity } => {
+                format!("Register entity '{}' in src/module.rs", entity)
+            }
+            FixAction::AddStore { entity } => {
+                format!("Add store for entity '{}' to src/stores.rs", entity)
+            }
+            FixAction::RemoveLink {
+                link_type,
+                source_type,
+                target_type,
+            } => format!(
+                "Remove link '{}' ({} -> {}) from config/links.yaml",
+                link_type, source_type, target_type
+            ),
+            FixAction::RemoveValidationRule { link_type, source } => format!(
+                "Remove validation rule '{}' for '{}' from config/links.yaml",
+                link_type, source
+            ),
+            FixAction::AddScaffolding { file, item } => {
+                format!("Add `{}` from the current template to {}", item, file)
+            }
+            FixAction::RestoreTemplateMarker { file, marker } => {
+                format!("Restore '// {}' marker in {}", marker, file)
+            }
+        }
+    }
+
+    /// Markers must be back before anything is inserted after them
+    fn priority(&self) -> u8 {
+        match self {
+            FixAction::RestoreMarker { .. }
+            | FixAction::AddScaffolding { .. }
+            | FixAction::RestoreTemplateMarker { .. } => 0,
+            FixAction::DeclareEntity { .. } => 1,
+            _ => 2,
+        }
+    }
+
+    /// Fixes that become necessary once this one is applied
+    fn follow_ups(&self) -> Vec<FixAction> {
+        match self {
+            FixAction::DeclareEntity { entity } => vec![
+                FixAction::RegisterEntity {
+                    entity: entity.clone(),
+                },
+                FixAction::AddStore {
+                    entity: entity.clone(),
+                },
+            ],
+            _ => vec![],
+        }
+    }
+
+    /// Compute the new content of `self.file()`; unchanged content means nothing to do
+    fn apply(&self, project_root: &Path, content: &str) -> Result<String> {
+        match self {
+            FixAction::RestoreMarker { marker, .. } => {
+                if content.contains(marker) {
+                    return Ok(content.to_string());
+                }
+                markers::restore(content, marker)
+                    .ok_or_else(|| anyhow::anyhow!("Cannot locate where {} belongs", marker))
+            }
+            FixAction::DeclareEntity { entity } => {
+                let declaration = format!("pub mod {};", entity);
+                if content.lines().any(|l| l.trim() == declaration) {
+                    Ok(content.to_string())
+                } else if content.trim().is_empty() {
+                    Ok(format!("{}\n", declaration))
+                } else {
+                    Ok(format!("{}\n{}\n", content.trim_end(), declaration))
+                }
+            }
+            FixAction::RegisterEntity { entity } => {
+                if ast::has_entity_type(content, entity) {
+                    return Ok(content.to_string());
+                }
+                super::add_entity::module_rs_with_entity(
+                    content,
+                    entity,
+                    &naming::to_pascal_case(entity),
+                    &naming::pluralize(entity),
+                )
+            }
+            FixAction::AddStore { entity } => {
+                let plural = naming::pluralize(entity);
+                if ast::has_store_field(content, &format!("{}_store", plural)) {
+                    return Ok(content.to_string());
+                }
+                let pascal = naming::to_pascal_case(entity);
+                let backend = detect_store_backend(project_root, entity, &pascal);
+                super::add_entity::stores_rs_with_entity(content, entity, &pascal, &plural, backend)
+            }
+            FixAction::RemoveLink {
+                link_type,
+                source_type,
+                target_type,
+            } => {
+                let mut config: super::add_link::LinksConfig = serde_yaml::from_str(content)?;
+                config.links.retain(|l| {
+                    !(l.link_type == *link_type
+                        && l.source_type == *source_type
+                        && l.target_type == *target_type)
+                });
+                if let Some(rules) = config.validation_rules.get_mut(link_type) {
+                    for rule in rules.iter_mut().filter(|r| r.source == *source_type) {
+                        rule.targets.retain(|t| t != target_type);
+                    }
+                    rules.retain(|r| !r.targets.is_empty());
+                    if rules.is_empty() {
+                        config.validation_rules.remove(link_type);
+                    }
+                }
+                Ok(serde_yaml::to_string(&config)?)
+            }
+            FixAction::RemoveValidationRule { link_type, source } => {
+                let mut config: super::add_link::LinksConfig = serde_yaml::from_str(content)?;
+                if let Some(rules) = config.validation_rules.get_mut(link_type) {
+                    rules.retain(|r| r.source != *source);
+                    if rules.is_empty() {
+                        config.validation_rules.remove(link_type);
+                    }
+                }
+                Ok(serde_yaml::to_string(&config)?)
+            }
+            FixAction::AddScaffolding { file, item } => {
+                let template = drift::template_for(file)
+                    .ok_or_else(|| anyhow::anyhow!("{} is not generator-owned", file))?;
+                drift::apply_add_scaffolding(project_root, template, content, item)
+            }
+            FixAction::RestoreTemplateMarker { file, marker } => {
+                let template = drift::template_for(file)
+                    .ok_or_else(|| anyhow::anyhow!("{} is not generator-owned", file))?;
+                drift::apply_restore_marker(project_root, template, content, marker)
+            }
         }
     }
 }
 
+/// Guess the storage backend of an entity from its generated store.rs
+fn detect_store_backend(project_root: &Path, entity: &str, pascal: &str) -> &'static str {
+    let store_path = project_root
+        .join("src/entities")
+        .join(entity)
+        .join("store.rs");
+    let content = std::fs::read_to_string(store_path).unwrap_or_default();
+    [
+        ("Postgres", "postgres"),
+        ("Mongo", "mongodb"),
+        ("Neo4j", "neo4j"),
+        ("Scylla", "scylladb"),
+        ("Mysql", "mysql"),
+        ("Lmdb", "lmdb"),
+    ]
+    .into_iter()
+    .find(|(prefix, _)| content.contains(&format!("struct {}{}Store", prefix, pascal)))
+    .map(|(_, backend)| backend)
+    .unwrap_or("in-memory")
+}
+
+/// Apply the fixes attached to `results` through `writer`.
+///
+/// Fixes are applied in memory file by file, so several fixes to the same
+/// file compose, and each changed file is written once. Returns the
+/// descriptions of the fixes that changed something.
+fn apply_fixes(
+    project_root: &Path,
+    results: &[DiagnosticResult],
+    writer: &dyn FileWriter,
+) -> Result<Vec<String>> {
+    let mut pending: Vec<FixAction> = results.iter().filter_map(|r| r.fix.clone()).collect();
+    pending.sort_by_key(FixAction::priority);
+
+    // (relative path, original content if the file exists, updated content)
+    let mut files: Vec<(&'static str, Option<String>, String)> = Vec::new();
+    let mut seen: Vec<FixAction> = Vec::new();
+    let mut applied = Vec::new();
+
+    let mut i = 0;
+    while i < pending.len() {
+        let fix = pending[i].clone();
+        i += 1;
+        if seen.contains(&fix) {
+            continue;
+        }
+        seen.push(fix.clone());
+
+        let idx = match files.iter().position(|(f, _, _)| *f == fix.file()) {
+            Some(idx) => idx,
+            None => {
+                let original = std::fs::read_to_string(project_root.join(fix.file())).ok();
+                let current = original.clone().unwrap_or_default();
+                files.push((fix.file(), original, current));
+                files.len() - 1
+            }
+        };
+
+        match fix.apply(project_root, &files[idx].2) {
+            Ok(updated) if updated != files[idx].2 => {
+                files[idx].2 = updated;
+                applied.push(fix.description());
+                pending.extend(fix.follow_ups());
+            }
+            Ok(_) => {}
+            Err(e) => output::print_warn(&format!("Cannot {}: {}", fix.description(), e)),
+        }
+    }
+
+    for (file, original, updated) in &files {
+        let path = project_root.join(file);
+        match original {
+            Some(original) if original != updated => {
+                writer.update_file(&path, original, updated)?
+            }
+            None if !updated.is_empty() => writer.write_file(&path, updated)?,
+            _ => {}
+        }
+    }
+
+    Ok(applied)
+}
+
 /// Collect diagnostics as structured data for MCP JSON serialization.
 pub fn collect_diagnostics() -> Result<Vec<SerializableDiagnostic>> {
     let project_root = project::detect_project_root()?;
@@ -96,28 +483,139 @@ pub fn collect_diagnostics() -> Result<Vec<SerializableDiagnostic>> {
     Ok(results.iter().map(|r| r.to_serializable()).collect())
 }
 
+/// Apply every available automatic fix, returning what was (or would be) changed.
+pub fn fix_project(writer: &dyn FileWriter) -> Result<Vec<String>> {
+    let project_root = project::detect_project_root()?;
+    let results = run_checks(&project_root);
+    apply_fixes(&project_root, &results, writer)
+}
+
 /// Run all diagnostic checks and return results
 fn run_checks(project_root: &Path) -> Vec<DiagnosticResult> {
     let mut results = Vec::new();
 
     // Workspace checks (only if inside a workspace)
     if let Some(ws_root) = project::find_workspace_root() {
-        results.extend(check_workspace(&ws_root));
+        results.extend(located(
+            "check_workspace",
+            &ws_root.join("this.yaml"),
+            check_workspace(&ws_root),
+        ));
     }
 
-    results.push(check_cargo_toml(project_root));
-    results.extend(check_entities(project_root));
-    results.extend(check_module_registration(project_root));
-    results.extend(check_stores_configuration(project_root));
-    results.extend(check_links(project_root));
-    results.extend(check_websocket(project_root));
-    results.extend(check_grpc(project_root));
-    results.extend(check_events(project_root));
+    let root = project_root;
+    let checks = [
+        (
+            "check_cargo_toml",
+            "Cargo.toml",
+            vec![check_cargo_toml(root)],
+        ),
+        (
+            "check_entities",
+            "src/entities/mod.rs",
+            check_entities(root),
+        ),
+        (
+            "check_module_registration",
+            "src/module.rs",
+            check_module_registration(root),
+        ),
+        (
+            "check_stores_configuration",
+            "src/stores.rs",
+            check_stores_configuration(root),
+        ),
+        ("check_markers", "src/module.rs", check_markers(root)),
+        (
+            "check_template_drift",
+            "src/stores.rs",
+            drift::check_template_drift(root),
+        ),
+        ("check_links", "config/links.yaml", check_links(root)),
+        ("check_websocket", "src/main.rs", check_websocket(root)),
+        ("check_grpc", "src/main.rs", check_grpc(root)),
+        ("check_graphql", "src/main.rs", check_graphql(root)),
+        ("check_events", "config/events.yaml", check_events(root)),
+        ("check_auth", "config/auth.yaml", auth::check_auth(root)),
+    ];
+    for (rule, default_file, found) in checks {
+        results.extend(located(rule, &root.join(default_file), found));
+    }
+
+    // Project-local rules carry their own ids and locations
+    results.extend(rules::check_custom_rules(root));
     results
 }
 
-pub fn run() -> Result<()> {
+/// Tag a check's findings with its rule id, pointing problems without a
+/// more precise location at the file the check inspects
+fn located(
+    rule: &str,
+    default_file: &Path,
+    results: Vec<DiagnosticResult>,
+) -> Vec<DiagnosticResult> {
+    results
+        .into_iter()
+        .map(|mut r| {
+            r.rule = rule.to_string();
+            if r.file.is_none() && !matches!(r.level, DiagnosticLevel::Pass) {
+                r.file = Some(default_file.to_path_buf());
+            }
+            r
+        })
+        .collect()
+}
+
+/// 1-based number of the first line of `path` containing `needle`
+fn line_of(path: &Path, needle: &str) -> Option<usize> {
+    std::fs::read_to_string(path)
+        .ok()?
+        .lines()
+        .position(|l| l.contains(needle))
+        .map(|i| i + 1)
+}
+
+/// Display a path relative to the current directory when it is below it
+fn relative_to_cwd(path: &Path) -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+pub fn run(args: DoctorArgs, writer: &dyn FileWriter) -> Result<()> {
     let project_root = project::detect_project_root()?;
+    let policy = Policy::new(
+        &args.deny,
+        &args.allow,
+        rules::custom_rule_ids(&project_root),
+    )?;
+
+    let results: Vec<DiagnosticResult> = run_checks(&project_root)
+        .into_iter()
+        .filter(|r| !policy.allows(r))
+        .collect();
+    let failures = results.iter().filter(|r| policy.fails(r)).count();
+
+    match args.format {
+        DoctorFormat::Text => {}
+        DoctorFormat::Json => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json_report(&results, &policy))?
+            );
+            return policy_outcome(failures);
+        }
+        DoctorFormat::Sarif => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&sarif_report(&results, &policy))?
+            );
+            return policy_outcome(failures);
+        }
+    }
 
     let project_name = detect_project_name(&project_root);
     println!();
@@ -136,8 +634,6 @@ pub fn run() -> Result<()> {
     }
     println!();
 
-    let results = run_checks(&project_root);
-
     // Display results
     for result in &results {
         result.display();
@@ -166,18 +662,190 @@ pub fn run() -> Result<()> {
     if errors > 0 {
         print!(", {}", format!("{} error(s)", errors).red());
     }
+    if failures > errors {
+        print!(
+            ", {}",
+            format!("{} denied warning(s)", failures - errors).red()
+        );
+    }
     println!();
+
+    if args.fix {
+        println!();
+        let applied = apply_fixes(&project_root, &results, writer)?;
+        let verb = if writer.is_dry_run() {
+            "Would fix:"
+        } else {
+            "Fixed:"
+        };
+        if applied.is_empty() {
+            println!("  {}", "No automatic fixes available".dimmed());
+        }
+        for description in &applied {
+            println!("  🔧 {} {}", verb.green(), description);
+        }
+        println!();
+
+        // Fixes are staged by the writer and committed once we return, so
+        // unfixable errors are reported without aborting the process
+        let unfixed = results
+            .iter()
+            .filter(|r| matches!(r.level, DiagnosticLevel::Error) && r.fix.is_none())
+            .count();
+        if unfixed > 0 {
+            output::print_warn(&format!("{} error(s) need manual attention", unfixed));
+        }
+        return Ok(());
+    }
+
+    let fixable = results.iter().filter(|r| r.fix.is_some()).count();
+    if fixable > 0 {
+        println!(
+            "  {}",
+            format!(
+                "Run `this doctor --fix` to apply {} automatic fix(es)",
+                fixable
+            )
+            .dimmed()
+        );
+    }
     println!();
 
-    if errors > 0 {
-        std::process::exit(1);
+    policy_outcome(failures)
+}
+
+/// Which findings are ignored and which fail the run (`--allow` / `--deny`)
+struct Policy {
+    deny_warnings: bool,
+    deny: Vec<String>,
+    allow: Vec<String>,
+    /// Project-local rules from `.this/doctor.yaml` (id, description)
+    custom: Vec<(String, String)>,
+}
+
+impl Policy {
+    fn new(deny: &[String], allow: &[String], custom: Vec<(String, String)>) -> Result<Self> {
+        for rule in deny.iter().filter(|r| *r != "warnings").chain(allow) {
+            let builtin = RULES.iter().any(|(id, _)| id == rule);
+            if !builtin && !custom.iter().any(|(id, _)| id == rule) {
+                let known: Vec<&str> = RULES
+                    .iter()
+                    .map(|(id, _)| *id)
+                    .chain(custom.iter().map(|(id, _)| id.as_str()))
+                    .collect();
+                anyhow::bail!(
+                    "Unknown doctor rule '{}'. Known rules: {}",
+                    rule,
+                    known.join(", ")
+                );
+            }
+        }
+        Ok(Self {
+            deny_warnings: deny.iter().any(|r| r == "warnings"),
+            deny: deny.to_vec(),
+            allow: allow.to_vec(),
+            custom,
+        })
+    }
+
+    fn allows(&self, result: &DiagnosticResult) -> bool {
+        self.allow.contains(&result.rule)
+    }
+
+    fn fails(&self, result: &DiagnosticResult) -> bool {
+        match result.level {
+            DiagnosticLevel::Pass => false,
+            DiagnosticLevel::Warn => self.deny_warnings || self.deny.contains(&result.rule),
+            DiagnosticLevel::Error => true,
+        }
     }
+}
 
+/// Turn the number of failing findings into the command's result (and exit code)
+fn policy_outcome(failures: usize) -> Result<()> {
+    if failures > 0 {
+        anyhow::bail!("Doctor found {} failing finding(s)", failures);
+    }
     Ok(())
 }
 
+/// `--format json`: every diagnostic plus counts, in the shape MCP returns
+fn json_report(results: &[DiagnosticResult], policy: &Policy) -> serde_json::Value {
+    let count = |level: &str| results.iter().filter(|r| r.level_str() == level).count();
+    let failures = results.iter().filter(|r| policy.fails(r)).count();
+    serde_json::json!({
+        "diagnostics": results.iter().map(|r| r.to_serializable()).collect::<Vec<_>>(),
+        "summary": {
+            "pass": count("pass"),
+            "warn": count("warn"),
+            "error": count("error"),
+            "total": results.len(),
+            "failing": failures,
+        },
+        "failed": failures > 0,
+    })
+}
+
+/// `--format sarif`: SARIF 2.1.0 log for code-scanning tools (passes are omitted)
+fn sarif_report(results: &[DiagnosticResult], policy: &Policy) -> serde_json::Value {
+    let rules: Vec<serde_json::Value> = RULES
+        .iter()
+        .map(|(id, description)| (*id, *description))
+        .chain(
+            policy
+                .custom
+                .iter()
+                .map(|(id, description)| (id.as_str(), description.as_str())),
+        )
+        .map(|(id, description)| {
+            serde_json::json!({
+                "id": id,
+                "shortDescription": { "text": description },
+            })
+        })
+        .collect();
+
+    let findings: Vec<serde_json::Value> = results
+        .iter()
+        .filter(|r| !matches!(r.level, DiagnosticLevel::Pass))
+        .map(|r| {
+            let mut finding = serde_json::json!({
+                "ruleId": r.rule,
+                "level": if policy.fails(r) { "error" } else { "warning" },
+                "message": { "text": format!("{}: {}", r.category, r.message) },
+            });
+            if let Some(file) = &r.file {
+                let mut location = serde_json::json!({
+                    "artifactLocation": { "uri": relative_to_cwd(file).replace('\\', "/") },
+                });
+                if let Some(line) = r.line {
+                    location["region"] = serde_json::json!({ "startLine": line });
+                }
+                finding["locations"] = serde_json::json!([{ "physicalLocation": location }]);
+            }
+            finding
+        })
+        .collect();
+
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "this doctor",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                }
+            },
+            "results": findings,
+        }],
+    })
+}
+
 /// Detect project name from Cargo.toml
-fn detect_project_name(project_root: &Path) -> String {
+pub(super) fn detect_project_name(project_root: &Path) -> String {
     let cargo_path = project_root.join("Cargo.toml");
     if let Ok(content) = std::fs::read_to_string(&cargo_path)
         && let Ok(doc) = content.parse::<toml_edit::DocumentMut>()
@@ -240,13 +908,19 @@ fn check_workspace(ws_root: &Path) -> Vec<DiagnosticResult> {
                 &format!("Target {} → {} exists", target.target_type, target.path),
             ));
         } else {
-            results.push(DiagnosticResult::warn(
-                "Workspace",
-                &format!(
-                    "Target {} declared but directory {} not found",
-                    target.target_type, target.path
+            results.push(
+                DiagnosticResult::warn(
+                    "Workspace",
+                    &format!(
+                        "Target {} declared but directory {} not found",
+                        target.target_type, target.path
+                    ),
+                )
+                .at(
+                    &this_yaml_path,
+                    line_of(&this_yaml_path, &format!("path: {}", target.path)),
                 ),
-            ));
+            );
         }
     }
 
@@ -267,7 +941,13 @@ fn check_cargo_toml(project_root: &Path) -> DiagnosticResult {
 
     let doc = match content.parse::<toml_edit::DocumentMut>() {
         Ok(d) => d,
-        Err(e) => return DiagnosticResult::error("Cargo.toml", &format!("Invalid TOML: {}", e)),
+        Err(e) => {
+            let line = e
+                .span()
+                .map(|span| content[..span.start].matches('\n').count() + 1);
+            return DiagnosticResult::error("Cargo.toml", &format!("Invalid TOML: {}", e))
+                .at(&cargo_path, line);
+        }
     };
 
     let deps = match doc.get("dependencies") {
@@ -386,21 +1066,33 @@ fn check_entities(project_root: &Path) -> Vec<DiagnosticResult> {
     } else {
         if !orphans.is_empty() {
             for orphan in &orphans {
-                results.push(DiagnosticResult::warn(
-                    "Entities",
-                    &format!(
-                        "Directory src/entities/{} exists but not declared in mod.rs",
-                        orphan
-                    ),
-                ));
+                results.push(
+                    DiagnosticResult::warn(
+                        "Entities",
+                        &format!(
+                            "Directory src/entities/{} exists but not declared in mod.rs",
+                            orphan
+                        ),
+                    )
+                    .at(&entities_dir.join(orphan), None)
+                    .with_fix(FixAction::DeclareEntity {
+                        entity: orphan.to_string(),
+                    }),
+                );
             }
         }
         if !missing.is_empty() {
             for m in &missing {
-                results.push(DiagnosticResult::error(
-                    "Entities",
-                    &format!("mod.rs declares 'pub mod {}' but directory not found", m),
-                ));
+                results.push(
+                    DiagnosticResult::error(
+                        "Entities",
+                        &format!("mod.rs declares 'pub mod {}' but directory not found", m),
+                    )
+                    .at(
+                        &entities_mod_path,
+                        line_of(&entities_mod_path, &format!("pub mod {};", m)),
+                    ),
+                );
             }
         }
     }
@@ -459,12 +1151,7 @@ fn check_module_registration(project_root: &Path) -> Vec<DiagnosticResult> {
     let mut unregistered = Vec::new();
 
     for name in &entity_names {
-        let needle = format!("\"{}\"", name);
-        let is_registered = if module_content.contains("[this:entity_types]") {
-            markers::has_line_after_marker(&module_content, "[this:entity_types]", &needle)
-        } else {
-            module_content.contains(&needle)
-        };
+        let is_registered = ast::has_entity_type(&module_content, name);
 
         if is_registered {
             registered += 1;
@@ -481,11 +1168,22 @@ fn check_module_registration(project_root: &Path) -> Vec<DiagnosticResult> {
             &format!("All {} entities registered", registered),
         ));
     } else {
+        let has_marker = module_content.contains("[this:entity_types]");
+        let line = line_of(&module_path, "[this:entity_types]")
+            .or_else(|| line_of(&module_path, "fn entity_types("));
         for name in &unregistered {
-            results.push(DiagnosticResult::warn(
+            let result = DiagnosticResult::warn(
                 "Module",
                 &format!("Entity '{}' not registered in module.rs entity_types", name),
-            ));
+            )
+            .at(&module_path, line);
+            results.push(if has_marker {
+                result.with_fix(FixAction::RegisterEntity {
+                    entity: name.clone(),
+                })
+            } else {
+                result
+            });
         }
     }
 
@@ -544,12 +1242,7 @@ fn check_stores_configuration(project_root: &Path) -> Vec<DiagnosticResult> {
 
     for name in &entity_names {
         let plural = naming::pluralize(name);
-        let needle = format!("{}_store:", plural);
-        let is_configured = if stores_content.contains("[this:store_fields]") {
-            markers::has_line_after_marker(&stores_content, "[this:store_fields]", &needle)
-        } else {
-            stores_content.contains(&needle)
-        };
+        let is_configured = ast::has_store_field(&stores_content, &format!("{}_store", plural));
 
         if is_configured {
             configured += 1;
@@ -566,11 +1259,58 @@ fn check_stores_configuration(project_root: &Path) -> Vec<DiagnosticResult> {
             &format!("All {} stores configured", configured),
         ));
     } else {
+        let has_marker = stores_content.contains("[this:store_fields]");
+        let line = line_of(&stores_path, "[this:store_fields]")
+            .or_else(|| line_of(&stores_path, "pub struct "));
         for name in &missing {
-            results.push(DiagnosticResult::warn(
+            let result = DiagnosticResult::warn(
                 "Stores",
                 &format!("No store configured for entity '{}'", name),
-            ));
+            )
+            .at(&stores_path, line);
+            results.push(if has_marker {
+                result.with_fix(FixAction::AddStore {
+                    entity: name.clone(),
+                })
+            } else {
+                result
+            });
+        }
+    }
+
+    results
+}
+
+/// Check that marker-based module.rs and stores.rs still contain every
+/// generator marker (files without any marker predate them and are skipped)
+fn check_markers(project_root: &Path) -> Vec<DiagnosticResult> {
+    let mut results = Vec::new();
+    let files = [
+        ("src/module.rs", markers::MODULE_RS_MARKERS),
+        ("src/stores.rs", markers::STORES_RS_MARKERS),
+    ];
+
+    for (file, specs) in files {
+        let Ok(content) = std::fs::read_to_string(project_root.join(file)) else {
+            continue;
+        };
+        if !content.contains("[this:") {
+            continue;
+        }
+        for spec in specs.iter().filter(|spec| !content.contains(spec.marker)) {
+            let result = DiagnosticResult::warn(
+                "Markers",
+                &format!("{} is missing the '// {}' marker", file, spec.marker),
+            )
+            .at(&project_root.join(file), None);
+            results.push(if markers::restore(&content, spec.marker).is_some() {
+                result.with_fix(FixAction::RestoreMarker {
+                    file,
+                    marker: spec.marker,
+                })
+            } else {
+                result
+            });
         }
     }
 
@@ -601,10 +1341,10 @@ fn check_links(project_root: &Path) -> Vec<DiagnosticResult> {
     let config: super::add_link::LinksConfig = match serde_yaml::from_str(&content) {
         Ok(c) => c,
         Err(e) => {
-            return vec![DiagnosticResult::error(
-                "Links",
-                &format!("Invalid YAML: {}", e),
-            )];
+            return vec![
+                DiagnosticResult::error("Links", &format!("Invalid YAML: {}", e))
+                    .at(&links_path, e.location().map(|l| l.line())),
+            ];
         }
     };
 
@@ -638,34 +1378,83 @@ fn check_links(project_root: &Path) -> Vec<DiagnosticResult> {
     let mut results = Vec::new();
     let mut has_issues = false;
 
+    let is_known = |name: &String| known_entities.contains(name) || yaml_entities.contains(name);
+
     for link in &config.links {
-        let source_known =
-            known_entities.contains(&link.source_type) || yaml_entities.contains(&link.source_type);
-        let target_known =
-            known_entities.contains(&link.target_type) || yaml_entities.contains(&link.target_type);
+        let remove_link = FixAction::RemoveLink {
+            link_type: link.link_type.clone(),
+            source_type: link.source_type.clone(),
+            target_type: link.target_type.clone(),
+        };
 
-        if !source_known {
-            results.push(DiagnosticResult::warn(
-                "Links",
-                &format!(
-                    "'{}' references unknown source entity '{}'",
-                    link.link_type, link.source_type
-                ),
-            ));
+        let line = line_of(&links_path, &format!("link_type: {}", link.link_type));
+
+        if !is_known(&link.source_type) {
+            results.push(
+                DiagnosticResult::warn(
+                    "Links",
+                    &format!(
+                        "'{}' references unknown source entity '{}'",
+                        link.link_type, link.source_type
+                    ),
+                )
+                .at(&links_path, line)
+                .with_fix(remove_link.clone()),
+            );
             has_issues = true;
         }
-        if !target_known {
-            results.push(DiagnosticResult::warn(
-                "Links",
-                &format!(
-                    "'{}' references unknown target entity '{}'",
-                    link.link_type, link.target_type
-                ),
-            ));
+        if !is_known(&link.target_type) {
+            results.push(
+                DiagnosticResult::warn(
+                    "Links",
+                    &format!(
+                        "'{}' references unknown target entity '{}'",
+                        link.link_type, link.target_type
+                    ),
+                )
+                .at(&links_path, line)
+                .with_fix(remove_link),
+            );
             has_issues = true;
         }
     }
 
+    // Validation rules not backed by a link (dangling links are reported above)
+    for (link_type, rules) in &config.validation_rules {
+        for rule in rules {
+            let has_link = config
+                .links
+                .iter()
+                .any(|l| l.link_type == *link_type && l.source_type == rule.source);
+            if has_link {
+                continue;
+            }
+            if let Some(unknown) = std::iter::once(&rule.source)
+                .chain(&rule.targets)
+                .find(|e| !is_known(e))
+            {
+                results.push(
+                    DiagnosticResult::warn(
+                        "Links",
+                        &format!(
+                            "Validation rule '{}' references unknown entity '{}'",
+                            link_type, unknown
+                        ),
+                    )
+                    .at(
+                        &links_path,
+                        line_of(&links_path, &format!("{}:", link_type)),
+                    )
+                    .with_fix(FixAction::RemoveValidationRule {
+                        link_type: link_type.clone(),
+                        source: rule.source.clone(),
+                    }),
+                );
+                has_issues = true;
+            }
+        }
+    }
+
     if !has_issues {
         results.push(DiagnosticResult::pass(
             "Links",
@@ -706,7 +1495,7 @@ fn check_websocket(project_root: &Path) -> Vec<DiagnosticResult> {
     } else {
         vec![DiagnosticResult::warn(
             "WebSocket",
-            "websocket feature enabled in Cargo.toml but WebSocketExposure not found in main.rs",
+            "websocket feature enabled in Cargo.toml but WebSocketExposure not found in main.rs (run `this enable websocket`)",
         )]
     }
 }
@@ -741,7 +1530,42 @@ fn check_grpc(project_root: &Path) -> Vec<DiagnosticResult> {
     } else {
         vec![DiagnosticResult::warn(
             "gRPC",
-            "grpc feature enabled in Cargo.toml but GrpcExposure not found in main.rs",
+            "grpc feature enabled in Cargo.toml but GrpcExposure not found in main.rs (run `this enable grpc`)",
+        )]
+    }
+}
+
+/// Check GraphQL configuration coherence:
+/// If the graphql feature is enabled in Cargo.toml, main.rs should use GraphQLExposure.
+fn check_graphql(project_root: &Path) -> Vec<DiagnosticResult> {
+    let features = super::info::detect_this_features(project_root);
+
+    if !features.graphql {
+        // GraphQL not enabled — nothing to check
+        return vec![];
+    }
+
+    // GraphQL feature is enabled — verify main.rs uses GraphQLExposure
+    let main_path = project_root.join("src/main.rs");
+    let main_content = match std::fs::read_to_string(&main_path) {
+        Ok(c) => c,
+        Err(_) => {
+            return vec![DiagnosticResult::warn(
+                "GraphQL",
+                "graphql feature enabled but src/main.rs not found",
+            )];
+        }
+    };
+
+    if main_content.contains("GraphQLExposure") {
+        vec![DiagnosticResult::pass(
+            "GraphQL",
+            "Feature enabled and GraphQLExposure configured in main.rs",
+        )]
+    } else {
+        vec![DiagnosticResult::warn(
+            "GraphQL",
+            "graphql feature enabled in Cargo.toml but GraphQLExposure not found in main.rs (run `this enable graphql`)",
         )]
     }
 }
@@ -760,10 +1584,13 @@ fn check_events(project_root: &Path) -> Vec<DiagnosticResult> {
             && (main_content.contains("with_default_event_bus")
                 || main_content.contains("with_event_bus"))
         {
-            return vec![DiagnosticResult::warn(
-                "Events",
-                "main.rs uses event bus but config/events.yaml not found",
-            )];
+            return vec![
+                DiagnosticResult::warn(
+                    "Events",
+                    "main.rs uses event bus but config/events.yaml not found",
+                )
+                .at(&main_path, line_of(&main_path, "event_bus")),
+            ];
         }
         return vec![];
     }
@@ -784,11 +1611,11 @@ fn check_events(project_root: &Path) -> Vec<DiagnosticResult> {
     let config: crate::commands::add_event_flow::EventsConfig = match serde_yaml::from_str(&content)
     {
         Ok(c) => c,
-        Err(_) => {
-            results.push(DiagnosticResult::error(
-                "Events",
-                "config/events.yaml has invalid YAML syntax",
-            ));
+        Err(e) => {
+            results.push(
+                DiagnosticResult::error("Events", "config/events.yaml has invalid YAML syntax")
+                    .at(&events_path, e.location().map(|l| l.line())),
+            );
             return results;
         }
     };
@@ -813,9 +1640,9 @@ fn check_events(project_root: &Path) -> Vec<DiagnosticResult> {
                 && let Some(ref sink) = step.sink
                 && !sink_names.contains(sink.as_str())
             {
-                flow_issues.push(format!(
-                    "Flow '{}' references unknown sink '{}'",
-                    flow.name, sink
+                flow_issues.push((
+                    format!("Flow '{}' references unknown sink '{}'", flow.name, sink),
+                    line_of(&events_path, &format!("sink: {}", sink)),
                 ));
             }
         }
@@ -831,8 +1658,8 @@ fn check_events(project_root: &Path) -> Vec<DiagnosticResult> {
             ),
         ));
     } else {
-        for issue in &flow_issues {
-            results.push(DiagnosticResult::warn("Events", issue));
+        for (issue, line) in &flow_issues {
+            results.push(DiagnosticResult::warn("Events", issue).at(&events_path, *line));
         }
     }
 
@@ -842,6 +1669,7 @@ fn check_events(project_root: &Path) -> Vec<DiagnosticResult> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::file_writer::RealWriter;
 
     #[test]
     fn test_check_cargo_toml_with_this() {
@@ -1320,6 +2148,77 @@ this = { package = "this-rs", version = "0.0.6", features = ["grpc"] }
         assert!(results[0].message.contains("main.rs not found"));
     }
 
+    // ================================================================
+    // check_graphql tests
+    // ================================================================
+
+    #[test]
+    fn test_check_graphql_not_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            r#"[package]
+name = "test"
+version = "0.1.0"
+
+[dependencies]
+this = { package = "this-rs", version = "0.0.9", features = ["grpc"] }
+"#,
+        )
+        .unwrap();
+
+        assert!(check_graphql(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_check_graphql_enabled_and_configured() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            r#"[package]
+name = "test"
+version = "0.1.0"
+
+[dependencies]
+this = { package = "this-rs", version = "0.0.9", features = ["graphql"] }
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("src/main.rs"),
+            "use this::server::exposure::graphql::GraphQLExposure;\nfn main() {}",
+        )
+        .unwrap();
+
+        let results = check_graphql(dir.path());
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0].level, DiagnosticLevel::Pass));
+    }
+
+    #[test]
+    fn test_check_graphql_enabled_but_not_configured() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            r#"[package]
+name = "test"
+version = "0.1.0"
+
+[dependencies]
+this = { package = "this-rs", version = "0.0.9", features = ["graphql"] }
+"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+        let results = check_graphql(dir.path());
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0].level, DiagnosticLevel::Warn));
+        assert!(results[0].message.contains("this enable graphql"));
+    }
+
     // ================================================================
     // check_workspace tests
     // ================================================================
@@ -2094,4 +2993,350 @@ this = { package = "this-rs", version = "0.0.8", features = ["websocket", "grpc"
                 .any(|r| matches!(r.level, DiagnosticLevel::Pass) && r.category == "gRPC")
         );
     }
+
+    // ================================================================
+    // --fix tests
+    // ================================================================
+
+    const MODULE_RS: &str = r#"use this::prelude::*;
+
+impl Module for TestModule {
+    fn entity_types(&self) -> Vec<&str> {
+        vec![
+            // [this:entity_types]
+        ]
+    }
+
+    fn register_entities(&self, _registry: &mut EntityRegistry) {
+        // [this:register_entities]
+    }
+
+    fn get_entity_fetcher(&self, _entity_type: &str) -> Option<Arc<dyn EntityFetcher>> {
+        match _entity_type {
+            // [this:entity_fetcher]
+            _ => None,
+        }
+    }
+
+    fn get_entity_creator(&self, _entity_type: &str) -> Option<Arc<dyn EntityCreator>> {
+        match _entity_type {
+            // [this:entity_creator]
+            _ => None,
+        }
+    }
+}
+"#;
+
+    const STORES_RS: &str = r#"use this::prelude::*;
+
+pub struct TestStores {
+    // [this:store_fields]
+}
+
+impl TestStores {
+    pub fn new_in_memory() -> Self {
+        // [this:store_init_vars]
+
+        Self {
+            // [this:store_init_fields]
+        }
+    }
+}
+"#;
+
+    fn fixable_project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/entities/product")).unwrap();
+        std::fs::create_dir_all(dir.path().join("config")).unwrap();
+        std::fs::write(dir.path().join("src/entities/mod.rs"), "").unwrap();
+        std::fs::write(dir.path().join("src/module.rs"), MODULE_RS).unwrap();
+        std::fs::write(dir.path().join("src/stores.rs"), STORES_RS).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_fix_declares_and_registers_orphan_entity() {
+        let dir = fixable_project();
+        // The minimal fixtures predate most of the current scaffolding
+        let results: Vec<DiagnosticResult> = run_checks(dir.path())
+            .into_iter()
+            .filter(|r| r.rule != "check_template_drift")
+            .collect();
+        assert!(results.iter().any(|r| r.fix
+            == Some(FixAction::DeclareEntity {
+                entity: "product".to_string()
+            })));
+
+        let applied = apply_fixes(dir.path(), &results, &RealWriter).unwrap();
+        assert_eq!(applied.len(), 3);
+
+        let mod_rs = std::fs::read_to_string(dir.path().join("src/entities/mod.rs")).unwrap();
+        assert_eq!(mod_rs, "pub mod product;\n");
+        let module = std::fs::read_to_string(dir.path().join("src/module.rs")).unwrap();
+        assert!(module.contains("\"product\","));
+        assert!(module.contains("registry.register(Box::new(ProductDescriptor"));
+        let stores = std::fs::read_to_string(dir.path().join("src/stores.rs")).unwrap();
+        assert!(stores.contains("pub products_store: Arc<dyn ProductStore>,"));
+        assert!(stores.contains("InMemoryProductStore::default()"));
+
+        // A second pass has nothing left to do
+        let results = run_checks(dir.path());
+        assert!(
+            results
+                .iter()
+                .filter(|r| r.rule != "check_template_drift")
+                .all(|r| r.fix.is_none())
+        );
+    }
+
+    #[test]
+    fn test_fix_restores_marker_before_registering() {
+        let dir = fixable_project();
+        std::fs::write(dir.path().join("src/entities/mod.rs"), "pub mod product;\n").unwrap();
+        std::fs::write(
+            dir.path().join("src/module.rs"),
+            MODULE_RS.replace("            // [this:entity_fetcher]\n", ""),
+        )
+        .unwrap();
+
+        let results = check_markers(dir.path());
+        assert_eq!(results.len(), 1);
+        assert!(results[0].message.contains("[this:entity_fetcher]"));
+
+        let results = run_checks(dir.path());
+        apply_fixes(dir.path(), &results, &RealWriter).unwrap();
+        let module = std::fs::read_to_string(dir.path().join("src/module.rs")).unwrap();
+        assert!(module.contains(
+            "match entity_type {\n            // [this:entity_fetcher]\n            \"product\" =>"
+        ));
+    }
+
+    #[test]
+    fn test_check_markers_skips_files_without_markers() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/module.rs"), "fn entity_types() {}").unwrap();
+        assert!(check_markers(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_fix_removes_dangling_link_and_rule() {
+        let dir = fixable_project();
+        std::fs::write(
+            dir.path().join("config/links.yaml"),
+            r#"entities: []
+links:
+  - link_type: has_ghost
+    source_type: product
+    target_type: ghost
+    forward_route_name: ghosts
+    reverse_route_name: product
+  - link_type: has_product
+    source_type: product
+    target_type: product
+    forward_route_name: products
+    reverse_route_name: product
+validation_rules:
+  has_ghost:
+    - source: product
+      targets: [ghost]
+  orphan_rule:
+    - source: phantom
+      targets: [product]
+"#,
+        )
+        .unwrap();
+
+        let results = check_links(dir.path());
+        assert!(
+            results
+                .iter()
+                .any(|r| r.message.contains("Validation rule 'orphan_rule'"))
+        );
+
+        apply_fixes(dir.path(), &results, &RealWriter).unwrap();
+        let content = std::fs::read_to_string(dir.path().join("config/links.yaml")).unwrap();
+        let config: crate::commands::add_link::LinksConfig =
+            serde_yaml::from_str(&content).unwrap();
+        assert_eq!(config.links.len(), 1);
+        assert_eq!(config.links[0].link_type, "has_product");
+        assert!(config.validation_rules.is_empty());
+    }
+
+    #[test]
+    fn test_fix_dry_run_writes_nothing() {
+        let dir = fixable_project();
+        let writer = crate::utils::file_writer::DryRunWriter::new();
+        let results = run_checks(dir.path());
+
+        let applied = apply_fixes(dir.path(), &results, &writer).unwrap();
+        assert!(!applied.is_empty());
+        assert_eq!(writer.files_updated().len(), 3);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/module.rs")).unwrap(),
+            MODULE_RS
+        );
+    }
+
+    #[test]
+    fn test_detect_store_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        let entity_dir = dir.path().join("src/entities/order");
+        std::fs::create_dir_all(&entity_dir).unwrap();
+        std::fs::write(
+            entity_dir.join("store.rs"),
+            "pub struct PostgresOrderStore {}",
+        )
+        .unwrap();
+        assert_eq!(
+            detect_store_backend(dir.path(), "order", "Order"),
+            "postgres"
+        );
+        assert_eq!(
+            detect_store_backend(dir.path(), "missing", "Missing"),
+            "in-memory"
+        );
+    }
+
+    // ================================================================
+    // Rule ids, locations, formats and policy
+    // ================================================================
+
+    #[test]
+    fn test_run_checks_tags_rules_and_locations() {
+        let dir = fixable_project();
+        std::fs::write(
+            dir.path().join("config/links.yaml"),
+            "entities: []\nlinks:\n  - link_type: has_ghost\n    source_type: product\n    target_type: ghost\n    forward_route_name: ghosts\n    reverse_route_name: product\nvalidation_rules: {}\n",
+        )
+        .unwrap();
+
+        let results = run_checks(dir.path());
+        assert!(results.iter().all(|r| !r.rule.is_empty()));
+        assert!(
+            results
+                .iter()
+                .all(|r| RULES.iter().any(|(id, _)| *id == r.rule))
+        );
+
+        let link = results
+            .iter()
+            .find(|r| r.rule == "check_links" && r.message.contains("ghost"))
+            .unwrap();
+        assert_eq!(
+            link.file.as_deref(),
+            Some(dir.path().join("config/links.yaml").as_path())
+        );
+        assert_eq!(link.line, Some(3));
+
+        let orphan = results.iter().find(|r| r.rule == "check_entities").unwrap();
+        assert_eq!(
+            orphan.file.as_deref(),
+            Some(dir.path().join("src/entities/product").as_path())
+        );
+    }
+
+    #[test]
+    fn test_invalid_links_yaml_reports_line() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("config")).unwrap();
+        std::fs::write(
+            dir.path().join("config/links.yaml"),
+            "entities: []\nlinks: [\n",
+        )
+        .unwrap();
+
+        let results = check_links(dir.path());
+        assert!(results[0].line.is_some());
+    }
+
+    #[test]
+    fn test_policy_deny_and_allow() {
+        let warn = DiagnosticResult {
+            rule: "check_links".to_string(),
+            ..DiagnosticResult::warn("Links", "dangling")
+        };
+        let error = DiagnosticResult {
+            rule: "check_events".to_string(),
+            ..DiagnosticResult::error("Events", "broken")
+        };
+
+        let default = Policy::new(&[], &[], Vec::new()).unwrap();
+        assert!(!default.fails(&warn));
+        assert!(default.fails(&error));
+
+        let deny_all = Policy::new(&["warnings".to_string()], &[], Vec::new()).unwrap();
+        assert!(deny_all.fails(&warn));
+
+        let deny_rule = Policy::new(&["check_links".to_string()], &[], Vec::new()).unwrap();
+        assert!(deny_rule.fails(&warn));
+        let deny_other = Policy::new(&["check_grpc".to_string()], &[], Vec::new()).unwrap();
+        assert!(!deny_other.fails(&warn));
+
+        let allow = Policy::new(&[], &["check_events".to_string()], Vec::new()).unwrap();
+        assert!(allow.allows(&error));
+        assert!(!allow.allows(&warn));
+    }
+
+    #[test]
+    fn test_policy_rejects_unknown_rule() {
+        let err = Policy::new(&[], &["check_nothing".to_string()], Vec::new())
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string()
+                .contains("Unknown doctor rule 'check_nothing'")
+        );
+        assert!(Policy::new(&["warnings".to_string()], &[], Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn test_json_report_summary() {
+        let results = vec![
+            DiagnosticResult::pass("Links", "ok"),
+            DiagnosticResult {
+                rule: "check_links".to_string(),
+                ..DiagnosticResult::warn("Links", "dangling")
+            },
+        ];
+        let report = json_report(&results, &Policy::new(&[], &[], Vec::new()).unwrap());
+        assert_eq!(report["summary"]["warn"], 1);
+        assert_eq!(report["failed"], false);
+        assert_eq!(report["diagnostics"][1]["rule"], "check_links");
+
+        let report = json_report(
+            &results,
+            &Policy::new(&["warnings".to_string()], &[], Vec::new()).unwrap(),
+        );
+        assert_eq!(report["failed"], true);
+        assert_eq!(report["summary"]["failing"], 1);
+    }
+
+    #[test]
+    fn test_sarif_report_shape() {
+        let results = vec![
+            DiagnosticResult::pass("Cargo.toml", "ok"),
+            DiagnosticResult {
+                rule: "check_links".to_string(),
+                ..DiagnosticResult::warn("Links", "dangling")
+                    .at(Path::new("config/links.yaml"), Some(4))
+            },
+        ];
+        let report = sarif_report(&results, &Policy::new(&[], &[], Vec::new()).unwrap());
+        assert_eq!(report["version"], "2.1.0");
+        let findings = report["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(findings.len(), 1, "passes are omitted");
+        assert_eq!(findings[0]["ruleId"], "check_links");
+        assert_eq!(findings[0]["level"], "warning");
+        let location = &findings[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "config/links.yaml");
+        assert_eq!(location["region"]["startLine"], 4);
+        assert_eq!(
+            report["runs"][0]["tool"]["driver"]["rules"]
+                .as_array()
+                .unwrap()
+                .len(),
+            RULES.len()
+        );
+    }
 }
@@ -0,0 +1,488 @@
+//! `check_auth`: validate `config/auth.yaml` policies against the project.
+
+use std::path::Path;
+
+use regex::Regex;
+
+use super::DiagnosticResult;
+
+/// Operations an entity policy can be declared for
+const OPERATIONS: &[&str] = &["list", "get", "create", "update", "delete", "read", "write"];
+
+/// A parsed authorization policy
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Policy {
+    Public,
+    Authenticated,
+    Owner,
+    AdminOnly,
+    ServiceOnly,
+    Role(String),
+    OwnerOrRole(String),
+    Resolver(String),
+    OwnerOrResolver(String),
+    /// `all:a,b` — every term must pass
+    All(Vec<Policy>),
+    /// `any:a,b` — one term must pass
+    Any(Vec<Policy>),
+    /// `not:a` — deny when the term passes (only meaningful inside `all:`)
+    Not(Box<Policy>),
+}
+
+impl Policy {
+    /// Parse a policy string such as `owner_or_role:admin` or
+    /// `all:authenticated,not:resolver:is_readonly`
+    pub(super) fn parse(policy: &str) -> Result<Self, String> {
+        let policy = policy.trim();
+        if let Some(terms) = policy.strip_prefix("all:") {
+            return Ok(Policy::All(Self::parse_terms(terms)?));
+        }
+        if let Some(terms) = policy.strip_prefix("any:") {
+            return Ok(Policy::Any(Self::parse_terms(terms)?));
+        }
+        Self::parse_term(policy)
+    }
+
+    fn parse_terms(terms: &str) -> Result<Vec<Self>, String> {
+        terms.split(',').map(Self::parse_term).collect()
+    }
+
+    fn parse_term(term: &str) -> Result<Self, String> {
+        let term = term.trim();
+        if term.starts_with("all:") || term.starts_with("any:") {
+            return Err(format!("nested statement '{}' is not supported", term));
+        }
+        if let Some(inner) = term.strip_prefix("not:") {
+            return Ok(Policy::Not(Box::new(Self::parse_term(inner)?)));
+        }
+        let named = |prefix: &str, make: fn(String) -> Policy| -> Option<Result<Policy, String>> {
+            let name = term.strip_prefix(prefix)?;
+            let valid = Regex::new(r"^[A-Za-z0-9_.-]+$").unwrap();
+            Some(if valid.is_match(name) {
+                Ok(make(name.to_string()))
+            } else {
+                Err(format!("'{}' needs a name after '{}'", term, prefix))
+            })
+        };
+        match term {
+            "" => Err("empty policy".to_string()),
+            "public" => Ok(Policy::Public),
+            "authenticated" => Ok(Policy::Authenticated),
+            "owner" => Ok(Policy::Owner),
+            "admin_only" => Ok(Policy::AdminOnly),
+            "service_only" => Ok(Policy::ServiceOnly),
+            _ => named("owner_or_role:", Policy::OwnerOrRole)
+                .or_else(|| named("owner_or_resolver:", Policy::OwnerOrResolver))
+                .or_else(|| named("role:", Policy::Role))
+                .or_else(|| named("resolver:", Policy::Resolver))
+                .unwrap_or_else(|| Err(format!("unknown policy '{}'", term))),
+        }
+    }
+
+    /// How restrictive the policy is: 0 = anyone, 1 = any signed-in caller,
+    /// 2 = a subset of callers (owner, role, resolver), 3 = admins or services
+    pub(super) fn strictness(&self) -> u8 {
+        match self {
+            Policy::Public | Policy::Not(_) => 0,
+            Policy::Authenticated => 1,
+            Policy::Owner
+            | Policy::Role(_)
+            | Policy::OwnerOrRole(_)
+            | Policy::Resolver(_)
+            | Policy::OwnerOrResolver(_) => 2,
+            Policy::AdminOnly | Policy::ServiceOnly => 3,
+            // Negations only narrow the other terms of an `all:` statement
+            Policy::All(terms) => terms
+                .iter()
+                .filter(|t| !matches!(t, Policy::Not(_)))
+                .map(Policy::strictness)
+                .max()
+                .unwrap_or(0),
+            Policy::Any(terms) => terms.iter().map(Policy::strictness).min().unwrap_or(0),
+        }
+    }
+
+    /// Names of the custom resolvers the policy refers to
+    pub(super) fn resolvers(&self) -> Vec<&str> {
+        match self {
+            Policy::Resolver(name) | Policy::OwnerOrResolver(name) => vec![name],
+            Policy::All(terms) | Policy::Any(terms) => {
+                terms.iter().flat_map(Policy::resolvers).collect()
+            }
+            Policy::Not(inner) => inner.resolvers(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Resolvers registered with `.with_auth_resolver("name", ...)` in main.rs,
+/// ignoring commented-out lines
+fn registered_resolvers(main_rs: &str) -> Vec<String> {
+    let re = Regex::new(r#"with_auth_resolver\(\s*"([^"]+)""#).unwrap();
+    main_rs
+        .lines()
+        .filter(|l| !l.trim_start().starts_with("//"))
+        .flat_map(|l| {
+            re.captures_iter(l)
+                .map(|c| c[1].to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Check config/auth.yaml: policy grammar, entity keys, resolvers, and
+/// policies looser than `default_policy`
+pub(super) fn check_auth(project_root: &Path) -> Vec<DiagnosticResult> {
+    let auth_path = project_root.join("config/auth.yaml");
+    if !auth_path.exists() {
+        return vec![];
+    }
+
+    let content = match std::fs::read_to_string(&auth_path) {
+        Ok(c) => c,
+        Err(e) => {
+            return vec![DiagnosticResult::error(
+                "Auth",
+                &format!("Cannot read config/auth.yaml: {}", e),
+            )];
+        }
+    };
+    let config: serde_yaml::Value = match serde_yaml::from_str(&content) {
+        Ok(c) => c,
+        Err(e) => {
+            return vec![
+                DiagnosticResult::error("Auth", &format!("Invalid YAML: {}", e))
+                    .at(&auth_path, e.location().map(|l| l.line())),
+            ];
+        }
+    };
+
+    let mut results = Vec::new();
+    let main_path = project_root.join("src/main.rs");
+    let registered = std::fs::read_to_string(&main_path)
+        .ok()
+        .map(|main_rs| registered_resolvers(&main_rs));
+
+    let default_line = find_line_after(&content, None, "default_policy:");
+    let default = match config.get("default_policy") {
+        None => Some(Policy::Authenticated),
+        Some(value) => match value.as_str() {
+            Some(policy) => check_policy(
+                &mut results,
+                &auth_path,
+                registered.as_deref(),
+                "default_policy",
+                policy,
+                default_line,
+            ),
+            None => {
+                results.push(
+                    DiagnosticResult::error("Auth", "default_policy must be a string")
+                        .at(&auth_path, default_line),
+                );
+                None
+            }
+        },
+    };
+
+    let known_entities: Vec<String> = std::fs::read_dir(project_root.join("src/entities"))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut entity_policies = 0;
+    let mut warnings = Vec::new();
+    let entities = config.get("entities").and_then(|e| e.as_mapping());
+    let entities_line = find_line_after(&content, None, "entities:");
+    for (entity, operations) in entities.into_iter().flatten() {
+        let entity = entity.as_str().unwrap_or_default();
+        let entity_line = find_line_after(&content, entities_line, &format!("{}:", entity));
+        if !known_entities.iter().any(|e| e == entity) {
+            warnings.push(
+                DiagnosticResult::warn(
+                    "Auth",
+                    &format!("Policies declared for unknown entity '{}'", entity),
+                )
+                .at(&auth_path, entity_line),
+            );
+        }
+        let Some(operations) = operations.as_mapping() else {
+            results.push(
+                DiagnosticResult::error(
+                    "Auth",
+                    &format!("entities.{} must map operations to policies", entity),
+                )
+                .at(&auth_path, entity_line),
+            );
+            continue;
+        };
+        for (operation, policy) in operations {
+            let operation = operation.as_str().unwrap_or_default();
+            let key = format!("entities.{}.{}", entity, operation);
+            let line = find_line_after(&content, entity_line, &format!("{}:", operation));
+            if !OPERATIONS.contains(&operation) {
+                warnings.push(
+                    DiagnosticResult::warn(
+                        "Auth",
+                        &format!(
+                            "{}: unknown operation (expected one of {})",
+                            key,
+                            OPERATIONS.join(", ")
+                        ),
+                    )
+                    .at(&auth_path, line),
+                );
+            }
+            let Some(policy) = policy.as_str() else {
+                results.push(
+                    DiagnosticResult::error("Auth", &format!("{}: policy must be a string", key))
+                        .at(&auth_path, line),
+                );
+                continue;
+            };
+            entity_policies += 1;
+            let Some(parsed) = check_policy(
+                &mut results,
+                &auth_path,
+                registered.as_deref(),
+                &key,
+                policy,
+                line,
+            ) else {
+                continue;
+            };
+            if let Some(default) = &default
+                && parsed.strictness() < default.strictness()
+            {
+                warnings.push(
+                    DiagnosticResult::warn(
+                        "Auth",
+                        &format!("{}: '{}' is looser than default_policy", key, policy.trim()),
+                    )
+                    .at(&auth_path, line),
+                );
+            }
+        }
+    }
+
+    if results.is_empty() && warnings.is_empty() {
+        results.push(DiagnosticResult::pass(
+            "Auth",
+            &format!(
+                "auth.yaml valid ({} entity polic{})",
+                entity_policies,
+                if entity_policies == 1 { "y" } else { "ies" }
+            ),
+        ));
+    }
+    results.extend(warnings);
+    results
+}
+
+/// Parse a policy, reporting grammar errors and resolvers missing from
+/// `registered` (when main.rs could be read)
+fn check_policy(
+    results: &mut Vec<DiagnosticResult>,
+    auth_path: &Path,
+    registered: Option<&[String]>,
+    key: &str,
+    policy: &str,
+    line: Option<usize>,
+) -> Option<Policy> {
+    let parsed = match Policy::parse(policy) {
+        Ok(p) => p,
+        Err(e) => {
+            results.push(
+                DiagnosticResult::error(
+                    "Auth",
+                    &format!("{}: invalid policy '{}' ({})", key, policy, e),
+                )
+                .at(auth_path, line),
+            );
+            return None;
+        }
+    };
+    for resolver in parsed.resolvers() {
+        if registered.is_some_and(|r| !r.iter().any(|name| name == resolver)) {
+            results.push(
+                DiagnosticResult::error(
+                    "Auth",
+                    &format!(
+                        "{}: resolver '{}' is not registered with with_auth_resolver in src/main.rs",
+                        key, resolver
+                    ),
+                )
+                .at(auth_path, line),
+            );
+        }
+    }
+    Some(parsed)
+}
+
+/// 1-based line of the first line containing `needle` after line `after`
+fn find_line_after(content: &str, after: Option<usize>, needle: &str) -> Option<usize> {
+    let start = after.unwrap_or(0);
+    content
+        .lines()
+        .enumerate()
+        .skip(start)
+        .find(|(_, l)| l.trim_start().starts_with(needle))
+        .map(|(i, _)| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::doctor::DiagnosticLevel;
+    use tempfile::TempDir;
+
+    fn project(auth_yaml: &str, main_rs: &str) -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("src/entities/order")).unwrap();
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::write(root.join("config/auth.yaml"), auth_yaml).unwrap();
+        std::fs::write(root.join("src/main.rs"), main_rs).unwrap();
+        tmp
+    }
+
+    fn findings(auth_yaml: &str, main_rs: &str) -> Vec<DiagnosticResult> {
+        let tmp = project(auth_yaml, main_rs);
+        check_auth(tmp.path())
+            .into_iter()
+            .filter(|r| !matches!(r.level, DiagnosticLevel::Pass))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_policies() {
+        assert_eq!(Policy::parse("public").unwrap(), Policy::Public);
+        assert_eq!(
+            Policy::parse("owner_or_role:admin").unwrap(),
+            Policy::OwnerOrRole("admin".to_string())
+        );
+        assert_eq!(
+            Policy::parse("all:authenticated,not:resolver:is_readonly").unwrap(),
+            Policy::All(vec![
+                Policy::Authenticated,
+                Policy::Not(Box::new(Policy::Resolver("is_readonly".to_string()))),
+            ])
+        );
+        assert_eq!(
+            Policy::parse("any:owner, role:admin").unwrap(),
+            Policy::Any(vec![Policy::Owner, Policy::Role("admin".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_policies() {
+        assert!(
+            Policy::parse("everyone")
+                .unwrap_err()
+                .contains("unknown policy")
+        );
+        assert!(Policy::parse("role:").unwrap_err().contains("needs a name"));
+        assert!(
+            Policy::parse("all:any:owner")
+                .unwrap_err()
+                .contains("nested")
+        );
+        assert!(Policy::parse("").is_err());
+    }
+
+    #[test]
+    fn test_strictness() {
+        let s = |p: &str| Policy::parse(p).unwrap().strictness();
+        assert_eq!(s("public"), 0);
+        assert_eq!(s("authenticated"), 1);
+        assert_eq!(s("owner_or_resolver:x"), 2);
+        assert_eq!(s("admin_only"), 3);
+        assert_eq!(s("any:public,admin_only"), 0);
+        assert_eq!(s("all:authenticated,not:role:guest"), 1);
+        assert_eq!(s("not:role:guest"), 0);
+    }
+
+    #[test]
+    fn test_registered_resolvers_ignores_comments() {
+        let main_rs = "    .with_auth_resolver(\"is_manager\", |ctx, _| true)\n    // .with_auth_resolver(\"is_delegated\", |ctx, _| true)\n";
+        assert_eq!(registered_resolvers(main_rs), vec!["is_manager"]);
+    }
+
+    #[test]
+    fn test_valid_auth_yaml_passes() {
+        let tmp = project(
+            "provider: wami\ndefault_policy: authenticated\nentities:\n  order:\n    list: authenticated\n    delete: admin_only\n    update: \"resolver:is_manager\"\n",
+            ".with_auth_resolver(\"is_manager\", |ctx, _| true)\n",
+        );
+        let results = check_auth(tmp.path());
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0].level, DiagnosticLevel::Pass));
+        assert!(results[0].message.contains("3 entity policies"));
+    }
+
+    #[test]
+    fn test_no_auth_yaml_reports_nothing() {
+        let tmp = TempDir::new().unwrap();
+        assert!(check_auth(tmp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_flags_unknown_entity_and_operation() {
+        let found = findings(
+            "default_policy: authenticated\nentities:\n  ghost:\n    list: authenticated\n  order:\n    archive: authenticated\n",
+            "",
+        );
+        assert_eq!(found.len(), 2);
+        assert!(found[0].message.contains("unknown entity 'ghost'"));
+        assert_eq!(found[0].line, Some(3));
+        assert!(
+            found[1]
+                .message
+                .contains("entities.order.archive: unknown operation")
+        );
+        assert_eq!(found[1].line, Some(6));
+    }
+
+    #[test]
+    fn test_flags_invalid_policy_and_unregistered_resolver() {
+        let found = findings(
+            "default_policy: authenticated\nentities:\n  order:\n    list: everyone\n    update: \"owner_or_resolver:is_delegated\"\n",
+            "// .with_auth_resolver(\"is_delegated\", |ctx, _| true)\n",
+        );
+        assert_eq!(found.len(), 2);
+        assert!(matches!(found[0].level, DiagnosticLevel::Error));
+        assert!(found[0].message.contains("invalid policy 'everyone'"));
+        assert_eq!(found[0].line, Some(4));
+        assert!(
+            found[1]
+                .message
+                .contains("resolver 'is_delegated' is not registered")
+        );
+        assert_eq!(found[1].line, Some(5));
+    }
+
+    #[test]
+    fn test_warns_on_policies_looser_than_default() {
+        let found = findings(
+            "default_policy: authenticated\nentities:\n  order:\n    list: public\n    get: \"any:owner,public\"\n    delete: admin_only\n",
+            "",
+        );
+        assert_eq!(found.len(), 2);
+        assert!(
+            found
+                .iter()
+                .all(|r| matches!(r.level, DiagnosticLevel::Warn))
+        );
+        assert!(
+            found[0]
+                .message
+                .contains("entities.order.list: 'public' is looser")
+        );
+        assert!(found[1].message.contains("entities.order.get"));
+    }
+}
@@ -0,0 +1,533 @@
+//! `check_template_drift`: compare generator-owned files with what the
+//! installed CLI's templates would scaffold today.
+
+use std::path::Path;
+
+use anyhow::Result;
+use regex::Regex;
+
+use super::{DiagnosticResult, FixAction};
+use crate::templates::TemplateEngine;
+use crate::utils::{markers, naming};
+
+/// Generator-owned files and the templates they are scaffolded from
+pub(super) const OWNED_FILES: &[(&str, &str)] = &[
+    ("src/module.rs", "project/module.rs"),
+    ("src/stores.rs", "project/stores.rs"),
+];
+
+/// A `fn`/`struct`/`trait`/`enum` declared in a template, with its extent
+#[derive(Debug)]
+struct Item {
+    /// `fn new_lmdb`, `struct EntityStore`, ...
+    key: String,
+    /// First line of the block, including doc comments and attributes
+    start: usize,
+    /// Line of the declaration itself
+    signature: usize,
+    /// Last line of the block (closing brace)
+    end: usize,
+    /// Trimmed header of the enclosing `impl`/`trait` block, if any
+    container: Option<String>,
+}
+
+/// Render the current template for a generator-owned file of this project
+fn render_current(project_root: &Path, template: &str) -> Result<String> {
+    let mut context = tera::Context::new();
+    let name = project_snake_name(project_root);
+    context.insert("project_name", &name);
+    context.insert("project_name_snake", &name);
+    TemplateEngine::for_project(project_root)?.render(template, &context)
+}
+
+/// Snake-case project name as used in type names (`FooStores`, `FooModule`),
+/// read back from the scaffolded files so renamed packages still match
+fn project_snake_name(project_root: &Path) -> String {
+    let re = Regex::new(r"pub struct (\w+?)(Stores|Module)\b").unwrap();
+    for (file, _) in OWNED_FILES {
+        if let Ok(content) = std::fs::read_to_string(project_root.join(file))
+            && let Some(caps) = re.captures(&content)
+        {
+            return naming::to_snake_case(&caps[1]);
+        }
+    }
+    super::detect_project_name(project_root).replace('-', "_")
+}
+
+fn declaration_re() -> Regex {
+    Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(fn|struct|trait|enum)\s+(\w+)").unwrap()
+}
+
+/// Whether `content` declares the item `key` (e.g. `fn new_lmdb`)
+fn declares(content: &str, key: &str) -> bool {
+    let (kind, name) = key.split_once(' ').unwrap_or(("", key));
+    Regex::new(&format!(r"\b{}\s+{}\b", kind, regex::escape(name)))
+        .unwrap()
+        .is_match(content)
+}
+
+/// Items declared at the top level or directly inside an impl/trait block
+fn template_items(template: &str) -> Vec<Item> {
+    let lines: Vec<&str> = template.lines().collect();
+    let re = declaration_re();
+    let mut items = Vec::new();
+    let mut depth: i32 = 0;
+    // Header of the block opened at each depth
+    let mut headers: Vec<String> = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if depth <= 1
+            && let Some(caps) = re.captures(line)
+        {
+            let container = if depth == 1 {
+                headers.first().cloned()
+            } else {
+                None
+            };
+            let mut start = i;
+            while start > 0 {
+                let prev = lines[start - 1].trim();
+                if prev.starts_with("///") || prev.starts_with("#[") {
+                    start -= 1;
+                } else {
+                    break;
+                }
+            }
+            items.push(Item {
+                key: format!("{} {}", &caps[1], &caps[2]),
+                start,
+                signature: i,
+                end: block_end(&lines, i),
+                container,
+            });
+        }
+        for c in line.chars() {
+            match c {
+                '{' => {
+                    if depth as usize == headers.len() {
+                        headers.push(line.trim().to_string());
+                    }
+                    depth += 1;
+                }
+                '}' => {
+                    depth -= 1;
+                    headers.truncate(depth.max(0) as usize);
+                }
+                _ => {}
+            }
+        }
+    }
+    items
+}
+
+/// Last line of the block starting at `start` (the line closing its braces)
+fn block_end(lines: &[&str], start: usize) -> usize {
+    let mut depth = 0;
+    let mut opened = false;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        for c in line.chars() {
+            match c {
+                '{' => {
+                    depth += 1;
+                    opened = true;
+                }
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        if (opened && depth <= 0) || (!opened && line.trim_end().ends_with(';')) {
+            return i;
+        }
+    }
+    lines.len().saturating_sub(1)
+}
+
+/// Compare the project's generator-owned files with the current templates
+pub(super) fn check_template_drift(project_root: &Path) -> Vec<DiagnosticResult> {
+    let mut results = Vec::new();
+    let mut checked = Vec::new();
+    let marker_re = Regex::new(r"\[this:[a-z0-9_]+\]").unwrap();
+    // Core markers are reported (and fixed) by check_markers
+    let core_markers: Vec<&str> = markers::MODULE_RS_MARKERS
+        .iter()
+        .chain(markers::STORES_RS_MARKERS)
+        .map(|spec| spec.marker)
+        .collect();
+
+    for (file, template) in OWNED_FILES {
+        let path = project_root.join(file);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        // Hand-written (pre-marker) files are not generator-owned
+        if !content.contains("[this:") {
+            continue;
+        }
+        let rendered = match render_current(project_root, template) {
+            Ok(r) => r,
+            Err(e) => {
+                results.push(DiagnosticResult::error(
+                    "Templates",
+                    &format!("Cannot render current {} template: {:#}", template, e),
+                ));
+                continue;
+            }
+        };
+        checked.push(*file);
+
+        let items = template_items(&rendered);
+        let missing_items: Vec<&Item> = items
+            .iter()
+            .filter(|item| !declares(&content, &item.key))
+            .collect();
+        for item in &missing_items {
+            let container_present = item
+                .container
+                .as_ref()
+                .is_none_or(|header| content.lines().any(|l| l.trim() == header));
+            let result = DiagnosticResult::warn(
+                "Templates",
+                &format!(
+                    "{} lacks `{}` scaffolded by this-cli {} templates",
+                    file,
+                    item.key,
+                    env!("CARGO_PKG_VERSION")
+                ),
+            )
+            .at(&path, None);
+            results.push(if container_present {
+                result.with_fix(FixAction::AddScaffolding {
+                    file,
+                    item: item.key.clone(),
+                })
+            } else {
+                result
+            });
+        }
+
+        let rendered_lines: Vec<&str> = rendered.lines().collect();
+        for (i, line) in rendered_lines.iter().enumerate() {
+            let Some(marker) = marker_re.find(line).map(|m| m.as_str()) else {
+                continue;
+            };
+            if content.contains(marker) || core_markers.contains(&marker) {
+                continue;
+            }
+            // Markers inside missing items come back with the item
+            if missing_items
+                .iter()
+                .any(|item| item.start <= i && i <= item.end)
+            {
+                continue;
+            }
+            let result = DiagnosticResult::warn(
+                "Templates",
+                &format!(
+                    "{} is missing the '// {}' marker used by newer generators",
+                    file, marker
+                ),
+            );
+            let anchor = marker_anchor_index(&rendered, &content, marker);
+            results.push(
+                if anchor.is_some() {
+                    result.with_fix(FixAction::RestoreTemplateMarker {
+                        file,
+                        marker: marker.to_string(),
+                    })
+                } else {
+                    result
+                }
+                .at(&path, anchor.map(|i| i + 1)),
+            );
+        }
+    }
+
+    if results.is_empty() && !checked.is_empty() {
+        results.push(DiagnosticResult::pass(
+            "Templates",
+            &format!(
+                "{} match the this-cli {} templates",
+                checked.join(" and "),
+                env!("CARGO_PKG_VERSION")
+            ),
+        ));
+    }
+    results
+}
+
+/// Lines locating a marker in the template: the declaration of the item it
+/// sits in, then the nearest non-blank line above it (if different)
+fn marker_anchors(rendered: &str, marker: &str) -> Option<Vec<String>> {
+    let lines: Vec<&str> = rendered.lines().collect();
+    let idx = lines.iter().position(|l| l.contains(marker))?;
+    let item = template_items(rendered)
+        .into_iter()
+        .filter(|item| item.signature < idx && idx <= item.end)
+        .max_by_key(|item| item.signature)?;
+    let mut anchors = vec![lines[item.signature].trim().to_string()];
+    if let Some(previous) = lines[..idx].iter().rposition(|l| !l.trim().is_empty())
+        && previous > item.signature
+    {
+        anchors.push(lines[previous].trim().to_string());
+    }
+    Some(anchors)
+}
+
+/// Index of the line in `content` after which a missing template marker belongs
+fn marker_anchor_index(rendered: &str, content: &str, marker: &str) -> Option<usize> {
+    let anchors = marker_anchors(rendered, marker)?;
+    let (item_anchor, rest) = anchors.split_first()?;
+    let caps = declaration_re().captures(item_anchor)?;
+    let key = format!("{} {}", &caps[1], &caps[2]);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut idx = lines.iter().position(|l| declares(l, &key))?;
+    for anchor in rest {
+        idx = lines[idx + 1..].iter().position(|l| l.trim() == anchor)? + idx + 1;
+    }
+    Some(idx)
+}
+
+/// Re-insert a template marker after its anchors, with the template's indentation
+fn restore_template_marker(rendered: &str, content: &str, marker: &str) -> Option<String> {
+    let marker_line = rendered.lines().find(|l| l.contains(marker))?;
+    let idx = marker_anchor_index(rendered, content, marker)?;
+    let mut lines: Vec<&str> = content.lines().collect();
+    lines.insert(idx + 1, marker_line);
+
+    let mut result = lines.join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    Some(result)
+}
+
+/// Copy a missing item from the current template into `content`, at the end
+/// of the block that encloses it in the template
+fn add_scaffolding(rendered: &str, content: &str, key: &str) -> Option<String> {
+    let lines: Vec<&str> = rendered.lines().collect();
+    let item = template_items(rendered)
+        .into_iter()
+        .find(|item| item.key == key)?;
+    let block = lines[item.start..=item.end].join("\n");
+
+    let mut result: Vec<String> = content.lines().map(str::to_string).collect();
+    let insert_at = match &item.container {
+        Some(header) => {
+            let open = result.iter().position(|l| l.trim() == header)?;
+            let indent = &result[open][..result[open].len() - result[open].trim_start().len()];
+            let closing = format!("{}}}", indent);
+            result[open + 1..].iter().position(|l| *l == closing)? + open + 1
+        }
+        None => result.len(),
+    };
+    let separated = insert_at == 0 || result[insert_at - 1].trim().is_empty();
+    result.insert(
+        insert_at,
+        if separated {
+            block
+        } else {
+            format!("\n{}", block)
+        },
+    );
+
+    let mut updated = result.join("\n");
+    if content.ends_with('\n') || item.container.is_none() {
+        updated.push('\n');
+    }
+    Some(updated)
+}
+
+/// `FixAction::AddScaffolding`: idempotent, unchanged when already declared
+pub(super) fn apply_add_scaffolding(
+    project_root: &Path,
+    template: &str,
+    content: &str,
+    key: &str,
+) -> Result<String> {
+    if declares(content, key) {
+        return Ok(content.to_string());
+    }
+    let rendered = render_current(project_root, template)?;
+    add_scaffolding(&rendered, content, key)
+        .ok_or_else(|| anyhow::anyhow!("Cannot locate where `{}` belongs", key))
+}
+
+/// `FixAction::RestoreTemplateMarker`: idempotent, unchanged when present
+pub(super) fn apply_restore_marker(
+    project_root: &Path,
+    template: &str,
+    content: &str,
+    marker: &str,
+) -> Result<String> {
+    if content.contains(marker) {
+        return Ok(content.to_string());
+    }
+    let rendered = render_current(project_root, template)?;
+    restore_template_marker(&rendered, content, marker)
+        .ok_or_else(|| anyhow::anyhow!("Cannot locate where {} belongs", marker))
+}
+
+/// Bring a generator-owned file up to the current templates: add every
+/// missing item, then every missing marker that can be placed. Files without
+/// markers (hand-written) are returned unchanged.
+pub(crate) fn sync_with_templates(
+    project_root: &Path,
+    file: &str,
+    content: &str,
+) -> Result<String> {
+    let Some(template) = template_for(file) else {
+        return Ok(content.to_string());
+    };
+    if !content.contains("[this:") {
+        return Ok(content.to_string());
+    }
+    let rendered = render_current(project_root, template)?;
+    let mut updated = content.to_string();
+    for item in template_items(&rendered) {
+        if !declares(&updated, &item.key)
+            && let Some(next) = add_scaffolding(&rendered, &updated, &item.key)
+        {
+            updated = next;
+        }
+    }
+    let marker_re = Regex::new(r"\[this:[a-z0-9_]+\]").unwrap();
+    for marker in marker_re.find_iter(&rendered).map(|m| m.as_str()) {
+        if !updated.contains(marker)
+            && let Some(next) = restore_template_marker(&rendered, &updated, marker)
+        {
+            updated = next;
+        }
+    }
+    Ok(updated)
+}
+
+/// Template a generator-owned file is scaffolded from
+pub(super) fn template_for(file: &str) -> Option<&'static str> {
+    OWNED_FILES
+        .iter()
+        .find(|(owned, _)| *owned == file)
+        .map(|(_, template)| *template)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::doctor::DiagnosticLevel;
+    use tempfile::TempDir;
+
+    fn scaffolded(name: &str) -> (TempDir, String, String) {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            format!("[package]\nname = \"{}\"\n", name),
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        let module = render_current(root, "project/module.rs").unwrap();
+        let stores = render_current(root, "project/stores.rs").unwrap();
+        std::fs::write(root.join("src/module.rs"), &module).unwrap();
+        std::fs::write(root.join("src/stores.rs"), &stores).unwrap();
+        (tmp, module, stores)
+    }
+
+    /// stores.rs as scaffolded before the LMDB constructor existed
+    fn without_lmdb(stores: &str) -> String {
+        let start = stores
+            .find("\n\n    /// Create stores backed by LMDB")
+            .unwrap();
+        let end = start + stores[start..].find("\n    }\n").unwrap() + "\n    }".len();
+        format!("{}{}", &stores[..start], &stores[end..])
+    }
+
+    #[test]
+    fn test_fresh_project_has_no_drift() {
+        let (tmp, _, _) = scaffolded("shop");
+        let results = check_template_drift(tmp.path());
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0].level, DiagnosticLevel::Pass));
+    }
+
+    #[test]
+    fn test_template_items_and_containers() {
+        let (_, _, stores) = scaffolded("shop");
+        let items = template_items(&stores);
+        let lmdb = items.iter().find(|i| i.key == "fn new_lmdb").unwrap();
+        assert_eq!(lmdb.container.as_deref(), Some("impl ShopStores {"));
+        let lines: Vec<&str> = stores.lines().collect();
+        assert!(lines[lmdb.start].contains("/// Create stores backed by LMDB"));
+        assert_eq!(lines[lmdb.end], "    }");
+        let store_trait = items.iter().find(|i| i.key == "trait EntityStore").unwrap();
+        assert!(store_trait.container.is_none());
+    }
+
+    #[test]
+    fn test_missing_constructor_is_reported_and_fixable() {
+        let (tmp, _, stores) = scaffolded("shop");
+        let old = without_lmdb(&stores);
+        std::fs::write(tmp.path().join("src/stores.rs"), &old).unwrap();
+
+        let results = check_template_drift(tmp.path());
+        assert_eq!(results.len(), 1, "markers inside the item are not repeated");
+        assert!(results[0].message.contains("lacks `fn new_lmdb`"));
+        assert!(results[0].fix.is_some());
+
+        let fixed =
+            apply_add_scaffolding(tmp.path(), "project/stores.rs", &old, "fn new_lmdb").unwrap();
+        assert_eq!(fixed, stores);
+    }
+
+    #[test]
+    fn test_missing_marker_is_restored_in_place() {
+        let (tmp, _, stores) = scaffolded("shop");
+        let old = stores.replace("            // [this:store_lmdb_init_fields]\n", "");
+        std::fs::write(tmp.path().join("src/stores.rs"), &old).unwrap();
+
+        let results = check_template_drift(tmp.path());
+        assert_eq!(results.len(), 1);
+        assert!(results[0].message.contains("[this:store_lmdb_init_fields]"));
+        assert!(results[0].line.is_some());
+
+        let fixed = apply_restore_marker(
+            tmp.path(),
+            "project/stores.rs",
+            &old,
+            "[this:store_lmdb_init_fields]",
+        )
+        .unwrap();
+        assert_eq!(fixed, stores);
+    }
+
+    #[test]
+    fn test_marker_directly_below_declaration() {
+        let (tmp, _, stores) = scaffolded("shop");
+        let old = stores.replace("        // [this:store_lmdb_init_vars]\n", "");
+        let fixed = apply_restore_marker(
+            tmp.path(),
+            "project/stores.rs",
+            &old,
+            "[this:store_lmdb_init_vars]",
+        )
+        .unwrap();
+        assert_eq!(fixed, stores);
+    }
+
+    #[test]
+    fn test_hand_written_files_are_skipped() {
+        let (tmp, _, _) = scaffolded("shop");
+        std::fs::write(tmp.path().join("src/module.rs"), "pub struct ShopModule;\n").unwrap();
+        std::fs::write(tmp.path().join("src/stores.rs"), "pub struct ShopStores;\n").unwrap();
+        assert!(check_template_drift(tmp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_project_name_read_from_scaffolded_types() {
+        let (tmp, _, _) = scaffolded("my-shop");
+        std::fs::write(
+            tmp.path().join("Cargo.toml"),
+            "[package]\nname = \"renamed\"\n",
+        )
+        .unwrap();
+        assert_eq!(project_snake_name(tmp.path()), "my_shop");
+    }
+}
@@ -0,0 +1,672 @@
+//! Project-local doctor rules declared in `.this/doctor.yaml`.
+//!
+//! Each rule is evaluated over the introspected model and the project's
+//! config files, and reported alongside the built-in checks under its own id.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+
+use super::{DiagnosticResult, RULES, detect_store_backend, line_of};
+use crate::codegen::introspect::{self, EntityMeta};
+use crate::commands::add_event_flow::EventsConfig;
+use crate::commands::add_link::LinksConfig;
+
+/// Location of the rules file, relative to the project or workspace root
+pub const RULES_FILE: &str = ".this/doctor.yaml";
+
+/// Rule id under which problems with the rules file itself are reported
+pub const CONFIG_RULE: &str = "check_custom_rules";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    rules: Vec<CustomRule>,
+}
+
+/// One rule from `.this/doctor.yaml`
+#[derive(Debug, Deserialize)]
+struct CustomRule {
+    id: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    level: RuleLevel,
+    #[serde(flatten)]
+    check: RuleCheck,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RuleLevel {
+    #[default]
+    Warn,
+    Error,
+}
+
+/// What a rule asserts, selected by its `check:` key
+#[derive(Debug, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case", deny_unknown_fields)]
+enum RuleCheck {
+    /// Every entity (or the listed ones) declares `field`
+    RequiredField {
+        field: String,
+        #[serde(default)]
+        entities: Vec<String>,
+    },
+    /// Every entity (or the listed ones) uses `impl_data_entity_validated!`
+    EntityValidated {
+        #[serde(default)]
+        entities: Vec<String>,
+    },
+    /// Entity, field, link or flow names match `pattern` in full
+    Naming {
+        target: NamingTarget,
+        pattern: String,
+    },
+    /// Entity stores use one of `backends`
+    AllowedBackends { backends: Vec<String> },
+    /// Links declare `auth`, and none of its policies is forbidden
+    LinkAuth {
+        #[serde(default)]
+        forbid: Vec<String>,
+    },
+    /// auth.yaml uses none of the forbidden policies
+    AuthPolicy { forbid: Vec<String> },
+    /// Every event flow ends with a step of type `step`
+    FlowLastStep { step: String },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum NamingTarget {
+    Entity,
+    Field,
+    Link,
+    Flow,
+}
+
+impl CustomRule {
+    /// Default description used for passes and SARIF metadata
+    fn summary(&self) -> String {
+        if let Some(description) = &self.description {
+            return description.clone();
+        }
+        match &self.check {
+            RuleCheck::RequiredField { field, .. } => format!("Entities declare '{}'", field),
+            RuleCheck::EntityValidated { .. } => "Entities are validated".to_string(),
+            RuleCheck::Naming { target, pattern } => {
+                format!("{:?} names match /{}/", target, pattern)
+            }
+            RuleCheck::AllowedBackends { backends } => {
+                format!("Stores use {}", backends.join(" or "))
+            }
+            RuleCheck::LinkAuth { .. } => "Links declare auth".to_string(),
+            RuleCheck::AuthPolicy { forbid } => {
+                format!("auth.yaml does not use {}", forbid.join(", "))
+            }
+            RuleCheck::FlowLastStep { step } => format!("Event flows end with '{}'", step),
+        }
+    }
+}
+
+/// A rule violation, with the location it points at
+struct Violation {
+    message: String,
+    file: PathBuf,
+    line: Option<usize>,
+}
+
+impl Violation {
+    fn new(message: String, file: PathBuf, line: Option<usize>) -> Self {
+        Self {
+            message,
+            file,
+            line,
+        }
+    }
+}
+
+/// Path of the rules file in effect: the project's, else the workspace's
+fn rules_path(project_root: &Path) -> Option<PathBuf> {
+    let local = project_root.join(RULES_FILE);
+    if local.exists() {
+        return Some(local);
+    }
+    crate::utils::project::find_workspace_root()
+        .map(|ws| ws.join(RULES_FILE))
+        .filter(|p| p.exists())
+}
+
+/// Parse and validate the rules file
+fn load(path: &Path) -> Result<Vec<CustomRule>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read: {}", path.display()))?;
+    let file: RulesFile = serde_yaml::from_str(&content)?;
+
+    let mut seen: Vec<&str> = Vec::new();
+    for rule in &file.rules {
+        if rule.id.trim().is_empty() {
+            anyhow::bail!("rule ids cannot be empty");
+        }
+        if rule.id == "warnings" || RULES.iter().any(|(id, _)| *id == rule.id) {
+            anyhow::bail!("rule id '{}' is reserved by a built-in check", rule.id);
+        }
+        if seen.contains(&rule.id.as_str()) {
+            anyhow::bail!("rule id '{}' is declared twice", rule.id);
+        }
+        seen.push(&rule.id);
+        if let RuleCheck::Naming { pattern, .. } = &rule.check {
+            Regex::new(pattern)
+                .with_context(|| format!("rule '{}' has an invalid pattern", rule.id))?;
+        }
+    }
+    Ok(file.rules)
+}
+
+/// Ids and descriptions of the project's custom rules, for `--deny`/`--allow`
+/// validation and SARIF metadata. Empty when there is no valid rules file.
+pub(super) fn custom_rule_ids(project_root: &Path) -> Vec<(String, String)> {
+    rules_path(project_root)
+        .and_then(|path| load(&path).ok())
+        .unwrap_or_default()
+        .iter()
+        .map(|rule| (rule.id.clone(), rule.summary()))
+        .collect()
+}
+
+/// Evaluate the custom rules. Returns nothing when no rules file exists.
+pub(super) fn check_custom_rules(project_root: &Path) -> Vec<DiagnosticResult> {
+    let Some(path) = rules_path(project_root) else {
+        return Vec::new();
+    };
+
+    let rules = match load(&path) {
+        Ok(rules) => rules,
+        Err(e) => {
+            let line = e
+                .downcast_ref::<serde_yaml::Error>()
+                .and_then(|e| e.location())
+                .map(|l| l.line());
+            return vec![config_error(
+                &format!("{} is invalid: {:#}", RULES_FILE, e),
+                &path,
+                line,
+            )];
+        }
+    };
+
+    let model = match introspect::introspect(project_root) {
+        Ok(model) => model,
+        Err(e) => {
+            return vec![config_error(
+                &format!("Cannot evaluate custom rules: {:#}", e),
+                &path,
+                None,
+            )];
+        }
+    };
+
+    let mut results = Vec::new();
+    for rule in &rules {
+        let violations = evaluate(project_root, &rule.check, &model.entities);
+        if violations.is_empty() {
+            results.push(DiagnosticResult::pass(&rule.id, &rule.summary()).for_rule(&rule.id));
+        }
+        for v in violations {
+            let result = match rule.level {
+                RuleLevel::Warn => DiagnosticResult::warn(&rule.id, &v.message),
+                RuleLevel::Error => DiagnosticResult::error(&rule.id, &v.message),
+            };
+            results.push(result.for_rule(&rule.id).at(&v.file, v.line));
+        }
+    }
+    results
+}
+
+fn config_error(message: &str, path: &Path, line: Option<usize>) -> DiagnosticResult {
+    DiagnosticResult::error("Custom rules", message)
+        .for_rule(CONFIG_RULE)
+        .at(path, line)
+}
+
+fn evaluate(project_root: &Path, check: &RuleCheck, entities: &[EntityMeta]) -> Vec<Violation> {
+    let model_path = |entity: &EntityMeta| {
+        project_root
+            .join("src/entities")
+            .join(&entity.snake_name)
+            .join("model.rs")
+    };
+    let selected = |names: &[String]| -> Vec<&EntityMeta> {
+        entities
+            .iter()
+            .filter(|e| names.is_empty() || names.contains(&e.snake_name))
+            .collect()
+    };
+
+    match check {
+        RuleCheck::RequiredField {
+            field,
+            entities: names,
+        } => selected(names)
+            .into_iter()
+            .filter(|e| !e.fields.iter().any(|f| &f.name == field))
+            .map(|e| {
+                let path = model_path(e);
+                let line = line_of(&path, "impl_data_entity");
+                Violation::new(
+                    format!("Entity '{}' has no '{}' field", e.snake_name, field),
+                    path,
+                    line,
+                )
+            })
+            .collect(),
+
+        RuleCheck::EntityValidated { entities: names } => selected(names)
+            .into_iter()
+            .filter_map(|e| {
+                let path = model_path(e);
+                let content = std::fs::read_to_string(&path).unwrap_or_default();
+                if content.contains("impl_data_entity_validated!") {
+                    return None;
+                }
+                let line = line_of(&path, "impl_data_entity");
+                Some(Violation::new(
+                    format!("Entity '{}' is not validated", e.snake_name),
+                    path,
+                    line,
+                ))
+            })
+            .collect(),
+
+        RuleCheck::Naming { target, pattern } => {
+            let Ok(re) = Regex::new(&format!("^(?:{})$", pattern)) else {
+                return Vec::new();
+            };
+            naming_candidates(project_root, *target, entities)
+                .into_iter()
+                .filter(|(name, _, _)| !re.is_match(name))
+                .map(|(name, path, line)| {
+                    Violation::new(
+                        format!("{:?} name '{}' does not match /{}/", target, name, pattern),
+                        path,
+                        line,
+                    )
+                })
+                .collect()
+        }
+
+        RuleCheck::AllowedBackends { backends } => entities
+            .iter()
+            .filter_map(|e| {
+                let backend = detect_store_backend(project_root, &e.snake_name, &e.pascal_name);
+                if backends.iter().any(|b| b == backend) {
+                    return None;
+                }
+                let path = project_root
+                    .join("src/entities")
+                    .join(&e.snake_name)
+                    .join("store.rs");
+                let line = line_of(&path, "Store");
+                Some(Violation::new(
+                    format!(
+                        "Entity '{}' uses the {} backend (allowed: {})",
+                        e.snake_name,
+                        backend,
+                        backends.join(", ")
+                    ),
+                    path,
+                    line,
+                ))
+            })
+            .collect(),
+
+        RuleCheck::LinkAuth { forbid } => {
+            let path = project_root.join("config/links.yaml");
+            let Some(config) = read_yaml::<LinksConfig>(&path) else {
+                return Vec::new();
+            };
+            let mut violations = Vec::new();
+            for link in &config.links {
+                let line = line_of(&path, &format!("link_type: {}", link.link_type));
+                let Some(auth) = &link.auth else {
+                    violations.push(Violation::new(
+                        format!("Link '{}' declares no auth", link.link_type),
+                        path.clone(),
+                        line,
+                    ));
+                    continue;
+                };
+                let operations = [
+                    ("list", &auth.list),
+                    ("get", &auth.get),
+                    ("create", &auth.create),
+                    ("update", &auth.update),
+                    ("delete", &auth.delete),
+                ];
+                for (operation, policy) in operations {
+                    if let Some(term) = forbidden_term(policy, forbid) {
+                        violations.push(Violation::new(
+                            format!(
+                                "Link '{}' uses forbidden policy '{}' for {}",
+                                link.link_type, term, operation
+                            ),
+                            path.clone(),
+                            line,
+                        ));
+                    }
+                }
+            }
+            violations
+        }
+
+        RuleCheck::AuthPolicy { forbid } => {
+            let path = project_root.join("config/auth.yaml");
+            let Some(config) = read_yaml::<serde_yaml::Value>(&path) else {
+                return Vec::new();
+            };
+            let mut policies: Vec<(String, String)> = Vec::new();
+            if let Some(policy) = config.get("default_policy").and_then(|p| p.as_str()) {
+                policies.push(("default_policy".to_string(), policy.to_string()));
+            }
+            if let Some(map) = config.get("entities").and_then(|e| e.as_mapping()) {
+                for (entity, operations) in map {
+                    let entity = entity.as_str().unwrap_or_default();
+                    for (operation, policy) in operations.as_mapping().into_iter().flatten() {
+                        if let (Some(operation), Some(policy)) =
+                            (operation.as_str(), policy.as_str())
+                        {
+                            policies
+                                .push((format!("{}.{}", entity, operation), policy.to_string()));
+                        }
+                    }
+                }
+            }
+            policies
+                .into_iter()
+                .filter_map(|(key, policy)| {
+                    let term = forbidden_term(&policy, forbid)?;
+                    let needle = key.rsplit('.').next().unwrap_or(&key).to_string();
+                    Some(Violation::new(
+                        format!("auth.yaml {} uses forbidden policy '{}'", key, term),
+                        path.clone(),
+                        line_of(&path, &format!("{}: {}", needle, policy))
+                            .or_else(|| line_of(&path, &format!("{}: \"{}\"", needle, policy))),
+                    ))
+                })
+                .collect()
+        }
+
+        RuleCheck::FlowLastStep { step } => {
+            let path = project_root.join("config/events.yaml");
+            let Some(config) = read_yaml::<EventsConfig>(&path) else {
+                return Vec::new();
+            };
+            config
+                .event_flows
+                .iter()
+                .filter(|flow| flow.steps.last().map(|s| &s.step_type) != Some(step))
+                .map(|flow| {
+                    Violation::new(
+                        format!(
+                            "Event flow '{}' does not end with a '{}' step",
+                            flow.name, step
+                        ),
+                        path.clone(),
+                        line_of(&path, &format!("name: {}", flow.name)),
+                    )
+                })
+                .collect()
+        }
+    }
+}
+
+/// Names checked by a naming rule, with where each is declared
+fn naming_candidates(
+    project_root: &Path,
+    target: NamingTarget,
+    entities: &[EntityMeta],
+) -> Vec<(String, PathBuf, Option<usize>)> {
+    let model_path = |e: &EntityMeta| {
+        project_root
+            .join("src/entities")
+            .join(&e.snake_name)
+            .join("model.rs")
+    };
+    match target {
+        NamingTarget::Entity => entities
+            .iter()
+            .map(|e| {
+                let path = model_path(e);
+                let line = line_of(&path, "impl_data_entity");
+                (e.snake_name.clone(), path, line)
+            })
+            .collect(),
+        NamingTarget::Field => entities
+            .iter()
+            .flat_map(|e| {
+                let path = model_path(e);
+                e.fields.iter().map(move |f| {
+                    let line = line_of(&path, &format!("{}:", f.name));
+                    (f.name.clone(), path.clone(), line)
+                })
+            })
+            .collect(),
+        NamingTarget::Link => {
+            let path = project_root.join("config/links.yaml");
+            read_yaml::<LinksConfig>(&path)
+                .map(|config| config.links)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|link| {
+                    let line = line_of(&path, &format!("link_type: {}", link.link_type));
+                    (link.link_type, path.clone(), line)
+                })
+                .collect()
+        }
+        NamingTarget::Flow => {
+            let path = project_root.join("config/events.yaml");
+            read_yaml::<EventsConfig>(&path)
+                .map(|config| config.event_flows)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|flow| {
+                    let line = line_of(&path, &format!("name: {}", flow.name));
+                    (flow.name, path.clone(), line)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Read and parse a YAML config file; missing or invalid files are left to
+/// the built-in checks to report
+fn read_yaml<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_yaml::from_str(&content).ok()
+}
+
+/// The first forbidden policy used by `policy`, looking inside
+/// `all:`/`any:` statements
+fn forbidden_term(policy: &str, forbid: &[String]) -> Option<String> {
+    let statement = policy
+        .strip_prefix("all:")
+        .or_else(|| policy.strip_prefix("any:"))
+        .unwrap_or(policy);
+    statement
+        .split(',')
+        .map(str::trim)
+        .find(|term| forbid.iter().any(|f| f == term))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::doctor::DiagnosticLevel;
+    use tempfile::TempDir;
+
+    const MODEL: &str = "use this::prelude::*;\n\nimpl_data_entity!(\n    Order,\n    \"order\",\n    [\"name\"],\n    {\n        amount: f64,\n    }\n);\n";
+
+    const VALIDATED_MODEL: &str = "use this::prelude::*;\n\nimpl_data_entity_validated!(\n    Invoice,\n    \"invoice\",\n    [\"name\"],\n    {\n        amount: f64,\n        created_by: String,\n    },\n    validate: {},\n    filters: {}\n);\n";
+
+    fn project(rules: &str) -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        for (entity, model) in [("order", MODEL), ("invoice", VALIDATED_MODEL)] {
+            let dir = root.join("src/entities").join(entity);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("model.rs"), model).unwrap();
+        }
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        std::fs::write(
+            root.join("config/links.yaml"),
+            "links:\n  - link_type: has_invoice\n    source_type: order\n    target_type: invoice\n    forward_route_name: invoices\n    reverse_route_name: order\n  - link_type: PaidBy\n    source_type: invoice\n    target_type: order\n    forward_route_name: payers\n    reverse_route_name: paid\n    auth:\n      list: public\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("config/auth.yaml"),
+            "provider: wami\ndefault_policy: authenticated\nentities:\n  order:\n    list: \"any:public,role:admin\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("config/events.yaml"),
+            "event_sinks:\n  - name: in-app\n    type: in_app\nevent_flows:\n  - name: notify\n    trigger: entity.created.order\n    steps:\n      - type: deliver\n        sink: in-app\n  - name: audit\n    trigger: entity.deleted.order\n    steps:\n      - type: filter\n        condition: \"true\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join(".this")).unwrap();
+        std::fs::write(root.join(RULES_FILE), rules).unwrap();
+        tmp
+    }
+
+    fn findings(rules: &str) -> Vec<DiagnosticResult> {
+        let tmp = project(rules);
+        check_custom_rules(tmp.path())
+            .into_iter()
+            .filter(|r| !matches!(r.level, DiagnosticLevel::Pass))
+            .collect()
+    }
+
+    #[test]
+    fn test_no_rules_file_reports_nothing() {
+        let tmp = TempDir::new().unwrap();
+        assert!(check_custom_rules(tmp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_required_field_and_validated() {
+        let found = findings(
+            "rules:\n  - id: audit-fields\n    check: required_field\n    field: created_by\n  - id: validated\n    check: entity_validated\n    level: error\n",
+        );
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].rule, "audit-fields");
+        assert!(found[0].message.contains("'order' has no 'created_by'"));
+        assert!(matches!(found[0].level, DiagnosticLevel::Warn));
+        assert_eq!(found[0].line, Some(3));
+        assert_eq!(found[1].rule, "validated");
+        assert!(matches!(found[1].level, DiagnosticLevel::Error));
+        assert!(found[1].message.contains("'order' is not validated"));
+    }
+
+    #[test]
+    fn test_passing_rule_reports_pass_with_description() {
+        let tmp = project(
+            "rules:\n  - id: amounts\n    description: Entities carry an amount\n    check: required_field\n    field: amount\n",
+        );
+        let results = check_custom_rules(tmp.path());
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0].level, DiagnosticLevel::Pass));
+        assert_eq!(results[0].rule, "amounts");
+        assert_eq!(results[0].message, "Entities carry an amount");
+    }
+
+    #[test]
+    fn test_naming_matches_whole_name() {
+        let found = findings(
+            "rules:\n  - id: snake-links\n    check: naming\n    target: link\n    pattern: '[a-z_]+'\n",
+        );
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("'PaidBy'"));
+        assert_eq!(found[0].line, Some(7));
+    }
+
+    #[test]
+    fn test_allowed_backends() {
+        let found = findings(
+            "rules:\n  - id: pg-only\n    check: allowed_backends\n    backends: [postgres]\n",
+        );
+        assert_eq!(found.len(), 2);
+        assert!(found[0].message.contains("in-memory backend"));
+    }
+
+    #[test]
+    fn test_link_auth_missing_and_forbidden() {
+        let found =
+            findings("rules:\n  - id: link-auth\n    check: link_auth\n    forbid: [public]\n");
+        assert_eq!(found.len(), 2);
+        assert!(found[0].message.contains("'has_invoice' declares no auth"));
+        assert!(found[1].message.contains("'public' for list"));
+    }
+
+    #[test]
+    fn test_auth_policy_looks_inside_statements() {
+        let found =
+            findings("rules:\n  - id: no-public\n    check: auth_policy\n    forbid: [public]\n");
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("order.list"));
+        assert_eq!(found[0].line, Some(5));
+    }
+
+    #[test]
+    fn test_flow_last_step() {
+        let found = findings(
+            "rules:\n  - id: deliver-last\n    check: flow_last_step\n    step: deliver\n",
+        );
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("'audit'"));
+    }
+
+    #[test]
+    fn test_invalid_rules_file_is_reported() {
+        for (rules, expected) in [
+            ("rules:\n  - id: x\n    check: nope\n", "unknown variant"),
+            (
+                "rules:\n  - id: check_links\n    check: entity_validated\n",
+                "reserved",
+            ),
+            (
+                "rules:\n  - id: a\n    check: entity_validated\n  - id: a\n    check: entity_validated\n",
+                "declared twice",
+            ),
+            (
+                "rules:\n  - id: a\n    check: naming\n    target: entity\n    pattern: '('\n",
+                "invalid pattern",
+            ),
+        ] {
+            let found = findings(rules);
+            assert_eq!(found.len(), 1, "{}", rules);
+            assert_eq!(found[0].rule, CONFIG_RULE);
+            assert!(
+                found[0].message.contains(expected),
+                "{}: {}",
+                expected,
+                found[0].message
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_rule_ids() {
+        let tmp = project("rules:\n  - id: validated\n    check: entity_validated\n");
+        assert_eq!(
+            custom_rule_ids(tmp.path()),
+            vec![(
+                "validated".to_string(),
+                "Entities are validated".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,583 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use regex::Regex;
+
+use super::{EnableArgs, Feature};
+use crate::templates::TemplateEngine;
+use crate::utils::file_writer::FileWriter;
+use crate::utils::{markers, output, project};
+
+/// What wiring a feature into a project takes, mirroring `project/main.rs.tera`
+struct Wiring {
+    name: &'static str,
+    /// this-rs Cargo feature
+    cargo_feature: Option<&'static str>,
+    /// `use` line of the exposure
+    import: Option<&'static str>,
+    /// (comment, router variable, exposure type) merged into the REST router
+    exposure: Option<(&'static str, &'static str, &'static str)>,
+    /// `ServerBuilder` calls, in order
+    builder_calls: &'static [&'static str],
+    /// Whether the server must be built as a host (exposures, auth)
+    needs_host: bool,
+    /// Config file rendered from a project template: (template, path)
+    config: Option<(&'static str, &'static str)>,
+    /// Startup banner lines; `{port}` is replaced with the server port
+    banner: &'static [&'static str],
+}
+
+const AUTH_CONFIG_CALL: &str =
+    r#".with_auth_config_file(concat!(env!("CARGO_MANIFEST_DIR"), "/config/auth.yaml"))?"#;
+
+fn wiring(feature: Feature) -> Wiring {
+    match feature {
+        Feature::Websocket => Wiring {
+            name: "websocket",
+            cargo_feature: Some("websocket"),
+            import: Some("use this::server::exposure::websocket::WebSocketExposure;"),
+            exposure: Some((
+                "WebSocket exposure — merge WS routes with REST",
+                "ws_router",
+                "WebSocketExposure",
+            )),
+            builder_calls: &[".with_default_event_bus()"],
+            needs_host: true,
+            config: None,
+            banner: &[r#"println!("WebSocket available at ws://127.0.0.1:{port}/ws");"#],
+        },
+        Feature::Grpc => Wiring {
+            name: "grpc",
+            cargo_feature: Some("grpc"),
+            import: Some("use this::server::exposure::grpc::GrpcExposure;"),
+            exposure: Some((
+                "gRPC exposure — merge gRPC routes with REST",
+                "grpc_router",
+                "GrpcExposure",
+            )),
+            builder_calls: &[],
+            needs_host: true,
+            config: None,
+            banner: &[
+                r#"println!("gRPC available on http://127.0.0.1:{port}");"#,
+                r#"println!("Proto export at http://127.0.0.1:{port}/grpc/proto");"#,
+            ],
+        },
+        Feature::Graphql => Wiring {
+            name: "graphql",
+            cargo_feature: Some("graphql"),
+            import: Some("use this::server::exposure::graphql::GraphQLExposure;"),
+            exposure: Some((
+                "GraphQL exposure — merge GraphQL routes with REST",
+                "graphql_router",
+                "GraphQLExposure",
+            )),
+            builder_calls: &[],
+            needs_host: true,
+            config: None,
+            banner: &[
+                r#"println!("GraphQL available at http://127.0.0.1:{port}/graphql");"#,
+                r#"println!("Playground at http://127.0.0.1:{port}/graphql/playground");"#,
+            ],
+        },
+        Feature::Events => Wiring {
+            name: "events",
+            cargo_feature: None,
+            import: None,
+            exposure: None,
+            builder_calls: &[
+                ".with_default_event_bus()",
+                ".with_default_notification_store()",
+            ],
+            needs_host: false,
+            config: Some(("project/events.yaml", "config/events.yaml")),
+            banner: &[
+                r#"println!("SSE stream at http://127.0.0.1:{port}/events/stream");"#,
+                r#"println!("Notifications at http://127.0.0.1:{port}/notifications");"#,
+            ],
+        },
+        Feature::Auth => Wiring {
+            name: "auth",
+            cargo_feature: Some("wami"),
+            import: None,
+            exposure: None,
+            builder_calls: &[AUTH_CONFIG_CALL],
+            needs_host: true,
+            config: Some(("project/auth.yaml", "config/auth.yaml")),
+            banner: &[
+                r#"println!("Auth endpoints:");"#,
+                r#"println!("  POST /auth/token   — Login / credential exchange");"#,
+                r#"println!("  GET  /auth/keys    — Public key (JWKS)");"#,
+                r#"println!("  POST /auth/refresh — Token refresh");"#,
+                r#"println!("  POST /auth/revoke  — Token revocation");"#,
+            ],
+        },
+    }
+}
+
+/// Add a feature to the `this` dependency, converting a plain version string
+/// to a table if needed. Returns `false` if it was already there.
+fn add_cargo_feature(doc: &mut toml_edit::DocumentMut, feature: &str) -> Result<bool> {
+    let deps = doc
+        .get_mut("dependencies")
+        .context("No [dependencies] table in Cargo.toml")?;
+    let key = if deps.get("this").is_some() {
+        "this"
+    } else {
+        "this-rs"
+    };
+    let dep = deps
+        .get_mut(key)
+        .context("No 'this' dependency found in Cargo.toml")?;
+
+    if let Some(version) = dep.as_str().map(str::to_string) {
+        let mut table = toml_edit::InlineTable::new();
+        table.insert("version", version.into());
+        *dep = toml_edit::value(table);
+    }
+    let Some(table) = dep.as_table_like_mut() else {
+        bail!("Unsupported 'this' dependency format in Cargo.toml");
+    };
+    if table.get("features").is_none() {
+        table.insert("features", toml_edit::value(toml_edit::Array::new()));
+    }
+    let features = table
+        .get_mut("features")
+        .and_then(|f| f.as_array_mut())
+        .context("'features' of the 'this' dependency is not an array")?;
+    if features.iter().any(|f| f.as_str() == Some(feature)) {
+        return Ok(false);
+    }
+    features.push(feature);
+    Ok(true)
+}
+
+fn indent_of(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Index of the line ending the statement that starts at `start`
+fn statement_end(lines: &[String], start: usize) -> usize {
+    (start..lines.len())
+        .find(|&i| lines[i].trim_end().ends_with(';'))
+        .unwrap_or(start)
+}
+
+fn join(lines: Vec<String>, original: &str) -> String {
+    let mut result = lines.join("\n");
+    if original.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Turn `let app = ServerBuilder::new()...build()?;` into a host whose REST
+/// router exposures can be merged into
+fn ensure_host(content: &str) -> Result<String> {
+    if content.contains(".build_host()") {
+        return Ok(content.to_string());
+    }
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let start = lines
+        .iter()
+        .position(|l| l.contains("let app = ServerBuilder::new()"))
+        .context("cannot find `let app = ServerBuilder::new()`")?;
+    let end = statement_end(&lines, start);
+    if !lines[end].contains(".build()?;") {
+        bail!("the ServerBuilder chain does not end with `.build()?;`");
+    }
+    let indent = indent_of(&lines[start]).to_string();
+    lines[start] = lines[start].replacen("let app =", "let host =", 1);
+    lines[end] = lines[end].replacen(".build()?;", ".build_host()?;", 1);
+    lines.splice(
+        end + 1..end + 1,
+        [
+            String::new(),
+            format!("{}let host = Arc::new(host);", indent),
+            format!(
+                "{}let app = this::server::exposure::rest::RestExposure::build_router(host.clone(), vec![])?;",
+                indent
+            ),
+        ],
+    );
+    Ok(markers::add_import(
+        &join(lines, content),
+        "use std::sync::Arc;",
+    ))
+}
+
+/// Insert a `ServerBuilder` call before `.register_module(...)`
+fn add_builder_call(content: &str, call: &str) -> Result<String> {
+    let method = &call[..call.find('(').unwrap_or(call.len())];
+    if content.contains(&format!("{}(", method)) {
+        return Ok(content.to_string());
+    }
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let idx = lines
+        .iter()
+        .position(|l| l.trim_start().starts_with(".register_module("))
+        .context("cannot find `.register_module(...)` in the ServerBuilder chain")?;
+    let indent = indent_of(&lines[idx]).to_string();
+    lines.insert(idx, format!("{}{}", indent, call));
+    Ok(join(lines, content))
+}
+
+/// Merge an exposure's router into `app`, after the last router statement
+fn add_exposure(content: &str, comment: &str, var: &str, exposure: &str) -> Result<String> {
+    if content.contains(&format!("{}::build_router", exposure)) {
+        return Ok(content.to_string());
+    }
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let start = lines
+        .iter()
+        .rposition(|l| {
+            l.contains("RestExposure::build_router") || l.contains("let app = app.merge(")
+        })
+        .context("cannot find the REST router (`RestExposure::build_router`)")?;
+    let end = statement_end(&lines, start);
+    let indent = indent_of(&lines[start]).to_string();
+    lines.splice(
+        end + 1..end + 1,
+        [
+            String::new(),
+            format!("{}// {}", indent, comment),
+            format!(
+                "{}let {} = {}::build_router(host.clone())?;",
+                indent, var, exposure
+            ),
+            format!("{}let app = app.merge({});", indent, var),
+        ],
+    );
+    Ok(join(lines, content))
+}
+
+/// Append startup banner lines after the last `println!` before the server starts
+fn add_banner(content: &str, banner: &[String]) -> Result<String> {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let serve = lines
+        .iter()
+        .position(|l| l.contains("TcpListener::bind") || l.contains("axum::serve"))
+        .unwrap_or(lines.len());
+    let mut idx = lines[..serve]
+        .iter()
+        .rposition(|l| l.trim_start().starts_with("println!("))
+        .context("cannot find the startup `println!` banner")?;
+    let indent = indent_of(&lines[idx]).to_string();
+    for line in banner {
+        if lines.iter().any(|l| l.trim() == line) {
+            continue;
+        }
+        idx += 1;
+        lines.insert(idx, format!("{}{}", indent, line));
+    }
+    Ok(join(lines, content))
+}
+
+/// Wire a feature into main.rs; every step is idempotent
+fn wire_main_rs(content: &str, wiring: &Wiring, port: &str) -> Result<String> {
+    let mut content = content.to_string();
+    if let Some(import) = wiring.import {
+        content = markers::add_import(&content, import);
+    }
+    if wiring.needs_host {
+        content = ensure_host(&content)?;
+    }
+    for call in wiring.builder_calls {
+        content = add_builder_call(&content, call)?;
+    }
+    if let Some((comment, var, exposure)) = wiring.exposure {
+        content = add_exposure(&content, comment, var, exposure)?;
+    }
+    let banner: Vec<String> = wiring
+        .banner
+        .iter()
+        .map(|line| line.replace("{port}", port))
+        .collect();
+    add_banner(&content, &banner)
+}
+
+pub fn run(args: EnableArgs, writer: &dyn FileWriter) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    run_in(args, writer, &cwd)
+}
+
+/// Enable features with an explicit starting directory.
+pub(crate) fn run_in(args: EnableArgs, writer: &dyn FileWriter, cwd: &Path) -> Result<()> {
+    let project_root = project::detect_project_root_from(cwd)?;
+
+    let cargo_path = project_root.join("Cargo.toml");
+    let cargo_content = std::fs::read_to_string(&cargo_path)
+        .with_context(|| format!("Failed to read: {}", cargo_path.display()))?;
+    let mut doc: toml_edit::DocumentMut = cargo_content
+        .parse()
+        .with_context(|| format!("Failed to parse: {}", cargo_path.display()))?;
+
+    let main_path = project_root.join("src/main.rs");
+    let main_content = std::fs::read_to_string(&main_path)
+        .with_context(|| format!("Failed to read: {}", main_path.display()))?;
+    let port = Regex::new(r"127\.0\.0\.1:(\d+)")
+        .unwrap()
+        .captures(&main_content)
+        .map_or("3000".to_string(), |caps| caps[1].to_string());
+
+    if writer.is_dry_run() {
+        println!("🔍 {}", "Dry run — no files will be written".cyan().bold());
+        println!();
+    }
+
+    let mut features: Vec<Feature> = Vec::new();
+    for feature in args.features {
+        if !features.contains(&feature) {
+            features.push(feature);
+        }
+    }
+    let names: Vec<&str> = features.iter().map(|f| wiring(*f).name).collect();
+    output::print_step(&format!("Enabling {}...", names.join(", ")));
+
+    let mut main_rs = main_content.clone();
+    let mut configs: Vec<(&str, &str)> = Vec::new();
+    let mut manual = 0;
+    for feature in &features {
+        let wiring = wiring(*feature);
+        let mut changes: Vec<&str> = Vec::new();
+
+        if let Some(cargo_feature) = wiring.cargo_feature
+            && add_cargo_feature(&mut doc, cargo_feature)?
+        {
+            changes.push("Cargo.toml");
+        }
+        match wire_main_rs(&main_rs, &wiring, &port) {
+            Ok(updated) => {
+                if updated != main_rs {
+                    main_rs = updated;
+                    changes.push("src/main.rs");
+                }
+            }
+            Err(err) => {
+                manual += 1;
+                output::print_warn(&format!(
+                    "Cannot wire {} into src/main.rs: {} — add it by hand",
+                    wiring.name, err
+                ));
+            }
+        }
+        if let Some((template, path)) = wiring.config
+            && !project_root.join(path).exists()
+        {
+            configs.push((template, path));
+            changes.push(path);
+        }
+
+        if changes.is_empty() {
+            println!(
+                "  {} {} {}",
+                "·".dimmed(),
+                wiring.name,
+                "(already enabled)".dimmed()
+            );
+        } else {
+            println!(
+                "  {} {} — {}",
+                "✓".green(),
+                wiring.name.bold(),
+                changes.join(", ")
+            );
+        }
+    }
+
+    if main_rs != main_content
+        && syn::parse_file(&main_content).is_ok()
+        && let Err(err) = syn::parse_file(&main_rs)
+    {
+        bail!(
+            "Refusing to edit src/main.rs: the result would not parse ({}). Enable the feature by hand.",
+            err
+        );
+    }
+
+    let mut changed = 0;
+    let cargo_updated = doc.to_string();
+    if cargo_updated != cargo_content {
+        writer.update_file(&cargo_path, &cargo_content, &cargo_updated)?;
+        changed += 1;
+    }
+    if main_rs != main_content {
+        writer.update_file(&main_path, &main_content, &main_rs)?;
+        changed += 1;
+    }
+    if !configs.is_empty() {
+        let engine = TemplateEngine::for_project(&project_root)?;
+        let name = super::doctor::detect_project_name(&project_root);
+        let mut context = tera::Context::new();
+        context.insert("project_name", &name);
+        context.insert("project_name_snake", &name.replace('-', "_"));
+        context.insert("port", &port);
+        writer.create_dir_all(&project_root.join("config"))?;
+        for (template, path) in &configs {
+            let rendered = engine.render(template, &context)?;
+            writer.write_file(&project_root.join(path), &rendered)?;
+            changed += 1;
+        }
+    }
+
+    println!();
+    if changed == 0 && manual == 0 {
+        output::print_success("Everything requested is already enabled");
+    } else if changed > 0 && !writer.is_dry_run() {
+        output::print_success(&format!(
+            "Updated {} file(s) — run `cargo build` to verify, `this undo` to revert",
+            changed
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_writer::RealWriter;
+
+    const CARGO_TOML: &str = r#"[package]
+name = "shop"
+version = "0.1.0"
+
+[dependencies]
+this = { package = "this-rs", version = "0.0.9", features = [] }
+tokio = { version = "1", features = ["full"] }
+"#;
+
+    const MAIN_RS: &str = r#"use std::sync::Arc;
+
+use this::server::builder::ServerBuilder;
+use this::storage::InMemoryLinkService;
+
+mod entities;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let link_service = Arc::new(InMemoryLinkService::new());
+
+    let app = ServerBuilder::new()
+        .with_link_service((*link_service).clone())
+        .register_module(module)?
+        .build()?;
+
+    println!("Server running on http://127.0.0.1:4000");
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:4000").await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+"#;
+
+    fn setup(tmp: &tempfile::TempDir) -> std::path::PathBuf {
+        let root = tmp.path().to_path_buf();
+        std::fs::write(root.join("Cargo.toml"), CARGO_TOML).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), MAIN_RS).unwrap();
+        root
+    }
+
+    fn enable(root: &Path, features: &[Feature]) {
+        let args = EnableArgs {
+            features: features.to_vec(),
+        };
+        run_in(args, &RealWriter, root).unwrap();
+    }
+
+    #[test]
+    fn test_add_cargo_feature() {
+        let mut doc: toml_edit::DocumentMut = "[dependencies]\nthis = \"0.0.9\"\n".parse().unwrap();
+        assert!(add_cargo_feature(&mut doc, "grpc").unwrap());
+        assert!(!add_cargo_feature(&mut doc, "grpc").unwrap());
+        assert_eq!(
+            doc.to_string(),
+            "[dependencies]\nthis = { version = \"0.0.9\", features = [\"grpc\"] }\n"
+        );
+    }
+
+    #[test]
+    fn test_enable_graphql_converts_to_host_and_merges_router() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = setup(&tmp);
+        enable(&root, &[Feature::Graphql]);
+
+        let cargo = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+        assert!(cargo.contains(r#"features = ["graphql"]"#));
+        let main = std::fs::read_to_string(root.join("src/main.rs")).unwrap();
+        syn::parse_file(&main).unwrap();
+        assert!(main.contains("use this::server::exposure::graphql::GraphQLExposure;"));
+        assert!(main.contains("    let host = ServerBuilder::new()"));
+        assert!(main.contains(
+            "        .build_host()?;\n\n    let host = Arc::new(host);\n    let app = this::server::exposure::rest::RestExposure::build_router(host.clone(), vec![])?;\n\n    // GraphQL exposure"
+        ));
+        assert!(main.contains(
+            "    let graphql_router = GraphQLExposure::build_router(host.clone())?;\n    let app = app.merge(graphql_router);\n"
+        ));
+        assert!(main.contains(
+            "Server running on http://127.0.0.1:4000\");\n    println!(\"GraphQL available at http://127.0.0.1:4000/graphql\");"
+        ));
+    }
+
+    #[test]
+    fn test_enable_is_idempotent_and_composes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = setup(&tmp);
+        enable(&root, &[Feature::Websocket, Feature::Events]);
+        enable(&root, &[Feature::Grpc, Feature::Auth]);
+        let cargo = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+        let main = std::fs::read_to_string(root.join("src/main.rs")).unwrap();
+
+        enable(
+            &root,
+            &[
+                Feature::Websocket,
+                Feature::Grpc,
+                Feature::Events,
+                Feature::Auth,
+            ],
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("Cargo.toml")).unwrap(),
+            cargo
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/main.rs")).unwrap(),
+            main
+        );
+
+        syn::parse_file(&main).unwrap();
+        assert!(cargo.contains(r#"features = ["websocket", "grpc", "wami"]"#));
+        assert_eq!(main.matches(".with_default_event_bus()").count(), 1);
+        assert!(main.contains(
+            "        .with_default_event_bus()\n        .with_default_notification_store()\n        .with_auth_config_file("
+        ));
+        assert!(main.contains("let app = app.merge(ws_router);\n\n    // gRPC exposure"));
+        assert!(root.join("config/events.yaml").exists());
+        assert!(
+            std::fs::read_to_string(root.join("config/auth.yaml"))
+                .unwrap()
+                .contains("shop")
+        );
+    }
+
+    #[test]
+    fn test_enable_warns_when_main_rs_cannot_be_wired() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = setup(&tmp);
+        std::fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        enable(&root, &[Feature::Grpc]);
+
+        assert!(
+            std::fs::read_to_string(root.join("Cargo.toml"))
+                .unwrap()
+                .contains(r#"features = ["grpc"]"#)
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/main.rs")).unwrap(),
+            "fn main() {}\n"
+        );
+    }
+}
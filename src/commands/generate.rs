@@ -1,13 +1,79 @@
-//! `this generate client` — generate a typed API client from project introspection
+//! `this generate client|graphql|proto|diagram|docs|json-schema` — generate typed API clients from project introspection
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::{
+    ClientExtra, DiagramFormat, DocsFormat, GenerateClientArgs, GenerateCommands,
+    GenerateDiagramArgs, GenerateDocsArgs, GenerateGraphqlArgs, GenerateJsonSchemaArgs,
+    GenerateProtoArgs,
+};
+use crate::codegen::introspect::{self, ProjectIntrospection};
+use crate::config::{TargetType, WorkspaceConfig, load_workspace_config};
+use crate::utils::file_writer::{CheckWriter, FileWriter, RealWriter};
+use crate::utils::{output, project, watch};
+
+/// Entry point for `this generate <kind> --check`.
+pub fn run_check(command: GenerateCommands) -> Result<()> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| anyhow::anyhow!("Failed to get current directory: {}", e))?;
+    run_check_in(command, &cwd)
+}
 
-use super::GenerateClientArgs;
-use crate::codegen::introspect;
-use crate::config::{TargetType, load_workspace_config};
-use crate::utils::file_writer::FileWriter;
-use crate::utils::{output, project};
+/// Render a generator in memory and compare its files with the disk.
+/// Fails, after printing a diff per file, when any of them is missing or
+/// differs; nothing is written. Meant for CI on committed generated files.
+pub(crate) fn run_check_in(command: GenerateCommands, cwd: &Path) -> Result<()> {
+    let writer = CheckWriter::new();
+    let kind = match command {
+        GenerateCommands::Client(args) => {
+            if args.watch {
+                bail!("--watch cannot be combined with --check");
+            }
+            run_in(args, &writer, cwd)?;
+            "client"
+        }
+        GenerateCommands::Graphql(args) => {
+            run_graphql_in(args, &writer, cwd)?;
+            "graphql"
+        }
+        GenerateCommands::Proto(args) => {
+            run_proto_in(args, &writer, cwd)?;
+            "proto"
+        }
+        GenerateCommands::Diagram(args) => {
+            if args.output.is_none() {
+                bail!(
+                    "`generate diagram --check` needs --output: without it the diagram goes to stdout"
+                );
+            }
+            run_diagram_in(args, &writer, cwd)?;
+            "diagram"
+        }
+        GenerateCommands::Docs(args) => {
+            run_docs_in(args, &writer, cwd)?;
+            "docs"
+        }
+        GenerateCommands::JsonSchema(args) => {
+            run_json_schema_in(args, &writer, cwd)?;
+            "json-schema"
+        }
+    };
+
+    let stale = writer.stale_files();
+    if !stale.is_empty() {
+        writer.print_report();
+        bail!(
+            "{} generated file(s) out of date. Run `this generate {}` and commit the result.",
+            stale.len(),
+            kind
+        );
+    }
+    output::print_success("Generated files are up to date");
+    Ok(())
+}
 
 pub fn run(args: GenerateClientArgs, writer: &dyn FileWriter) -> Result<()> {
     let cwd = std::env::current_dir()
@@ -25,64 +91,645 @@ pub(crate) fn run_in(args: GenerateClientArgs, writer: &dyn FileWriter, cwd: &Pa
         );
     }
 
-    // Find workspace root and API directory
+    if args.watch {
+        if writer.is_dry_run() {
+            bail!("--watch cannot be combined with --dry-run");
+        }
+        let running = Arc::new(AtomicBool::new(true));
+        let r = running.clone();
+        ctrlc::set_handler(move || {
+            r.store(false, Ordering::SeqCst);
+        })
+        .context("Failed to set Ctrl+C handler")?;
+        return watch_client(&args, cwd, &running);
+    }
+
+    let (workspace_root, config, project) = introspect_workspace(writer, cwd)?;
+    let api_root = workspace_root.join(&config.api.path);
+
+    // Generate TypeScript client
+    let options = client_options(&args, &api_root)?;
+    if options.auth {
+        output::print_info("Auth is enabled: the client sends bearer tokens and refreshes on 401");
+    }
+    output::print_step("Generating TypeScript API client...");
+    let ts_content = crate::codegen::typescript::generate(&project, &options);
+
+    let output_path = client_output_path(&args, &workspace_root, &config)?;
+    let mut written = vec![];
+    if write_if_changed(writer, &output_path, &ts_content)? {
+        written.push(output_path.clone());
+    } else {
+        output::print_info(&format!("{} is up to date", output_path.display()));
+    }
+
+    let packages: Vec<&str> = [
+        (options.zod, "zod"),
+        (options.react_query, "@tanstack/react-query"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, package)| enabled.then_some(package))
+    .collect();
+    let mut hints = vec![];
+    if !packages.is_empty() {
+        hints.push(format!(
+            "The client imports {}: run `npm install {}` in the webapp if needed",
+            packages.join(" and "),
+            packages.join(" ")
+        ));
+    }
+    report_generated(
+        writer,
+        &written,
+        &format!(
+            "Generated API client: {} ({} entities, {} links)",
+            output_path.display(),
+            project.entities.len(),
+            project.links.len()
+        ),
+        &hints,
+    );
+
+    Ok(())
+}
+
+/// Regenerate the TypeScript client whenever entities, links or auth settings change.
+///
+/// Runs until `running` is cleared. Writes go straight to disk, without a
+/// journal entry, and only when the content differs, so an unchanged client
+/// does not trigger the webapp's hot reload. Introspection errors (typically
+/// a file saved halfway) are reported and watching goes on.
+pub(crate) fn watch_client(
+    args: &GenerateClientArgs,
+    cwd: &Path,
+    running: &AtomicBool,
+) -> Result<()> {
     let workspace_root = project::find_workspace_root_from(cwd).ok_or_else(|| {
         anyhow::anyhow!("Not inside a this-rs workspace. Run `this init <name> --workspace` first.")
     })?;
+    let config = load_workspace_config(&workspace_root.join("this.yaml"))?;
+    let api_root = workspace_root.join(&config.api.path);
+    let output_path = client_output_path(args, &workspace_root, &config)?;
+
+    let watched = vec![
+        api_root.join("src/entities"),
+        api_root.join("config/links.yaml"),
+        api_root.join("config/auth.yaml"),
+    ];
+    let mut files = watch::snapshot(&watched);
+    sync_client(args, &api_root, &output_path);
+
+    output::print_info(&format!(
+        "Watching {} and config/links.yaml for API client changes",
+        api_root.join("src/entities").display()
+    ));
+    while watch::wait_for_change(&watched, &mut files, running) {
+        sync_client(args, &api_root, &output_path);
+    }
+    Ok(())
+}
+
+/// Introspect the API and rewrite the client if it changed, reporting the outcome.
+fn sync_client(args: &GenerateClientArgs, api_root: &Path, output_path: &Path) {
+    let result = introspect::introspect(api_root).and_then(|project| {
+        if project.entities.is_empty() {
+            bail!(
+                "No entities found in {}",
+                api_root.join("src/entities").display()
+            );
+        }
+        let options = client_options(args, api_root)?;
+        let content = crate::codegen::typescript::generate(&project, &options);
+        let changed = write_if_changed(&RealWriter, output_path, &content)?;
+        Ok((changed, project))
+    });
+    match result {
+        Ok((true, project)) => output::print_success(&format!(
+            "Regenerated API client: {} ({} entities, {} links)",
+            output_path.display(),
+            project.entities.len(),
+            project.links.len()
+        )),
+        Ok((false, _)) => output::print_info(&format!("{} is up to date", output_path.display())),
+        Err(e) => output::print_warn(&format!("API client not regenerated: {:#}", e)),
+    }
+}
+
+/// Client options from the CLI extras and the API's auth settings.
+/// Bearer tokens are only wired in when the API has an auth provider.
+fn client_options(
+    args: &GenerateClientArgs,
+    api_root: &Path,
+) -> Result<crate::codegen::typescript::ClientOptions> {
+    let auth = introspect::parse_auth(api_root)?
+        .provider
+        .is_some_and(|provider| provider != "none");
+    Ok(crate::codegen::typescript::ClientOptions {
+        auth,
+        zod: args.with.contains(&ClientExtra::Zod),
+        react_query: args.with.contains(&ClientExtra::ReactQuery),
+    })
+}
+
+fn client_output_path(
+    args: &GenerateClientArgs,
+    workspace_root: &Path,
+    config: &WorkspaceConfig,
+) -> Result<PathBuf> {
+    match &args.output {
+        Some(path) => Ok(path.clone()),
+        None => auto_detect_output(workspace_root, config),
+    }
+}
+
+/// Write `content` unless the file already holds exactly that.
+/// Returns whether the file was written.
+fn write_if_changed(writer: &dyn FileWriter, path: &Path, content: &str) -> Result<bool> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        writer.create_dir_all(parent)?;
+    }
+    writer.write_file(path, content)?;
+    Ok(true)
+}
+
+/// Arguments of the client watcher run by `this dev`: the auto-detected
+/// output, keeping the extras the existing client was generated with.
+pub(crate) fn dev_client_args(
+    workspace_root: &Path,
+    config: &WorkspaceConfig,
+) -> Result<GenerateClientArgs> {
+    let output = auto_detect_output(workspace_root, config)?;
+    let existing = std::fs::read_to_string(&output).unwrap_or_default();
+    let with = [
+        (ClientExtra::Zod, "from 'zod';"),
+        (ClientExtra::ReactQuery, "from '@tanstack/react-query';"),
+    ]
+    .into_iter()
+    .filter(|(_, import)| existing.contains(import))
+    .map(|(extra, _)| extra)
+    .collect();
+    Ok(GenerateClientArgs {
+        lang: "typescript".to_string(),
+        output: Some(output),
+        with,
+        watch: true,
+    })
+}
+
+pub fn run_graphql(args: GenerateGraphqlArgs, writer: &dyn FileWriter) -> Result<()> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| anyhow::anyhow!("Failed to get current directory: {}", e))?;
+    run_graphql_in(args, writer, &cwd)
+}
+
+/// Run the generate graphql command with an explicit starting directory.
+pub(crate) fn run_graphql_in(
+    args: GenerateGraphqlArgs,
+    writer: &dyn FileWriter,
+    cwd: &Path,
+) -> Result<()> {
+    let (workspace_root, config, project) = introspect_workspace(writer, cwd)?;
 
-    let this_yaml = workspace_root.join("this.yaml");
-    let config = load_workspace_config(&this_yaml)?;
     let api_root = workspace_root.join(&config.api.path);
+    if !super::info::detect_this_features(&api_root).graphql {
+        output::print_warn(
+            "The graphql feature is not enabled in the API — run `this enable graphql` to serve this schema",
+        );
+    }
+
+    // React hooks only make sense for a React webapp
+    let react = webapp_target(&config).is_some_and(|t| t.framework.as_deref() == Some("react"));
+    let hooks = react && !args.no_hooks;
+
+    output::print_step("Generating GraphQL schema and typed operations...");
+    let sdl = crate::codegen::graphql::generate_sdl(&project);
+    let operations = crate::codegen::graphql::generate_operations(&project, hooks);
+
+    let output_dir = match args.output {
+        Some(dir) => dir,
+        None => match webapp_target(&config) {
+            Some(webapp) => workspace_root.join(&webapp.path).join("src/graphql"),
+            None => workspace_root.join("graphql"),
+        },
+    };
+    writer.create_dir_all(&output_dir)?;
+
+    let mut written = vec![];
+    for (name, content) in [("schema.graphql", &sdl), ("operations.ts", &operations)] {
+        let path = output_dir.join(name);
+        writer.write_file(&path, content)?;
+        written.push(path);
+    }
+
+    report_generated(
+        writer,
+        &written,
+        &format!(
+            "Generated GraphQL schema and operations in {} ({} entities, {} links{})",
+            output_dir.display(),
+            project.entities.len(),
+            project.links.len(),
+            if hooks { ", React hooks" } else { "" }
+        ),
+        &[],
+    );
+
+    Ok(())
+}
+
+pub fn run_proto(args: GenerateProtoArgs, writer: &dyn FileWriter) -> Result<()> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| anyhow::anyhow!("Failed to get current directory: {}", e))?;
+    run_proto_in(args, writer, &cwd)
+}
+
+/// Run the generate proto command with an explicit starting directory.
+/// Unlike the TypeScript generators, this also works in a classic project.
+pub(crate) fn run_proto_in(
+    args: GenerateProtoArgs,
+    writer: &dyn FileWriter,
+    cwd: &Path,
+) -> Result<()> {
+    let project_root = project::detect_project_root_from(cwd)?;
 
     if writer.is_dry_run() {
         output::print_step("Dry run — no files will be written");
     }
 
-    // Introspect the project
     output::print_step("Introspecting project entities and links...");
-    let project = introspect::introspect(&api_root)?;
-
+    let project = introspect::introspect(&project_root)?;
     if project.entities.is_empty() {
         bail!(
             "No entities found in {}. Add entities with `this add entity <name>` first.",
-            api_root.join("src/entities").display()
+            project_root.join("src/entities").display()
         );
     }
-
     output::print_info(&format!(
         "Found {} entities, {} links",
         project.entities.len(),
         project.links.len()
     ));
 
-    // Generate TypeScript client
-    output::print_step("Generating TypeScript API client...");
-    let ts_content = crate::codegen::typescript::generate(&project);
+    if !super::info::detect_this_features(&project_root).grpc {
+        output::print_warn(
+            "The grpc feature is not enabled in the API — run `this enable grpc` to serve these services",
+        );
+    }
+
+    output::print_step("Generating protobuf package...");
+    let name = super::doctor::detect_project_name(&project_root);
+    let package = crate::codegen::proto::package_name(&name);
+    let content = crate::codegen::proto::generate(&project, &package);
+
+    // buf expects the directory layout to mirror the package: <out>/shop/v1/shop.proto
+    let output_dir = match args.output {
+        Some(dir) => dir,
+        None => project::find_workspace_root_from(cwd)
+            .unwrap_or_else(|| project_root.clone())
+            .join("proto"),
+    };
+    let package_dir = package
+        .split('.')
+        .fold(output_dir.clone(), |dir, part| dir.join(part));
+    let file_stem = package.split('.').next().unwrap_or("api");
+    let path = package_dir.join(format!("{}.proto", file_stem));
+
+    writer.create_dir_all(&package_dir)?;
+    writer.write_file(&path, &content)?;
+
+    report_generated(
+        writer,
+        std::slice::from_ref(&path),
+        &format!(
+            "Generated proto package {}: {} ({} services)",
+            package,
+            path.display(),
+            project.entities.len()
+        ),
+        &[format!(
+            "Point protoc (-I {}) or buf at this directory to generate gRPC clients",
+            output_dir.display()
+        )],
+    );
+
+    Ok(())
+}
+
+pub fn run_diagram(args: GenerateDiagramArgs, writer: &dyn FileWriter) -> Result<()> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| anyhow::anyhow!("Failed to get current directory: {}", e))?;
+    run_diagram_in(args, writer, &cwd)
+}
+
+/// Run the generate diagram command with an explicit starting directory.
+/// Without `--output` the diagram alone is printed, so it can be redirected.
+pub(crate) fn run_diagram_in(
+    args: GenerateDiagramArgs,
+    writer: &dyn FileWriter,
+    cwd: &Path,
+) -> Result<()> {
+    use crate::codegen::diagram;
 
-    // Determine output path
-    let output_path = match args.output {
-        Some(path) => path,
-        None => auto_detect_output(&workspace_root, &config)?,
+    if args.cluster_by_backend && args.format == DiagramFormat::Mermaid {
+        bail!(
+            "--cluster-by-backend is not supported for Mermaid ER diagrams. Use --format dot or --format svg."
+        );
+    }
+
+    let project_root = project::detect_project_root_from(cwd)?;
+    let project = introspect::introspect(&project_root)?;
+    if project.entities.is_empty() {
+        bail!(
+            "No entities found in {}. Add entities with `this add entity <name>` first.",
+            project_root.join("src/entities").display()
+        );
+    }
+
+    let backends: Option<diagram::Backends> = args.cluster_by_backend.then(|| {
+        project
+            .entities
+            .iter()
+            .map(|e| {
+                let backend = introspect::detect_store_backend(&project_root, &e.snake_name);
+                (e.snake_name.clone(), backend)
+            })
+            .collect()
+    });
+
+    let content = match args.format {
+        DiagramFormat::Mermaid => diagram::mermaid(&project),
+        DiagramFormat::Dot => diagram::dot(&project, backends.as_ref()),
+        DiagramFormat::Svg => diagram::svg(&project, backends.as_ref()),
     };
 
-    // Ensure parent directory exists
-    if let Some(parent) = output_path.parent() {
+    let Some(output_path) = args.output else {
+        print!("{}", content);
+        return Ok(());
+    };
+
+    if writer.is_dry_run() {
+        output::print_step("Dry run — no files will be written");
+    }
+    if let Some(parent) = output_path.parent()
+        && !parent.as_os_str().is_empty()
+    {
         writer.create_dir_all(parent)?;
     }
+    writer.write_file(&output_path, &content)?;
+
+    report_generated(
+        writer,
+        std::slice::from_ref(&output_path),
+        &format!(
+            "Generated diagram: {} ({} entities, {} links)",
+            output_path.display(),
+            project.entities.len(),
+            project.links.len()
+        ),
+        &[],
+    );
+
+    Ok(())
+}
+
+pub fn run_docs(args: GenerateDocsArgs, writer: &dyn FileWriter) -> Result<()> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| anyhow::anyhow!("Failed to get current directory: {}", e))?;
+    run_docs_in(args, writer, &cwd)
+}
+
+/// Run the generate docs command with an explicit starting directory.
+/// Works in a classic project as well as in a workspace.
+pub(crate) fn run_docs_in(
+    args: GenerateDocsArgs,
+    writer: &dyn FileWriter,
+    cwd: &Path,
+) -> Result<()> {
+    use crate::codegen::docs;
 
-    writer.write_file(&output_path, &ts_content)?;
-    output::print_file_created(&output_path.display().to_string());
+    let project_root = project::detect_project_root_from(cwd)?;
 
-    output::print_success(&format!(
-        "Generated API client: {} ({} entities, {} links)",
-        output_path.display(),
+    if writer.is_dry_run() {
+        output::print_step("Dry run — no files will be written");
+    }
+
+    output::print_step("Introspecting project entities, links, auth and events...");
+    let project = introspect::introspect(&project_root)?;
+    if project.entities.is_empty() {
+        bail!(
+            "No entities found in {}. Add entities with `this add entity <name>` first.",
+            project_root.join("src/entities").display()
+        );
+    }
+    let auth = introspect::parse_auth(&project_root)?;
+    let flows = introspect::parse_event_flows(&project_root)?;
+    let backends = project
+        .entities
+        .iter()
+        .map(|e| {
+            let backend = introspect::detect_store_backend(&project_root, &e.snake_name);
+            (e.snake_name.clone(), backend)
+        })
+        .collect();
+    output::print_info(&format!(
+        "Found {} entities, {} links, {} event flows",
         project.entities.len(),
-        project.links.len()
+        project.links.len(),
+        flows.len()
+    ));
+
+    output::print_step("Generating API reference...");
+    let name = super::doctor::detect_project_name(&project_root);
+    let format = match args.format {
+        DocsFormat::Markdown => docs::DocsFormat::Markdown,
+        DocsFormat::Html => docs::DocsFormat::Html,
+    };
+    let pages = docs::generate(
+        &docs::DocsInput {
+            title: &name,
+            project: &project,
+            auth: &auth,
+            flows: &flows,
+            backends: &backends,
+        },
+        format,
+    );
+
+    let output_dir = match args.output {
+        Some(dir) => dir,
+        None => project::find_workspace_root_from(cwd)
+            .unwrap_or_else(|| project_root.clone())
+            .join("docs/api"),
+    };
+    writer.create_dir_all(&output_dir)?;
+    let mut written = vec![];
+    for (file, content) in &pages {
+        let path = output_dir.join(file);
+        writer.write_file(&path, content)?;
+        written.push(path);
+    }
+
+    report_generated(
+        writer,
+        &written,
+        &format!(
+            "Generated API reference: {} ({} pages)",
+            output_dir.join(format.index_file()).display(),
+            pages.len()
+        ),
+        &[],
+    );
+
+    Ok(())
+}
+
+pub fn run_json_schema(args: GenerateJsonSchemaArgs, writer: &dyn FileWriter) -> Result<()> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| anyhow::anyhow!("Failed to get current directory: {}", e))?;
+    run_json_schema_in(args, writer, &cwd)
+}
+
+/// Run the generate json-schema command with an explicit starting directory.
+/// Config schemas are emitted even when the project has no entities yet.
+pub(crate) fn run_json_schema_in(
+    args: GenerateJsonSchemaArgs,
+    writer: &dyn FileWriter,
+    cwd: &Path,
+) -> Result<()> {
+    use crate::codegen::json_schema;
+
+    let project_root = project::detect_project_root_from(cwd)?;
+
+    if writer.is_dry_run() {
+        output::print_step("Dry run — no files will be written");
+    }
+
+    output::print_step("Introspecting project entities and validators...");
+    let project = introspect::introspect(&project_root)?;
+    let mut validations = std::collections::BTreeMap::new();
+    for entity in &project.entities {
+        let model_path = project_root
+            .join("src/entities")
+            .join(&entity.snake_name)
+            .join("model.rs");
+        if model_path.exists()
+            && let Some(validation) = introspect::parse_entity_validation(&model_path)?
+        {
+            validations.insert(entity.snake_name.clone(), validation);
+        }
+    }
+    output::print_info(&format!(
+        "Found {} entities ({} validated)",
+        project.entities.len(),
+        validations.len()
     ));
 
+    output::print_step("Generating JSON Schemas...");
+    let output_dir = match args.output {
+        Some(dir) => dir,
+        None => project::find_workspace_root_from(cwd)
+            .unwrap_or_else(|| project_root.clone())
+            .join("schemas"),
+    };
+    let entity_files = json_schema::generate_entities(&project, &validations);
+    let config_files = json_schema::generate_config();
+    for dir in ["entities", "config"] {
+        writer.create_dir_all(&output_dir.join(dir))?;
+    }
+    let mut written = vec![];
+    for (file, content) in entity_files.iter().chain(&config_files) {
+        let path = output_dir.join(file);
+        writer.write_file(&path, content)?;
+        written.push(path);
+    }
+
+    report_generated(
+        writer,
+        &written,
+        &format!(
+            "Generated {} entity schemas and {} config schemas in {}",
+            entity_files.len(),
+            config_files.len(),
+            output_dir.display()
+        ),
+        &["Point editors at a config schema with `# yaml-language-server: $schema=<path>` at the top of the file".to_string()],
+    );
+
     Ok(())
 }
 
+/// Report what a generator wrote: one line per file, its success message,
+/// then `hints`. Dry-run writers list the files themselves, so only the
+/// per-file lines are skipped; `--check` prints its own report instead.
+fn report_generated(
+    writer: &dyn FileWriter,
+    paths: &[PathBuf],
+    success_msg: &str,
+    hints: &[String],
+) {
+    if writer.is_check() {
+        return;
+    }
+    if !writer.is_dry_run() {
+        for path in paths {
+            output::print_file_created(&path.display().to_string());
+        }
+    }
+    output::print_success(success_msg);
+    for hint in hints {
+        output::print_info(hint);
+    }
+}
+
+/// Locate the workspace, then introspect its API project.
+/// Fails when there is no workspace or the API has no entities.
+fn introspect_workspace(
+    writer: &dyn FileWriter,
+    cwd: &Path,
+) -> Result<(PathBuf, WorkspaceConfig, ProjectIntrospection)> {
+    // Find workspace root and API directory
+    let workspace_root = project::find_workspace_root_from(cwd).ok_or_else(|| {
+        anyhow::anyhow!("Not inside a this-rs workspace. Run `this init <name> --workspace` first.")
+    })?;
+
+    let this_yaml = workspace_root.join("this.yaml");
+    let config = load_workspace_config(&this_yaml)?;
+    let api_root = workspace_root.join(&config.api.path);
+
+    if writer.is_dry_run() {
+        output::print_step("Dry run — no files will be written");
+    }
+
+    // Introspect the project
+    output::print_step("Introspecting project entities and links...");
+    let project = introspect::introspect(&api_root)?;
+
+    if project.entities.is_empty() {
+        bail!(
+            "No entities found in {}. Add entities with `this add entity <name>` first.",
+            api_root.join("src/entities").display()
+        );
+    }
+
+    output::print_info(&format!(
+        "Found {} entities, {} links",
+        project.entities.len(),
+        project.links.len()
+    ));
+
+    Ok((workspace_root, config, project))
+}
+
+fn webapp_target(config: &WorkspaceConfig) -> Option<&crate::config::TargetConfig> {
+    config
+        .targets
+        .iter()
+        .find(|t| t.target_type == TargetType::Webapp)
+}
+
 /// Auto-detect the output path from this.yaml webapp target.
 /// Falls back to `<workspace>/api-client.ts` if no webapp target exists.
 fn auto_detect_output(
@@ -90,11 +737,7 @@ fn auto_detect_output(
     config: &crate::config::WorkspaceConfig,
 ) -> Result<PathBuf> {
     // Try to find webapp target
-    if let Some(webapp) = config
-        .targets
-        .iter()
-        .find(|t| t.target_type == TargetType::Webapp)
-    {
+    if let Some(webapp) = webapp_target(config) {
         Ok(workspace_root.join(&webapp.path).join("src/api-client.ts"))
     } else {
         // No webapp target — output next to this.yaml
@@ -105,7 +748,10 @@ fn auto_detect_output(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::commands::GenerateClientArgs;
+    use crate::commands::{
+        DiagramFormat, GenerateClientArgs, GenerateDiagramArgs, GenerateGraphqlArgs,
+        GenerateProtoArgs,
+    };
     use tempfile::TempDir;
 
     #[test]
@@ -123,6 +769,7 @@ mod tests {
                 runtime: None,
                 path: "front".to_string(),
             }],
+            templates: None,
         };
         let path = auto_detect_output(&root, &config).unwrap();
         assert_eq!(path, PathBuf::from("/project/front/src/api-client.ts"));
@@ -138,6 +785,7 @@ mod tests {
                 port: 3000,
             },
             targets: vec![],
+            templates: None,
         };
         let path = auto_detect_output(&root, &config).unwrap();
         assert_eq!(path, PathBuf::from("/project/api-client.ts"));
@@ -217,6 +865,8 @@ impl EntityDescriptor for ProductDescriptor {
         let args = GenerateClientArgs {
             lang: "typescript".to_string(),
             output: Some(output_path.clone()),
+            with: vec![],
+            watch: false,
         };
 
         let result = run_in(args, &writer, &ws);
@@ -253,6 +903,8 @@ impl EntityDescriptor for ProductDescriptor {
         let args = GenerateClientArgs {
             lang: "typescript".to_string(),
             output: None, // auto-detect
+            with: vec![],
+            watch: false,
         };
 
         let result = run_in(args, &writer, &ws);
@@ -277,6 +929,8 @@ impl EntityDescriptor for ProductDescriptor {
         let args = GenerateClientArgs {
             lang: "typescript".to_string(),
             output: None, // auto-detect, no webapp target
+            with: vec![],
+            watch: false,
         };
 
         let result = run_in(args, &writer, &ws);
@@ -300,6 +954,8 @@ impl EntityDescriptor for ProductDescriptor {
         let args = GenerateClientArgs {
             lang: "typescript".to_string(),
             output: None,
+            with: vec![],
+            watch: false,
         };
 
         // Pass a directory with no this.yaml
@@ -323,6 +979,8 @@ impl EntityDescriptor for ProductDescriptor {
         let args = GenerateClientArgs {
             lang: "python".to_string(),
             output: None,
+            with: vec![],
+            watch: false,
         };
 
         let result = run_in(args, &writer, tmp.path());
@@ -350,6 +1008,8 @@ impl EntityDescriptor for ProductDescriptor {
         let args = GenerateClientArgs {
             lang: "typescript".to_string(),
             output: Some(ws.join("output.ts")),
+            with: vec![],
+            watch: false,
         };
 
         let result = run_in(args, &writer, &ws);
@@ -412,6 +1072,8 @@ impl EntityDescriptor for OrderDescriptor {
         let args = GenerateClientArgs {
             lang: "typescript".to_string(),
             output: Some(output_path.clone()),
+            with: vec![],
+            watch: false,
         };
 
         let result = run_in(args, &writer, &ws);
@@ -434,6 +1096,8 @@ impl EntityDescriptor for OrderDescriptor {
         let args = GenerateClientArgs {
             lang: "typescript".to_string(),
             output: Some(output_path.clone()),
+            with: vec![],
+            watch: false,
         };
 
         let result = run_in(args, &writer, &ws);
@@ -442,4 +1106,466 @@ impl EntityDescriptor for OrderDescriptor {
         // The file should NOT exist in dry-run mode
         assert!(!output_path.exists(), "Dry run should not create the file");
     }
+
+    // ── Generate graphql ─────────────────────────────────────────────
+
+    #[test]
+    fn test_generate_client_with_zod_and_react_query() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "gen_extras");
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+
+        let output_path = ws.join("api-client.ts");
+        let args = GenerateClientArgs {
+            lang: "typescript".to_string(),
+            output: Some(output_path.clone()),
+            with: vec![ClientExtra::Zod, ClientExtra::ReactQuery],
+            watch: false,
+        };
+        run_in(args, &writer, &ws).unwrap();
+
+        let content = std::fs::read_to_string(&output_path).unwrap();
+        assert!(content.contains("export const ProductSchema: z.ZodType<Product>"));
+        assert!(content.contains("export function useCreateProduct()"));
+    }
+
+    // ── Check mode ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_check_client_detects_stale_file_without_writing() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "gen_check");
+        let output_path = ws.join("api-client.ts");
+        let args = || GenerateClientArgs {
+            lang: "typescript".to_string(),
+            output: Some(output_path.clone()),
+            with: vec![],
+            watch: false,
+        };
+
+        // Missing file
+        let err = run_check_in(GenerateCommands::Client(args()), &ws).unwrap_err();
+        assert!(err.to_string().contains("1 generated file(s) out of date"));
+        assert!(!output_path.exists());
+
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+        run_in(args(), &writer, &ws).unwrap();
+        run_check_in(GenerateCommands::Client(args()), &ws).unwrap();
+
+        std::fs::write(&output_path, "// edited\n").unwrap();
+        let err = run_check_in(GenerateCommands::Client(args()), &ws).unwrap_err();
+        assert!(err.to_string().contains("this generate client"));
+        assert_eq!(
+            std::fs::read_to_string(&output_path).unwrap(),
+            "// edited\n"
+        );
+    }
+
+    #[test]
+    fn test_check_covers_multi_file_generators() {
+        let tmp = TempDir::new().unwrap();
+        let api = setup_diagram_project(&tmp, "check_schema");
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+        run_json_schema_in(GenerateJsonSchemaArgs { output: None }, &writer, &api).unwrap();
+
+        let check = || GenerateCommands::JsonSchema(GenerateJsonSchemaArgs { output: None });
+        run_check_in(check(), &api).unwrap();
+
+        let schemas = tmp.path().join("check_schema/schemas");
+        std::fs::remove_file(schemas.join("config/links.schema.json")).unwrap();
+        assert!(run_check_in(check(), &api).is_err());
+    }
+
+    #[test]
+    fn test_check_rejects_watch_and_stdout_diagram() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "gen_check_reject");
+
+        let watch = GenerateCommands::Client(GenerateClientArgs {
+            lang: "typescript".to_string(),
+            output: None,
+            with: vec![],
+            watch: true,
+        });
+        let err = run_check_in(watch, &ws).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("--watch cannot be combined with --check")
+        );
+
+        let diagram = GenerateCommands::Diagram(GenerateDiagramArgs {
+            format: DiagramFormat::Mermaid,
+            output: None,
+            cluster_by_backend: false,
+        });
+        let err = run_check_in(diagram, &ws).unwrap_err();
+        assert!(err.to_string().contains("needs --output"));
+    }
+
+    // ── Watch mode ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_write_if_changed_skips_identical_content() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("front/src/api-client.ts");
+        let writer = RealWriter;
+
+        assert!(write_if_changed(&writer, &path, "a").unwrap());
+        assert!(!write_if_changed(&writer, &path, "a").unwrap());
+        assert!(write_if_changed(&writer, &path, "b").unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "b");
+    }
+
+    #[test]
+    fn test_generate_client_watch_rejects_dry_run() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "gen_watch_dry");
+        let writer = crate::utils::file_writer::DryRunWriter::new();
+        let args = GenerateClientArgs {
+            lang: "typescript".to_string(),
+            output: None,
+            with: vec![],
+            watch: true,
+        };
+
+        let err = run_in(args, &writer, &ws).unwrap_err();
+        assert!(err.to_string().contains("--dry-run"));
+    }
+
+    #[test]
+    fn test_watch_client_syncs_once_when_stopped() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "gen_watch");
+        let output_path = ws.join("api-client.ts");
+        let args = GenerateClientArgs {
+            lang: "typescript".to_string(),
+            output: Some(output_path.clone()),
+            with: vec![],
+            watch: true,
+        };
+
+        // Not running: the initial sync happens, then the watch returns
+        watch_client(&args, &ws, &AtomicBool::new(false)).unwrap();
+        let content = std::fs::read_to_string(&output_path).unwrap();
+        assert!(content.contains("export interface Product {"));
+    }
+
+    #[test]
+    fn test_dev_client_args_keep_existing_extras() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "gen_dev");
+        let config = load_workspace_config(&ws.join("this.yaml")).unwrap();
+
+        let args = dev_client_args(&ws, &config).unwrap();
+        assert_eq!(args.output, Some(ws.join("api-client.ts")));
+        assert!(args.with.is_empty());
+        assert!(args.watch);
+
+        std::fs::write(
+            ws.join("api-client.ts"),
+            "import { type QueryClient } from '@tanstack/react-query';\n",
+        )
+        .unwrap();
+        let args = dev_client_args(&ws, &config).unwrap();
+        assert_eq!(args.with, vec![ClientExtra::ReactQuery]);
+    }
+
+    #[test]
+    fn test_generate_graphql_writes_schema_and_operations() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "gql_test");
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+
+        let args = GenerateGraphqlArgs {
+            output: None,
+            no_hooks: false,
+        };
+        run_graphql_in(args, &writer, &ws).unwrap();
+
+        let sdl = std::fs::read_to_string(ws.join("graphql/schema.graphql")).unwrap();
+        assert!(sdl.contains("type Product {"));
+        assert!(sdl.contains("input CreateProductInput {"));
+        let ops = std::fs::read_to_string(ws.join("graphql/operations.ts")).unwrap();
+        assert!(ops.contains("export async function listProducts(): Promise<Product[]>"));
+        assert!(!ops.contains("from 'react'"), "No React webapp, no hooks");
+    }
+
+    #[test]
+    fn test_generate_graphql_react_webapp_gets_hooks() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "gql_react");
+        let yaml = "name: gql_react\napi:\n  path: api\n  port: 3000\ntargets:\n  - target_type: webapp\n    framework: react\n    path: front\n";
+        std::fs::write(ws.join("this.yaml"), yaml).unwrap();
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+
+        let args = GenerateGraphqlArgs {
+            output: None,
+            no_hooks: false,
+        };
+        run_graphql_in(args, &writer, &ws).unwrap();
+        let ops = std::fs::read_to_string(ws.join("front/src/graphql/operations.ts")).unwrap();
+        assert!(ops.contains("export function useProducts()"));
+
+        let args = GenerateGraphqlArgs {
+            output: Some(ws.join("plain")),
+            no_hooks: true,
+        };
+        run_graphql_in(args, &writer, &ws).unwrap();
+        let ops = std::fs::read_to_string(ws.join("plain/operations.ts")).unwrap();
+        assert!(!ops.contains("useProducts"));
+    }
+
+    #[test]
+    fn test_generate_graphql_dry_run() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "gql_dry");
+        let writer = crate::utils::file_writer::DryRunWriter::new();
+
+        let args = GenerateGraphqlArgs {
+            output: None,
+            no_hooks: false,
+        };
+        run_graphql_in(args, &writer, &ws).unwrap();
+        assert!(!ws.join("graphql").exists());
+    }
+
+    // ── Generate proto ───────────────────────────────────────────────
+
+    #[test]
+    fn test_generate_proto_in_workspace() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "proto_ws");
+        std::fs::write(
+            ws.join("api/Cargo.toml"),
+            "[package]\nname = \"proto-ws\"\n\n[dependencies]\nthis = { package = \"this-rs\", version = \"0.0.9\", features = [\"grpc\"] }\n",
+        )
+        .unwrap();
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+
+        run_proto_in(GenerateProtoArgs { output: None }, &writer, &ws).unwrap();
+
+        let proto = std::fs::read_to_string(ws.join("proto/proto_ws/v1/proto_ws.proto")).unwrap();
+        assert!(proto.contains("package proto_ws.v1;"));
+        assert!(proto.contains("service ProductService {"));
+        assert!(proto.contains("  double price = 3;"));
+    }
+
+    #[test]
+    fn test_generate_proto_in_classic_project() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "proto_classic");
+        let api = ws.join("api");
+        std::fs::remove_file(ws.join("this.yaml")).unwrap();
+        std::fs::write(
+            api.join("Cargo.toml"),
+            "[package]\nname = \"shop\"\n\n[dependencies]\nthis = { package = \"this-rs\", version = \"0.0.9\" }\n",
+        )
+        .unwrap();
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+
+        run_proto_in(GenerateProtoArgs { output: None }, &writer, &api).unwrap();
+        assert!(api.join("proto/shop/v1/shop.proto").exists());
+
+        let out = tmp.path().join("protos");
+        run_proto_in(
+            GenerateProtoArgs {
+                output: Some(out.clone()),
+            },
+            &writer,
+            &api,
+        )
+        .unwrap();
+        assert!(out.join("shop/v1/shop.proto").exists());
+    }
+
+    #[test]
+    fn test_generate_proto_dry_run() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "proto_dry");
+        std::fs::write(
+            ws.join("api/Cargo.toml"),
+            "[package]\nname = \"dry\"\n\n[dependencies]\nthis = { package = \"this-rs\", version = \"0.0.9\" }\n",
+        )
+        .unwrap();
+        let writer = crate::utils::file_writer::DryRunWriter::new();
+
+        run_proto_in(GenerateProtoArgs { output: None }, &writer, &ws).unwrap();
+        assert!(!ws.join("proto").exists());
+    }
+
+    // ── Generate diagram ─────────────────────────────────────────────
+
+    fn setup_diagram_project(tmp: &TempDir, name: &str) -> PathBuf {
+        let api = setup_generate_workspace(tmp, name).join("api");
+        std::fs::write(
+            api.join("Cargo.toml"),
+            "[package]\nname = \"shop\"\n\n[dependencies]\nthis = { package = \"this-rs\", version = \"0.0.9\" }\n",
+        )
+        .unwrap();
+        api
+    }
+
+    fn diagram_args(format: DiagramFormat, output: Option<PathBuf>) -> GenerateDiagramArgs {
+        GenerateDiagramArgs {
+            format,
+            output,
+            cluster_by_backend: false,
+        }
+    }
+
+    #[test]
+    fn test_generate_diagram_to_file() {
+        let tmp = TempDir::new().unwrap();
+        let api = setup_diagram_project(&tmp, "diagram_ws");
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+        let output_path = api.join("docs/er.mmd");
+
+        run_diagram_in(
+            diagram_args(DiagramFormat::Mermaid, Some(output_path.clone())),
+            &writer,
+            &api,
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&output_path).unwrap();
+        assert!(content.starts_with("erDiagram"));
+        assert!(content.contains("    Product {"));
+    }
+
+    #[test]
+    fn test_generate_diagram_clusters_by_store_backend() {
+        let tmp = TempDir::new().unwrap();
+        let api = setup_diagram_project(&tmp, "diagram_cluster");
+        std::fs::write(
+            api.join("src/entities/product/store.rs"),
+            "use this::storage::PostgresDataService;\n",
+        )
+        .unwrap();
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+        let output_path = api.join("er.dot");
+
+        let mut args = diagram_args(DiagramFormat::Dot, Some(output_path.clone()));
+        args.cluster_by_backend = true;
+        run_diagram_in(args, &writer, &api).unwrap();
+
+        let content = std::fs::read_to_string(&output_path).unwrap();
+        assert!(content.contains("subgraph \"cluster_postgres\""));
+    }
+
+    #[test]
+    fn test_generate_diagram_mermaid_rejects_clustering() {
+        let tmp = TempDir::new().unwrap();
+        let ws = setup_generate_workspace(&tmp, "diagram_mermaid");
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+
+        let mut args = diagram_args(DiagramFormat::Mermaid, None);
+        args.cluster_by_backend = true;
+        let err = run_diagram_in(args, &writer, &ws).unwrap_err();
+        assert!(err.to_string().contains("--format dot"));
+    }
+
+    #[test]
+    fn test_generate_docs_markdown_in_workspace() {
+        let tmp = TempDir::new().unwrap();
+        let api = setup_diagram_project(&tmp, "docs_ws");
+        std::fs::write(
+            api.join("config/auth.yaml"),
+            "provider: wami\ndefault_policy: authenticated\nentities:\n  product:\n    delete: admin_only\n",
+        )
+        .unwrap();
+        std::fs::write(
+            api.join("config/events.yaml"),
+            "event_flows:\n  - name: audit\n    trigger: \"entity.*.product\"\n    steps:\n      - type: deliver\n        sink: audit-log\n",
+        )
+        .unwrap();
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+
+        let args = GenerateDocsArgs {
+            format: DocsFormat::Markdown,
+            output: None,
+        };
+        run_docs_in(args, &writer, &api).unwrap();
+
+        let docs = tmp.path().join("docs_ws/docs/api");
+        let index = std::fs::read_to_string(docs.join("README.md")).unwrap();
+        assert!(index.contains("[Product](product.md)"));
+
+        let product = std::fs::read_to_string(docs.join("product.md")).unwrap();
+        assert!(product.contains("| `price` | `f64` | `number` |"));
+        assert!(product.contains("| delete | `admin_only` (auth.yaml) |"));
+        assert!(product.contains("| `audit` | created, updated, deleted |"));
+    }
+
+    #[test]
+    fn test_generate_docs_html_to_output_dir() {
+        let tmp = TempDir::new().unwrap();
+        let api = setup_diagram_project(&tmp, "docs_html");
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+        let output_dir = tmp.path().join("site");
+
+        let args = GenerateDocsArgs {
+            format: DocsFormat::Html,
+            output: Some(output_dir.clone()),
+        };
+        run_docs_in(args, &writer, &api).unwrap();
+
+        assert!(output_dir.join("index.html").exists());
+        let product = std::fs::read_to_string(output_dir.join("product.html")).unwrap();
+        assert!(product.contains("<h1>Product</h1>"));
+        assert!(product.contains("No event flows are triggered by this entity."));
+    }
+
+    #[test]
+    fn test_generate_json_schema_entities_and_config() {
+        let tmp = TempDir::new().unwrap();
+        let api = setup_diagram_project(&tmp, "schema_ws");
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+
+        let args = GenerateJsonSchemaArgs { output: None };
+        run_json_schema_in(args, &writer, &api).unwrap();
+
+        let schemas = tmp.path().join("schema_ws/schemas");
+        let create: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(schemas.join("entities/product.create.schema.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(create["title"], "CreateProduct");
+        assert_eq!(create["properties"]["price"]["type"], "number");
+        for file in [
+            "entities/product.schema.json",
+            "entities/product.update.schema.json",
+            "config/this.schema.json",
+            "config/links.schema.json",
+            "config/events.schema.json",
+            "config/auth.schema.json",
+        ] {
+            assert!(schemas.join(file).exists(), "missing {}", file);
+        }
+    }
+
+    #[test]
+    fn test_generate_json_schema_reads_validators() {
+        let tmp = TempDir::new().unwrap();
+        let api = setup_diagram_project(&tmp, "schema_validated");
+        std::fs::write(
+            api.join("src/entities/product/model.rs"),
+            "use this::prelude::*;\n\nimpl_data_entity_validated!(\n    Product,\n    \"product\",\n    [\"name\"],\n    {\n        price: f64,\n    },\n    validate: {\n        create: {\n            price: [required positive],\n        },\n        update: {\n            price: [optional positive],\n        },\n    },\n    filters: {\n        create: {},\n        update: {},\n    }\n);\n",
+        )
+        .unwrap();
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+        let output_dir = tmp.path().join("out");
+
+        let args = GenerateJsonSchemaArgs {
+            output: Some(output_dir.clone()),
+        };
+        run_json_schema_in(args, &writer, &api).unwrap();
+
+        let update: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(output_dir.join("entities/product.update.schema.json"))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(update["properties"]["price"]["exclusiveMinimum"], 0);
+        assert!(update.get("required").is_none());
+    }
 }
@@ -5,7 +5,7 @@ use colored::Colorize;
 use serde::Serialize;
 
 use crate::config;
-use crate::utils::{markers, project};
+use crate::utils::{ast, project};
 
 /// Parsed entity info from scanning the project
 #[derive(Debug, Serialize)]
@@ -606,16 +606,7 @@ fn check_module_registration(project_root: &Path, entity_names: &[&str]) -> usiz
 
     entity_names
         .iter()
-        .filter(|name| {
-            // Check if entity is in entity_types via marker or direct string
-            let needle = format!("\"{}\"", name);
-            if content.contains("[this:entity_types]") {
-                markers::has_line_after_marker(&content, "[this:entity_types]", &needle)
-            } else {
-                // Fallback: check if the entity name string appears in entity_types() method
-                content.contains(&needle)
-            }
-        })
+        .filter(|name| ast::has_entity_type(&content, name))
         .count()
 }
 
@@ -635,12 +626,7 @@ fn check_stores_configuration(project_root: &Path, entity_names: &[&str]) -> usi
         .iter()
         .filter(|name| {
             let plural = crate::utils::naming::pluralize(name);
-            let needle = format!("{}_store:", plural);
-            if content.contains("[this:store_fields]") {
-                markers::has_line_after_marker(&content, "[this:store_fields]", &needle)
-            } else {
-                content.contains(&needle)
-            }
+            ast::has_store_field(&content, &format!("{}_store", plural))
         })
         .count()
 }
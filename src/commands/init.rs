@@ -4,7 +4,7 @@ use anyhow::{Context, Result, bail};
 use colored::Colorize;
 
 use super::InitArgs;
-use crate::templates::TemplateEngine;
+use crate::templates::{THIS_RS_VERSION, TemplateEngine};
 use crate::utils::file_writer::FileWriter;
 use crate::utils::output;
 
@@ -63,6 +63,9 @@ fn run_classic(args: InitArgs, writer: &dyn FileWriter, cwd: &Path) -> Result<()
     if args.grpc {
         context.insert("grpc", &true);
     }
+    if args.graphql {
+        context.insert("graphql", &true);
+    }
     // --cognitive implies --events (signals travel through EventBus)
     let events = args.events || args.cognitive;
     if events {
@@ -86,6 +89,9 @@ fn run_classic(args: InitArgs, writer: &dyn FileWriter, cwd: &Path) -> Result<()
     if args.grpc {
         features.push("\"grpc\"");
     }
+    if args.graphql {
+        features.push("\"graphql\"");
+    }
     if args.auth {
         features.push("\"wami\"");
     }
@@ -93,6 +99,7 @@ fn run_classic(args: InitArgs, writer: &dyn FileWriter, cwd: &Path) -> Result<()
         features.push("\"obrain\"");
     }
     context.insert("features_list", &features.join(", "));
+    context.insert("this_rs_version", THIS_RS_VERSION);
 
     let mut files: Vec<(&str, &str)> = vec![
         ("project/Cargo.toml", "Cargo.toml"),
@@ -206,6 +213,9 @@ fn run_workspace(args: InitArgs, writer: &dyn FileWriter, cwd: &Path) -> Result<
     if args.grpc {
         api_context.insert("grpc", &true);
     }
+    if args.graphql {
+        api_context.insert("graphql", &true);
+    }
     let ws_events = args.events || args.cognitive;
     if ws_events {
         api_context.insert("events", &true);
@@ -228,6 +238,9 @@ fn run_workspace(args: InitArgs, writer: &dyn FileWriter, cwd: &Path) -> Result<
     if args.grpc {
         ws_features.push("\"grpc\"");
     }
+    if args.graphql {
+        ws_features.push("\"graphql\"");
+    }
     if args.auth {
         ws_features.push("\"wami\"");
     }
@@ -235,6 +248,7 @@ fn run_workspace(args: InitArgs, writer: &dyn FileWriter, cwd: &Path) -> Result<
         ws_features.push("\"obrain\"");
     }
     api_context.insert("features_list", &ws_features.join(", "));
+    api_context.insert("this_rs_version", THIS_RS_VERSION);
 
     let mut api_files: Vec<(&str, &str)> = vec![
         ("project/Cargo.toml", "Cargo.toml"),
@@ -310,9 +324,9 @@ fn init_git(args: &InitArgs, project_dir: &Path, writer: &dyn FileWriter) -> Res
     if !args.no_git && !writer.is_dry_run() {
         let gitignore_content = if args.workspace {
             // Workspace .gitignore includes frontend artifacts
-            "/target\n*.swp\n.env\n.DS_Store\nnode_modules/\ndist/\n.next/\n.nuxt/\n"
+            "/target\n*.swp\n.env\n.DS_Store\n.this/history/\nnode_modules/\ndist/\n.next/\n.nuxt/\n"
         } else {
-            "/target\n*.swp\n.env\n.DS_Store\n"
+            "/target\n*.swp\n.env\n.DS_Store\n.this/history/\n"
         };
         writer.write_file(&project_dir.join(".gitignore"), gitignore_content)?;
         output::print_file_created(".gitignore");
@@ -353,6 +367,7 @@ mod tests {
             workspace: false,
             websocket: false,
             grpc: false,
+            graphql: false,
             events: false,
             auth: false,
             cognitive: false,
@@ -370,6 +385,7 @@ mod tests {
             workspace: true,
             websocket: false,
             grpc: false,
+            graphql: false,
             events: false,
             auth: false,
             cognitive: false,
@@ -529,6 +545,21 @@ mod tests {
         assert_file_contains(&project, "src/main.rs", "GrpcExposure");
     }
 
+    #[test]
+    fn test_init_with_graphql() {
+        let tmp = TempDir::new().unwrap();
+        let writer = crate::mcp::handlers::McpFileWriter::new();
+        let mut args = classic_args("graphql-project");
+        args.graphql = true;
+
+        run_in(args, &writer, tmp.path()).unwrap();
+
+        let project = tmp.path().join("graphql-project");
+        assert_file_contains(&project, "Cargo.toml", "\"graphql\"");
+        assert_file_contains(&project, "src/main.rs", "GraphQLExposure::build_router");
+        assert_file_contains(&project, "src/main.rs", ".build_host()?");
+    }
+
     // ========================================================================
     // Custom port
     // ========================================================================
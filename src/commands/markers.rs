@@ -0,0 +1,362 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use regex::Regex;
+
+use crate::utils::file_writer::FileWriter;
+use crate::utils::markers::{self, MARKER_FILES, MARKERS};
+use crate::utils::{output, project};
+
+/// Where a marker stands in the project
+#[derive(Debug, PartialEq)]
+enum Status {
+    /// Found at this 1-based line
+    Present(usize),
+    /// Not found; `true` if `this markers restore` can re-insert it
+    Missing(bool),
+    /// The file that should hold it does not exist
+    NoFile,
+}
+
+/// One row of `this markers list`
+#[derive(Debug)]
+struct Entry {
+    file: &'static str,
+    marker: String,
+    /// Commands relying on the marker; `None` for markers the CLI doesn't know
+    used_by: Option<&'static str>,
+    status: Status,
+}
+
+/// 1-based number of the first line containing `needle`
+fn line_of(content: &str, needle: &str) -> Option<usize> {
+    content
+        .lines()
+        .position(|l| l.contains(needle))
+        .map(|i| i + 1)
+}
+
+/// Check every known marker, and report unknown `[this:...]` comments too
+fn inspect(project_root: &Path) -> Vec<Entry> {
+    let marker_re = Regex::new(r"\[this:[A-Za-z0-9_]+\]").unwrap();
+    let mut entries = Vec::new();
+
+    for file in MARKER_FILES {
+        let content = std::fs::read_to_string(project_root.join(file)).ok();
+        for known in MARKERS.iter().filter(|m| m.file == *file) {
+            let status = match &content {
+                None => Status::NoFile,
+                Some(content) => match line_of(content, known.marker) {
+                    Some(line) => Status::Present(line),
+                    None => Status::Missing(markers::restore(content, known.marker).is_some()),
+                },
+            };
+            entries.push(Entry {
+                file,
+                marker: known.marker.to_string(),
+                used_by: Some(known.used_by),
+                status,
+            });
+        }
+
+        let Some(content) = &content else {
+            continue;
+        };
+        for found in marker_re.find_iter(content) {
+            let marker = found.as_str();
+            if entries
+                .iter()
+                .any(|e| e.file == *file && e.marker == marker)
+            {
+                continue;
+            }
+            entries.push(Entry {
+                file,
+                marker: marker.to_string(),
+                used_by: None,
+                status: Status::Present(content[..found.start()].matches('\n').count() + 1),
+            });
+        }
+    }
+
+    entries
+}
+
+pub fn list() -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    list_in(&cwd)
+}
+
+/// List markers with an explicit starting directory.
+pub(crate) fn list_in(cwd: &Path) -> Result<()> {
+    let project_root = project::detect_project_root_from(cwd)?;
+    let entries = inspect(&project_root);
+
+    println!();
+    println!(
+        "{} Markers in project: {}",
+        "🔖".bold(),
+        super::doctor::detect_project_name(&project_root)
+            .cyan()
+            .bold()
+    );
+
+    let width = entries.iter().map(|e| e.marker.len()).max().unwrap_or(0);
+    for file in MARKER_FILES {
+        println!();
+        println!("  {}", file.bold());
+        let rows: Vec<&Entry> = entries.iter().filter(|e| e.file == *file).collect();
+        if rows.is_empty() {
+            let note = if project_root.join(file).exists() {
+                "(no markers — no command edits this file)"
+            } else {
+                "(file not found)"
+            };
+            println!("    {}", note.dimmed());
+        }
+        for entry in rows {
+            let used_by = match entry.used_by {
+                Some("") => "not used by any command yet".dimmed(),
+                Some(used_by) => used_by.normal(),
+                None => "unknown marker".yellow(),
+            };
+            let (icon, state) = match entry.status {
+                Status::Present(line) => {
+                    let icon = if entry.used_by.is_some() {
+                        "✅"
+                    } else {
+                        "⚠️ "
+                    };
+                    (icon, format!("line {}", line).dimmed())
+                }
+                Status::Missing(true) => ("❌", "missing".red()),
+                Status::Missing(false) => ("❌", "missing (cannot locate its block)".red()),
+                Status::NoFile => ("❌", "file not found".red()),
+            };
+            println!(
+                "    {} {:width$}  {:<10} {}",
+                icon,
+                entry.marker,
+                state,
+                used_by,
+                width = width
+            );
+        }
+    }
+
+    let present = entries
+        .iter()
+        .filter(|e| e.used_by.is_some() && matches!(e.status, Status::Present(_)))
+        .count();
+    let missing = entries
+        .iter()
+        .filter(|e| !matches!(e.status, Status::Present(_)))
+        .count();
+    let restorable = entries
+        .iter()
+        .filter(|e| e.status == Status::Missing(true))
+        .count();
+
+    println!();
+    print!("Summary: {}", format!("{} present", present).green());
+    if missing > 0 {
+        print!(", {}", format!("{} missing", missing).red());
+    }
+    println!();
+    if restorable > 0 {
+        println!(
+            "  {}",
+            format!(
+                "Run `this markers restore` to re-insert {} marker(s)",
+                restorable
+            )
+            .dimmed()
+        );
+    }
+    println!();
+    Ok(())
+}
+
+pub fn restore(writer: &dyn FileWriter) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    restore_in(writer, &cwd)
+}
+
+/// Restore missing markers with an explicit starting directory.
+pub(crate) fn restore_in(writer: &dyn FileWriter, cwd: &Path) -> Result<()> {
+    let project_root = project::detect_project_root_from(cwd)?;
+
+    output::print_step("Restoring generator markers...");
+    let mut restored = 0;
+    let mut unresolved = 0;
+    for file in MARKER_FILES {
+        let path = project_root.join(file);
+        let Ok(original) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let mut content = original.clone();
+        for known in MARKERS.iter().filter(|m| m.file == *file) {
+            if content.contains(known.marker) {
+                continue;
+            }
+            match markers::restore(&content, known.marker) {
+                Some(updated) => {
+                    content = updated;
+                    restored += 1;
+                    println!(
+                        "  {} {} → {}:{}",
+                        "✓".green(),
+                        known.marker,
+                        file,
+                        line_of(&content, known.marker).unwrap_or_default()
+                    );
+                }
+                None => {
+                    unresolved += 1;
+                    output::print_warn(&format!(
+                        "Cannot locate where {} belongs in {}",
+                        known.marker, file
+                    ));
+                }
+            }
+        }
+        if content != original {
+            writer.update_file(&path, &original, &content)?;
+        }
+    }
+
+    println!();
+    if restored == 0 && unresolved == 0 {
+        output::print_success("All markers are present — nothing to restore");
+    } else if restored > 0 && !writer.is_dry_run() {
+        output::print_success(&format!(
+            "Restored {} marker(s) — `this undo` to revert",
+            restored
+        ));
+    }
+    if unresolved > 0 {
+        println!(
+            "  {}",
+            "Hint: `this doctor --fix` re-creates missing constructors from the templates".dimmed()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_writer::RealWriter;
+
+    const MODULE_RS: &str = r#"use std::sync::Arc;
+
+use crate::stores::ShopStores;
+
+pub struct ShopModule {
+    pub stores: ShopStores,
+}
+
+impl Module for ShopModule {
+    fn entity_types(&self) -> Vec<&str> {
+        vec![
+            "product",
+        ]
+    }
+
+    fn register_entities(&self, registry: &mut EntityRegistry) {
+        registry.register(product());
+    }
+
+    fn get_entity_fetcher(&self, entity_type: &str) -> Option<Arc<dyn EntityFetcher>> {
+        match entity_type {
+            "product" => Some(self.stores.products_entity.clone()),
+            _ => None,
+        }
+    }
+
+    fn get_entity_creator(&self, entity_type: &str) -> Option<Arc<dyn EntityCreator>> {
+        match entity_type {
+            // [this:entity_creator]
+            "product" => Some(self.stores.products_entity.clone()),
+            _ => None,
+        }
+    }
+}
+"#;
+
+    fn setup(tmp: &tempfile::TempDir) -> std::path::PathBuf {
+        let root = tmp.path().to_path_buf();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"shop\"\n\n[dependencies]\nthis = { package = \"this-rs\", version = \"0.0.9\" }\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/module.rs"), MODULE_RS).unwrap();
+        std::fs::write(root.join("src/main.rs"), "// [this:custom]\nfn main() {}\n").unwrap();
+        root
+    }
+
+    #[test]
+    fn test_inspect_reports_present_missing_and_unknown_markers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = setup(&tmp);
+        let entries = inspect(&root);
+
+        let status = |marker: &str| {
+            &entries
+                .iter()
+                .find(|e| e.marker == format!("[this:{}]", marker))
+                .unwrap()
+                .status
+        };
+        assert_eq!(status("entity_creator"), &Status::Present(29));
+        assert_eq!(status("entity_fetcher"), &Status::Missing(true));
+        assert_eq!(status("module_imports"), &Status::Missing(true));
+        assert_eq!(status("store_fields"), &Status::NoFile);
+
+        let custom = entries
+            .iter()
+            .find(|e| e.marker == "[this:custom]")
+            .unwrap();
+        assert_eq!(custom.file, "src/main.rs");
+        assert!(custom.used_by.is_none());
+        assert_eq!(custom.status, Status::Present(1));
+    }
+
+    #[test]
+    fn test_restore_keeps_existing_registrations() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = setup(&tmp);
+        restore_in(&RealWriter, &root).unwrap();
+
+        let module = std::fs::read_to_string(root.join("src/module.rs")).unwrap();
+        assert!(module.contains(
+            "use std::sync::Arc;\n\n// [this:module_imports]\n\nuse crate::stores::ShopStores;"
+        ));
+        assert!(module.contains(
+            "vec![\n            // [this:entity_types]\n            \"product\",\n        ]"
+        ));
+        assert!(module.contains(
+            "registry: &mut EntityRegistry) {\n        // [this:register_entities]\n        registry.register(product());\n    }"
+        ));
+        assert!(module.contains(
+            "match entity_type {\n            // [this:entity_fetcher]\n            \"product\" =>"
+        ));
+        assert_eq!(module.matches("[this:entity_creator]").count(), 1);
+        assert!(
+            inspect(&root)
+                .iter()
+                .filter(|e| e.file == "src/module.rs")
+                .all(|e| matches!(e.status, Status::Present(_)))
+        );
+
+        // Running it again changes nothing
+        restore_in(&RealWriter, &root).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/module.rs")).unwrap(),
+            module
+        );
+    }
+}
@@ -7,21 +7,41 @@ pub mod build;
 pub mod completions;
 pub mod dev;
 pub mod doctor;
+pub mod enable;
 pub mod generate;
 pub mod info;
 pub mod init;
+pub mod markers;
+pub mod templates;
+pub mod undo;
+pub mod upgrade;
 
 use clap::{Parser, Subcommand};
 use clap_complete::Shell;
 
+use crate::utils::file_writer::{DryRunFormat, DryRunMode};
+
 /// this - CLI scaffolding tool for this-rs projects
 #[derive(Parser)]
 #[command(name = "this", version, about, long_about = None)]
 #[command(propagate_version = true)]
 pub struct Cli {
     /// Simulate operations without writing any files
-    #[arg(long, global = true)]
-    pub dry_run: bool,
+    /// (`--dry-run=full` also previews the content of new files)
+    #[arg(
+        long,
+        global = true,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "summary"
+    )]
+    pub dry_run: Option<DryRunMode>,
+
+    /// Output format of the dry run; `patch` prints a `git apply`-compatible
+    /// patch (implies --dry-run)
+    #[arg(long, global = true, value_name = "FORMAT")]
+    pub dry_run_format: Option<DryRunFormat>,
 
     #[command(subcommand)]
     pub command: Commands,
@@ -48,7 +68,22 @@ pub enum Commands {
     Generate(GenerateCommand),
 
     /// Check project health and consistency
-    Doctor,
+    Doctor(DoctorArgs),
+
+    /// Enable a this-rs feature in an existing project (Cargo.toml + main.rs)
+    Enable(EnableArgs),
+
+    /// Inspect and restore the `// [this:...]` markers generators rely on
+    Markers(MarkersCommand),
+
+    /// List and eject the templates generators render from
+    Templates(TemplatesCommand),
+
+    /// Revert the files changed by the last generator command
+    Undo(UndoArgs),
+
+    /// Upgrade the this-rs dependency and migrate project code to its API
+    Upgrade(UpgradeArgs),
 
     /// Generate shell completions
     ///
@@ -59,9 +94,9 @@ pub enum Commands {
         shell: Shell,
     },
 
-    /// Start MCP server on stdio for AI agent integration
+    /// Start MCP server for AI agent integration (stdio, or HTTP with --http)
     #[command(hide = true)]
-    Mcp,
+    Mcp(McpArgs),
 }
 
 #[derive(Parser)]
@@ -88,6 +123,47 @@ pub enum AddCommands {
     Sink(AddSinkArgs),
 }
 
+#[derive(Parser)]
+pub struct MarkersCommand {
+    #[command(subcommand)]
+    pub command: MarkersCommands,
+}
+
+#[derive(Subcommand)]
+pub enum MarkersCommands {
+    /// List every marker, the commands relying on it, and whether it is present
+    List,
+
+    /// Re-insert missing markers where they structurally belong
+    Restore,
+}
+
+#[derive(Parser)]
+pub struct TemplatesCommand {
+    #[command(subcommand)]
+    pub command: TemplatesCommands,
+}
+
+#[derive(Subcommand)]
+pub enum TemplatesCommands {
+    /// List built-in templates and the project overrides replacing them
+    List,
+
+    /// Copy a built-in template into the overrides directory for customisation
+    Eject(TemplatesEjectArgs),
+}
+
+/// Arguments for `this templates eject <name>`
+#[derive(Parser)]
+pub struct TemplatesEjectArgs {
+    /// Template name, e.g. entity/handlers.rs (the .tera suffix is optional)
+    pub name: String,
+
+    /// Overwrite an already ejected template
+    #[arg(long)]
+    pub force: bool,
+}
+
 /// Arguments for `this init <name>`
 #[derive(Parser)]
 pub struct InitArgs {
@@ -122,6 +198,10 @@ pub struct InitArgs {
     #[arg(long)]
     pub grpc: bool,
 
+    /// Enable GraphQL support (adds graphql feature and GraphQLExposure in main.rs)
+    #[arg(long)]
+    pub graphql: bool,
+
     /// Enable event system (EventBus, NotificationStore, SSE streaming, event flows)
     #[arg(long)]
     pub events: bool,
@@ -267,6 +347,80 @@ pub struct BuildArgs {
     pub target: Option<String>,
 }
 
+/// Arguments for `this doctor`
+#[derive(Parser)]
+pub struct DoctorArgs {
+    /// Apply automatic fixes for the problems found (respects --dry-run)
+    #[arg(long)]
+    pub fix: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "text", conflicts_with = "fix")]
+    pub format: DoctorFormat,
+
+    /// Fail on `warnings`, or on the warnings of a rule id (repeatable)
+    #[arg(long, value_name = "RULE")]
+    pub deny: Vec<String>,
+
+    /// Ignore all findings of a rule id, e.g. `check_links` (repeatable)
+    #[arg(long, value_name = "RULE")]
+    pub allow: Vec<String>,
+}
+
+/// Output format of `this doctor`
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum DoctorFormat {
+    /// Colored, human-readable report
+    Text,
+    /// JSON with rule ids, file paths and line numbers
+    Json,
+    /// SARIF 2.1.0 for code-scanning tools
+    Sarif,
+}
+
+/// Arguments for `this enable <feature>...`
+#[derive(Parser)]
+pub struct EnableArgs {
+    /// Features to enable
+    #[arg(value_enum, required = true)]
+    pub features: Vec<Feature>,
+}
+
+/// Feature `this enable` can retrofit into a project
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum Feature {
+    /// WebSocketExposure (websocket feature)
+    Websocket,
+    /// GrpcExposure (grpc feature)
+    Grpc,
+    /// GraphQLExposure (graphql feature)
+    Graphql,
+    /// EventBus, NotificationStore and config/events.yaml
+    Events,
+    /// WAMI auth (wami feature) and config/auth.yaml
+    Auth,
+}
+
+/// Arguments for `this undo`
+#[derive(Parser)]
+pub struct UndoArgs {
+    /// List recorded commands instead of undoing the last one
+    #[arg(long)]
+    pub list: bool,
+
+    /// Restore files even if they were edited after the command ran
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments for `this upgrade`
+#[derive(Parser)]
+pub struct UpgradeArgs {
+    /// this-rs version to upgrade to (default: the version new projects use)
+    #[arg(long, value_name = "VERSION")]
+    pub to: Option<String>,
+}
+
 /// Arguments for `this dev`
 #[derive(Parser)]
 pub struct DevArgs {
@@ -281,18 +435,36 @@ pub struct DevArgs {
     /// Override the API port from this.yaml
     #[arg(long)]
     pub port: Option<u16>,
+
+    /// Do not keep the webapp's API client in sync with entities and links
+    #[arg(long)]
+    pub no_client: bool,
 }
 
 #[derive(Parser)]
 pub struct GenerateCommand {
     #[command(subcommand)]
     pub command: GenerateCommands,
+
+    /// Write nothing; fail with a diff if the generated files on disk are out of date
+    #[arg(long, global = true)]
+    pub check: bool,
 }
 
 #[derive(Subcommand)]
 pub enum GenerateCommands {
     /// Generate a typed API client from project entities
     Client(GenerateClientArgs),
+    /// Generate a GraphQL schema and typed TypeScript operations
+    Graphql(GenerateGraphqlArgs),
+    /// Generate a .proto package for gRPC client generation
+    Proto(GenerateProtoArgs),
+    /// Generate an entity-relationship diagram of entities and links
+    Diagram(GenerateDiagramArgs),
+    /// Generate an API reference (one page per entity) as Markdown or HTML
+    Docs(GenerateDocsArgs),
+    /// Generate JSON Schemas for entities and config files
+    JsonSchema(GenerateJsonSchemaArgs),
 }
 
 /// Arguments for `this generate client`
@@ -305,4 +477,117 @@ pub struct GenerateClientArgs {
     /// Output file path (default: auto-detected from this.yaml webapp target)
     #[arg(long)]
     pub output: Option<std::path::PathBuf>,
+
+    /// Extras to generate alongside the fetch functions (repeatable or comma-separated)
+    #[arg(long = "with", value_enum, value_delimiter = ',')]
+    pub with: Vec<ClientExtra>,
+
+    /// Regenerate whenever entities or links change, until Ctrl+C
+    #[arg(long)]
+    pub watch: bool,
+}
+
+/// Optional extras of `this generate client`
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum ClientExtra {
+    /// zod schemas per entity, used to validate responses
+    Zod,
+    /// TanStack Query hooks with per-entity and per-link cache keys
+    ReactQuery,
+}
+
+/// Arguments for `this generate graphql`
+#[derive(Parser)]
+pub struct GenerateGraphqlArgs {
+    /// Output directory for schema.graphql and operations.ts
+    /// (default: <webapp>/src/graphql, else <workspace>/graphql)
+    #[arg(long)]
+    pub output: Option<std::path::PathBuf>,
+
+    /// Do not emit React hooks (emitted by default for a React webapp target)
+    #[arg(long)]
+    pub no_hooks: bool,
+}
+
+/// Arguments for `this generate proto`
+#[derive(Parser)]
+pub struct GenerateProtoArgs {
+    /// Output directory of the proto tree (default: <workspace or project>/proto)
+    #[arg(long)]
+    pub output: Option<std::path::PathBuf>,
+}
+
+/// Arguments for `this generate diagram`
+#[derive(Parser)]
+pub struct GenerateDiagramArgs {
+    /// Diagram format
+    #[arg(long, value_enum, default_value = "mermaid")]
+    pub format: DiagramFormat,
+
+    /// Output file path (default: print to stdout)
+    #[arg(long)]
+    pub output: Option<std::path::PathBuf>,
+
+    /// Group entities into one cluster per storage backend (dot and svg)
+    #[arg(long)]
+    pub cluster_by_backend: bool,
+}
+
+/// Output format of `this generate diagram`
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum DiagramFormat {
+    /// Mermaid erDiagram, for Markdown docs
+    Mermaid,
+    /// Graphviz DOT source
+    Dot,
+    /// Standalone SVG image (no Graphviz needed)
+    Svg,
+}
+
+/// Arguments for `this generate docs`
+#[derive(Parser)]
+pub struct GenerateDocsArgs {
+    /// Page format
+    #[arg(long, value_enum, default_value = "markdown")]
+    pub format: DocsFormat,
+
+    /// Output directory (default: <workspace or project>/docs/api)
+    #[arg(long)]
+    pub output: Option<std::path::PathBuf>,
+}
+
+/// Output format of `this generate docs`
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum DocsFormat {
+    /// Markdown pages, rendered by GitHub and most doc sites
+    Markdown,
+    /// Standalone HTML pages
+    Html,
+}
+
+/// Arguments for `this generate json-schema`
+#[derive(Parser)]
+pub struct GenerateJsonSchemaArgs {
+    /// Output directory (default: <workspace or project>/schemas)
+    #[arg(long)]
+    pub output: Option<std::path::PathBuf>,
+}
+
+/// Arguments for `this mcp`
+#[derive(Parser)]
+pub struct McpArgs {
+    /// Serve the MCP streamable HTTP transport on ADDR instead of stdio
+    /// (PORT or HOST:PORT; a bare port binds to 127.0.0.1)
+    #[arg(long, value_name = "ADDR")]
+    pub http: Option<String>,
+
+    /// Require "Authorization: Bearer <TOKEN>" on every HTTP request
+    /// (mandatory when binding to a non-loopback address)
+    #[arg(
+        long,
+        env = "THIS_MCP_TOKEN",
+        requires = "http",
+        hide_env_values = true
+    )]
+    pub token: Option<String>,
 }
@@ -0,0 +1,274 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+
+use super::TemplatesEjectArgs;
+use crate::config;
+use crate::templates::{self, BUILTIN_TEMPLATES, OVERRIDES_DIR, packs};
+use crate::utils::file_writer::FileWriter;
+use crate::utils::{output, project};
+
+/// Root whose overrides apply: the project if there is one, else the workspace
+fn templates_root(cwd: &Path) -> Result<PathBuf> {
+    project::detect_project_root_from(cwd)
+        .or_else(|err| project::find_workspace_root_from(cwd).ok_or(err))
+}
+
+/// Directory `this templates eject` writes to: the `templates:` directory of
+/// `this.yaml`, else the workspace's `.this/templates`, else the project's
+fn eject_dir(cwd: &Path) -> Result<PathBuf> {
+    if let Some(ws_root) = project::find_workspace_root_from(cwd) {
+        let config = config::load_workspace_config(&ws_root.join("this.yaml"))?;
+        return Ok(match config.templates {
+            Some(templates) => ws_root.join(templates),
+            None => ws_root.join(OVERRIDES_DIR),
+        });
+    }
+    Ok(project::detect_project_root_from(cwd)?.join(OVERRIDES_DIR))
+}
+
+pub fn list() -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    list_in(&cwd)
+}
+
+/// List templates with an explicit starting directory.
+pub(crate) fn list_in(cwd: &Path) -> Result<()> {
+    let root = templates_root(cwd)?;
+    let dirs = templates::override_dirs(&root);
+
+    // Highest priority first: the first directory providing a name wins
+    let mut overrides: Vec<(String, PathBuf)> = Vec::new();
+    for dir in &dirs {
+        for (name, path) in templates::override_files(dir) {
+            if !overrides.iter().any(|(n, _)| *n == name) {
+                overrides.push((name, path));
+            }
+        }
+    }
+    let display = |path: &Path| {
+        path.strip_prefix(&root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+
+    println!();
+    println!("{}", "📄 Templates:".bold());
+    let width = BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(overrides.iter().map(|(name, _)| name.len()))
+        .max()
+        .unwrap_or(0);
+    for (name, _) in BUILTIN_TEMPLATES {
+        match overrides.iter().find(|(n, _)| n == name) {
+            Some((_, path)) => println!(
+                "  ✏️  {:width$}  {} {}",
+                name,
+                "overridden by".yellow(),
+                display(path),
+                width = width
+            ),
+            None => println!(
+                "  📦 {:width$}  {}",
+                name,
+                "built-in".dimmed(),
+                width = width
+            ),
+        }
+    }
+    for (name, path) in overrides
+        .iter()
+        .filter(|(n, _)| templates::builtin_template(n).is_none() && !n.starts_with("packs/"))
+    {
+        println!(
+            "  ➕ {:width$}  {} {}",
+            name,
+            "custom, not used by any generator:".yellow(),
+            display(path),
+            width = width
+        );
+    }
+
+    let packs = packs::discover(&root)?;
+    if !packs.is_empty() {
+        println!();
+        println!("{}", "📦 Template packs:".bold());
+        for pack in &packs {
+            let hooks: Vec<&str> = [
+                ("add entity", &pack.manifest.entity),
+                ("add link", &pack.manifest.link),
+            ]
+            .iter()
+            .filter(|(_, hooks)| !hooks.files.is_empty() || !hooks.inserts.is_empty())
+            .map(|(command, _)| *command)
+            .collect();
+            println!(
+                "  {}  {}  {}",
+                pack.name.cyan(),
+                display(&pack.dir),
+                format!("({})", hooks.join(", ")).dimmed()
+            );
+            if let Some(description) = &pack.manifest.description {
+                println!("     {}", description.dimmed());
+            }
+        }
+    }
+
+    println!();
+    if dirs.is_empty() {
+        println!(
+            "  {}",
+            format!(
+                "No override directory — `this templates eject <name>` copies a template into {}",
+                OVERRIDES_DIR
+            )
+            .dimmed()
+        );
+    } else {
+        for dir in &dirs {
+            println!("  {} {}", "Overrides from:".dimmed(), display(dir));
+        }
+    }
+    println!();
+    Ok(())
+}
+
+pub fn eject(args: TemplatesEjectArgs, writer: &dyn FileWriter) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    eject_in(args, writer, &cwd)
+}
+
+/// Eject a built-in template with an explicit starting directory.
+pub(crate) fn eject_in(
+    args: TemplatesEjectArgs,
+    writer: &dyn FileWriter,
+    cwd: &Path,
+) -> Result<()> {
+    let name = args.name.strip_suffix(".tera").unwrap_or(&args.name);
+    let Some(content) = templates::builtin_template(name) else {
+        let names: Vec<&str> = BUILTIN_TEMPLATES.iter().map(|(n, _)| *n).collect();
+        bail!(
+            "Unknown template '{}'. Available templates:\n  {}",
+            name,
+            names.join("\n  ")
+        );
+    };
+
+    let dir = eject_dir(cwd)?;
+    let path = dir.join(format!("{}.tera", name));
+    if path.exists() && !args.force {
+        bail!(
+            "{} already exists. Use --force to overwrite it with the built-in template.",
+            path.display()
+        );
+    }
+
+    if let Some(parent) = path.parent() {
+        writer.create_dir_all(parent)?;
+    }
+    match std::fs::read_to_string(&path) {
+        Ok(original) => writer.update_file(&path, &original, content)?,
+        Err(_) => writer.write_file(&path, content)?,
+    }
+
+    if !writer.is_dry_run() {
+        output::print_success(&format!(
+            "Ejected {} to {}",
+            name,
+            path.strip_prefix(cwd).unwrap_or(&path).display()
+        ));
+        output::print_info("Edit it to customise what generators produce for this project");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_writer::RealWriter;
+
+    fn setup_project(tmp: &tempfile::TempDir) -> PathBuf {
+        let root = tmp.path().to_path_buf();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"shop\"\n\n[dependencies]\nthis = { package = \"this-rs\", version = \"0.0.9\" }\n",
+        )
+        .unwrap();
+        root
+    }
+
+    fn eject_args(name: &str, force: bool) -> TemplatesEjectArgs {
+        TemplatesEjectArgs {
+            name: name.to_string(),
+            force,
+        }
+    }
+
+    #[test]
+    fn test_eject_copies_builtin_into_project() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = setup_project(&tmp);
+
+        eject_in(
+            eject_args("entity/handlers.rs.tera", false),
+            &RealWriter,
+            &root,
+        )
+        .unwrap();
+
+        let ejected =
+            std::fs::read_to_string(root.join(".this/templates/entity/handlers.rs.tera")).unwrap();
+        assert_eq!(
+            ejected,
+            templates::builtin_template("entity/handlers.rs").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_eject_refuses_to_overwrite_without_force() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = setup_project(&tmp);
+        let path = root.join(".this/templates/entity/model.rs.tera");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "// mine\n").unwrap();
+
+        let err = eject_in(eject_args("entity/model.rs", false), &RealWriter, &root).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "// mine\n");
+
+        eject_in(eject_args("entity/model.rs", true), &RealWriter, &root).unwrap();
+        assert_ne!(std::fs::read_to_string(&path).unwrap(), "// mine\n");
+    }
+
+    #[test]
+    fn test_eject_unknown_template_lists_available() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = setup_project(&tmp);
+
+        let err = eject_in(eject_args("entity/nope.rs", false), &RealWriter, &root).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("Unknown template 'entity/nope.rs'"));
+        assert!(msg.contains("entity/handlers.rs"));
+    }
+
+    #[test]
+    fn test_eject_uses_configured_workspace_directory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws = tmp.path();
+        std::fs::write(
+            ws.join("this.yaml"),
+            "name: shop\napi:\n  path: api\n  port: 3000\ntemplates: conventions/templates\n",
+        )
+        .unwrap();
+
+        eject_in(eject_args("workspace/Dockerfile", false), &RealWriter, ws).unwrap();
+
+        assert!(
+            ws.join("conventions/templates/workspace/Dockerfile.tera")
+                .exists()
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use super::UndoArgs;
+use crate::utils::history::{self, UndoReport};
+use crate::utils::output;
+
+/// Entry point for `this undo`.
+pub fn run(args: UndoArgs, dry_run: bool) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    run_in(args, dry_run, &cwd)
+}
+
+pub(crate) fn run_in(args: UndoArgs, dry_run: bool, cwd: &Path) -> Result<()> {
+    let root = history::find_history_root(cwd).context(
+        "No command history found. Only commands run with this-cli's journal can be undone.",
+    )?;
+
+    if args.list {
+        print_history(&root)?;
+        return Ok(());
+    }
+
+    let report = history::undo_latest(&root, args.force, dry_run)?;
+    print_report(&report, dry_run);
+    Ok(())
+}
+
+fn print_history(root: &Path) -> Result<()> {
+    let entries = history::list(root)?;
+    if entries.is_empty() {
+        println!("  {}", "History is empty".dimmed());
+        return Ok(());
+    }
+
+    println!("{}", "Command history (most recent last):".bold());
+    for (_, entry) in &entries {
+        println!(
+            "  {}  {} ({} file(s))",
+            entry.id.dimmed(),
+            entry.command,
+            entry.files.len()
+        );
+    }
+    Ok(())
+}
+
+fn print_report(report: &UndoReport, dry_run: bool) {
+    let (restore, remove) = if dry_run {
+        ("Would restore:", "Would remove:")
+    } else {
+        ("Restored:", "Removed:")
+    };
+
+    for path in &report.restored {
+        println!("  {} {}", restore.yellow(), path);
+    }
+    for path in &report.removed {
+        println!("  {} {}", remove.red(), path);
+    }
+
+    if dry_run {
+        output::print_info(&format!("Would undo '{}'", report.command));
+    } else {
+        output::print_success(&format!("Undid '{}'", report.command));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::history::{JournalEntry, JournalFile};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn record_change(root: &Path) {
+        std::fs::write(root.join("f.rs"), "after\n").unwrap();
+        let entry = JournalEntry::new(
+            "add entity product",
+            vec![JournalFile {
+                path: PathBuf::from("f.rs"),
+                before: Some("before\n".to_string()),
+                after: "after\n".to_string(),
+            }],
+            vec![],
+        );
+        history::record(root, &entry).unwrap();
+    }
+
+    #[test]
+    fn test_undo_without_history_errors() {
+        let tmp = TempDir::new().unwrap();
+        let args = UndoArgs {
+            list: false,
+            force: false,
+        };
+        let err = run_in(args, false, tmp.path()).unwrap_err();
+        assert!(err.to_string().contains("No command history"));
+    }
+
+    #[test]
+    fn test_undo_restores_latest() {
+        let tmp = TempDir::new().unwrap();
+        record_change(tmp.path());
+        let args = UndoArgs {
+            list: false,
+            force: false,
+        };
+        run_in(args, false, tmp.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("f.rs")).unwrap(),
+            "before\n"
+        );
+    }
+
+    #[test]
+    fn test_undo_list_does_not_modify() {
+        let tmp = TempDir::new().unwrap();
+        record_change(tmp.path());
+        let args = UndoArgs {
+            list: true,
+            force: false,
+        };
+        run_in(args, false, tmp.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("f.rs")).unwrap(),
+            "after\n"
+        );
+    }
+}
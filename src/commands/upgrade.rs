@@ -0,0 +1,382 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use regex::Regex;
+
+use super::UpgradeArgs;
+use crate::templates::THIS_RS_VERSION;
+use crate::utils::file_writer::FileWriter;
+use crate::utils::{output, project};
+
+/// A versioned migration of project source code.
+///
+/// Codemods run in order for every version crossed by the upgrade and must
+/// be idempotent: running one on already-migrated code changes nothing.
+pub struct Codemod {
+    /// this-rs version whose projects need the migration
+    pub since: &'static str,
+    pub id: &'static str,
+    pub description: &'static str,
+    /// Files to migrate, relative to the project root; `*` matches an entity directory
+    pub files: &'static [&'static str],
+    /// Migrate one file: (project root, relative path, content) -> new content
+    pub apply: fn(&Path, &str, &str) -> Result<String>,
+}
+
+/// All codemods, ordered by version
+pub const CODEMODS: &[Codemod] = &[
+    Codemod {
+        since: "0.0.8",
+        id: "axum-path-params",
+        description: "Rewrite `:param` route segments to `{param}` (axum 0.8)",
+        files: &["src/main.rs", "src/entities/*/descriptor.rs"],
+        apply: axum_path_params,
+    },
+    Codemod {
+        since: "0.0.9",
+        id: "generator-markers",
+        description: "Add the scaffolding and `// [this:...]` markers newer generators rely on",
+        files: &["src/module.rs", "src/stores.rs"],
+        apply: super::doctor::sync_with_templates,
+    },
+];
+
+/// A version as comparable numeric components (`^0.0.9` -> [0, 0, 9])
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim().trim_start_matches(['^', '~', '=', 'v']);
+    let parts: Option<Vec<u64>> = version.split('.').map(|p| p.parse().ok()).collect();
+    parts.filter(|p| !p.is_empty())
+}
+
+/// Codemods to run when going from `from` (unknown: run all) to `to`
+fn codemods_between(from: Option<&[u64]>, to: &[u64]) -> Vec<&'static Codemod> {
+    CODEMODS
+        .iter()
+        .filter(|codemod| {
+            let since = parse_version(codemod.since).unwrap_or_default();
+            from.is_none_or(|from| since.as_slice() > from) && since.as_slice() <= to
+        })
+        .collect()
+}
+
+/// Expand a codemod file pattern into existing files, relative to the project root
+fn expand_files(project_root: &Path, pattern: &str) -> Vec<String> {
+    let Some((dir, rest)) = pattern.split_once("/*/") else {
+        return if project_root.join(pattern).is_file() {
+            vec![pattern.to_string()]
+        } else {
+            vec![]
+        };
+    };
+    let mut entities: Vec<String> = std::fs::read_dir(project_root.join(dir))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    entities.sort();
+    entities
+        .into_iter()
+        .map(|entity| format!("{}/{}/{}", dir, entity, rest))
+        .filter(|file| project_root.join(file).is_file())
+        .collect()
+}
+
+/// Codemod `axum-path-params`: `.route("/orders/:id", ...)` -> `.route("/orders/{id}", ...)`
+fn axum_path_params(_project_root: &Path, _file: &str, content: &str) -> Result<String> {
+    let route = Regex::new(r#"(\.route\(\s*)"([^"]*)""#).unwrap();
+    let param = Regex::new(r"/:(\w+)").unwrap();
+    Ok(route
+        .replace_all(content, |caps: &regex::Captures| {
+            format!("{}\"{}\"", &caps[1], param.replace_all(&caps[2], "/{$1}"))
+        })
+        .into_owned())
+}
+
+/// Set the `this` dependency version, keeping the rest of Cargo.toml intact.
+/// Returns the previous version (`None` for path dependencies, which are left alone).
+fn bump_dependency(doc: &mut toml_edit::DocumentMut, to: &str) -> Result<Option<String>> {
+    let dep = doc
+        .get_mut("dependencies")
+        .and_then(|deps| deps.get_mut("this"))
+        .context("No 'this' dependency found in Cargo.toml")?;
+
+    if let Some(version) = dep.as_str().map(str::to_string) {
+        *dep = toml_edit::value(to);
+        return Ok(Some(version));
+    }
+    let Some(table) = dep.as_table_like_mut() else {
+        bail!("Unsupported 'this' dependency format in Cargo.toml");
+    };
+    match table
+        .get("version")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+    {
+        Some(version) => {
+            table.insert("version", toml_edit::value(to));
+            Ok(Some(version))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn run(args: UpgradeArgs, writer: &dyn FileWriter) -> Result<()> {
+    let cwd = std::env::current_dir()
+        .map_err(|e| anyhow::anyhow!("Failed to get current directory: {}", e))?;
+    run_in(args, writer, &cwd)
+}
+
+/// Run the upgrade command with an explicit starting directory.
+pub(crate) fn run_in(args: UpgradeArgs, writer: &dyn FileWriter, cwd: &Path) -> Result<()> {
+    let project_root = project::detect_project_root_from(cwd)?;
+    let target = args.to.as_deref().unwrap_or(THIS_RS_VERSION);
+    let to = parse_version(target)
+        .with_context(|| format!("Invalid version '{}' (expected e.g. 0.0.9)", target))?;
+
+    let cargo_path = project_root.join("Cargo.toml");
+    let cargo_content = std::fs::read_to_string(&cargo_path)
+        .with_context(|| format!("Failed to read: {}", cargo_path.display()))?;
+    let mut doc: toml_edit::DocumentMut = cargo_content
+        .parse()
+        .with_context(|| format!("Failed to parse: {}", cargo_path.display()))?;
+
+    let previous = bump_dependency(&mut doc, target)?;
+    let from = previous.as_deref().and_then(parse_version);
+    if let Some(from) = &from
+        && *from > to
+    {
+        bail!(
+            "this-rs {} is newer than {}; downgrades are not supported",
+            previous.as_deref().unwrap_or_default(),
+            target
+        );
+    }
+
+    if writer.is_dry_run() {
+        println!("🔍 {}", "Dry run — no files will be written".cyan().bold());
+        println!();
+    }
+    output::print_step(&format!(
+        "Upgrading to this-rs {} (from {})...",
+        target,
+        previous.as_deref().unwrap_or("a path dependency")
+    ));
+
+    let mut changed = 0;
+    let bumped = doc.to_string();
+    if bumped != cargo_content {
+        writer.update_file(&cargo_path, &cargo_content, &bumped)?;
+        println!(
+            "  {} Cargo.toml: this-rs {} → {}",
+            "✓".green(),
+            previous.as_deref().unwrap_or_default(),
+            target
+        );
+        changed += 1;
+    } else if previous.is_none() {
+        output::print_warn("'this' is a path dependency — Cargo.toml left unchanged");
+    }
+
+    // Compose every codemod in memory so each file is written once
+    let mut files: Vec<(String, String, String)> = Vec::new();
+    for codemod in codemods_between(from.as_deref(), &to) {
+        let mut touched = Vec::new();
+        for pattern in codemod.files {
+            for file in expand_files(&project_root, pattern) {
+                let idx = match files.iter().position(|(f, _, _)| *f == file) {
+                    Some(idx) => idx,
+                    None => {
+                        let path = project_root.join(&file);
+                        let content = std::fs::read_to_string(&path)
+                            .with_context(|| format!("Failed to read: {}", path.display()))?;
+                        files.push((file.clone(), content.clone(), content));
+                        files.len() - 1
+                    }
+                };
+                let updated = (codemod.apply)(&project_root, &file, &files[idx].2)
+                    .with_context(|| format!("Codemod '{}' failed on {}", codemod.id, file))?;
+                if updated != files[idx].2 {
+                    files[idx].2 = updated;
+                    touched.push(file);
+                }
+            }
+        }
+
+        if touched.is_empty() {
+            println!(
+                "  {} {} {}",
+                "·".dimmed(),
+                codemod.id,
+                "(nothing to migrate)".dimmed()
+            );
+        } else {
+            println!(
+                "  {} {} — {}",
+                "✓".green(),
+                codemod.id.bold(),
+                codemod.description
+            );
+            for file in &touched {
+                println!("      {}", file.dimmed());
+            }
+        }
+    }
+
+    for (file, original, updated) in &files {
+        if original != updated {
+            writer.update_file(&project_root.join(file), original, updated)?;
+            changed += 1;
+        }
+    }
+
+    println!();
+    if changed == 0 {
+        output::print_success(&format!("Already up to date with this-rs {}", target));
+    } else if !writer.is_dry_run() {
+        output::print_success(&format!(
+            "Upgraded {} file(s) — run `cargo build` to verify, `this undo` to revert",
+            changed
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_writer::RealWriter;
+    use tempfile::TempDir;
+
+    const DESCRIPTOR: &str = r#"        Router::new()
+            .route("/orders", get(list_orders).post(create_order))
+            .route(
+                "/orders/:id",
+                get(get_order).put(update_order),
+            )
+            .route("/orders/:order_id/items/:item_id", get(get_item))
+"#;
+
+    fn project(this_dep: &str) -> TempDir {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"shop\"\n\n[dependencies]\n# the framework\n{}\ntokio = \"1\"\n",
+                this_dep
+            ),
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("src/entities/order")).unwrap();
+        std::fs::write(root.join("src/entities/order/descriptor.rs"), DESCRIPTOR).unwrap();
+        tmp
+    }
+
+    fn upgrade(root: &Path, to: Option<&str>) -> Result<()> {
+        run_in(
+            UpgradeArgs {
+                to: to.map(str::to_string),
+            },
+            &RealWriter,
+            root,
+        )
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("0.0.9"), Some(vec![0, 0, 9]));
+        assert_eq!(parse_version("^1.2"), Some(vec![1, 2]));
+        assert_eq!(parse_version("latest"), None);
+    }
+
+    #[test]
+    fn test_codemods_between_versions() {
+        let ids = |from: Option<&[u64]>, to: &[u64]| -> Vec<&str> {
+            codemods_between(from, to).iter().map(|c| c.id).collect()
+        };
+        assert_eq!(
+            ids(Some(&[0, 0, 7]), &[0, 0, 9]),
+            vec!["axum-path-params", "generator-markers"]
+        );
+        assert_eq!(ids(Some(&[0, 0, 8]), &[0, 0, 9]), vec!["generator-markers"]);
+        assert_eq!(ids(Some(&[0, 0, 7]), &[0, 0, 8]), vec!["axum-path-params"]);
+        assert!(ids(Some(&[0, 0, 9]), &[0, 0, 9]).is_empty());
+        assert_eq!(ids(None, &[0, 0, 9]).len(), CODEMODS.len());
+    }
+
+    #[test]
+    fn test_codemods_are_ordered_by_version() {
+        let versions: Vec<Vec<u64>> = CODEMODS
+            .iter()
+            .map(|c| parse_version(c.since).unwrap())
+            .collect();
+        assert!(versions.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_axum_path_params_is_idempotent() {
+        let migrated = axum_path_params(Path::new("."), "", DESCRIPTOR).unwrap();
+        assert!(migrated.contains("\"/orders/{id}\""));
+        assert!(migrated.contains("\"/orders/{order_id}/items/{item_id}\""));
+        assert_eq!(
+            axum_path_params(Path::new("."), "", &migrated).unwrap(),
+            migrated
+        );
+    }
+
+    #[test]
+    fn test_upgrade_bumps_dependency_and_runs_codemods() {
+        let tmp = project(r#"this = { package = "this-rs", version = "0.0.7", features = [] }"#);
+        upgrade(tmp.path(), Some("0.0.8")).unwrap();
+
+        let cargo = std::fs::read_to_string(tmp.path().join("Cargo.toml")).unwrap();
+        assert!(
+            cargo.contains(r#"this = { package = "this-rs", version = "0.0.8", features = [] }"#)
+        );
+        assert!(
+            cargo.contains("# the framework\n"),
+            "formatting is preserved"
+        );
+        let descriptor =
+            std::fs::read_to_string(tmp.path().join("src/entities/order/descriptor.rs")).unwrap();
+        assert!(descriptor.contains("\"/orders/{id}\""));
+    }
+
+    #[test]
+    fn test_upgrade_plain_version_string() {
+        let tmp = project(r#"this = "0.0.8""#);
+        upgrade(tmp.path(), None).unwrap();
+        let cargo = std::fs::read_to_string(tmp.path().join("Cargo.toml")).unwrap();
+        assert!(cargo.contains(&format!("this = \"{}\"", THIS_RS_VERSION)));
+        // 0.0.8 projects already use axum 0.8 routes
+        let descriptor =
+            std::fs::read_to_string(tmp.path().join("src/entities/order/descriptor.rs")).unwrap();
+        assert_eq!(descriptor, DESCRIPTOR);
+    }
+
+    #[test]
+    fn test_upgrade_path_dependency_runs_all_codemods() {
+        let tmp = project(r#"this = { package = "this-rs", path = "../this" }"#);
+        upgrade(tmp.path(), None).unwrap();
+        let cargo = std::fs::read_to_string(tmp.path().join("Cargo.toml")).unwrap();
+        assert!(cargo.contains(r#"path = "../this""#));
+        assert!(!cargo.contains("version"));
+        let descriptor =
+            std::fs::read_to_string(tmp.path().join("src/entities/order/descriptor.rs")).unwrap();
+        assert!(descriptor.contains("\"/orders/{id}\""));
+    }
+
+    #[test]
+    fn test_upgrade_rejects_downgrade_and_bad_versions() {
+        let tmp = project(r#"this = "0.0.9""#);
+        let err = upgrade(tmp.path(), Some("0.0.8")).unwrap_err();
+        assert!(err.to_string().contains("downgrades are not supported"));
+        let err = upgrade(tmp.path(), Some("next")).unwrap_err();
+        assert!(err.to_string().contains("Invalid version 'next'"));
+    }
+}
@@ -4,6 +4,8 @@ use anyhow::{Context, Result};
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
+use crate::utils::file_writer::FileWriter;
+
 /// Root configuration for a this-rs workspace, stored in `this.yaml`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct WorkspaceConfig {
@@ -16,6 +18,10 @@ pub struct WorkspaceConfig {
     /// Deployment targets (webapp, desktop, ios, android)
     #[serde(default)]
     pub targets: Vec<TargetConfig>,
+
+    /// Directory of template overrides, relative to the workspace root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub templates: Option<String>,
 }
 
 /// Configuration for the API (this-rs backend) within a workspace.
@@ -76,14 +82,18 @@ pub fn load_workspace_config(path: &Path) -> Result<WorkspaceConfig> {
     Ok(config)
 }
 
-/// Save a workspace configuration to a `this.yaml` file.
-#[allow(dead_code)] // Will be used by `this init --workspace` (Task 2)
-pub fn save_workspace_config(path: &Path, config: &WorkspaceConfig) -> Result<()> {
+/// Save a workspace configuration to a `this.yaml` file through the given writer.
+pub fn save_workspace_config(
+    path: &Path,
+    config: &WorkspaceConfig,
+    writer: &dyn FileWriter,
+) -> Result<()> {
     let content =
         serde_yaml::to_string(config).with_context(|| "Failed to serialize workspace config")?;
-    std::fs::write(path, content)
-        .with_context(|| format!("Failed to write workspace config: {}", path.display()))?;
-    Ok(())
+    match std::fs::read_to_string(path) {
+        Ok(original) => writer.update_file(path, &original, &content),
+        Err(_) => writer.write_file(path, &content),
+    }
 }
 
 impl std::fmt::Display for TargetType {
@@ -101,6 +111,7 @@ impl std::fmt::Display for TargetType {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::file_writer::RealWriter;
     use tempfile::TempDir;
 
     fn make_test_config() -> WorkspaceConfig {
@@ -116,6 +127,7 @@ mod tests {
                 runtime: None,
                 path: "front".to_string(),
             }],
+            templates: None,
         }
     }
 
@@ -133,7 +145,7 @@ mod tests {
         let path = tmp.path().join("this.yaml");
         let config = make_test_config();
 
-        save_workspace_config(&path, &config).unwrap();
+        save_workspace_config(&path, &config, &RealWriter).unwrap();
         let loaded = load_workspace_config(&path).unwrap();
 
         assert_eq!(config, loaded);
@@ -8,23 +8,27 @@ mod test_helpers;
 mod utils;
 
 use clap::Parser;
-use commands::{AddCommands, Cli, Commands};
-use utils::file_writer::{DryRunWriter, RealWriter};
+use commands::{AddCommands, Cli, Commands, MarkersCommands, TemplatesCommands};
+use utils::file_writer::{DryRunWriter, TransactionWriter};
 use utils::output;
 
 fn main() {
     let cli = Cli::parse();
 
-    let dry_run = cli.dry_run;
+    let dry_run = cli.dry_run.is_some() || cli.dry_run_format.is_some();
 
     let result = if dry_run {
-        let writer = DryRunWriter::new();
+        let writer = DryRunWriter::with_options(
+            cli.dry_run.unwrap_or_default(),
+            cli.dry_run_format.unwrap_or_default(),
+        );
         let res = run_command(cli, &writer);
         writer.print_summary();
         res
     } else {
-        let writer = RealWriter;
-        run_command(cli, &writer)
+        // Stage all writes and apply them only if the whole command succeeds
+        let writer = TransactionWriter::new();
+        run_command(cli, &writer).and_then(|()| writer.commit(&command_label()).map(|_| ()))
     };
 
     if let Err(e) = result {
@@ -44,16 +48,49 @@ fn run_command(cli: Cli, writer: &dyn utils::file_writer::FileWriter) -> anyhow:
             AddCommands::Sink(args) => commands::add_sink::run(args, writer),
         },
         Commands::Info => commands::info::run(),
+        Commands::Generate(generate) if generate.check => {
+            commands::generate::run_check(generate.command)
+        }
         Commands::Generate(generate) => match generate.command {
             commands::GenerateCommands::Client(args) => commands::generate::run(args, writer),
+            commands::GenerateCommands::Graphql(args) => {
+                commands::generate::run_graphql(args, writer)
+            }
+            commands::GenerateCommands::Proto(args) => commands::generate::run_proto(args, writer),
+            commands::GenerateCommands::Diagram(args) => {
+                commands::generate::run_diagram(args, writer)
+            }
+            commands::GenerateCommands::Docs(args) => commands::generate::run_docs(args, writer),
+            commands::GenerateCommands::JsonSchema(args) => {
+                commands::generate::run_json_schema(args, writer)
+            }
+        },
+        Commands::Doctor(args) => commands::doctor::run(args, writer),
+        Commands::Enable(args) => commands::enable::run(args, writer),
+        Commands::Markers(markers) => match markers.command {
+            MarkersCommands::List => commands::markers::list(),
+            MarkersCommands::Restore => commands::markers::restore(writer),
         },
-        Commands::Doctor => commands::doctor::run(),
+        Commands::Templates(templates) => match templates.command {
+            TemplatesCommands::List => commands::templates::list(),
+            TemplatesCommands::Eject(args) => commands::templates::eject(args, writer),
+        },
+        Commands::Undo(args) => commands::undo::run(args, writer.is_dry_run()),
+        Commands::Upgrade(args) => commands::upgrade::run(args, writer),
         Commands::Completions { shell } => commands::completions::run(shell),
         Commands::Build(args) => commands::build::run(args, writer),
         Commands::Dev(args) => commands::dev::run(args),
-        Commands::Mcp => {
-            let mut server = mcp::server::McpServer::new();
-            server.run()
-        }
+        Commands::Mcp(args) => match args.http {
+            Some(addr) => mcp::http::serve(&addr, args.token),
+            None => {
+                let mut server = mcp::server::McpServer::new();
+                server.run()
+            }
+        },
     }
 }
+
+/// The command line as typed (minus the binary), recorded in the undo journal
+fn command_label() -> String {
+    std::env::args().skip(1).collect::<Vec<_>>().join(" ")
+}
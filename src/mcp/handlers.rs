@@ -27,6 +27,7 @@ impl ToolHandler {
             "add_event_flow" => handle_add_event_flow(&args),
             "add_sink" => handle_add_sink(&args),
             "generate_client" => handle_generate_client(&args),
+            "undo" => handle_undo(&args),
             _ => anyhow::bail!("Unknown tool: {}", name),
         }
     }
@@ -65,12 +66,22 @@ use crate::commands::{
     AddEntityArgs, AddEventFlowArgs, AddLinkArgs, AddSinkArgs, AddTargetArgs, BuildArgs, DevArgs,
     InitArgs,
 };
-use crate::utils::file_writer::FileWriter;
-
-/// FileWriter that performs real operations AND tracks created/modified files
+use crate::utils::diff;
+use crate::utils::file_writer::{FileWriter, StagedWrite, TransactionWriter, stage_write};
+use crate::utils::history;
+
+/// FileWriter that performs real operations AND tracks created/modified files.
+///
+/// In dry-run mode nothing is written: the full proposed content of every file
+/// is captured instead, and returned as unified diffs by `changes()`.
+/// In transactional mode writes are staged in a `TransactionWriter` and only
+/// applied (and journaled for `undo`) by `commit()`.
 pub struct McpFileWriter {
     files_created: std::cell::RefCell<Vec<std::path::PathBuf>>,
     files_modified: std::cell::RefCell<Vec<std::path::PathBuf>>,
+    dry_run: bool,
+    proposed: std::cell::RefCell<Vec<StagedWrite>>,
+    transaction: Option<TransactionWriter>,
 }
 
 impl McpFileWriter {
@@ -78,9 +89,49 @@ impl McpFileWriter {
         Self {
             files_created: std::cell::RefCell::new(Vec::new()),
             files_modified: std::cell::RefCell::new(Vec::new()),
+            dry_run: false,
+            proposed: std::cell::RefCell::new(Vec::new()),
+            transaction: None,
+        }
+    }
+
+    /// Create a writer that stages writes until `commit()` and journals them
+    pub fn transactional() -> Self {
+        Self {
+            transaction: Some(TransactionWriter::new()),
+            ..Self::new()
         }
     }
 
+    /// Create a writer that captures proposed changes without touching the disk
+    pub fn dry_run() -> Self {
+        Self {
+            dry_run: true,
+            ..Self::new()
+        }
+    }
+
+    /// Pick a transactional or dry-run writer based on the `dry_run` tool argument
+    fn from_args(args: &Value) -> Self {
+        if args
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            Self::dry_run()
+        } else {
+            Self::transactional()
+        }
+    }
+
+    /// Apply staged writes (transactional mode only) and journal them under `command`
+    pub fn commit(&self, command: &str) -> Result<()> {
+        if let Some(transaction) = &self.transaction {
+            transaction.commit(command)?;
+        }
+        Ok(())
+    }
+
     pub fn files_created(&self) -> Vec<String> {
         self.files_created
             .borrow()
@@ -96,31 +147,105 @@ impl McpFileWriter {
             .map(|p| p.display().to_string())
             .collect()
     }
+
+    /// Proposed changes as structured unified diffs, one entry per file
+    pub fn changes(&self) -> Vec<Value> {
+        self.proposed
+            .borrow()
+            .iter()
+            .map(|change| {
+                let original = change.original.as_deref();
+                let (additions, deletions) =
+                    diff::line_stats(original.unwrap_or(""), &change.content);
+                serde_json::json!({
+                    "path": change.path.display().to_string(),
+                    "action": if original.is_some() { "modify" } else { "create" },
+                    "additions": additions,
+                    "deletions": deletions,
+                    "diff": diff::unified_diff(&change.path, original, &change.content),
+                    "content": change.content,
+                })
+            })
+            .collect()
+    }
+
+    /// Record a proposed change, merging repeated writes to the same file
+    fn propose(&self, path: &std::path::Path, original: Option<&str>, content: &str) -> Result<()> {
+        stage_write(&mut self.proposed.borrow_mut(), path, original, content)
+    }
 }
 
 impl FileWriter for McpFileWriter {
     fn create_dir_all(&self, path: &std::path::Path) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        if let Some(transaction) = &self.transaction {
+            return transaction.create_dir_all(path);
+        }
         std::fs::create_dir_all(path)
             .map_err(|e| anyhow::anyhow!("Failed to create directory '{}': {}", path.display(), e))
     }
 
     fn write_file(&self, path: &std::path::Path, content: &str) -> Result<()> {
-        std::fs::write(path, content)
-            .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", path.display(), e))?;
+        if self.dry_run {
+            let existing = std::fs::read_to_string(path).ok();
+            self.propose(path, existing.as_deref(), content)?;
+        } else if let Some(transaction) = &self.transaction {
+            transaction.write_file(path, content)?;
+        } else {
+            std::fs::write(path, content)
+                .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", path.display(), e))?;
+        }
         self.files_created.borrow_mut().push(path.to_path_buf());
         Ok(())
     }
 
-    fn update_file(&self, path: &std::path::Path, _original: &str, updated: &str) -> Result<()> {
-        std::fs::write(path, updated)
-            .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", path.display(), e))?;
+    fn update_file(&self, path: &std::path::Path, original: &str, updated: &str) -> Result<()> {
+        if self.dry_run {
+            self.propose(path, Some(original), updated)?;
+        } else if let Some(transaction) = &self.transaction {
+            transaction.update_file(path, original, updated)?;
+        } else {
+            let content = crate::utils::file_writer::reconcile(path, original, updated).map_err(
+                |conflict| {
+                    anyhow::anyhow!(
+                        "Conflicting changes — file was not written:\n  {}",
+                        conflict
+                    )
+                },
+            )?;
+            std::fs::write(path, content)
+                .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", path.display(), e))?;
+        }
         self.files_modified.borrow_mut().push(path.to_path_buf());
         Ok(())
     }
 
     fn is_dry_run(&self) -> bool {
-        false
+        self.dry_run
+    }
+
+    fn read_file(&self, path: &std::path::Path) -> Result<String> {
+        if let Some(transaction) = &self.transaction {
+            return transaction.read_file(path);
+        }
+        if let Some(change) = self.proposed.borrow().iter().find(|c| c.path == path) {
+            return Ok(change.content.clone());
+        }
+        std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path.display(), e))
+    }
+}
+
+/// Attach the proposed changes to a tool result when the writer is in dry-run mode
+fn with_preview(mut result: Value, writer: &McpFileWriter) -> Value {
+    if writer.is_dry_run() {
+        result["status"] = Value::from("dry_run");
+        result["dry_run"] = Value::Bool(true);
+        result["changes"] = Value::Array(writer.changes());
     }
+    result
 }
 
 fn handle_init_project(args: &Value) -> Result<Value> {
@@ -144,7 +269,7 @@ fn handle_init_project(args: &Value) -> Result<Value> {
     let port = args.get("port").and_then(|v| v.as_u64()).unwrap_or(3000) as u16;
 
     let _cwd_guard = CwdGuard::from_args(args)?;
-    let writer = McpFileWriter::new();
+    let writer = McpFileWriter::from_args(args);
 
     let workspace = args
         .get("workspace")
@@ -158,6 +283,11 @@ fn handle_init_project(args: &Value) -> Result<Value> {
 
     let grpc = args.get("grpc").and_then(|v| v.as_bool()).unwrap_or(false);
 
+    let graphql = args
+        .get("graphql")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
     let events = args
         .get("events")
         .and_then(|v| v.as_bool())
@@ -172,12 +302,14 @@ fn handle_init_project(args: &Value) -> Result<Value> {
         workspace,
         websocket,
         grpc,
+        graphql,
         events,
         auth: false,
         cognitive: false,
     };
 
     crate::commands::init::run(init_args, &writer)?;
+    writer.commit("mcp init_project")?;
 
     let project_path = if path == "." {
         name.clone()
@@ -185,15 +317,19 @@ fn handle_init_project(args: &Value) -> Result<Value> {
         format!("{}/{}", path, name)
     };
 
-    Ok(serde_json::json!({
-        "status": "success",
-        "project_name": name,
-        "project_path": project_path,
-        "port": port,
-        "websocket_enabled": websocket,
-        "grpc_enabled": grpc,
-        "files_created": writer.files_created(),
-    }))
+    Ok(with_preview(
+        serde_json::json!({
+            "status": "success",
+            "project_name": name,
+            "project_path": project_path,
+            "port": port,
+            "websocket_enabled": websocket,
+            "grpc_enabled": grpc,
+            "graphql_enabled": graphql,
+            "files_created": writer.files_created(),
+        }),
+        &writer,
+    ))
 }
 
 fn handle_add_entity(args: &Value) -> Result<Value> {
@@ -220,7 +356,7 @@ fn handle_add_entity(args: &Value) -> Result<Value> {
         .to_string();
 
     let _cwd_guard = CwdGuard::from_args(args)?;
-    let writer = McpFileWriter::new();
+    let writer = McpFileWriter::from_args(args);
 
     let backend = args
         .get("backend")
@@ -237,13 +373,17 @@ fn handle_add_entity(args: &Value) -> Result<Value> {
     };
 
     crate::commands::add_entity::run(entity_args, &writer)?;
-
-    Ok(serde_json::json!({
-        "status": "success",
-        "entity_name": name,
-        "files_created": writer.files_created(),
-        "files_modified": writer.files_modified(),
-    }))
+    writer.commit("mcp add_entity")?;
+
+    Ok(with_preview(
+        serde_json::json!({
+            "status": "success",
+            "entity_name": name,
+            "files_created": writer.files_created(),
+            "files_modified": writer.files_modified(),
+        }),
+        &writer,
+    ))
 }
 
 fn handle_add_link(args: &Value) -> Result<Value> {
@@ -285,7 +425,7 @@ fn handle_add_link(args: &Value) -> Result<Value> {
         .unwrap_or(false);
 
     let _cwd_guard = CwdGuard::from_args(args)?;
-    let writer = McpFileWriter::new();
+    let writer = McpFileWriter::from_args(args);
 
     let link_args = AddLinkArgs {
         source: source.clone(),
@@ -298,12 +438,16 @@ fn handle_add_link(args: &Value) -> Result<Value> {
     };
 
     crate::commands::add_link::run(link_args, &writer)?;
-
-    Ok(serde_json::json!({
-        "status": "success",
-        "link": format!("{} -> {}", source, target),
-        "files_modified": writer.files_modified(),
-    }))
+    writer.commit("mcp add_link")?;
+
+    Ok(with_preview(
+        serde_json::json!({
+            "status": "success",
+            "link": format!("{} -> {}", source, target),
+            "files_modified": writer.files_modified(),
+        }),
+        &writer,
+    ))
 }
 
 fn handle_get_project_info(args: &Value) -> Result<Value> {
@@ -315,15 +459,26 @@ fn handle_get_project_info(args: &Value) -> Result<Value> {
 }
 
 fn handle_check_project_health(args: &Value) -> Result<Value> {
+    let fix = args.get("fix").and_then(|v| v.as_bool()).unwrap_or(false);
+
     let _cwd_guard = CwdGuard::from_args(args)?;
 
     let diagnostics = crate::commands::doctor::collect_diagnostics()?;
 
+    let fixed = if fix {
+        let writer = McpFileWriter::from_args(args);
+        let fixed = crate::commands::doctor::fix_project(&writer)?;
+        writer.commit("mcp check_project_health --fix")?;
+        Some((fixed, writer))
+    } else {
+        None
+    };
+
     let pass = diagnostics.iter().filter(|d| d.level == "pass").count();
     let warn = diagnostics.iter().filter(|d| d.level == "warn").count();
     let error = diagnostics.iter().filter(|d| d.level == "error").count();
 
-    Ok(serde_json::json!({
+    let result = serde_json::json!({
         "diagnostics": diagnostics,
         "summary": {
             "pass": pass,
@@ -331,7 +486,18 @@ fn handle_check_project_health(args: &Value) -> Result<Value> {
             "error": error,
             "total": diagnostics.len(),
         }
-    }))
+    });
+
+    Ok(match fixed {
+        Some((fixed, writer)) => {
+            let mut result = result;
+            result["fixed"] = serde_json::json!(fixed);
+            result["files_modified"] = serde_json::json!(writer.files_modified());
+            result["files_created"] = serde_json::json!(writer.files_created());
+            with_preview(result, &writer)
+        }
+        None => result,
+    })
 }
 
 fn handle_build_project(args: &Value) -> Result<Value> {
@@ -358,7 +524,13 @@ fn handle_build_project(args: &Value) -> Result<Value> {
         .unwrap_or(true);
 
     let _cwd_guard = CwdGuard::from_args(args)?;
-    let writer = McpFileWriter::new();
+    let writer = McpFileWriter::from_args(args);
+
+    if writer.is_dry_run() && !docker {
+        anyhow::bail!(
+            "dry_run is only supported for build_project with docker: true (other modes run cargo/npm)"
+        );
+    }
 
     let target = args
         .get("target")
@@ -387,13 +559,17 @@ fn handle_build_project(args: &Value) -> Result<Value> {
     };
 
     crate::commands::build::run(build_args, &writer)?;
-
-    Ok(serde_json::json!({
-        "status": "success",
-        "mode": mode,
-        "files_created": writer.files_created(),
-        "files_modified": writer.files_modified(),
-    }))
+    writer.commit("mcp build_project")?;
+
+    Ok(with_preview(
+        serde_json::json!({
+            "status": "success",
+            "mode": mode,
+            "files_created": writer.files_created(),
+            "files_modified": writer.files_modified(),
+        }),
+        &writer,
+    ))
 }
 
 fn handle_start_dev(args: &Value) -> Result<Value> {
@@ -407,6 +583,11 @@ fn handle_start_dev(args: &Value) -> Result<Value> {
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
+    let no_client = args
+        .get("no_client")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
     let port = args.get("port").and_then(|v| v.as_u64()).map(|p| p as u16);
 
     let _cwd_guard = CwdGuard::from_args(args)?;
@@ -415,6 +596,7 @@ fn handle_start_dev(args: &Value) -> Result<Value> {
         api_only,
         no_watch,
         port,
+        no_client,
     };
 
     // Note: this dev is a long-running process. In MCP context, it will block
@@ -460,7 +642,7 @@ fn handle_add_target(args: &Value) -> Result<Value> {
         .map(|s| s.to_string());
 
     let _cwd_guard = CwdGuard::from_args(args)?;
-    let writer = McpFileWriter::new();
+    let writer = McpFileWriter::from_args(args);
 
     let target_args = AddTargetArgs {
         target_type: target_type.clone(),
@@ -469,15 +651,19 @@ fn handle_add_target(args: &Value) -> Result<Value> {
     };
 
     crate::commands::add_target::run(target_args, &writer)?;
-
-    Ok(serde_json::json!({
-        "status": "success",
-        "target_type": target_type_str,
-        "framework": framework,
-        "files_created": writer.files_created(),
-        "files_modified": writer.files_modified(),
-        "next_steps": ["cd front && npm install", "this dev"],
-    }))
+    writer.commit("mcp add_target")?;
+
+    Ok(with_preview(
+        serde_json::json!({
+            "status": "success",
+            "target_type": target_type_str,
+            "framework": framework,
+            "files_created": writer.files_created(),
+            "files_modified": writer.files_modified(),
+            "next_steps": ["cd front && npm install", "this dev"],
+        }),
+        &writer,
+    ))
 }
 
 fn handle_add_event_flow(args: &Value) -> Result<Value> {
@@ -500,7 +686,7 @@ fn handle_add_event_flow(args: &Value) -> Result<Value> {
         .to_string();
 
     let _cwd_guard = CwdGuard::from_args(args)?;
-    let writer = McpFileWriter::new();
+    let writer = McpFileWriter::from_args(args);
 
     let flow_args = AddEventFlowArgs {
         name: name.clone(),
@@ -509,14 +695,18 @@ fn handle_add_event_flow(args: &Value) -> Result<Value> {
     };
 
     crate::commands::add_event_flow::run(flow_args, &writer)?;
-
-    Ok(serde_json::json!({
-        "status": "success",
-        "flow_name": name,
-        "trigger": trigger,
-        "sink": sink,
-        "files_modified": writer.files_modified(),
-    }))
+    writer.commit("mcp add_event_flow")?;
+
+    Ok(with_preview(
+        serde_json::json!({
+            "status": "success",
+            "flow_name": name,
+            "trigger": trigger,
+            "sink": sink,
+            "files_modified": writer.files_modified(),
+        }),
+        &writer,
+    ))
 }
 
 fn handle_add_sink(args: &Value) -> Result<Value> {
@@ -538,7 +728,7 @@ fn handle_add_sink(args: &Value) -> Result<Value> {
         .map(|s| s.to_string());
 
     let _cwd_guard = CwdGuard::from_args(args)?;
-    let writer = McpFileWriter::new();
+    let writer = McpFileWriter::from_args(args);
 
     let sink_args = AddSinkArgs {
         name: name.clone(),
@@ -547,14 +737,18 @@ fn handle_add_sink(args: &Value) -> Result<Value> {
     };
 
     crate::commands::add_sink::run(sink_args, &writer)?;
-
-    Ok(serde_json::json!({
-        "status": "success",
-        "sink_name": name,
-        "sink_type": sink_type,
-        "url": url,
-        "files_modified": writer.files_modified(),
-    }))
+    writer.commit("mcp add_sink")?;
+
+    Ok(with_preview(
+        serde_json::json!({
+            "status": "success",
+            "sink_name": name,
+            "sink_type": sink_type,
+            "url": url,
+            "files_modified": writer.files_modified(),
+        }),
+        &writer,
+    ))
 }
 
 fn handle_generate_client(args: &Value) -> Result<Value> {
@@ -576,17 +770,76 @@ fn handle_generate_client(args: &Value) -> Result<Value> {
         .and_then(|v| v.as_str())
         .map(std::path::PathBuf::from);
 
+    let with = args
+        .get("with")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .map(|v| {
+            let name = v.as_str().unwrap_or_default();
+            <crate::commands::ClientExtra as clap::ValueEnum>::from_str(name, true)
+                .map_err(|_| anyhow::anyhow!("Unknown client extra: '{}'", name))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
     let _cwd_guard = CwdGuard::from_args(args)?;
-    let writer = McpFileWriter::new();
+    let writer = McpFileWriter::from_args(args);
 
-    let generate_args = crate::commands::GenerateClientArgs { lang, output };
+    let generate_args = crate::commands::GenerateClientArgs {
+        lang,
+        output,
+        with,
+        watch: false,
+    };
 
     crate::commands::generate::run(generate_args, &writer)?;
+    writer.commit("mcp generate_client")?;
+
+    Ok(with_preview(
+        serde_json::json!({
+            "status": "success",
+            "lang": "typescript",
+            "files_created": writer.files_created(),
+        }),
+        &writer,
+    ))
+}
+
+fn handle_undo(args: &Value) -> Result<Value> {
+    let list = args.get("list").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let force = args.get("force").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let dry_run = args
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let _cwd_guard = CwdGuard::from_args(args)?;
+
+    let root = history::find_history_root(&std::env::current_dir()?)
+        .ok_or_else(|| anyhow::anyhow!("No command history found in this project"))?;
+
+    if list {
+        let entries: Vec<Value> = history::list(&root)?
+            .into_iter()
+            .map(|(_, entry)| {
+                serde_json::json!({
+                    "id": entry.id,
+                    "command": entry.command,
+                    "timestamp": entry.timestamp,
+                    "files": entry.files.iter().map(|f| f.path.display().to_string()).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        return Ok(serde_json::json!({ "history": entries }));
+    }
+
+    let report = history::undo_latest(&root, force, dry_run)?;
 
     Ok(serde_json::json!({
-        "status": "success",
-        "lang": "typescript",
-        "files_created": writer.files_created(),
+        "status": if dry_run { "dry_run" } else { "success" },
+        "undone": report,
     }))
 }
 
@@ -674,6 +927,68 @@ mod tests {
         assert_eq!(writer.files_created().len(), 3);
     }
 
+    #[test]
+    fn test_mcp_file_writer_dry_run_does_not_write() {
+        let tmp = TempDir::new().unwrap();
+        let created = tmp.path().join("new.txt");
+        let existing = tmp.path().join("existing.txt");
+        std::fs::write(&existing, "line 1\n").unwrap();
+
+        let writer = McpFileWriter::dry_run();
+        assert!(writer.is_dry_run());
+        writer.create_dir_all(&tmp.path().join("dir")).unwrap();
+        writer.write_file(&created, "hello\n").unwrap();
+        writer
+            .update_file(&existing, "line 1\n", "line 1\nline 2\n")
+            .unwrap();
+
+        assert!(!created.exists());
+        assert!(!tmp.path().join("dir").exists());
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "line 1\n");
+        assert_eq!(writer.files_created().len(), 1);
+        assert_eq!(writer.files_modified().len(), 1);
+
+        let changes = writer.changes();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0]["action"], "create");
+        assert_eq!(changes[0]["content"], "hello\n");
+        assert!(
+            changes[0]["diff"]
+                .as_str()
+                .unwrap()
+                .starts_with("--- /dev/null")
+        );
+        assert_eq!(changes[1]["action"], "modify");
+        assert_eq!(changes[1]["additions"], 1);
+        assert_eq!(changes[1]["deletions"], 0);
+        assert!(changes[1]["diff"].as_str().unwrap().contains("+line 2"));
+    }
+
+    #[test]
+    fn test_mcp_file_writer_dry_run_merges_repeated_updates() {
+        let writer = McpFileWriter::dry_run();
+        let path = std::path::Path::new("/fake/file.txt");
+        writer.update_file(path, "a\n", "a\nb\n").unwrap();
+        writer.update_file(path, "a\nb\n", "a\nb\nc\n").unwrap();
+
+        let changes = writer.changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0]["content"], "a\nb\nc\n");
+        assert_eq!(changes[0]["additions"], 2);
+    }
+
+    #[test]
+    fn test_mcp_file_writer_dry_run_merges_updates_from_disk() {
+        let writer = McpFileWriter::dry_run();
+        let path = std::path::Path::new("/fake/module.rs");
+        writer.update_file(path, "a\nb\n", "x\na\nb\n").unwrap();
+        writer.update_file(path, "a\nb\n", "a\nb\ny\n").unwrap();
+
+        let changes = writer.changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0]["content"], "x\na\nb\ny\n");
+    }
+
     // ── ToolHandler dispatch tests ───────────────────────────────────
 
     #[test]
@@ -1426,6 +1741,96 @@ impl AppModule {
         assert_eq!(result["url"], "https://example.com/hook");
     }
 
+    #[test]
+    #[serial]
+    fn test_handle_add_entity_dry_run() {
+        let tmp = TempDir::new().unwrap();
+        let project_dir = tmp.path().join("dry-project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        scaffold_project(&project_dir);
+        let module_before = std::fs::read_to_string(project_dir.join("src/module.rs")).unwrap();
+
+        let handler = ToolHandler::new();
+        let args = serde_json::json!({
+            "name": "product",
+            "dry_run": true,
+            "cwd": project_dir.to_str().unwrap()
+        });
+
+        let result = handler.handle("add_entity", Some(args)).unwrap();
+        assert_eq!(result["status"], "dry_run");
+        assert_eq!(result["dry_run"], true);
+
+        // Nothing touched on disk
+        assert!(!project_dir.join("src/entities/product").exists());
+        assert_eq!(
+            std::fs::read_to_string(project_dir.join("src/module.rs")).unwrap(),
+            module_before
+        );
+
+        // One structured diff per file
+        let changes = result["changes"].as_array().unwrap();
+        let model = changes
+            .iter()
+            .find(|c| c["path"].as_str().unwrap().ends_with("product/model.rs"))
+            .expect("model.rs should be proposed");
+        assert_eq!(model["action"], "create");
+        assert!(model["content"].as_str().unwrap().contains("Product"));
+        let module = changes
+            .iter()
+            .find(|c| c["path"].as_str().unwrap().ends_with("src/module.rs"))
+            .expect("module.rs should be proposed");
+        assert_eq!(module["action"], "modify");
+        assert!(module["diff"].as_str().unwrap().contains("@@"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_handle_undo_reverts_add_entity() {
+        let tmp = TempDir::new().unwrap();
+        let project_dir = tmp.path().join("undo-project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        scaffold_project(&project_dir);
+        let module_before = std::fs::read_to_string(project_dir.join("src/module.rs")).unwrap();
+        let cwd = project_dir.to_str().unwrap();
+
+        let handler = ToolHandler::new();
+        handler
+            .handle(
+                "add_entity",
+                Some(serde_json::json!({ "name": "product", "cwd": cwd })),
+            )
+            .unwrap();
+        assert!(project_dir.join("src/entities/product/model.rs").exists());
+
+        let history = handler
+            .handle(
+                "undo",
+                Some(serde_json::json!({ "list": true, "cwd": cwd })),
+            )
+            .unwrap();
+        assert_eq!(history["history"][0]["command"], "mcp add_entity");
+
+        let result = handler
+            .handle("undo", Some(serde_json::json!({ "cwd": cwd })))
+            .unwrap();
+        assert_eq!(result["status"], "success");
+        assert!(!project_dir.join("src/entities/product").exists());
+        assert_eq!(
+            std::fs::read_to_string(project_dir.join("src/module.rs")).unwrap(),
+            module_before
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_handle_build_project_dry_run_requires_docker() {
+        let handler = ToolHandler::new();
+        let args = serde_json::json!({ "dry_run": true });
+        let err = handler.handle("build_project", Some(args)).unwrap_err();
+        assert!(err.to_string().contains("docker"));
+    }
+
     #[test]
     #[serial]
     fn test_handle_add_sink_in_app_no_url() {
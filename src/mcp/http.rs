@@ -0,0 +1,911 @@
+//! MCP streamable HTTP transport
+//!
+//! Serves the MCP server over HTTP on a single `/mcp` endpoint:
+//! - `POST` carries a JSON-RPC message (or batch); responses are returned as
+//!   `application/json`, or as an SSE stream when the client only accepts
+//!   `text/event-stream`
+//! - `GET` opens an SSE stream for server-initiated messages
+//! - `DELETE` terminates the session
+//!
+//! Every `initialize` request opens a new session whose id is returned in the
+//! `Mcp-Session-Id` header and must be sent back on subsequent requests.
+//! Sessions idle for longer than [`SESSION_IDLE_TIMEOUT`] expire, and at most
+//! [`MAX_SESSIONS`] are kept (the least recently used one makes room).
+//! Synchronous, one thread per connection — no tokio required.
+
+use super::server::McpServer;
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Path of the MCP endpoint
+const MCP_PATH: &str = "/mcp";
+
+/// Header carrying the session id (lowercase, as stored by `read_request`)
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Largest request body accepted (4 MiB)
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// Interval between keep-alive comments on `GET` event streams
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Sessions without any request (or open event stream) for this long expire
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Most sessions kept at once; clients often never send `DELETE`
+const MAX_SESSIONS: usize = 64;
+
+/// Shared state of the HTTP transport
+struct HttpState {
+    /// One `McpServer` per session. The lock also serializes tool calls, which
+    /// is required because tool handlers change the process working directory.
+    sessions: Mutex<HashMap<String, Session>>,
+    token: Option<String>,
+    loopback_only: bool,
+}
+
+/// An MCP session and the time of its last activity
+struct Session {
+    server: McpServer,
+    last_seen: Instant,
+}
+
+impl HttpState {
+    fn new(token: Option<String>, loopback_only: bool) -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            token,
+            loopback_only,
+        }
+    }
+
+    /// Lock the session map. A tool call that panicked while holding the lock
+    /// leaves the map itself consistent, so poisoning is ignored rather than
+    /// failing every later request.
+    fn sessions(&self) -> MutexGuard<'_, HashMap<String, Session>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn has_session(&self, id: &str) -> bool {
+        let mut sessions = self.sessions();
+        expire_idle(&mut sessions, Instant::now());
+        sessions.contains_key(id)
+    }
+
+    /// Record activity on a session; `false` if it no longer exists
+    fn touch(&self, id: &str) -> bool {
+        let now = Instant::now();
+        let mut sessions = self.sessions();
+        expire_idle(&mut sessions, now);
+        match sessions.get_mut(id) {
+            Some(session) => {
+                session.last_seen = now;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Drop the sessions idle for longer than [`SESSION_IDLE_TIMEOUT`]
+fn expire_idle(sessions: &mut HashMap<String, Session>, now: Instant) {
+    sessions.retain(|_, s| now.duration_since(s.last_seen) <= SESSION_IDLE_TIMEOUT);
+}
+
+/// Open a session, evicting the least recently used ones beyond [`MAX_SESSIONS`]
+fn open_session(sessions: &mut HashMap<String, Session>, id: String, now: Instant) {
+    expire_idle(sessions, now);
+    while sessions.len() >= MAX_SESSIONS {
+        let Some(oldest) = sessions
+            .iter()
+            .min_by_key(|(_, s)| s.last_seen)
+            .map(|(id, _)| id.clone())
+        else {
+            break;
+        };
+        sessions.remove(&oldest);
+    }
+    sessions.insert(
+        id,
+        Session {
+            server: McpServer::new(),
+            last_seen: now,
+        },
+    );
+}
+
+/// Parsed HTTP request
+#[derive(Debug)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    /// Header names are lowercased
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(|v| v.as_str())
+    }
+
+    fn accepts(&self, mime: &str) -> bool {
+        match self.header("accept") {
+            Some(accept) => accept
+                .split(',')
+                .map(|part| part.split(';').next().unwrap_or("").trim())
+                .any(|m| m == mime || m == "*/*"),
+            None => true,
+        }
+    }
+}
+
+/// HTTP response to be written back to the client
+#[derive(Debug)]
+struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl HttpResponse {
+    fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    fn json(status: u16, value: &Value) -> Self {
+        Self::new(status)
+            .with_header("Content-Type", "application/json")
+            .with_body(serde_json::to_string(value).unwrap_or_default())
+    }
+
+    /// A plain JSON error body (not a JSON-RPC message)
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &json!({ "error": message }))
+    }
+
+    fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn with_body(mut self, body: String) -> Self {
+        self.body = body;
+        self
+    }
+
+    #[cfg(test)]
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn write_to(&self, out: &mut impl Write) -> std::io::Result<()> {
+        write!(out, "HTTP/1.1 {} {}\r\n", self.status, reason(self.status))?;
+        for (name, value) in &self.headers {
+            write!(out, "{}: {}\r\n", name, value)?;
+        }
+        write!(out, "Content-Length: {}\r\n", self.body.len())?;
+        write!(out, "Connection: close\r\n\r\n")?;
+        out.write_all(self.body.as_bytes())?;
+        out.flush()
+    }
+}
+
+/// What to do with a routed request
+#[derive(Debug)]
+enum Reply {
+    /// Write a complete response and close the connection
+    Response(HttpResponse),
+    /// Hold the connection open as an SSE stream for the given session
+    EventStream(String),
+}
+
+/// Run the MCP server over streamable HTTP on `addr` until the process exits
+pub fn serve(addr: &str, token: Option<String>) -> Result<()> {
+    let addr = parse_addr(addr)?;
+    let loopback_only = addr.ip().is_loopback();
+
+    if !loopback_only && token.is_none() {
+        bail!(
+            "Refusing to expose the MCP server on non-loopback address {} without --token",
+            addr
+        );
+    }
+
+    let listener = TcpListener::bind(addr)
+        .with_context(|| format!("Failed to bind MCP HTTP server to {}", addr))?;
+
+    eprintln!(
+        "this-cli MCP server listening on http://{}{}",
+        listener.local_addr().unwrap_or(addr),
+        MCP_PATH
+    );
+    if token.is_some() {
+        eprintln!("Bearer token authentication enabled");
+    }
+
+    let state = Arc::new(HttpState::new(token, loopback_only));
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            let _ = handle_connection(stream, &state);
+        });
+    }
+
+    Ok(())
+}
+
+/// Parse a `--http` address. A bare port binds to 127.0.0.1.
+fn parse_addr(addr: &str) -> Result<SocketAddr> {
+    if let Ok(port) = addr.parse::<u16>() {
+        return Ok(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port));
+    }
+    if let Some(port) = addr.strip_prefix("localhost:") {
+        let port: u16 = port
+            .parse()
+            .with_context(|| format!("Invalid port in address '{}'", addr))?;
+        return Ok(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port));
+    }
+    addr.parse().with_context(|| {
+        format!(
+            "Invalid address '{}'. Expected PORT or HOST:PORT (e.g. 3939 or 127.0.0.1:3939)",
+            addr
+        )
+    })
+}
+
+fn handle_connection(stream: TcpStream, state: &HttpState) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut out = stream;
+
+    let request = match read_request(&mut reader) {
+        Ok(r) => r,
+        Err(e) => {
+            HttpResponse::error(400, &format!("{:#}", e)).write_to(&mut out)?;
+            return Ok(());
+        }
+    };
+
+    match route(state, &request) {
+        Reply::Response(response) => response.write_to(&mut out)?,
+        Reply::EventStream(session_id) => stream_events(&mut out, state, &session_id)?,
+    }
+
+    Ok(())
+}
+
+/// Read a single HTTP/1.1 request (request line, headers, `Content-Length` body)
+fn read_request(reader: &mut impl BufRead) -> Result<HttpRequest> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Empty request line"))?
+        .to_string();
+    let target = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Missing request target"))?;
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let trimmed = line.trim_end();
+        if trimmed.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = trimmed.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = match headers.get("content-length") {
+        Some(v) => v
+            .parse::<usize>()
+            .map_err(|_| anyhow::anyhow!("Invalid Content-Length: {}", v))?,
+        None => 0,
+    };
+    if length > MAX_BODY_BYTES {
+        bail!("Request body too large ({} bytes)", length);
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    Ok(HttpRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// Route a request to the matching handler
+fn route(state: &HttpState, request: &HttpRequest) -> Reply {
+    if request.path != MCP_PATH {
+        return Reply::Response(HttpResponse::error(404, "Not found"));
+    }
+    if let Some(response) = check_origin(state, request) {
+        return Reply::Response(response);
+    }
+    if let Some(response) = check_auth(state, request) {
+        return Reply::Response(response);
+    }
+
+    match request.method.as_str() {
+        "POST" => Reply::Response(handle_post(state, request)),
+        "GET" => handle_get(state, request),
+        "DELETE" => Reply::Response(handle_delete(state, request)),
+        _ => Reply::Response(
+            HttpResponse::error(405, "Method not allowed")
+                .with_header("Allow", "GET, POST, DELETE"),
+        ),
+    }
+}
+
+/// Reject cross-origin browser requests to a loopback server (DNS rebinding protection)
+fn check_origin(state: &HttpState, request: &HttpRequest) -> Option<HttpResponse> {
+    if !state.loopback_only {
+        return None;
+    }
+    let origin = request.header("origin")?;
+    let host = origin
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(origin);
+    // Strip the port; an IPv6 literal keeps its brackets and inner colons
+    let host = match host.find(']') {
+        Some(end) if host.starts_with('[') => &host[..=end],
+        _ => host.rsplit_once(':').map(|(h, _)| h).unwrap_or(host),
+    };
+
+    match host {
+        "localhost" | "127.0.0.1" | "[::1]" => None,
+        _ => Some(HttpResponse::error(403, "Origin not allowed")),
+    }
+}
+
+/// Check the bearer token, if one is configured
+fn check_auth(state: &HttpState, request: &HttpRequest) -> Option<HttpResponse> {
+    let expected = state.token.as_deref()?;
+    let provided = request
+        .header("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => None,
+        _ => Some(
+            HttpResponse::error(401, "Missing or invalid bearer token")
+                .with_header("WWW-Authenticate", "Bearer"),
+        ),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Handle a `POST`: dispatch each JSON-RPC message to the session's server
+fn handle_post(state: &HttpState, request: &HttpRequest) -> HttpResponse {
+    let payload: Value = match serde_json::from_slice(&request.body) {
+        Ok(v) => v,
+        Err(e) => {
+            let error = super::protocol::JsonRpcResponse::error(
+                Value::Null,
+                super::protocol::JsonRpcError::parse_error(e.to_string()),
+            );
+            return HttpResponse::json(400, &serde_json::to_value(error).unwrap_or_default());
+        }
+    };
+
+    let (messages, is_batch) = match payload {
+        Value::Array(items) => (items, true),
+        other => (vec![other], false),
+    };
+    if messages.is_empty() {
+        return HttpResponse::error(400, "Empty batch");
+    }
+
+    let is_initialize = messages
+        .iter()
+        .any(|m| m.get("method").and_then(|v| v.as_str()) == Some("initialize"));
+
+    let now = Instant::now();
+    let mut sessions = state.sessions();
+    expire_idle(&mut sessions, now);
+
+    let session_id = if is_initialize {
+        let id = new_session_id();
+        open_session(&mut sessions, id.clone(), now);
+        id
+    } else {
+        match request.header(SESSION_HEADER) {
+            Some(id) if sessions.contains_key(id) => id.to_string(),
+            Some(_) => return HttpResponse::error(404, "Unknown session"),
+            None => return HttpResponse::error(400, "Missing Mcp-Session-Id header"),
+        }
+    };
+
+    let session = sessions
+        .get_mut(&session_id)
+        .expect("session was just looked up");
+    session.last_seen = now;
+    let server = &mut session.server;
+    let responses: Vec<Value> = messages
+        .iter()
+        .filter_map(|m| server.handle_message(&m.to_string()))
+        .filter_map(|r| serde_json::to_value(r).ok())
+        .collect();
+    drop(sessions);
+
+    if responses.is_empty() {
+        return HttpResponse::new(202).with_header("Mcp-Session-Id", &session_id);
+    }
+
+    let response = if request.accepts("application/json") {
+        let body = if is_batch {
+            Value::Array(responses)
+        } else {
+            responses.into_iter().next().unwrap_or_default()
+        };
+        HttpResponse::json(200, &body)
+    } else if request.accepts("text/event-stream") {
+        let body: String = responses.iter().map(sse_event).collect();
+        HttpResponse::new(200)
+            .with_header("Content-Type", "text/event-stream")
+            .with_header("Cache-Control", "no-cache")
+            .with_body(body)
+    } else {
+        return HttpResponse::error(
+            406,
+            "Client must accept application/json or text/event-stream",
+        );
+    };
+
+    response.with_header("Mcp-Session-Id", &session_id)
+}
+
+/// Handle a `GET`: validate the session before opening an event stream
+fn handle_get(state: &HttpState, request: &HttpRequest) -> Reply {
+    if !request.accepts("text/event-stream") {
+        return Reply::Response(HttpResponse::error(
+            406,
+            "Client must accept text/event-stream",
+        ));
+    }
+    match request.header(SESSION_HEADER) {
+        Some(id) if state.has_session(id) => Reply::EventStream(id.to_string()),
+        Some(_) => Reply::Response(HttpResponse::error(404, "Unknown session")),
+        None => Reply::Response(HttpResponse::error(400, "Missing Mcp-Session-Id header")),
+    }
+}
+
+/// Handle a `DELETE`: terminate the session
+fn handle_delete(state: &HttpState, request: &HttpRequest) -> HttpResponse {
+    let Some(id) = request.header(SESSION_HEADER) else {
+        return HttpResponse::error(400, "Missing Mcp-Session-Id header");
+    };
+    let removed = state.sessions().remove(id).is_some();
+
+    if removed {
+        HttpResponse::new(200)
+    } else {
+        HttpResponse::error(404, "Unknown session")
+    }
+}
+
+/// Keep an SSE stream open for server-initiated messages.
+///
+/// The server does not currently emit unsolicited messages, so the stream only
+/// carries keep-alive comments until the client disconnects or the session ends.
+fn stream_events(out: &mut impl Write, state: &HttpState, session_id: &str) -> Result<()> {
+    write!(
+        out,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nMcp-Session-Id: {}\r\nConnection: close\r\n\r\n",
+        session_id
+    )?;
+    out.flush()?;
+
+    // An open stream keeps its session alive
+    while state.touch(session_id) {
+        write!(out, ": keep-alive\n\n")?;
+        out.flush()?;
+        std::thread::sleep(KEEP_ALIVE_INTERVAL);
+    }
+
+    Ok(())
+}
+
+/// Format a JSON-RPC message as an SSE `message` event
+fn sse_event(message: &Value) -> String {
+    format!("event: message\ndata: {}\n\n", message)
+}
+
+/// Generate an unguessable session id (128 bits, hex-encoded)
+fn new_session_id() -> String {
+    use std::hash::{BuildHasher, RandomState};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+
+    let high = RandomState::new().hash_one((n, nanos));
+    let low = RandomState::new().hash_one((nanos, std::process::id(), n));
+    format!("{:016x}{:016x}", high, low)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INIT: &str = r#"{"jsonrpc":"2.0","method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{}},"id":1}"#;
+    const PING: &str = r#"{"jsonrpc":"2.0","method":"ping","id":2}"#;
+
+    fn request(method: &str, headers: &[(&str, &str)], body: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: MCP_PATH.to_string(),
+            headers: headers
+                .iter()
+                .map(|(n, v)| (n.to_ascii_lowercase(), v.to_string()))
+                .collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn response(reply: Reply) -> HttpResponse {
+        match reply {
+            Reply::Response(r) => r,
+            Reply::EventStream(id) => panic!("Expected a response, got event stream for {}", id),
+        }
+    }
+
+    fn initialize(state: &HttpState) -> String {
+        let resp = response(route(state, &request("POST", &[], INIT)));
+        assert_eq!(resp.status, 200);
+        resp.header("Mcp-Session-Id").unwrap().to_string()
+    }
+
+    #[test]
+    fn test_parse_addr_bare_port_is_loopback() {
+        let addr = parse_addr("3939").unwrap();
+        assert!(addr.ip().is_loopback());
+        assert_eq!(addr.port(), 3939);
+    }
+
+    #[test]
+    fn test_parse_addr_localhost_and_explicit() {
+        assert!(parse_addr("localhost:8080").unwrap().ip().is_loopback());
+        assert_eq!(
+            parse_addr("0.0.0.0:9000").unwrap().to_string(),
+            "0.0.0.0:9000"
+        );
+        assert!(parse_addr("not an address").is_err());
+    }
+
+    #[test]
+    fn test_serve_refuses_public_bind_without_token() {
+        let err = serve("0.0.0.0:0", None).unwrap_err();
+        assert!(err.to_string().contains("--token"));
+    }
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /mcp?x=1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 2\r\nMcp-Session-Id: abc\r\n\r\n{}";
+        let req = read_request(&mut BufReader::new(raw.as_bytes())).unwrap();
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/mcp");
+        assert_eq!(req.header(SESSION_HEADER), Some("abc"));
+        assert_eq!(req.body, b"{}");
+    }
+
+    #[test]
+    fn test_read_request_rejects_oversized_body() {
+        let raw = format!(
+            "POST /mcp HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_BYTES + 1
+        );
+        assert!(read_request(&mut BufReader::new(raw.as_bytes())).is_err());
+    }
+
+    #[test]
+    fn test_initialize_creates_session() {
+        let state = HttpState::new(None, true);
+        let id = initialize(&state);
+        assert_eq!(id.len(), 32);
+        assert!(state.has_session(&id));
+    }
+
+    #[test]
+    fn test_post_without_session_rejected() {
+        let state = HttpState::new(None, true);
+        let body = r#"{"jsonrpc":"2.0","method":"tools/list","id":2}"#;
+        let resp = response(route(&state, &request("POST", &[], body)));
+        assert_eq!(resp.status, 400);
+
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Mcp-Session-Id", "nope")], body),
+        ));
+        assert_eq!(resp.status, 404);
+    }
+
+    #[test]
+    fn test_post_with_session_returns_json() {
+        let state = HttpState::new(None, true);
+        let id = initialize(&state);
+        let body = r#"{"jsonrpc":"2.0","method":"tools/list","id":2}"#;
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Mcp-Session-Id", &id)], body),
+        ));
+        assert_eq!(resp.status, 200);
+        let value: Value = serde_json::from_str(&resp.body).unwrap();
+        assert_eq!(value["id"], 2);
+        assert!(value["result"]["tools"].is_array());
+    }
+
+    #[test]
+    fn test_post_notification_returns_accepted() {
+        let state = HttpState::new(None, true);
+        let id = initialize(&state);
+        let body = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Mcp-Session-Id", &id)], body),
+        ));
+        assert_eq!(resp.status, 202);
+        assert!(resp.body.is_empty());
+    }
+
+    #[test]
+    fn test_post_batch_returns_array() {
+        let state = HttpState::new(None, true);
+        let id = initialize(&state);
+        let body = r#"[{"jsonrpc":"2.0","method":"ping","id":2},{"jsonrpc":"2.0","method":"ping","id":3}]"#;
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Mcp-Session-Id", &id)], body),
+        ));
+        let value: Value = serde_json::from_str(&resp.body).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_post_event_stream_only_client() {
+        let state = HttpState::new(None, true);
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Accept", "text/event-stream")], INIT),
+        ));
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.header("Content-Type"), Some("text/event-stream"));
+        assert!(resp.body.starts_with("event: message\ndata: {"));
+    }
+
+    #[test]
+    fn test_post_invalid_json() {
+        let state = HttpState::new(None, true);
+        let resp = response(route(&state, &request("POST", &[], "not json")));
+        assert_eq!(resp.status, 400);
+        assert!(resp.body.contains("-32700"));
+    }
+
+    #[test]
+    fn test_sessions_are_isolated() {
+        let state = HttpState::new(None, true);
+        let first = initialize(&state);
+        let second = initialize(&state);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_idle_session_expires() {
+        let state = HttpState::new(None, true);
+        let id = initialize(&state);
+        let Some(long_ago) =
+            Instant::now().checked_sub(SESSION_IDLE_TIMEOUT + Duration::from_secs(1))
+        else {
+            return; // Monotonic clock too close to its origin to go back
+        };
+        state.sessions().get_mut(&id).unwrap().last_seen = long_ago;
+
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Mcp-Session-Id", &id)], PING),
+        ));
+        assert_eq!(resp.status, 404);
+        assert!(state.sessions().is_empty());
+    }
+
+    #[test]
+    fn test_session_count_is_bounded() {
+        let state = HttpState::new(None, true);
+        let first = initialize(&state);
+        for _ in 0..MAX_SESSIONS {
+            initialize(&state);
+        }
+        assert_eq!(state.sessions().len(), MAX_SESSIONS);
+        // The least recently used session made room
+        assert!(!state.has_session(&first));
+    }
+
+    #[test]
+    fn test_poisoned_session_lock_is_recovered() {
+        let state = HttpState::new(None, true);
+        let id = initialize(&state);
+        std::thread::scope(|scope| {
+            let _ = scope
+                .spawn(|| {
+                    let _guard = state.sessions.lock().unwrap();
+                    panic!("tool handler panicked");
+                })
+                .join();
+        });
+        assert!(state.sessions.is_poisoned());
+
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Mcp-Session-Id", &id)], PING),
+        ));
+        assert_eq!(resp.status, 200);
+    }
+
+    #[test]
+    fn test_bearer_token_required() {
+        let state = HttpState::new(Some("secret".to_string()), true);
+        let resp = response(route(&state, &request("POST", &[], INIT)));
+        assert_eq!(resp.status, 401);
+        assert_eq!(resp.header("WWW-Authenticate"), Some("Bearer"));
+
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Authorization", "Bearer wrong")], INIT),
+        ));
+        assert_eq!(resp.status, 401);
+
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Authorization", "Bearer secret")], INIT),
+        ));
+        assert_eq!(resp.status, 200);
+    }
+
+    #[test]
+    fn test_foreign_origin_rejected_on_loopback() {
+        let state = HttpState::new(None, true);
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Origin", "https://evil.example")], INIT),
+        ));
+        assert_eq!(resp.status, 403);
+
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Origin", "http://localhost:5173")], INIT),
+        ));
+        assert_eq!(resp.status, 200);
+    }
+
+    #[test]
+    fn test_ipv6_loopback_origin_accepted_with_and_without_port() {
+        let state = HttpState::new(None, true);
+        for origin in ["http://[::1]", "http://[::1]:3000"] {
+            let resp = response(route(&state, &request("POST", &[("Origin", origin)], INIT)));
+            assert_eq!(resp.status, 200, "{} should be allowed", origin);
+        }
+
+        let resp = response(route(
+            &state,
+            &request("POST", &[("Origin", "http://[::2]:3000")], INIT),
+        ));
+        assert_eq!(resp.status, 403);
+    }
+
+    #[test]
+    fn test_get_opens_event_stream_for_known_session() {
+        let state = HttpState::new(None, true);
+        let id = initialize(&state);
+        let reply = route(
+            &state,
+            &request(
+                "GET",
+                &[("Accept", "text/event-stream"), ("Mcp-Session-Id", &id)],
+                "",
+            ),
+        );
+        assert!(matches!(reply, Reply::EventStream(ref s) if *s == id));
+
+        let resp = response(route(
+            &state,
+            &request(
+                "GET",
+                &[("Accept", "application/json"), ("Mcp-Session-Id", &id)],
+                "",
+            ),
+        ));
+        assert_eq!(resp.status, 406);
+    }
+
+    #[test]
+    fn test_delete_terminates_session() {
+        let state = HttpState::new(None, true);
+        let id = initialize(&state);
+        let resp = response(route(
+            &state,
+            &request("DELETE", &[("Mcp-Session-Id", &id)], ""),
+        ));
+        assert_eq!(resp.status, 200);
+        assert!(!state.has_session(&id));
+
+        let resp = response(route(
+            &state,
+            &request("DELETE", &[("Mcp-Session-Id", &id)], ""),
+        ));
+        assert_eq!(resp.status, 404);
+    }
+
+    #[test]
+    fn test_unknown_path_and_method() {
+        let state = HttpState::new(None, true);
+        let mut req = request("POST", &[], INIT);
+        req.path = "/other".to_string();
+        assert_eq!(response(route(&state, &req)).status, 404);
+
+        let resp = response(route(&state, &request("PUT", &[], "")));
+        assert_eq!(resp.status, 405);
+        assert_eq!(resp.header("Allow"), Some("GET, POST, DELETE"));
+    }
+
+    #[test]
+    fn test_response_write_to() {
+        let mut buf = Vec::new();
+        HttpResponse::json(200, &json!({"ok": true}))
+            .write_to(&mut buf)
+            .unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(text.contains("Content-Length: 11\r\n"));
+        assert!(text.ends_with("{\"ok\":true}"));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod handlers;
+pub mod http;
 pub mod protocol;
 pub mod server;
 pub mod tools;
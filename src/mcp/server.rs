@@ -1,7 +1,8 @@
 //! MCP Server implementation
 //!
 //! Implements the MCP server that communicates over stdio using JSON-RPC 2.0.
-//! Synchronous implementation — no tokio required.
+//! Synchronous implementation — no tokio required. The HTTP transport
+//! (`mcp::http`) reuses `handle_message` with one server per session.
 
 use super::handlers::ToolHandler;
 use super::protocol::*;
@@ -59,7 +60,7 @@ impl McpServer {
     }
 
     /// Handle a single JSON-RPC message
-    fn handle_message(&mut self, message: &str) -> Option<JsonRpcResponse> {
+    pub fn handle_message(&mut self, message: &str) -> Option<JsonRpcResponse> {
         // Parse the request
         let request: JsonRpcRequest = match serde_json::from_str(message) {
             Ok(r) => r,
@@ -280,7 +281,7 @@ mod tests {
         assert!(resp.result.is_some());
         let result = resp.result.unwrap();
         let tools = result["tools"].as_array().unwrap();
-        assert_eq!(tools.len(), 11);
+        assert_eq!(tools.len(), 12);
     }
 
     #[test]
@@ -17,6 +17,7 @@ pub fn all_tools() -> Vec<ToolDefinition> {
         start_dev_tool(),
         add_target_tool(),
         generate_client_tool(),
+        undo_tool(),
     ]
 }
 
@@ -55,10 +56,18 @@ fn init_project_tool() -> ToolDefinition {
                     "type": "boolean",
                     "description": "If true, enable gRPC support in the generated project (adds grpc feature to this-rs dependency and GrpcExposure in main.rs) (default: false)"
                 },
+                "graphql": {
+                    "type": "boolean",
+                    "description": "If true, enable GraphQL support in the generated project (adds graphql feature to this-rs dependency and GraphQLExposure in main.rs) (default: false)"
+                },
                 "events": {
                     "type": "boolean",
                     "description": "If true, enable event system (EventBus, NotificationStore, SSE streaming, event flows). Generates config/events.yaml and adds .with_default_event_bus() / .with_default_notification_store() to main.rs (default: false)"
                 },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, do not write anything: return the full proposed content and a unified diff for every file that would be created or modified (default: false)"
+                },
                 "cwd": {
                     "type": "string",
                     "description": "Working directory for the command. If provided, the command runs as if invoked from this directory."
@@ -92,6 +101,10 @@ fn add_entity_tool() -> ToolDefinition {
                     "type": "string",
                     "description": "Fields to index, comma-separated (default: 'name')"
                 },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, do not write anything: return the full proposed content and a unified diff for every file that would be created or modified (default: false)"
+                },
                 "cwd": {
                     "type": "string",
                     "description": "Working directory (must be inside a this-rs project root)"
@@ -137,6 +150,10 @@ fn add_link_tool() -> ToolDefinition {
                     "type": "boolean",
                     "description": "If true, do not add a validation rule for this link (default: false)"
                 },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, do not write anything: return the full proposed content and a unified diff for every file that would be created or modified (default: false)"
+                },
                 "cwd": {
                     "type": "string",
                     "description": "Working directory (must be inside a this-rs project root)"
@@ -166,6 +183,10 @@ fn add_event_flow_tool() -> ToolDefinition {
                     "type": "string",
                     "description": "Delivery sink name to use in the final deliver step. Must exist in events.yaml. Default: 'in-app'"
                 },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, do not write anything: return the full proposed content and a unified diff for every file that would be created or modified (default: false)"
+                },
                 "cwd": {
                     "type": "string",
                     "description": "Working directory (must be inside a this-rs project with config/events.yaml)"
@@ -195,6 +216,10 @@ fn add_sink_tool() -> ToolDefinition {
                     "type": "string",
                     "description": "URL for webhook sinks (required when sink_type is 'webhook')"
                 },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, do not write anything: return the full proposed content and a unified diff for every file that would be created or modified (default: false)"
+                },
                 "cwd": {
                     "type": "string",
                     "description": "Working directory (must be inside a this-rs project with config/events.yaml)"
@@ -225,10 +250,18 @@ fn get_project_info_tool() -> ToolDefinition {
 fn check_project_health_tool() -> ToolDefinition {
     ToolDefinition {
         name: "check_project_health".to_string(),
-        description: "Run diagnostics on the this-rs project: check Cargo.toml validity, entity file presence, module registration, store implementations, and links.yaml consistency. Returns structured results with pass/warn/error levels.".to_string(),
+        description: "Run diagnostics on the this-rs project: check Cargo.toml validity, entity file presence, module registration, store implementations, and links.yaml consistency. Returns structured results with pass/warn/error levels. With fix=true, also applies the automatic fixes listed in each diagnostic's 'fix' field.".to_string(),
         input_schema: InputSchema {
             schema_type: "object".to_string(),
             properties: Some(json!({
+                "fix": {
+                    "type": "boolean",
+                    "description": "Apply automatic fixes: restore deleted markers, register orphaned entities, remove dangling links and validation rules (default: false)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "With fix=true, do not write anything: return the proposed content and a unified diff for every file the fixes would change (default: false)"
+                },
                 "cwd": {
                     "type": "string",
                     "description": "Working directory (must be inside a this-rs project root)"
@@ -270,6 +303,10 @@ fn build_project_tool() -> ToolDefinition {
                     "type": "string",
                     "description": "Build a specific native target: 'desktop', 'ios', 'android', or 'all'"
                 },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, do not write anything: return the proposed Dockerfile as a unified diff. Only supported with docker: true (default: false)"
+                },
                 "cwd": {
                     "type": "string",
                     "description": "Working directory (must be inside a this-rs workspace)"
@@ -295,6 +332,10 @@ fn start_dev_tool() -> ToolDefinition {
                     "type": "boolean",
                     "description": "Disable auto-detection of cargo-watch, force plain cargo run"
                 },
+                "no_client": {
+                    "type": "boolean",
+                    "description": "Do not keep the webapp's API client in sync with entities and links"
+                },
                 "port": {
                     "type": "integer",
                     "description": "Override the API port from this.yaml"
@@ -329,6 +370,10 @@ fn add_target_tool() -> ToolDefinition {
                     "type": "string",
                     "description": "Custom name for the target directory (default: auto-generated from type, e.g. 'front' for webapp)"
                 },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, do not write anything: return the full proposed content and a unified diff for every file that would be created or modified (default: false)"
+                },
                 "cwd": {
                     "type": "string",
                     "description": "Working directory (must be inside a this-rs workspace)"
@@ -355,6 +400,44 @@ fn generate_client_tool() -> ToolDefinition {
                     "type": "string",
                     "description": "Output file path. Default: auto-detected from this.yaml webapp target (e.g. front/src/api-client.ts)"
                 },
+                "with": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": ["zod", "react-query"] },
+                    "description": "Extras to generate: zod schemas validating responses, TanStack Query hooks with cache invalidation (default: none)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, do not write anything: return the full proposed content and a unified diff for every file that would be created or modified (default: false)"
+                },
+                "cwd": {
+                    "type": "string",
+                    "description": "Working directory (must be inside a this-rs project)"
+                }
+            })),
+            required: None,
+        },
+    }
+}
+
+fn undo_tool() -> ToolDefinition {
+    ToolDefinition {
+        name: "undo".to_string(),
+        description: "Revert the most recent generator command (CLI or MCP) by restoring every file it created or modified from the journal in .this/history/. Refuses if those files were edited since, unless force is set.".to_string(),
+        input_schema: InputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(json!({
+                "list": {
+                    "type": "boolean",
+                    "description": "If true, return the recorded command history instead of undoing (default: false)"
+                },
+                "force": {
+                    "type": "boolean",
+                    "description": "If true, restore files even if they were edited after the command ran (default: false)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "If true, report which files would be restored or removed without changing anything (default: false)"
+                },
                 "cwd": {
                     "type": "string",
                     "description": "Working directory (must be inside a this-rs project)"
@@ -371,7 +454,7 @@ mod tests {
 
     #[test]
     fn test_all_tools_count() {
-        assert_eq!(all_tools().len(), 11);
+        assert_eq!(all_tools().len(), 12);
     }
 
     #[test]
@@ -410,6 +493,31 @@ mod tests {
         assert_eq!(required, vec!["source", "target"]);
     }
 
+    #[test]
+    fn test_mutating_tools_have_dry_run_param() {
+        let mutating = [
+            "init_project",
+            "add_entity",
+            "add_link",
+            "add_event_flow",
+            "add_sink",
+            "build_project",
+            "add_target",
+            "generate_client",
+            "undo",
+            "check_project_health",
+        ];
+        for tool in all_tools() {
+            let props = tool.input_schema.properties.as_ref().unwrap();
+            assert_eq!(
+                props.get("dry_run").is_some(),
+                mutating.contains(&tool.name.as_str()),
+                "Tool {} has unexpected dry_run parameter presence",
+                tool.name
+            );
+        }
+    }
+
     #[test]
     fn test_all_tools_have_cwd_param() {
         for tool in all_tools() {
@@ -1,9 +1,16 @@
+pub mod packs;
+
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use tera::Tera;
 
-use crate::utils::naming;
+use crate::config;
+use crate::utils::{naming, project};
+
+/// this-rs version new projects depend on (and `this upgrade` targets by default)
+pub const THIS_RS_VERSION: &str = "0.0.9";
 
 // ============================================================================
 // Project Templates
@@ -74,64 +81,123 @@ const TPL_ENTITY_HANDLERS_RS: &str = include_str!("entity/handlers.rs.tera");
 const TPL_ENTITY_DESCRIPTOR_RS: &str = include_str!("entity/descriptor.rs.tera");
 const TPL_ENTITY_MOD_RS: &str = include_str!("entity/mod.rs.tera");
 
+/// Every built-in template, by name
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("project/Cargo.toml", TPL_PROJECT_CARGO_TOML),
+    ("project/main.rs", TPL_PROJECT_MAIN_RS),
+    ("project/module.rs", TPL_PROJECT_MODULE_RS),
+    ("project/entities_mod.rs", TPL_PROJECT_ENTITIES_MOD_RS),
+    ("project/stores.rs", TPL_PROJECT_STORES_RS),
+    ("project/links.yaml", TPL_PROJECT_LINKS_YAML),
+    ("project/events.yaml", TPL_PROJECT_EVENTS_YAML),
+    ("project/auth.yaml", TPL_PROJECT_AUTH_YAML),
+    (
+        "project/embedded_frontend.rs",
+        TPL_PROJECT_EMBEDDED_FRONTEND_RS,
+    ),
+    ("workspace/this.yaml", TPL_WORKSPACE_THIS_YAML),
+    ("workspace/Dockerfile", TPL_WORKSPACE_DOCKERFILE),
+    ("webapp/package.json", TPL_WEBAPP_PACKAGE_JSON),
+    ("webapp/vite.config.ts", TPL_WEBAPP_VITE_CONFIG_TS),
+    ("webapp/tsconfig.json", TPL_WEBAPP_TSCONFIG_JSON),
+    ("webapp/index.html", TPL_WEBAPP_INDEX_HTML),
+    ("webapp/main.tsx", TPL_WEBAPP_MAIN_TSX),
+    ("webapp/App.tsx", TPL_WEBAPP_APP_TSX),
+    ("webapp/App.css", TPL_WEBAPP_APP_CSS),
+    ("desktop/tauri-cargo.toml", TPL_DESKTOP_TAURI_CARGO_TOML),
+    ("desktop/tauri.conf.json", TPL_DESKTOP_TAURI_CONF_JSON),
+    ("desktop/tauri-main.rs", TPL_DESKTOP_TAURI_MAIN_RS),
+    ("desktop/tauri-build.rs", TPL_DESKTOP_TAURI_BUILD_RS),
+    ("desktop/capabilities.json", TPL_DESKTOP_CAPABILITIES_JSON),
+    (
+        "mobile/capacitor-package.json",
+        TPL_MOBILE_CAPACITOR_PACKAGE_JSON,
+    ),
+    ("mobile/capacitor.config.ts", TPL_MOBILE_CAPACITOR_CONFIG_TS),
+    ("mobile/capacitor-gitignore", TPL_MOBILE_CAPACITOR_GITIGNORE),
+    ("entity/model.rs", TPL_ENTITY_MODEL_RS),
+    ("entity/model_validated.rs", TPL_ENTITY_MODEL_VALIDATED_RS),
+    ("entity/store.rs", TPL_ENTITY_STORE_RS),
+    ("entity/postgres_store.rs", TPL_ENTITY_POSTGRES_STORE_RS),
+    ("entity/mongodb_store.rs", TPL_ENTITY_MONGODB_STORE_RS),
+    ("entity/neo4j_store.rs", TPL_ENTITY_NEO4J_STORE_RS),
+    ("entity/scylladb_store.rs", TPL_ENTITY_SCYLLADB_STORE_RS),
+    ("entity/mysql_store.rs", TPL_ENTITY_MYSQL_STORE_RS),
+    ("entity/lmdb_store.rs", TPL_ENTITY_LMDB_STORE_RS),
+    ("entity/migration.sql", TPL_ENTITY_MIGRATION_SQL),
+    ("entity/handlers.rs", TPL_ENTITY_HANDLERS_RS),
+    ("entity/descriptor.rs", TPL_ENTITY_DESCRIPTOR_RS),
+    ("entity/mod.rs", TPL_ENTITY_MOD_RS),
+];
+
+/// Directory, relative to a project or workspace root, whose `<name>.tera`
+/// files override the built-in template `<name>`
+pub const OVERRIDES_DIR: &str = ".this/templates";
+
+/// Content of a built-in template
+pub fn builtin_template(name: &str) -> Option<&'static str> {
+    BUILTIN_TEMPLATES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, content)| *content)
+}
+
+/// Template override directories that apply to `root`, highest priority first:
+/// the root's own `.this/templates`, then the `templates:` directory of the
+/// enclosing workspace's `this.yaml`, then the workspace's `.this/templates`.
+pub fn override_dirs(root: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![root.join(OVERRIDES_DIR)];
+    if let Some(ws_root) = project::find_workspace_root_from(root) {
+        if let Ok(config) = config::load_workspace_config(&ws_root.join("this.yaml"))
+            && let Some(templates) = config.templates
+        {
+            dirs.push(ws_root.join(templates));
+        }
+        dirs.push(ws_root.join(OVERRIDES_DIR));
+    }
+    let mut unique: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        if dir.is_dir() && !unique.contains(&dir) {
+            unique.push(dir);
+        }
+    }
+    unique
+}
+
+/// `*.tera` files of an override directory, as (template name, path)
+pub fn override_files(dir: &Path) -> Vec<(String, PathBuf)> {
+    fn walk(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                walk(&path, &format!("{}{}/", prefix, file_name), files);
+            } else if let Some(stem) = file_name.strip_suffix(".tera") {
+                files.push((format!("{}{}", prefix, stem), path));
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    walk(dir, "", &mut files);
+    files.sort();
+    files
+}
+
 pub struct TemplateEngine {
     tera: Tera,
 }
 
 impl TemplateEngine {
+    /// Engine with the built-in templates only
     pub fn new() -> Result<Self> {
         let mut tera = Tera::default();
 
         // Register all templates
-        let templates: HashMap<&str, &str> = HashMap::from([
-            ("project/Cargo.toml", TPL_PROJECT_CARGO_TOML),
-            ("project/main.rs", TPL_PROJECT_MAIN_RS),
-            ("project/module.rs", TPL_PROJECT_MODULE_RS),
-            ("project/entities_mod.rs", TPL_PROJECT_ENTITIES_MOD_RS),
-            ("project/stores.rs", TPL_PROJECT_STORES_RS),
-            ("project/links.yaml", TPL_PROJECT_LINKS_YAML),
-            ("project/events.yaml", TPL_PROJECT_EVENTS_YAML),
-            ("project/auth.yaml", TPL_PROJECT_AUTH_YAML),
-            (
-                "project/embedded_frontend.rs",
-                TPL_PROJECT_EMBEDDED_FRONTEND_RS,
-            ),
-            ("workspace/this.yaml", TPL_WORKSPACE_THIS_YAML),
-            ("workspace/Dockerfile", TPL_WORKSPACE_DOCKERFILE),
-            ("webapp/package.json", TPL_WEBAPP_PACKAGE_JSON),
-            ("webapp/vite.config.ts", TPL_WEBAPP_VITE_CONFIG_TS),
-            ("webapp/tsconfig.json", TPL_WEBAPP_TSCONFIG_JSON),
-            ("webapp/index.html", TPL_WEBAPP_INDEX_HTML),
-            ("webapp/main.tsx", TPL_WEBAPP_MAIN_TSX),
-            ("webapp/App.tsx", TPL_WEBAPP_APP_TSX),
-            ("webapp/App.css", TPL_WEBAPP_APP_CSS),
-            ("desktop/tauri-cargo.toml", TPL_DESKTOP_TAURI_CARGO_TOML),
-            ("desktop/tauri.conf.json", TPL_DESKTOP_TAURI_CONF_JSON),
-            ("desktop/tauri-main.rs", TPL_DESKTOP_TAURI_MAIN_RS),
-            ("desktop/tauri-build.rs", TPL_DESKTOP_TAURI_BUILD_RS),
-            ("desktop/capabilities.json", TPL_DESKTOP_CAPABILITIES_JSON),
-            (
-                "mobile/capacitor-package.json",
-                TPL_MOBILE_CAPACITOR_PACKAGE_JSON,
-            ),
-            ("mobile/capacitor.config.ts", TPL_MOBILE_CAPACITOR_CONFIG_TS),
-            ("mobile/capacitor-gitignore", TPL_MOBILE_CAPACITOR_GITIGNORE),
-            ("entity/model.rs", TPL_ENTITY_MODEL_RS),
-            ("entity/model_validated.rs", TPL_ENTITY_MODEL_VALIDATED_RS),
-            ("entity/store.rs", TPL_ENTITY_STORE_RS),
-            ("entity/postgres_store.rs", TPL_ENTITY_POSTGRES_STORE_RS),
-            ("entity/mongodb_store.rs", TPL_ENTITY_MONGODB_STORE_RS),
-            ("entity/neo4j_store.rs", TPL_ENTITY_NEO4J_STORE_RS),
-            ("entity/scylladb_store.rs", TPL_ENTITY_SCYLLADB_STORE_RS),
-            ("entity/mysql_store.rs", TPL_ENTITY_MYSQL_STORE_RS),
-            ("entity/lmdb_store.rs", TPL_ENTITY_LMDB_STORE_RS),
-            ("entity/migration.sql", TPL_ENTITY_MIGRATION_SQL),
-            ("entity/handlers.rs", TPL_ENTITY_HANDLERS_RS),
-            ("entity/descriptor.rs", TPL_ENTITY_DESCRIPTOR_RS),
-            ("entity/mod.rs", TPL_ENTITY_MOD_RS),
-        ]);
-
-        for (name, content) in &templates {
+        for (name, content) in BUILTIN_TEMPLATES {
             tera.add_raw_template(name, content)
                 .with_context(|| format!("Failed to register template: {}", name))?;
         }
@@ -144,11 +210,36 @@ impl TemplateEngine {
         Ok(Self { tera })
     }
 
+    /// Engine with the built-in templates, overridden by the files found in
+    /// the `override_dirs` of a project or workspace root
+    pub fn for_project(root: &Path) -> Result<Self> {
+        let mut engine = Self::new()?;
+        // Lowest priority first, so that higher ones replace it
+        for dir in override_dirs(root).iter().rev() {
+            for (name, path) in override_files(dir) {
+                let content = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read: {}", path.display()))?;
+                engine
+                    .tera
+                    .add_raw_template(&name, &content)
+                    .with_context(|| format!("Invalid template override: {}", path.display()))?;
+            }
+        }
+        Ok(engine)
+    }
+
     pub fn render(&self, template_name: &str, context: &tera::Context) -> Result<String> {
         self.tera
             .render(template_name, context)
             .with_context(|| format!("Failed to render template: {}", template_name))
     }
+
+    /// Render an inline template string, e.g. a path from a pack manifest
+    pub fn render_str(&mut self, source: &str, context: &tera::Context) -> Result<String> {
+        self.tera
+            .render_str(source, context)
+            .with_context(|| format!("Failed to render: {}", source))
+    }
 }
 
 // ============================================================================
@@ -195,6 +286,7 @@ mod tests {
         ctx.insert("project_name_snake", "test_project");
         ctx.insert("port", &3000u16);
         ctx.insert("features_list", "");
+        ctx.insert("this_rs_version", THIS_RS_VERSION);
         ctx
     }
 
@@ -253,6 +345,10 @@ mod tests {
         let content = result.unwrap();
         assert!(content.contains("name = \"test-project\""));
         assert!(content.contains("this = "));
+        assert!(content.contains(&format!(
+            "this = {{ package = \"this-rs\", version = \"{}\"",
+            THIS_RS_VERSION
+        )));
         // No unresolved placeholders
         assert!(!content.contains("{{"), "No unresolved Tera placeholders");
     }
@@ -1199,4 +1295,80 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_for_project_without_overrides_matches_builtins() {
+        let tmp = tempfile::tempdir().unwrap();
+        let engine = TemplateEngine::for_project(tmp.path()).unwrap();
+        let ctx = make_project_context();
+        assert_eq!(
+            engine.render("project/links.yaml", &ctx).unwrap(),
+            TemplateEngine::new()
+                .unwrap()
+                .render("project/links.yaml", &ctx)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_for_project_applies_overrides_by_priority() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ws = tmp.path();
+        std::fs::write(
+            ws.join("this.yaml"),
+            "name: shop\napi:\n  path: api\n  port: 3000\ntemplates: conventions\n",
+        )
+        .unwrap();
+        let write = |path: &str, content: &str| {
+            let path = ws.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(".this/templates/entity/mod.rs.tera", "// workspace\n");
+        write(".this/templates/entity/store.rs.tera", "// workspace\n");
+        write("conventions/entity/mod.rs.tera", "// configured\n");
+        write("conventions/entity/store.rs.tera", "// configured\n");
+        write(
+            "api/.this/templates/entity/mod.rs.tera",
+            "// {{ entity_name }}\n",
+        );
+        write("api/.this/templates/partials/header.tera", "// custom\n");
+
+        let engine = TemplateEngine::for_project(&ws.join("api")).unwrap();
+        let ctx = make_entity_context();
+        assert_eq!(
+            engine.render("entity/mod.rs", &ctx).unwrap(),
+            format!("// {}\n", ctx.get("entity_name").unwrap().as_str().unwrap())
+        );
+        assert_eq!(
+            engine.render("entity/store.rs", &ctx).unwrap(),
+            "// configured\n"
+        );
+        assert_eq!(
+            engine.render("partials/header", &ctx).unwrap(),
+            "// custom\n"
+        );
+        assert_eq!(
+            engine.render("entity/model.rs", &ctx).unwrap(),
+            TemplateEngine::new()
+                .unwrap()
+                .render("entity/model.rs", &ctx)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_for_project_reports_invalid_override() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join(".this/templates/entity");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("model.rs.tera"), "{% if %}").unwrap();
+
+        let err = TemplateEngine::for_project(tmp.path())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("Invalid template override"));
+        assert!(err.contains("model.rs.tera"));
+    }
 }
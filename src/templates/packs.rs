@@ -0,0 +1,609 @@
+//! Template packs: organisation-specific scaffolding rendered by generators.
+//!
+//! A pack is a `packs/<name>/` directory, in any template override directory,
+//! holding a `pack.yaml` manifest and the `.tera` templates it refers to.
+//! The manifest declares, per generator hook, extra files to render and
+//! lines to insert after `// [this:...]` markers of existing files:
+//!
+//! ```yaml
+//! description: Domain events for every entity
+//! entity:
+//!   files:
+//!     - template: events.rs.tera
+//!       path: src/entities/{{ entity_name }}/events.rs
+//!   inserts:
+//!     - file: src/main.rs
+//!       marker: "[this:events]"
+//!       line: "events::register::<{{ entity_pascal }}>();"
+//!       backends: [postgres]
+//! link:
+//!   files: []
+//! ```
+//!
+//! Pack templates are ordinary overridable templates named
+//! `packs/<name>/<file>`, so a project can replace one template of a pack
+//! shared at the workspace level.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::{TemplateEngine, override_dirs};
+use crate::utils::file_writer::FileWriter;
+use crate::utils::{markers, output};
+
+/// Directory of packs inside a template override directory
+pub const PACKS_DIR: &str = "packs";
+
+/// Manifest file of a pack
+pub const MANIFEST: &str = "pack.yaml";
+
+/// Generator a pack hooks into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hook {
+    /// `this add entity`
+    Entity,
+    /// `this add link`
+    Link,
+}
+
+/// Contents of `pack.yaml`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// One-line summary shown by `this templates list`
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Rendered by `this add entity`
+    #[serde(default)]
+    pub entity: Hooks,
+
+    /// Rendered by `this add link`
+    #[serde(default)]
+    pub link: Hooks,
+}
+
+/// What a pack generates for one hook
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    #[serde(default)]
+    pub files: Vec<PackFile>,
+
+    #[serde(default)]
+    pub inserts: Vec<PackInsert>,
+}
+
+/// A file rendered from a pack template
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackFile {
+    /// Template path relative to the pack directory
+    pub template: String,
+
+    /// Destination relative to the project root (a template string)
+    pub path: String,
+
+    /// Only render for these storage backends (default: all)
+    #[serde(default)]
+    pub backends: Vec<String>,
+}
+
+/// A line inserted after a marker of an existing project file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackInsert {
+    /// File relative to the project root (a template string)
+    pub file: String,
+
+    /// Marker to insert after, e.g. `[this:register_entities]`
+    pub marker: String,
+
+    /// Line to insert (a template string)
+    pub line: String,
+
+    /// Only insert for these storage backends (default: all)
+    #[serde(default)]
+    pub backends: Vec<String>,
+}
+
+/// A pack found in an override directory
+#[derive(Debug)]
+pub struct Pack {
+    pub name: String,
+    pub dir: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl Pack {
+    fn hooks(&self, hook: Hook) -> &Hooks {
+        match hook {
+            Hook::Entity => &self.manifest.entity,
+            Hook::Link => &self.manifest.link,
+        }
+    }
+}
+
+/// A pack file rendered for the current generator run
+#[derive(Debug)]
+pub struct RenderedFile {
+    pub pack: String,
+    /// Destination relative to the project root
+    pub path: String,
+    pub content: String,
+}
+
+/// Packs available to a project or workspace root, sorted by name.
+/// A pack in a higher-priority override directory hides one of the same name.
+pub fn discover(root: &Path) -> Result<Vec<Pack>> {
+    let mut packs: Vec<Pack> = Vec::new();
+    for dir in override_dirs(root) {
+        let Ok(entries) = std::fs::read_dir(dir.join(PACKS_DIR)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let pack_dir = entry.path();
+            let manifest_path = pack_dir.join(MANIFEST);
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !manifest_path.is_file() || packs.iter().any(|p| p.name == name) {
+                continue;
+            }
+            let content = std::fs::read_to_string(&manifest_path)
+                .with_context(|| format!("Failed to read: {}", manifest_path.display()))?;
+            let manifest: Manifest = serde_yaml::from_str(&content)
+                .with_context(|| format!("Invalid pack manifest: {}", manifest_path.display()))?;
+            packs.push(Pack {
+                name,
+                dir: pack_dir,
+                manifest,
+            });
+        }
+    }
+    packs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(packs)
+}
+
+/// Whether a `backends:` filter lets the current run through
+fn applies(backends: &[String], context: &tera::Context) -> bool {
+    backends.is_empty()
+        || context
+            .get("backend")
+            .and_then(|b| b.as_str())
+            .is_some_and(|backend| backends.iter().any(|b| b == backend))
+}
+
+/// Reject a rendered pack path that could escape the project root
+fn check_relative(pack: &str, path: &str) -> Result<()> {
+    let escapes = Path::new(path)
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if escapes || path.trim().is_empty() {
+        anyhow::bail!(
+            "Pack '{}': path '{}' must be relative to the project root, without '..'",
+            pack,
+            path
+        );
+    }
+    Ok(())
+}
+
+/// Render the files every pack declares for `hook`
+pub fn render_files(
+    packs: &[Pack],
+    hook: Hook,
+    engine: &mut TemplateEngine,
+    context: &tera::Context,
+) -> Result<Vec<RenderedFile>> {
+    let mut files = Vec::new();
+    for pack in packs {
+        for file in &pack.hooks(hook).files {
+            if !applies(&file.backends, context) {
+                continue;
+            }
+            let template = format!(
+                "{}/{}/{}",
+                PACKS_DIR,
+                pack.name,
+                file.template
+                    .strip_suffix(".tera")
+                    .unwrap_or(&file.template)
+            );
+            let content = engine.render(&template, context).with_context(|| {
+                format!("Pack '{}': cannot render {}", pack.name, file.template)
+            })?;
+            let path = engine
+                .render_str(&file.path, context)
+                .with_context(|| format!("Pack '{}': invalid path", pack.name))?;
+            check_relative(&pack.name, &path)?;
+            files.push(RenderedFile {
+                pack: pack.name.clone(),
+                path,
+                content,
+            });
+        }
+    }
+    Ok(files)
+}
+
+/// Write rendered pack files, then perform the insertions packs declare for
+/// `hook` on top of the content the command already wrote to each file.
+/// Existing files are never overwritten, and a line already present
+/// in its target file is not inserted again. A marker missing from its file
+/// is restored first when `this markers` knows where it belongs.
+pub fn apply(
+    project_root: &Path,
+    packs: &[Pack],
+    hook: Hook,
+    files: &[RenderedFile],
+    engine: &mut TemplateEngine,
+    context: &tera::Context,
+    writer: &dyn FileWriter,
+) -> Result<()> {
+    for file in files {
+        check_relative(&file.pack, &file.path)?;
+        let path = project_root.join(&file.path);
+        // Also skips files an earlier step of the command wrote
+        if writer.read_file(&path).is_ok() {
+            output::print_warn(&format!(
+                "{} already exists — skipping (pack '{}')",
+                file.path, file.pack
+            ));
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            writer.create_dir_all(parent)?;
+        }
+        writer.write_file(&path, &file.content)?;
+        if !writer.is_dry_run() {
+            output::print_file_created(&file.path);
+        }
+    }
+
+    // Batch insertions per file so that each file is written once
+    let mut updates: BTreeMap<String, (String, String)> = BTreeMap::new();
+    for pack in packs {
+        for insert in &pack.hooks(hook).inserts {
+            if !applies(&insert.backends, context) {
+                continue;
+            }
+            let file = engine
+                .render_str(&insert.file, context)
+                .with_context(|| format!("Pack '{}': invalid insert file", pack.name))?;
+            check_relative(&pack.name, &file)?;
+            let line = engine
+                .render_str(&insert.line, context)
+                .with_context(|| format!("Pack '{}': invalid insert line", pack.name))?;
+
+            if !updates.contains_key(&file) {
+                // Build on the edits the command already staged for this file
+                let Ok(original) = writer.read_file(&project_root.join(&file)) else {
+                    output::print_warn(&format!(
+                        "{} not found — skipping insertion (pack '{}')",
+                        file, pack.name
+                    ));
+                    continue;
+                };
+                updates.insert(file.clone(), (original.clone(), original));
+            }
+            let (_, content) = updates.get_mut(&file).unwrap();
+
+            if content.lines().any(|l| l.trim() == line.trim()) {
+                continue;
+            }
+            if !content.contains(&insert.marker) {
+                match markers::restore(content, &insert.marker) {
+                    Some(restored) => *content = restored,
+                    None => {
+                        output::print_warn(&format!(
+                            "Marker '{}' not found in {} — skipping insertion (pack '{}')",
+                            insert.marker, file, pack.name
+                        ));
+                        continue;
+                    }
+                }
+            }
+            *content = markers::insert_after_marker(content, &insert.marker, &line)?;
+        }
+    }
+
+    for (file, (original, updated)) in updates {
+        if original == updated {
+            continue;
+        }
+        writer.update_file(&project_root.join(&file), &original, &updated)?;
+        if !writer.is_dry_run() {
+            output::print_info(&format!("Updated {}", file));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_writer::{DryRunWriter, RealWriter};
+
+    const MANIFEST_YAML: &str = r#"description: Domain events
+entity:
+  files:
+    - template: events.rs.tera
+      path: "src/entities/{{ entity_name }}/events.rs"
+    - template: outbox.sql.tera
+      path: "migrations/{{ entity_plural }}_outbox.sql"
+      backends: [postgres]
+  inserts:
+    - file: src/main.rs
+      marker: "[this:events]"
+      line: "events::register::<{{ entity_pascal }}>();"
+link:
+  inserts:
+    - file: src/main.rs
+      marker: "[this:events]"
+      line: "events::link(\"{{ source }}\", \"{{ target }}\");"
+"#;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn setup(root: &Path) {
+        write(
+            root,
+            ".this/templates/packs/events/pack.yaml",
+            MANIFEST_YAML,
+        );
+        write(
+            root,
+            ".this/templates/packs/events/events.rs.tera",
+            "pub struct {{ entity_pascal }}Created;\n",
+        );
+        write(
+            root,
+            ".this/templates/packs/events/outbox.sql.tera",
+            "CREATE TABLE {{ entity_plural }}_outbox ();\n",
+        );
+        write(
+            root,
+            "src/main.rs",
+            "fn main() {\n    // [this:events]\n}\n",
+        );
+    }
+
+    fn entity_context(backend: &str) -> tera::Context {
+        let mut ctx = tera::Context::new();
+        ctx.insert("entity_name", "order");
+        ctx.insert("entity_pascal", "Order");
+        ctx.insert("entity_plural", "orders");
+        ctx.insert("backend", backend);
+        ctx
+    }
+
+    #[test]
+    fn test_discover_reads_manifests() {
+        let tmp = tempfile::tempdir().unwrap();
+        setup(tmp.path());
+        write(tmp.path(), ".this/templates/packs/not-a-pack/x.tera", "");
+
+        let packs = discover(tmp.path()).unwrap();
+        assert_eq!(packs.len(), 1);
+        assert_eq!(packs[0].name, "events");
+        assert_eq!(
+            packs[0].manifest.description.as_deref(),
+            Some("Domain events")
+        );
+        assert_eq!(packs[0].manifest.entity.files.len(), 2);
+        assert_eq!(packs[0].manifest.link.inserts.len(), 1);
+    }
+
+    #[test]
+    fn test_discover_rejects_unknown_keys() {
+        let tmp = tempfile::tempdir().unwrap();
+        write(
+            tmp.path(),
+            ".this/templates/packs/bad/pack.yaml",
+            "entity:\n  file: []\n",
+        );
+        let err = discover(tmp.path()).unwrap_err();
+        assert!(format!("{:#}", err).contains("Invalid pack manifest"));
+    }
+
+    #[test]
+    fn test_render_files_filters_by_backend() {
+        let tmp = tempfile::tempdir().unwrap();
+        setup(tmp.path());
+        let packs = discover(tmp.path()).unwrap();
+        let mut engine = TemplateEngine::for_project(tmp.path()).unwrap();
+
+        let files = render_files(
+            &packs,
+            Hook::Entity,
+            &mut engine,
+            &entity_context("in_memory"),
+        )
+        .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "src/entities/order/events.rs");
+        assert_eq!(files[0].content, "pub struct OrderCreated;\n");
+
+        let files = render_files(
+            &packs,
+            Hook::Entity,
+            &mut engine,
+            &entity_context("postgres"),
+        )
+        .unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1].path, "migrations/orders_outbox.sql");
+
+        assert!(
+            render_files(&packs, Hook::Link, &mut engine, &entity_context("postgres"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_apply_writes_files_and_inserts_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        setup(root);
+        let packs = discover(root).unwrap();
+        let mut engine = TemplateEngine::for_project(root).unwrap();
+        let ctx = entity_context("in_memory");
+
+        let files = render_files(&packs, Hook::Entity, &mut engine, &ctx).unwrap();
+        apply(
+            root,
+            &packs,
+            Hook::Entity,
+            &files,
+            &mut engine,
+            &ctx,
+            &RealWriter,
+        )
+        .unwrap();
+        assert!(root.join("src/entities/order/events.rs").exists());
+        let main = std::fs::read_to_string(root.join("src/main.rs")).unwrap();
+        assert_eq!(
+            main,
+            "fn main() {\n    // [this:events]\n    events::register::<Order>();\n}\n"
+        );
+
+        // Running again neither overwrites nor duplicates
+        std::fs::write(root.join("src/entities/order/events.rs"), "// edited\n").unwrap();
+        apply(
+            root,
+            &packs,
+            Hook::Entity,
+            &files,
+            &mut engine,
+            &ctx,
+            &RealWriter,
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/entities/order/events.rs")).unwrap(),
+            "// edited\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/main.rs")).unwrap(),
+            main
+        );
+    }
+
+    #[test]
+    fn test_apply_skips_missing_marker() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        setup(root);
+        write(root, "src/main.rs", "fn main() {}\n");
+        let packs = discover(root).unwrap();
+        let mut engine = TemplateEngine::for_project(root).unwrap();
+        let ctx = entity_context("in_memory");
+
+        apply(
+            root,
+            &packs,
+            Hook::Entity,
+            &[],
+            &mut engine,
+            &ctx,
+            &RealWriter,
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/main.rs")).unwrap(),
+            "fn main() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_skips_file_written_earlier_in_the_command() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        setup(root);
+        let packs = discover(root).unwrap();
+        let mut engine = TemplateEngine::for_project(root).unwrap();
+        let ctx = entity_context("in_memory");
+
+        let writer = DryRunWriter::new();
+        let events = root.join("src/entities/order/events.rs");
+        writer
+            .write_file(&events, "// from the generator\n")
+            .unwrap();
+
+        let files = render_files(&packs, Hook::Entity, &mut engine, &ctx).unwrap();
+        apply(
+            root,
+            &packs,
+            Hook::Entity,
+            &files,
+            &mut engine,
+            &ctx,
+            &writer,
+        )
+        .unwrap();
+        assert_eq!(
+            writer.read_file(&events).unwrap(),
+            "// from the generator\n"
+        );
+    }
+
+    #[test]
+    fn test_paths_escaping_the_project_are_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        setup(root);
+        let mut engine = TemplateEngine::for_project(root).unwrap();
+        let ctx = entity_context("in_memory");
+
+        for path in ["../outside.rs", "src/../../outside.rs", "/etc/outside.rs"] {
+            write(
+                root,
+                ".this/templates/packs/events/pack.yaml",
+                &format!(
+                    "entity:\n  files:\n    - template: events.rs.tera\n      path: {}\n",
+                    path
+                ),
+            );
+            let packs = discover(root).unwrap();
+            let err = render_files(&packs, Hook::Entity, &mut engine, &ctx).unwrap_err();
+            assert!(
+                err.to_string().contains("relative to the project root"),
+                "{}",
+                err
+            );
+
+            write(
+                root,
+                ".this/templates/packs/events/pack.yaml",
+                &format!(
+                    "entity:\n  inserts:\n    - file: {}\n      marker: x\n      line: y\n",
+                    path
+                ),
+            );
+            let packs = discover(root).unwrap();
+            let err = apply(
+                root,
+                &packs,
+                Hook::Entity,
+                &[],
+                &mut engine,
+                &ctx,
+                &RealWriter,
+            )
+            .unwrap_err();
+            assert!(
+                err.to_string().contains("relative to the project root"),
+                "{}",
+                err
+            );
+        }
+        assert!(!root.parent().unwrap().join("outside.rs").exists());
+    }
+}
@@ -0,0 +1,716 @@
+//! Structural editing of the generated `src/module.rs` and `src/stores.rs`.
+//!
+//! Registration code is located by syntax — the `*Stores` struct and its
+//! constructors, the `impl Module for ...` block and its methods — instead of
+//! by `// [this:...]` comments, so `add entity` keeps working after a marker
+//! is deleted or rustfmt reshapes the file. A marker found inside the located
+//! block still decides where new lines go; markers alone are only used when
+//! the file does not parse.
+
+use anyhow::{Result, anyhow};
+use proc_macro2::extra::DelimSpan;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Expr, FnArg, ImplItem, ImplItemFn, Item, ItemImpl, ItemStruct, MacroDelimiter, Pat, Stmt,
+};
+
+use super::markers;
+
+const STORE_FIELDS: &str = "[this:store_fields]";
+const ENTITY_TYPES: &str = "[this:entity_types]";
+const REGISTER_ENTITIES: &str = "[this:register_entities]";
+
+/// A block located by syntax, and where new lines go inside it
+struct Site {
+    /// Byte offset of the opening delimiter
+    open: usize,
+    /// Byte offset of the closing delimiter
+    close: usize,
+    /// Insert before the element starting here (e.g. a `_ =>` arm)
+    before: Option<usize>,
+    /// Insert after the element ending here (e.g. the last `let`)
+    after: Option<usize>,
+}
+
+impl Site {
+    fn block(span: &DelimSpan) -> Self {
+        Self {
+            open: span.open().byte_range().start,
+            close: span.close().byte_range().start,
+            before: None,
+            after: None,
+        }
+    }
+}
+
+/// A block of `module.rs` or `stores.rs` that generators insert code into
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Slot<'a> {
+    /// `use` declarations at the top of the file
+    Imports,
+    /// Fields of the `*Stores` struct
+    StoreFields,
+    /// Statements of a `*Stores` constructor such as `new_in_memory`
+    ConstructorVars(&'a str),
+    /// The `Self { .. }` literal a `*Stores` constructor returns
+    ConstructorFields(&'a str),
+    /// The `vec![...]` returned by `Module::entity_types`
+    EntityTypes,
+    /// Body of `Module::register_entities`
+    Registrations,
+    /// The `match` of a `Module` method such as `get_entity_fetcher`
+    MatchArms(&'a str),
+}
+
+/// Locate a slot's block, and where new lines go by default: after the last
+/// `let` of a constructor, ahead of the `_ =>` arm of a match, else last.
+fn locate(file: &syn::File, slot: Slot) -> Option<Site> {
+    match slot {
+        Slot::Imports => None,
+        Slot::StoreFields => match &stores_struct(file)?.fields {
+            syn::Fields::Named(fields) => Some(Site::block(&fields.brace_token.span)),
+            _ => None,
+        },
+        Slot::ConstructorVars(name) => {
+            let body = &constructor(file, name)?.block;
+            let mut site = Site::block(&body.brace_token.span);
+            site.after = body
+                .stmts
+                .iter()
+                .filter_map(|stmt| match stmt {
+                    Stmt::Local(local) => Some(local.span().byte_range().end),
+                    _ => None,
+                })
+                .next_back();
+            if site.after.is_none() {
+                site.before = body.stmts.first().map(|s| s.span().byte_range().start);
+            }
+            Some(site)
+        }
+        Slot::ConstructorFields(name) => match constructor(file, name)?.block.stmts.last()? {
+            Stmt::Expr(Expr::Struct(init), None) => Some(Site::block(&init.brace_token.span)),
+            _ => None,
+        },
+        Slot::EntityTypes => Some(Site::block(delim_span(&entity_types_vec(file)?.delimiter))),
+        Slot::Registrations => {
+            let body = &module_method(file, "register_entities")?.block;
+            Some(Site::block(&body.brace_token.span))
+        }
+        Slot::MatchArms(name) => {
+            let Stmt::Expr(Expr::Match(expr), _) = module_method(file, name)?.block.stmts.last()?
+            else {
+                return None;
+            };
+            let mut site = Site::block(&expr.brace_token.span);
+            site.before = expr
+                .arms
+                .iter()
+                .find(|arm| matches!(arm.pat, Pat::Wild(_)))
+                .map(|arm| arm.span().byte_range().start);
+            Some(site)
+        }
+    }
+}
+
+fn parse(content: &str) -> Option<syn::File> {
+    syn::parse_str(content).ok()
+}
+
+fn stores_struct(file: &syn::File) -> Option<&ItemStruct> {
+    file.items.iter().find_map(|item| match item {
+        Item::Struct(s)
+            if s.ident.to_string().ends_with("Stores")
+                && matches!(s.fields, syn::Fields::Named(_)) =>
+        {
+            Some(s)
+        }
+        _ => None,
+    })
+}
+
+/// Inherent `impl` blocks of the `*Stores` struct
+fn stores_impls(file: &syn::File) -> impl Iterator<Item = &ItemImpl> {
+    let stores = stores_struct(file).map(|s| s.ident.to_string());
+    file.items.iter().filter_map(move |item| match item {
+        Item::Impl(imp) if imp.trait_.is_none() && self_type(imp) == stores => Some(imp),
+        _ => None,
+    })
+}
+
+fn module_impl(file: &syn::File) -> Option<&ItemImpl> {
+    file.items.iter().find_map(|item| match item {
+        Item::Impl(imp)
+            if imp
+                .trait_
+                .as_ref()
+                .and_then(|(_, path, _)| path.segments.last())
+                .is_some_and(|segment| segment.ident == "Module") =>
+        {
+            Some(imp)
+        }
+        _ => None,
+    })
+}
+
+fn self_type(imp: &ItemImpl) -> Option<String> {
+    match &*imp.self_ty {
+        syn::Type::Path(ty) => ty.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn method<'a>(imp: &'a ItemImpl, name: &str) -> Option<&'a ImplItemFn> {
+    imp.items.iter().find_map(|item| match item {
+        ImplItem::Fn(func) if func.sig.ident == name => Some(func),
+        _ => None,
+    })
+}
+
+fn constructor<'a>(file: &'a syn::File, name: &str) -> Option<&'a ImplItemFn> {
+    stores_impls(file).find_map(|imp| method(imp, name))
+}
+
+fn module_method<'a>(file: &'a syn::File, name: &str) -> Option<&'a ImplItemFn> {
+    method(module_impl(file)?, name)
+}
+
+/// The `vec![...]` returned by `Module::entity_types`
+fn entity_types_vec(file: &syn::File) -> Option<&syn::Macro> {
+    let mac = match module_method(file, "entity_types")?.block.stmts.last()? {
+        Stmt::Macro(stmt) => &stmt.mac,
+        Stmt::Expr(Expr::Macro(expr), _) => &expr.mac,
+        _ => return None,
+    };
+    mac.path.is_ident("vec").then_some(mac)
+}
+
+fn delim_span(delimiter: &MacroDelimiter) -> &DelimSpan {
+    match delimiter {
+        MacroDelimiter::Paren(paren) => &paren.span,
+        MacroDelimiter::Brace(brace) => &brace.span,
+        MacroDelimiter::Bracket(bracket) => &bracket.span,
+    }
+}
+
+fn line_start(content: &str, pos: usize) -> usize {
+    content[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+fn line_end(content: &str, pos: usize) -> usize {
+    content[pos..].find('\n').map_or(content.len(), |i| pos + i)
+}
+
+fn indent_at(content: &str, pos: usize) -> String {
+    let start = line_start(content, pos);
+    let line = &content[start..line_end(content, start)];
+    line[..line.len() - line.trim_start().len()].to_string()
+}
+
+/// Whether only whitespace precedes `pos` on its line
+fn starts_line(content: &str, pos: usize) -> bool {
+    content[line_start(content, pos)..pos].trim().is_empty()
+}
+
+/// Whether only whitespace or a comment follows `pos` on its line
+fn ends_line(content: &str, pos: usize) -> bool {
+    let rest = content[pos..line_end(content, pos)].trim();
+    rest.is_empty() || rest.starts_with("//")
+}
+
+fn indented(lines: &[String], indent: &str) -> String {
+    lines
+        .iter()
+        .map(|line| format!("{}{}\n", indent, line))
+        .collect()
+}
+
+/// Insert `lines` into a located block.
+///
+/// Blocks collapsed onto one line (`{}`, `vec![]`, `Self { a }`) are expanded
+/// when empty and extended inline otherwise.
+fn insert(content: &str, site: &Site, marker: &str, lines: &[String]) -> String {
+    let mut updated = content.to_string();
+
+    if let Some(offset) = content[site.open..site.close].find(marker) {
+        let pos = site.open + offset;
+        let at = line_end(content, pos) + 1;
+        updated.insert_str(at, &indented(lines, &indent_at(content, pos)));
+    } else if let Some(pos) = site.before {
+        if starts_line(content, pos) {
+            let at = line_start(content, pos);
+            updated.insert_str(at, &indented(lines, &indent_at(content, pos)));
+        } else {
+            updated.insert_str(pos, &format!("{} ", lines.join(" ")));
+        }
+    } else if let Some(pos) = site.after {
+        if ends_line(content, pos) {
+            let at = line_end(content, pos) + 1;
+            updated.insert_str(at, &indented(lines, &indent_at(content, pos)));
+        } else {
+            updated.insert_str(pos, &format!(" {}", lines.join(" ")));
+        }
+    } else {
+        let indent = indent_at(content, site.close);
+        let inner = content[site.open + 1..site.close].trim_end();
+        if starts_line(content, site.close) {
+            let at = line_start(content, site.close);
+            updated.insert_str(at, &indented(lines, &format!("{}    ", indent)));
+        } else if inner.trim().is_empty() {
+            let expanded = format!(
+                "\n{}{}",
+                indented(lines, &format!("{}    ", indent)),
+                indent
+            );
+            updated.replace_range(site.open + 1..site.close, &expanded);
+        } else {
+            let joined = lines.join(" ");
+            let (sep, item) = match joined.strip_suffix(',') {
+                Some(item) if !inner.ends_with(',') => (",", item),
+                Some(item) => ("", item),
+                None => ("", joined.as_str()),
+            };
+            updated.replace_range(
+                site.open + 1 + inner.len()..site.close,
+                &format!("{} {} ", sep, item),
+            );
+        }
+    }
+
+    updated
+}
+
+/// Insert `lines` into a slot, or after `marker` when the file cannot be
+/// analysed.
+fn insert_located(content: &str, slot: Slot, marker: &str, lines: &[String]) -> Result<String> {
+    if let Some(site) = parse(content).and_then(|file| locate(&file, slot)) {
+        return Ok(insert(content, &site, marker, lines));
+    }
+    markers::insert_lines_after_marker(content, marker, lines).map_err(|_| {
+        anyhow!(
+            "Cannot locate {} and marker '{}' not found",
+            describe(slot),
+            marker
+        )
+    })
+}
+
+fn describe(slot: Slot) -> String {
+    match slot {
+        Slot::Imports => "the imports".to_string(),
+        Slot::StoreFields => "the `*Stores` struct".to_string(),
+        Slot::ConstructorVars(name) | Slot::ConstructorFields(name) => format!("`fn {}`", name),
+        Slot::EntityTypes => "`fn entity_types`".to_string(),
+        Slot::Registrations => "`fn register_entities`".to_string(),
+        Slot::MatchArms(name) => format!("`fn {}`", name),
+    }
+}
+
+/// Drop the `_` prefix of a `Module` method's parameter now that it will be
+/// used, along with the `match` on it. Returns the updated content and the
+/// parameter name, or `None` if the method cannot be found.
+fn use_parameter(content: &str, name: &str) -> Option<(String, String)> {
+    let file = parse(content)?;
+    let func = module_method(&file, name)?;
+    let ident = func.sig.inputs.iter().find_map(|arg| match arg {
+        FnArg::Typed(arg) => match &*arg.pat {
+            Pat::Ident(pat) => Some(&pat.ident),
+            _ => None,
+        },
+        FnArg::Receiver(_) => None,
+    })?;
+    let param = ident.to_string();
+    let used = match param.strip_prefix('_') {
+        Some(used) if !used.is_empty() => used,
+        _ => return Some((content.to_string(), param)),
+    };
+
+    let mut ranges = vec![ident.span().byte_range()];
+    if let Some(Stmt::Expr(Expr::Match(expr), _)) = func.block.stmts.last()
+        && let Expr::Path(scrutinee) = &*expr.expr
+        && scrutinee.path.is_ident(&param)
+    {
+        ranges.push(scrutinee.span().byte_range());
+    }
+    let mut updated = content.to_string();
+    for range in ranges.into_iter().rev() {
+        updated.replace_range(range, used);
+    }
+    Some((updated, used.to_string()))
+}
+
+/// Whether the file has a `*Stores` struct, or at least its fields marker.
+pub fn has_stores_struct(content: &str) -> bool {
+    parse(content).is_some_and(|file| stores_struct(&file).is_some())
+        || content.contains(STORE_FIELDS)
+}
+
+/// Whether the `*Stores` struct declares `field`.
+pub fn has_store_field(content: &str, field: &str) -> bool {
+    if let Some(file) = parse(content)
+        && let Some(stores) = stores_struct(&file)
+    {
+        return stores
+            .fields
+            .iter()
+            .any(|f| f.ident.as_ref().is_some_and(|ident| ident == field));
+    }
+    let needle = format!("{}:", field);
+    if content.contains(STORE_FIELDS) {
+        markers::has_line_after_marker(content, STORE_FIELDS, &needle)
+    } else {
+        content.contains(&needle)
+    }
+}
+
+/// Add fields to the `*Stores` struct.
+pub fn add_store_fields(content: &str, lines: &[String]) -> Result<String> {
+    insert_located(content, Slot::StoreFields, STORE_FIELDS, lines)
+}
+
+/// Whether the `*Stores` struct has a `fn name` constructor.
+pub fn has_constructor(content: &str, name: &str) -> bool {
+    match parse(content) {
+        Some(file) if stores_struct(&file).is_some() => constructor(&file, name).is_some(),
+        _ => content.contains(&format!("fn {}(", name)),
+    }
+}
+
+/// Append a constructor, already indented, to the `*Stores` impl block.
+pub fn add_constructor(content: &str, code: &str) -> Result<String> {
+    let close = parse(content).and_then(|file| {
+        stores_impls(&file)
+            .next()
+            .map(|imp| imp.brace_token.span.close().byte_range().start)
+    });
+    let mut updated = content.to_string();
+    match close {
+        Some(close) if starts_line(content, close) => {
+            updated.insert_str(line_start(content, close), code);
+        }
+        Some(close) => updated.insert_str(close, &format!("\n{}", code)),
+        None => {
+            let last_closing = updated
+                .rfind("\n}")
+                .ok_or_else(|| anyhow!("Cannot find closing brace of impl block in stores.rs"))?;
+            updated.insert_str(last_closing, code);
+        }
+    }
+    Ok(updated)
+}
+
+/// Add `let` statements and `Self { .. }` fields to a `*Stores` constructor.
+///
+/// Variables go after the last `let` of the body, fields at the end of the
+/// struct literal it returns, unless the given markers say otherwise.
+pub fn add_constructor_init(
+    content: &str,
+    name: &str,
+    vars: &[String],
+    fields: &[String],
+    vars_marker: &str,
+    fields_marker: &str,
+) -> Result<String> {
+    let updated = insert_located(content, Slot::ConstructorVars(name), vars_marker, vars)?;
+    insert_located(
+        &updated,
+        Slot::ConstructorFields(name),
+        fields_marker,
+        fields,
+    )
+}
+
+/// Whether the file has an `impl Module for ...` block, or at least its
+/// entity types marker.
+pub fn has_module_impl(content: &str) -> bool {
+    parse(content).is_some_and(|file| module_impl(&file).is_some())
+        || content.contains(ENTITY_TYPES)
+}
+
+/// Whether `Module::entity_types` lists `name`.
+pub fn has_entity_type(content: &str, name: &str) -> bool {
+    if let Some(file) = parse(content)
+        && let Some(mac) = entity_types_vec(&file)
+    {
+        return mac
+            .parse_body_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated)
+            .is_ok_and(|types| {
+                types.iter().any(|ty| {
+                    matches!(ty, Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }) if s.value() == name)
+                })
+            });
+    }
+    let needle = format!("\"{}\"", name);
+    if content.contains(ENTITY_TYPES) {
+        markers::has_line_after_marker(content, ENTITY_TYPES, &needle)
+    } else {
+        content.contains(&needle)
+    }
+}
+
+/// Add an entry to the `vec![...]` returned by `Module::entity_types`.
+pub fn add_entity_type(content: &str, line: &str) -> Result<String> {
+    insert_located(
+        content,
+        Slot::EntityTypes,
+        ENTITY_TYPES,
+        &[line.to_string()],
+    )
+}
+
+/// Add a statement to `Module::register_entities`.
+///
+/// `line` is given the name of the registry parameter.
+pub fn add_registration(content: &str, line: impl Fn(&str) -> String) -> Result<String> {
+    let (content, registry) = use_parameter(content, "register_entities").unwrap_or_else(|| {
+        (
+            content.replace(
+                "_registry: &mut EntityRegistry",
+                "registry: &mut EntityRegistry",
+            ),
+            "registry".to_string(),
+        )
+    });
+    insert_located(
+        &content,
+        Slot::Registrations,
+        REGISTER_ENTITIES,
+        &[line(&registry)],
+    )
+}
+
+/// Add an arm to the `match` of a `Module` method such as
+/// `get_entity_fetcher`, ahead of its `_ =>` fallback.
+pub fn add_match_arm(content: &str, name: &str, arm: &str, marker: &str) -> Result<String> {
+    let content = match use_parameter(content, name) {
+        Some((content, _)) => content,
+        None => content
+            .replace(
+                &format!("fn {}(&self, _entity_type: &str)", name),
+                &format!("fn {}(&self, entity_type: &str)", name),
+            )
+            .replace("match _entity_type {", "match entity_type {"),
+    };
+    insert_located(&content, Slot::MatchArms(name), marker, &[arm.to_string()])
+}
+
+/// Re-insert `marker` as the first line of its slot, leaving the code
+/// already registered there untouched.
+///
+/// Returns `None` if the slot cannot be located.
+pub fn restore_marker(content: &str, slot: Slot, marker: &str) -> Option<String> {
+    let file = parse(content)?;
+    let comment = format!("// {}", marker);
+    let mut updated = content.to_string();
+
+    if slot == Slot::Imports {
+        if content.contains(marker) {
+            return Some(updated);
+        }
+        let uses: Vec<&syn::ItemUse> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Use(item) => Some(item),
+                _ => None,
+            })
+            .collect();
+        let local = uses
+            .iter()
+            .find(|item| matches!(&item.tree, syn::UseTree::Path(path) if path.ident == "crate"));
+        match (local, uses.last()) {
+            (Some(local), _) => {
+                let at = line_start(content, local.span().byte_range().start);
+                updated.insert_str(at, &format!("{}\n\n", comment));
+            }
+            (None, Some(last)) => {
+                let at = line_end(content, last.span().byte_range().end);
+                updated.insert_str(at, &format!("\n\n{}", comment));
+            }
+            (None, None) => updated.insert_str(0, &format!("{}\n\n", comment)),
+        }
+        return Some(updated);
+    }
+
+    let site = locate(&file, slot)?;
+    if content[site.open..site.close].contains(marker) {
+        return Some(updated);
+    }
+    let indent = format!("{}    ", indent_at(content, site.open));
+    if ends_line(content, site.open + 1) {
+        let at = line_end(content, site.open) + 1;
+        updated.insert_str(at, &format!("{}{}\n", indent, comment));
+    } else if content[site.open + 1..site.close].trim().is_empty() {
+        let expanded = format!(
+            "\n{}{}\n{}",
+            indent,
+            comment,
+            indent_at(content, site.close)
+        );
+        updated.replace_range(site.open + 1..site.close, &expanded);
+    } else {
+        let rest = content[site.open + 1..].trim_start();
+        let at = content.len() - rest.len();
+        updated.replace_range(
+            site.open + 1..at,
+            &format!("\n{}{}\n{}", indent, comment, indent),
+        );
+    }
+    Some(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODULE: &str = r#"use std::sync::Arc;
+
+pub struct ShopModule {
+    pub stores: ShopStores,
+}
+
+impl Module for ShopModule {
+    fn entity_types(&self) -> Vec<&str> {
+        vec![]
+    }
+
+    fn register_entities(&self, _registry: &mut EntityRegistry) {}
+
+    fn get_entity_fetcher(&self, _entity_type: &str) -> Option<Arc<dyn EntityFetcher>> {
+        match _entity_type {
+            _ => None,
+        }
+    }
+}
+"#;
+
+    const STORES: &str = r#"pub struct ShopStores {
+    pub orders_store: Arc<dyn OrderStore>,
+}
+
+impl ShopStores {
+    pub fn new_in_memory() -> Self {
+        let orders = Arc::new(InMemoryOrderStore::default());
+        Self { orders_store: orders.clone() }
+    }
+}
+"#;
+
+    fn lines(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_add_store_fields_without_marker_appends_to_struct() {
+        let updated = add_store_fields(
+            STORES,
+            &lines(&["pub products_store: Arc<dyn ProductStore>,"]),
+        )
+        .unwrap();
+        assert!(updated.contains(
+            "    pub orders_store: Arc<dyn OrderStore>,\n    pub products_store: Arc<dyn ProductStore>,\n}"
+        ));
+        assert!(has_store_field(&updated, "products_store"));
+        assert!(parse(&updated).is_some());
+    }
+
+    #[test]
+    fn test_add_store_fields_keeps_marker_position() {
+        let content = "pub struct ShopStores {\n    // [this:store_fields]\n    pub a: A,\n}\n";
+        let updated = add_store_fields(content, &lines(&["pub b: B,", "pub c: C,"])).unwrap();
+        assert!(
+            updated.contains("// [this:store_fields]\n    pub b: B,\n    pub c: C,\n    pub a: A,")
+        );
+    }
+
+    #[test]
+    fn test_add_store_fields_expands_empty_struct() {
+        let updated =
+            add_store_fields("pub struct ShopStores {}\n", &lines(&["pub a: A,"])).unwrap();
+        assert_eq!(updated, "pub struct ShopStores {\n    pub a: A,\n}\n");
+    }
+
+    #[test]
+    fn test_add_constructor_init_after_last_let_and_into_inline_literal() {
+        let updated = add_constructor_init(
+            STORES,
+            "new_in_memory",
+            &lines(&["let products = Arc::new(InMemoryProductStore::default());"]),
+            &lines(&[
+                "products_store: products.clone(),",
+                "products_entity: products,",
+            ]),
+            "[this:store_init_vars]",
+            "[this:store_init_fields]",
+        )
+        .unwrap();
+        assert!(updated.contains(
+            "        let orders = Arc::new(InMemoryOrderStore::default());\n        let products = Arc::new(InMemoryProductStore::default());\n"
+        ));
+        assert!(updated.contains(
+            "Self { orders_store: orders.clone(), products_store: products.clone(), products_entity: products }"
+        ));
+        assert!(parse(&updated).is_some());
+    }
+
+    #[test]
+    fn test_add_constructor_when_missing() {
+        assert!(has_constructor(STORES, "new_in_memory"));
+        assert!(!has_constructor(STORES, "new_postgres"));
+        let code = "\n    pub fn new_postgres(pool: PgPool) -> Self {\n        Self {}\n    }\n";
+        let updated = add_constructor(STORES, code).unwrap();
+        assert!(has_constructor(&updated, "new_postgres"));
+        assert!(updated.ends_with(
+            "    }\n\n    pub fn new_postgres(pool: PgPool) -> Self {\n        Self {}\n    }\n}\n"
+        ));
+    }
+
+    #[test]
+    fn test_add_entity_type_into_collapsed_vec() {
+        assert!(!has_entity_type(MODULE, "product"));
+        let updated = add_entity_type(MODULE, "\"product\",").unwrap();
+        assert!(updated.contains("vec![\n            \"product\",\n        ]"));
+        assert!(has_entity_type(&updated, "product"));
+        assert!(!has_entity_type(&updated, "prod"));
+    }
+
+    #[test]
+    fn test_add_registration_renames_unused_parameter() {
+        let updated = add_registration(MODULE, |registry| {
+            format!("{}.register(product());", registry)
+        })
+        .unwrap();
+        assert!(updated.contains(
+            "fn register_entities(&self, registry: &mut EntityRegistry) {\n        registry.register(product());\n    }"
+        ));
+    }
+
+    #[test]
+    fn test_add_match_arm_before_wildcard() {
+        let updated = add_match_arm(
+            MODULE,
+            "get_entity_fetcher",
+            "\"product\" => Some(fetcher()),",
+            "[this:entity_fetcher]",
+        )
+        .unwrap();
+        assert!(updated.contains(
+            "fn get_entity_fetcher(&self, entity_type: &str) -> Option<Arc<dyn EntityFetcher>> {\n        match entity_type {\n            \"product\" => Some(fetcher()),\n            _ => None,"
+        ));
+        assert!(parse(&updated).is_some());
+    }
+
+    #[test]
+    fn test_unparsable_content_falls_back_to_markers() {
+        let content = "pub struct ShopStores {\n    // [this:store_fields]\n    broken(\n}\n";
+        let updated = add_store_fields(content, &lines(&["pub a: A,"])).unwrap();
+        assert!(updated.contains("// [this:store_fields]\n    pub a: A,"));
+        assert!(has_store_field(&updated, "a"));
+
+        let err = add_store_fields("fn broken(\n", &lines(&["pub a: A,"])).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Cannot locate the `*Stores` struct")
+        );
+    }
+}
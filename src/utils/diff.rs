@@ -0,0 +1,308 @@
+use std::path::Path;
+
+use similar::{Algorithm, ChangeTag, TextDiff, capture_diff_slices};
+
+/// Lines of unchanged context around each hunk
+const CONTEXT_LINES: usize = 3;
+
+/// Render a unified diff between two versions of a file.
+///
+/// `original` is `None` for a file that does not exist yet, which produces
+/// a `--- /dev/null` header.
+pub fn unified_diff(path: &Path, original: Option<&str>, updated: &str) -> String {
+    let display = path.display().to_string();
+    let old_header = match original {
+        Some(_) => format!("a/{}", display),
+        None => "/dev/null".to_string(),
+    };
+    let new_header = format!("b/{}", display);
+
+    TextDiff::from_lines(original.unwrap_or(""), updated)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&old_header, &new_header)
+        .to_string()
+}
+
+/// Render a `git apply`-compatible patch for one file.
+///
+/// `path` should be relative to the directory the patch will be applied in.
+/// Returns an empty string when the content is unchanged.
+pub fn git_patch(path: &Path, original: Option<&str>, updated: &str) -> String {
+    if original == Some(updated) {
+        return String::new();
+    }
+    let display = path.display().to_string();
+    let mut patch = format!("diff --git a/{} b/{}\n", display, display);
+    if original.is_none() {
+        patch.push_str("new file mode 100644\n");
+        if updated.is_empty() {
+            return patch;
+        }
+    }
+    patch.push_str(&unified_diff(path, original, updated));
+    patch
+}
+
+/// Count the lines added and removed between two versions of a file
+pub fn line_stats(original: &str, updated: &str) -> (usize, usize) {
+    let diff = TextDiff::from_lines(original, updated);
+    let mut added = 0;
+    let mut removed = 0;
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => added += 1,
+            ChangeTag::Delete => removed += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    (added, removed)
+}
+
+/// A line range (1-based, inclusive) of the base file edited on both sides
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    pub first_line: usize,
+    pub last_line: usize,
+}
+
+/// Contiguous edit of the base: lines `start..end` replaced by `lines`
+#[derive(Debug, PartialEq)]
+struct Hunk<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+/// Three-way line merge of two edits (`ours`, `theirs`) of the same `base`.
+///
+/// Non-overlapping edits are combined; identical edits are applied once.
+/// Edits that overlap or touch the same base lines are reported as conflicts.
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> Result<String, Vec<MergeConflict>> {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours_hunks = hunks(&base_lines, ours);
+    let theirs_hunks = hunks(&base_lines, theirs);
+
+    let mut merge = Merge {
+        base: &base_lines,
+        merged: String::new(),
+        pos: 0,
+        conflicts: Vec::new(),
+    };
+    let (mut i, mut j) = (0, 0);
+
+    loop {
+        match (ours_hunks.get(i), theirs_hunks.get(j)) {
+            (None, None) => break,
+            (Some(a), None) => {
+                merge.apply(a);
+                i += 1;
+            }
+            (None, Some(b)) => {
+                merge.apply(b);
+                j += 1;
+            }
+            (Some(a), Some(b)) => {
+                if a == b {
+                    merge.apply(a);
+                    i += 1;
+                    j += 1;
+                } else if a.start <= b.end && b.start <= a.end {
+                    merge.conflict(a.start.min(b.start), a.end.max(b.end));
+                    i += 1;
+                    j += 1;
+                } else if a.start < b.start {
+                    merge.apply(a);
+                    i += 1;
+                } else {
+                    merge.apply(b);
+                    j += 1;
+                }
+            }
+        }
+    }
+
+    let Merge {
+        base,
+        mut merged,
+        pos,
+        conflicts,
+    } = merge;
+    merged.extend(base[pos..].iter().copied());
+
+    if conflicts.is_empty() {
+        Ok(merged)
+    } else {
+        Err(conflicts)
+    }
+}
+
+/// Edits turning `base` into `other`, with adjacent operations coalesced
+fn hunks<'a>(base: &[&str], other: &'a str) -> Vec<Hunk<'a>> {
+    let other_lines: Vec<&str> = other.split_inclusive('\n').collect();
+    let mut hunks: Vec<Hunk> = Vec::new();
+
+    for op in capture_diff_slices(Algorithm::Myers, base, &other_lines) {
+        let (tag, old, new) = op.as_tag_tuple();
+        if tag == similar::DiffTag::Equal {
+            continue;
+        }
+        let lines = other_lines[new].to_vec();
+        match hunks.last_mut() {
+            Some(last) if last.end == old.start => {
+                last.end = old.end;
+                last.lines.extend(lines);
+            }
+            _ => hunks.push(Hunk {
+                start: old.start,
+                end: old.end,
+                lines,
+            }),
+        }
+    }
+    hunks
+}
+
+/// In-progress three-way merge over the base lines
+struct Merge<'a> {
+    base: &'a [&'a str],
+    merged: String,
+    /// Next base line not yet copied to `merged`
+    pos: usize,
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merge<'_> {
+    /// Copy base lines up to the hunk, then the hunk's replacement lines
+    fn apply(&mut self, hunk: &Hunk) {
+        if hunk.start < self.pos {
+            self.conflict(hunk.start, hunk.end);
+            return;
+        }
+        self.merged
+            .extend(self.base[self.pos..hunk.start].iter().copied());
+        self.merged.extend(hunk.lines.iter().copied());
+        self.pos = hunk.end;
+    }
+
+    /// Record a conflict over base lines `start..end` and skip past them
+    fn conflict(&mut self, start: usize, end: usize) {
+        self.conflicts.push(MergeConflict {
+            first_line: start + 1,
+            last_line: end.max(start + 1),
+        });
+        self.pos = self.pos.max(end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff_modification() {
+        let diff = unified_diff(
+            Path::new("src/module.rs"),
+            Some("a\nb\nc\n"),
+            "a\nb\nnew\nc\n",
+        );
+        assert!(diff.starts_with("--- a/src/module.rs\n+++ b/src/module.rs\n"));
+        assert!(diff.contains("@@ -1,3 +1,4 @@"));
+        assert!(diff.contains("\n+new\n"));
+    }
+
+    #[test]
+    fn test_unified_diff_new_file() {
+        let diff = unified_diff(Path::new("new.rs"), None, "fn main() {}\n");
+        assert!(diff.starts_with("--- /dev/null\n+++ b/new.rs\n"));
+        assert!(diff.contains("+fn main() {}"));
+    }
+
+    #[test]
+    fn test_unified_diff_removal() {
+        let diff = unified_diff(Path::new("f.txt"), Some("keep\ndrop\n"), "keep\n");
+        assert!(diff.contains("\n-drop\n"));
+    }
+
+    #[test]
+    fn test_unified_diff_identical_is_empty() {
+        assert!(unified_diff(Path::new("f.txt"), Some("same\n"), "same\n").is_empty());
+    }
+
+    #[test]
+    fn test_git_patch_new_file() {
+        let patch = git_patch(Path::new("src/new.rs"), None, "x\n");
+        assert!(patch.starts_with(
+            "diff --git a/src/new.rs b/src/new.rs\nnew file mode 100644\n--- /dev/null\n"
+        ));
+        assert!(patch.contains("@@ -0,0 +1 @@\n+x\n"));
+    }
+
+    #[test]
+    fn test_git_patch_empty_new_file_has_no_hunk() {
+        let patch = git_patch(Path::new("empty.txt"), None, "");
+        assert_eq!(
+            patch,
+            "diff --git a/empty.txt b/empty.txt\nnew file mode 100644\n"
+        );
+    }
+
+    #[test]
+    fn test_git_patch_unchanged_is_empty() {
+        assert!(git_patch(Path::new("f.txt"), Some("a\n"), "a\n").is_empty());
+    }
+
+    #[test]
+    fn test_line_stats() {
+        assert_eq!(line_stats("a\nb\n", "a\nc\nd\n"), (2, 1));
+        assert_eq!(line_stats("", "x\n"), (1, 0));
+    }
+
+    #[test]
+    fn test_merge3_non_overlapping_edits() {
+        let base = "a\nb\nc\nd\ne\n";
+        let ours = "a\nb\nc\nd\ne\nours\n";
+        let theirs = "theirs\na\nb\nc\nd\ne\n";
+        assert_eq!(
+            merge3(base, ours, theirs).unwrap(),
+            "theirs\na\nb\nc\nd\ne\nours\n"
+        );
+    }
+
+    #[test]
+    fn test_merge3_identical_edits_applied_once() {
+        let base = "a\nb\n";
+        let edited = "a\nx\nb\n";
+        assert_eq!(merge3(base, edited, edited).unwrap(), edited);
+    }
+
+    #[test]
+    fn test_merge3_unchanged_theirs() {
+        let base = "a\nb\n";
+        let ours = "a\nb\nc\n";
+        assert_eq!(merge3(base, ours, base).unwrap(), ours);
+    }
+
+    #[test]
+    fn test_merge3_overlapping_edits_conflict() {
+        let base = "a\nb\nc\n";
+        let ours = "a\nOURS\nc\n";
+        let theirs = "a\nTHEIRS\nc\n";
+        let conflicts = merge3(base, ours, theirs).unwrap_err();
+        assert_eq!(
+            conflicts,
+            vec![MergeConflict {
+                first_line: 2,
+                last_line: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_merge3_insert_at_same_point_conflicts() {
+        let base = "marker\nend\n";
+        let ours = "marker\nours\nend\n";
+        let theirs = "marker\ntheirs\nend\n";
+        assert!(merge3(base, ours, theirs).is_err());
+    }
+}
@@ -3,6 +3,8 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
 use colored::Colorize;
 
+use super::{diff, history, output};
+
 /// Abstraction for file system operations, enabling dry-run mode.
 pub trait FileWriter {
     /// Create a directory and all parent directories
@@ -17,9 +19,23 @@ pub trait FileWriter {
 
     /// Whether this is a dry-run (no actual writes)
     fn is_dry_run(&self) -> bool;
+
+    /// Whether this is a `--check` run, which prints its own report in place
+    /// of the command's usual output
+    fn is_check(&self) -> bool {
+        false
+    }
+
+    /// Current content of a file as the command sees it: what an earlier
+    /// write of the same command staged or recorded, else the file on disk.
+    /// Updates to a file written earlier in the command must build on this.
+    fn read_file(&self, path: &Path) -> Result<String> {
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read: {}", path.display()))
+    }
 }
 
-/// Real file writer — actually writes to disk
+/// Real file writer — actually writes to disk, without journaling.
+/// The CLI uses TransactionWriter; watch mode writes directly with this one.
 pub struct RealWriter;
 
 impl FileWriter for RealWriter {
@@ -33,8 +49,14 @@ impl FileWriter for RealWriter {
             .with_context(|| format!("Failed to write: {}", path.display()))
     }
 
-    fn update_file(&self, path: &Path, _original: &str, updated: &str) -> Result<()> {
-        std::fs::write(path, updated)
+    fn update_file(&self, path: &Path, original: &str, updated: &str) -> Result<()> {
+        let content = reconcile(path, original, updated).map_err(|conflict| {
+            anyhow::anyhow!(
+                "Conflicting changes — file was not written:\n  {}",
+                conflict
+            )
+        })?;
+        std::fs::write(path, content)
             .with_context(|| format!("Failed to write: {}", path.display()))
     }
 
@@ -43,20 +65,316 @@ impl FileWriter for RealWriter {
     }
 }
 
+/// Transactional writer — stages file writes and applies them all at once.
+///
+/// Directories are created immediately (commands may run tools such as
+/// `git init` inside them), but file contents only reach the disk in
+/// `commit()`, which first records a journal entry under `.this/history/`
+/// so that `this undo` can restore the previous state. Dropping the writer
+/// without committing discards staged writes and removes the (empty)
+/// directories it created.
+pub struct TransactionWriter {
+    staged: std::cell::RefCell<Vec<StagedWrite>>,
+    dirs_created: std::cell::RefCell<Vec<PathBuf>>,
+    committed: std::cell::Cell<bool>,
+}
+
+impl TransactionWriter {
+    pub fn new() -> Self {
+        Self {
+            staged: std::cell::RefCell::new(Vec::new()),
+            dirs_created: std::cell::RefCell::new(Vec::new()),
+            committed: std::cell::Cell::new(false),
+        }
+    }
+
+    fn stage(&self, path: &Path, original: Option<&str>, content: &str) -> Result<()> {
+        stage_write(&mut self.staged.borrow_mut(), path, original, content)
+    }
+
+    /// Apply all staged writes, journaling them under `command`.
+    ///
+    /// Returns the journal path, or `None` when the command wrote nothing.
+    /// Updated files that were edited on disk in the meantime are merged with
+    /// those edits; if any of them conflict, nothing is written. If any write
+    /// fails, the files already written are restored.
+    pub fn commit(&self, command: &str) -> Result<Option<PathBuf>> {
+        self.committed.set(true);
+        let mut staged = self.staged.take();
+        let dirs = self.dirs_created.take();
+        if staged.is_empty() {
+            return Ok(None);
+        }
+
+        let mut conflicts = Vec::new();
+        for write in &mut staged {
+            if let Some(original) = &write.original {
+                match reconcile(&write.path, original, &write.content) {
+                    Ok(content) => write.content = content,
+                    Err(conflict) => conflicts.push(conflict),
+                }
+            }
+        }
+        if !conflicts.is_empty() {
+            remove_empty_dirs(&dirs);
+            anyhow::bail!(
+                "Conflicting changes — no files were written:\n  {}",
+                conflicts.join("\n  ")
+            );
+        }
+
+        let cwd = std::env::current_dir()?;
+        let touched: Vec<PathBuf> = staged.iter().map(|w| w.path.clone()).collect();
+        let root = history::history_root(&touched, &cwd);
+
+        let befores: Vec<Option<String>> = staged
+            .iter()
+            .map(|w| std::fs::read_to_string(&w.path).ok())
+            .collect();
+
+        let entry = history::JournalEntry::new(
+            command,
+            staged
+                .iter()
+                .zip(&befores)
+                .map(|(write, before)| history::JournalFile {
+                    path: history::relative_to(&write.path, &root, &cwd),
+                    before: before.clone(),
+                    after: write.content.clone(),
+                })
+                .collect(),
+            dirs.iter()
+                .map(|d| history::relative_to(d, &root, &cwd))
+                .collect(),
+        );
+        let journal = history::record(&root, &entry)?;
+
+        for (i, write) in staged.iter().enumerate() {
+            if let Err(e) = write_atomic(&write.path, &write.content) {
+                for (done, before) in staged[..i].iter().zip(&befores) {
+                    let _ = match before {
+                        Some(b) => std::fs::write(&done.path, b),
+                        None => std::fs::remove_file(&done.path),
+                    };
+                }
+                let _ = std::fs::remove_file(&journal);
+                remove_empty_dirs(&dirs);
+                return Err(e).context("Transaction rolled back — no files were changed");
+            }
+        }
+
+        Ok(Some(journal))
+    }
+}
+
+impl Drop for TransactionWriter {
+    fn drop(&mut self) {
+        if !self.committed.get() {
+            remove_empty_dirs(&self.dirs_created.borrow());
+        }
+    }
+}
+
+impl FileWriter for TransactionWriter {
+    fn create_dir_all(&self, path: &Path) -> Result<()> {
+        // Record every missing ancestor so undo can remove them all
+        let mut missing = Vec::new();
+        let mut current = Some(path);
+        while let Some(dir) = current {
+            if dir.as_os_str().is_empty() || dir.exists() {
+                break;
+            }
+            missing.push(dir.to_path_buf());
+            current = dir.parent();
+        }
+
+        std::fs::create_dir_all(path)
+            .with_context(|| format!("Failed to create directory: {}", path.display()))?;
+
+        self.dirs_created
+            .borrow_mut()
+            .extend(missing.into_iter().rev());
+        Ok(())
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> Result<()> {
+        self.stage(path, None, content)
+    }
+
+    fn update_file(&self, path: &Path, original: &str, updated: &str) -> Result<()> {
+        self.stage(path, Some(original), updated)
+    }
+
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+
+    fn read_file(&self, path: &Path) -> Result<String> {
+        match self.staged.borrow().iter().find(|w| w.path == path) {
+            Some(write) => Ok(write.content.clone()),
+            None => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read: {}", path.display())),
+        }
+    }
+}
+
+/// A file write staged by `TransactionWriter`, or recorded by a dry run
+pub(crate) struct StagedWrite {
+    pub(crate) path: PathBuf,
+    /// Content the command read before updating (`None` for `write_file`)
+    pub(crate) original: Option<String>,
+    pub(crate) content: String,
+}
+
+/// Add a write to `writes`, or fold it into the one already there for `path`.
+///
+/// A second update that was computed from another text (typically the file
+/// on disk rather than the staged content) is merged onto the staged content,
+/// so the first edit is not lost; overlapping edits are an error.
+pub(crate) fn stage_write(
+    writes: &mut Vec<StagedWrite>,
+    path: &Path,
+    original: Option<&str>,
+    content: &str,
+) -> Result<()> {
+    match writes.iter_mut().find(|w| w.path == path) {
+        // Keep the first original: it is what the command read from disk
+        Some(existing) => {
+            existing.content = match original {
+                Some(base) if base != existing.content => {
+                    diff::merge3(base, &existing.content, content).map_err(|conflicts| {
+                        anyhow::anyhow!(
+                            "Conflicting changes to {} within one command ({} edited twice)",
+                            path.display(),
+                            conflict_ranges(&conflicts)
+                        )
+                    })?
+                }
+                _ => content.to_string(),
+            };
+        }
+        None => writes.push(StagedWrite {
+            path: path.to_path_buf(),
+            original: original.map(str::to_string),
+            content: content.to_string(),
+        }),
+    }
+    Ok(())
+}
+
+/// Resolve an update against the file's current content on disk.
+///
+/// Returns `updated` when the file still matches `original`. If the file was
+/// edited since it was read, both edits are merged when they touch different
+/// lines; otherwise the returned error describes the conflict.
+pub(crate) fn reconcile(
+    path: &Path,
+    original: &str,
+    updated: &str,
+) -> std::result::Result<String, String> {
+    let current = match std::fs::read_to_string(path) {
+        Ok(current) => current,
+        Err(_) => return Err(format!("{}: deleted since it was read", path.display())),
+    };
+    if current == original || current == updated {
+        return Ok(updated.to_string());
+    }
+
+    match diff::merge3(original, updated, &current) {
+        Ok(merged) => {
+            output::print_warn(&format!(
+                "{} was modified on disk; merged with your changes",
+                path.display()
+            ));
+            Ok(merged)
+        }
+        Err(conflicts) => Err(format!(
+            "{}: modified on disk since it was read ({} edited on both sides)",
+            path.display(),
+            conflict_ranges(&conflicts)
+        )),
+    }
+}
+
+/// Human-readable line ranges of merge conflicts, e.g. "line 3, lines 7-9"
+fn conflict_ranges(conflicts: &[diff::MergeConflict]) -> String {
+    conflicts
+        .iter()
+        .map(|c| {
+            if c.first_line == c.last_line {
+                format!("line {}", c.first_line)
+            } else {
+                format!("lines {}-{}", c.first_line, c.last_line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Write a file via a temporary sibling and rename, so readers never see a partial file
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.this-tmp", file_name));
+    std::fs::write(&tmp, content)
+        .with_context(|| format!("Failed to write: {}", path.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to write: {}", path.display()))
+}
+
+/// Remove directories (deepest first) that are still empty
+fn remove_empty_dirs(dirs: &[PathBuf]) {
+    for dir in dirs.iter().rev() {
+        let _ = std::fs::remove_dir(dir);
+    }
+}
+
+/// How much `--dry-run` shows for new files
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum DryRunMode {
+    /// List new files and show diffs for modified files
+    #[default]
+    Summary,
+    /// Also preview the full content of new files
+    Full,
+}
+
+/// Output format of `--dry-run`
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum DryRunFormat {
+    /// Human-readable listing with colored diffs
+    #[default]
+    Text,
+    /// A `git apply`-compatible patch of every change
+    Patch,
+}
+
 /// Dry-run writer — prints what would happen without writing
 pub struct DryRunWriter {
+    mode: DryRunMode,
+    format: DryRunFormat,
     files_created: std::cell::RefCell<Vec<PathBuf>>,
     files_updated: std::cell::RefCell<Vec<PathBuf>>,
     dirs_created: std::cell::RefCell<Vec<PathBuf>>,
+    /// Planned content per file, used to build the patch
+    changes: std::cell::RefCell<Vec<StagedWrite>>,
 }
 
 #[allow(dead_code)]
 impl DryRunWriter {
     pub fn new() -> Self {
+        Self::with_options(DryRunMode::default(), DryRunFormat::default())
+    }
+
+    pub fn with_options(mode: DryRunMode, format: DryRunFormat) -> Self {
         Self {
+            mode,
+            format,
             files_created: std::cell::RefCell::new(Vec::new()),
             files_updated: std::cell::RefCell::new(Vec::new()),
             dirs_created: std::cell::RefCell::new(Vec::new()),
+            changes: std::cell::RefCell::new(Vec::new()),
         }
     }
 
@@ -75,8 +393,13 @@ impl DryRunWriter {
         self.dirs_created.borrow().clone()
     }
 
-    /// Print summary of what would be done
+    /// Print summary of what would be done (or the patch, in patch format)
     pub fn print_summary(&self) {
+        if self.format == DryRunFormat::Patch {
+            print!("{}", self.patch());
+            return;
+        }
+
         let created = self.files_created.borrow();
         let updated = self.files_updated.borrow();
 
@@ -97,6 +420,26 @@ impl DryRunWriter {
             println!("  {}", "No changes would be made".dimmed());
         }
     }
+
+    /// Build a `git apply`-compatible patch, with paths relative to the cwd
+    pub fn patch(&self) -> String {
+        self.changes
+            .borrow()
+            .iter()
+            .map(|change| {
+                diff::git_patch(
+                    &display_path(&change.path),
+                    change.original.as_deref(),
+                    &change.content,
+                )
+            })
+            .collect()
+    }
+
+    /// Record a planned change, merging repeated writes to the same file
+    fn record(&self, path: &Path, original: Option<&str>, content: &str) -> Result<()> {
+        stage_write(&mut self.changes.borrow_mut(), path, original, content)
+    }
 }
 
 impl FileWriter for DryRunWriter {
@@ -105,39 +448,162 @@ impl FileWriter for DryRunWriter {
         Ok(())
     }
 
-    fn write_file(&self, path: &Path, _content: &str) -> Result<()> {
-        println!("  {} {}", "Would create:".cyan(), path.display());
+    fn write_file(&self, path: &Path, content: &str) -> Result<()> {
+        // Overwriting an existing file shows up as a modification in the patch
+        let existing = std::fs::read_to_string(path).ok();
+        self.record(path, existing.as_deref(), content)?;
         self.files_created.borrow_mut().push(path.to_path_buf());
+
+        if self.format == DryRunFormat::Text {
+            println!("  {} {}", "Would create:".cyan(), path.display());
+            if self.mode == DryRunMode::Full {
+                print_diff(&diff::unified_diff(&display_path(path), None, content));
+            }
+        }
         Ok(())
     }
 
     fn update_file(&self, path: &Path, original: &str, updated: &str) -> Result<()> {
-        println!("  {} {}", "Would modify:".yellow(), path.display());
+        self.record(path, Some(original), updated)?;
         self.files_updated.borrow_mut().push(path.to_path_buf());
 
-        // Show a simplified diff
-        print_simple_diff(original, updated);
-
+        if self.format == DryRunFormat::Text {
+            println!("  {} {}", "Would modify:".yellow(), path.display());
+            print_diff(&diff::unified_diff(
+                &display_path(path),
+                Some(original),
+                updated,
+            ));
+        }
         Ok(())
     }
 
     fn is_dry_run(&self) -> bool {
         true
     }
+
+    fn read_file(&self, path: &Path) -> Result<String> {
+        match self.changes.borrow().iter().find(|c| c.path == path) {
+            Some(change) => Ok(change.content.clone()),
+            None => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read: {}", path.display())),
+        }
+    }
+}
+
+/// Check writer — records generated content and compares it with the disk.
+///
+/// Used by `this generate <kind> --check` in CI: nothing is written, and
+/// `print_report()` lists every generated file that is missing or differs.
+pub struct CheckWriter {
+    generated: std::cell::RefCell<Vec<StagedWrite>>,
 }
 
-/// Print a simplified diff showing only added lines
-fn print_simple_diff(original: &str, updated: &str) {
-    let original_lines: Vec<&str> = original.lines().collect();
-    let updated_lines: Vec<&str> = updated.lines().collect();
+impl CheckWriter {
+    pub fn new() -> Self {
+        Self {
+            generated: std::cell::RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Generated files whose content on disk differs (or that do not exist)
+    pub fn stale_files(&self) -> Vec<PathBuf> {
+        self.generated
+            .borrow()
+            .iter()
+            .filter(|write| is_stale(write))
+            .map(|write| write.path.clone())
+            .collect()
+    }
 
-    for line in &updated_lines {
-        if !original_lines.contains(line) {
-            println!("    {} {}", "+".green(), line.green());
+    /// Print each stale file with the diff that regenerating would apply
+    pub fn print_report(&self) {
+        for write in self.generated.borrow().iter().filter(|w| is_stale(w)) {
+            output::print_warn(&format!("Out of date: {}", write.path.display()));
+            print_diff(&diff::unified_diff(
+                &display_path(&write.path),
+                write.original.as_deref(),
+                &write.content,
+            ));
         }
     }
 }
 
+/// `original` holds the content on disk when the file was generated
+fn is_stale(write: &StagedWrite) -> bool {
+    write.original.as_deref() != Some(write.content.as_str())
+}
+
+impl FileWriter for CheckWriter {
+    fn create_dir_all(&self, _path: &Path) -> Result<()> {
+        Ok(())
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> Result<()> {
+        // Later writes to the same file win, compared with what is on disk now
+        let mut generated = self.generated.borrow_mut();
+        generated.retain(|write| write.path != path);
+        generated.push(StagedWrite {
+            path: path.to_path_buf(),
+            original: std::fs::read_to_string(path).ok(),
+            content: content.to_string(),
+        });
+        Ok(())
+    }
+
+    fn update_file(&self, path: &Path, _original: &str, updated: &str) -> Result<()> {
+        self.write_file(path, updated)
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+
+    fn is_check(&self) -> bool {
+        true
+    }
+
+    fn read_file(&self, path: &Path) -> Result<String> {
+        match self.generated.borrow().iter().find(|w| w.path == path) {
+            Some(write) => Ok(write.content.clone()),
+            None => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read: {}", path.display())),
+        }
+    }
+}
+
+/// Print a unified diff, indented and colored line by line
+fn print_diff(diff: &str) {
+    for line in diff.lines() {
+        let styled = if line.starts_with("+++") || line.starts_with("---") {
+            line.bold()
+        } else if line.starts_with("@@") {
+            line.cyan()
+        } else if line.starts_with('+') {
+            line.green()
+        } else if line.starts_with('-') {
+            line.red()
+        } else {
+            line.dimmed()
+        };
+        println!("    {}", styled);
+    }
+}
+
+/// Path shown in diff headers: relative to the current directory when possible
+fn display_path(path: &Path) -> PathBuf {
+    patch_path(path, &std::env::current_dir().unwrap_or_default())
+}
+
+/// Express a written path relative to `cwd` for use in a patch header
+fn patch_path(path: &Path, cwd: &Path) -> PathBuf {
+    let relative = path.strip_prefix(cwd).unwrap_or(path);
+    relative
+        .components()
+        .filter(|c| !matches!(c, std::path::Component::CurDir))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -181,12 +647,257 @@ mod tests {
         assert_eq!(content, "version 2");
     }
 
+    #[test]
+    fn test_real_writer_update_merges_concurrent_edit() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("mod.rs");
+        std::fs::write(&file, "// edited by user\na\nb\nc\n").unwrap();
+
+        RealWriter
+            .update_file(&file, "a\nb\nc\n", "a\nb\nc\nd\n")
+            .unwrap();
+
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(content, "// edited by user\na\nb\nc\nd\n");
+    }
+
+    #[test]
+    fn test_real_writer_update_aborts_on_conflict() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("mod.rs");
+        std::fs::write(&file, "a\nuser\nc\n").unwrap();
+
+        let err = RealWriter
+            .update_file(&file, "a\nb\nc\n", "a\nours\nc\n")
+            .unwrap_err();
+
+        let msg = err.to_string();
+        assert!(msg.contains("Conflicting changes"));
+        assert!(msg.contains("mod.rs"));
+        assert!(msg.contains("line 2"));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "a\nuser\nc\n");
+    }
+
+    #[test]
+    fn test_reconcile_unchanged_file() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("a.txt");
+        std::fs::write(&file, "x\n").unwrap();
+
+        assert_eq!(reconcile(&file, "x\n", "y\n").unwrap(), "y\n");
+    }
+
+    #[test]
+    fn test_reconcile_deleted_file() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("gone.txt");
+
+        let err = reconcile(&file, "x\n", "y\n").unwrap_err();
+        assert!(err.contains("deleted"));
+    }
+
     #[test]
     fn test_real_writer_is_not_dry_run() {
         let writer = RealWriter;
         assert!(!writer.is_dry_run());
     }
 
+    // ── TransactionWriter tests ─────────────────────────────────────────
+
+    #[test]
+    #[serial_test::serial]
+    fn test_transaction_writer_stages_until_commit() {
+        let tmp = TempDir::new().unwrap();
+        let writer = TransactionWriter::new();
+        let file = tmp.path().join("a.txt");
+        std::fs::write(&file, "old").unwrap();
+
+        writer.update_file(&file, "old", "new").unwrap();
+        writer.write_file(&tmp.path().join("b.txt"), "b").unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "old");
+        assert!(!tmp.path().join("b.txt").exists());
+        // Later steps of the command see the staged content
+        assert_eq!(writer.read_file(&file).unwrap(), "new");
+        assert_eq!(writer.read_file(&tmp.path().join("b.txt")).unwrap(), "b");
+
+        let journal = writer.commit("test").unwrap().unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "new");
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("b.txt")).unwrap(),
+            "b"
+        );
+        assert!(journal.starts_with(tmp.path().join(history::HISTORY_DIR)));
+
+        let entries = history::list(tmp.path()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].1.files[0].before.as_deref(), Some("old"));
+        assert_eq!(entries[0].1.files[1].before, None);
+    }
+
+    #[test]
+    fn test_transaction_writer_commit_nothing() {
+        let writer = TransactionWriter::new();
+        assert!(writer.commit("noop").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_transaction_writer_drop_discards_and_cleans_dirs() {
+        let tmp = TempDir::new().unwrap();
+        let nested = tmp.path().join("x").join("y");
+        {
+            let writer = TransactionWriter::new();
+            writer.create_dir_all(&nested).unwrap();
+            writer.write_file(&nested.join("f.txt"), "f").unwrap();
+            assert!(nested.is_dir());
+        }
+        assert!(!tmp.path().join("x").exists());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_transaction_writer_rolls_back_on_failure() {
+        let tmp = TempDir::new().unwrap();
+        let good = tmp.path().join("good.txt");
+        std::fs::write(&good, "original").unwrap();
+
+        let writer = TransactionWriter::new();
+        writer.update_file(&good, "original", "changed").unwrap();
+        // Parent directory does not exist, so this write fails
+        writer
+            .write_file(&tmp.path().join("missing/dir/bad.txt"), "bad")
+            .unwrap();
+
+        let err = writer.commit("test").unwrap_err();
+        assert!(format!("{:#}", err).contains("rolled back"));
+        assert_eq!(std::fs::read_to_string(&good).unwrap(), "original");
+        assert!(history::list(tmp.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_transaction_writer_conflict_writes_nothing() {
+        let tmp = TempDir::new().unwrap();
+        let clean = tmp.path().join("clean.txt");
+        let edited = tmp.path().join("edited.txt");
+        std::fs::write(&clean, "a\n").unwrap();
+        std::fs::write(&edited, "a\nb\n").unwrap();
+
+        let writer = TransactionWriter::new();
+        writer.update_file(&clean, "a\n", "a\nnew\n").unwrap();
+        writer.update_file(&edited, "a\nb\n", "a\nours\n").unwrap();
+        writer.write_file(&tmp.path().join("new.txt"), "n").unwrap();
+        // The user's editor saves the file before the command commits
+        std::fs::write(&edited, "a\ntheirs\n").unwrap();
+
+        let err = writer.commit("test").unwrap_err().to_string();
+        assert!(err.contains("no files were written"));
+        assert!(err.contains("edited.txt"));
+        assert!(!err.contains("clean.txt"));
+        assert_eq!(std::fs::read_to_string(&clean).unwrap(), "a\n");
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "a\ntheirs\n");
+        assert!(!tmp.path().join("new.txt").exists());
+        assert!(history::list(tmp.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_transaction_writer_merges_two_updates_from_disk() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("module.rs");
+        std::fs::write(&file, "a\nb\nc\n").unwrap();
+
+        // Both callers read the file from disk, not the staged content
+        let writer = TransactionWriter::new();
+        writer
+            .update_file(&file, "a\nb\nc\n", "a\nentity\nb\nc\n")
+            .unwrap();
+        writer
+            .update_file(&file, "a\nb\nc\n", "a\nb\nc\npack\n")
+            .unwrap();
+
+        writer.commit("test").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "a\nentity\nb\nc\npack\n"
+        );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_transaction_writer_rejects_overlapping_updates_from_disk() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("module.rs");
+        std::fs::write(&file, "a\nb\n").unwrap();
+
+        let writer = TransactionWriter::new();
+        writer.update_file(&file, "a\nb\n", "a\nx\n").unwrap();
+        let err = writer.update_file(&file, "a\nb\n", "a\ny\n").unwrap_err();
+        assert!(err.to_string().contains("edited twice"));
+
+        // The first edit is still staged
+        writer.commit("test").unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "a\nx\n");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_transaction_writer_journals_merged_content() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("mod.rs");
+        std::fs::write(&file, "a\nb\nc\n").unwrap();
+
+        let writer = TransactionWriter::new();
+        writer
+            .update_file(&file, "a\nb\nc\n", "a\nb\nc\nd\n")
+            .unwrap();
+        std::fs::write(&file, "z\na\nb\nc\n").unwrap();
+
+        writer.commit("test").unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "z\na\nb\nc\nd\n");
+        let entries = history::list(tmp.path()).unwrap();
+        assert_eq!(
+            entries[0].1.files[0].before.as_deref(),
+            Some("z\na\nb\nc\n")
+        );
+        assert_eq!(entries[0].1.files[0].after, "z\na\nb\nc\nd\n");
+    }
+
+    // ── CheckWriter tests ───────────────────────────────────────────────
+
+    #[test]
+    fn test_check_writer_reports_missing_and_changed_files() {
+        let tmp = TempDir::new().unwrap();
+        let current = tmp.path().join("current.ts");
+        let changed = tmp.path().join("changed.ts");
+        let missing = tmp.path().join("missing.ts");
+        std::fs::write(&current, "same").unwrap();
+        std::fs::write(&changed, "old").unwrap();
+
+        let writer = CheckWriter::new();
+        writer.create_dir_all(tmp.path()).unwrap();
+        writer.write_file(&current, "same").unwrap();
+        writer.write_file(&changed, "new").unwrap();
+        writer.write_file(&missing, "new").unwrap();
+
+        assert!(writer.is_dry_run());
+        assert_eq!(writer.stale_files(), vec![changed.clone(), missing.clone()]);
+        // Nothing is written
+        assert_eq!(std::fs::read_to_string(&changed).unwrap(), "old");
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn test_check_writer_keeps_last_write_per_file() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("schema.graphql");
+        std::fs::write(&path, "type Query").unwrap();
+
+        let writer = CheckWriter::new();
+        writer.write_file(&path, "draft").unwrap();
+        writer.write_file(&path, "type Query").unwrap();
+        assert!(writer.stale_files().is_empty());
+    }
+
     // ── DryRunWriter tests ──────────────────────────────────────────────
 
     #[test]
@@ -257,8 +968,65 @@ mod tests {
     }
 
     #[test]
-    fn test_print_simple_diff() {
+    fn test_print_diff() {
         // Should not panic — just prints to stdout
-        print_simple_diff("line1\nline2\n", "line1\nline2\nline3\n");
+        print_diff(&diff::unified_diff(
+            Path::new("f.txt"),
+            Some("line1\nline2\n"),
+            "line1\nline3\n",
+        ));
+    }
+
+    #[test]
+    fn test_dry_run_writer_patch() {
+        let writer = DryRunWriter::with_options(DryRunMode::Summary, DryRunFormat::Patch);
+        writer
+            .write_file(Path::new("./src/new.rs"), "fn a() {}\n")
+            .unwrap();
+        writer
+            .update_file(Path::new("src/lib.rs"), "a\nb\n", "a\nc\n")
+            .unwrap();
+        writer
+            .update_file(Path::new("src/lib.rs"), "a\nc\n", "a\nc\nd\n")
+            .unwrap();
+
+        let patch = writer.patch();
+        assert!(patch.starts_with("diff --git a/src/new.rs b/src/new.rs\nnew file mode 100644\n"));
+        assert!(patch.contains("diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n"));
+        // Repeated updates collapse into one diff against the first original
+        assert_eq!(patch.matches("diff --git a/src/lib.rs").count(), 1);
+        assert!(patch.contains("-b\n+c\n+d\n"));
+    }
+
+    #[test]
+    fn test_dry_run_writer_merges_two_updates_from_disk() {
+        let writer = DryRunWriter::with_options(DryRunMode::Summary, DryRunFormat::Patch);
+        let file = Path::new("src/module.rs");
+        // Both callers read the file from disk, not the recorded content
+        writer
+            .update_file(file, "a\nb\nc\n", "a\nentity\nb\nc\n")
+            .unwrap();
+        writer
+            .update_file(file, "a\nb\nc\n", "a\nb\nc\npack\n")
+            .unwrap();
+        assert_eq!(writer.read_file(file).unwrap(), "a\nentity\nb\nc\npack\n");
+
+        let err = writer
+            .update_file(file, "a\nb\nc\n", "a\nother\nb\nc\n")
+            .unwrap_err();
+        assert!(err.to_string().contains("edited twice"));
+    }
+
+    #[test]
+    fn test_patch_path_relative_to_cwd() {
+        let cwd = Path::new("/work/project");
+        assert_eq!(
+            patch_path(Path::new("/work/project/src/a.rs"), cwd),
+            PathBuf::from("src/a.rs")
+        );
+        assert_eq!(
+            patch_path(Path::new("./my-api/Cargo.toml"), cwd),
+            PathBuf::from("my-api/Cargo.toml")
+        );
     }
 }
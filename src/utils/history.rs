@@ -0,0 +1,422 @@
+//! Command history journal stored under `.this/history/`.
+//!
+//! Every committed `TransactionWriter` records one journal entry holding the
+//! before/after content of each file it wrote. `this undo` replays the latest
+//! entry backwards.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// Location of the journal, relative to the project (or workspace) root
+pub const HISTORY_DIR: &str = ".this/history";
+
+/// Number of journal entries kept; older ones are pruned on record
+const MAX_ENTRIES: usize = 50;
+
+/// One committed command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Sortable identifier (also the journal file stem)
+    pub id: String,
+    /// Command that produced the changes (e.g. "add entity product")
+    pub command: String,
+    /// Unix timestamp (seconds)
+    pub timestamp: u64,
+    /// Files written by the command, relative to the history root when possible
+    pub files: Vec<JournalFile>,
+    /// Directories created by the command (removed on undo when empty)
+    #[serde(default)]
+    pub dirs_created: Vec<PathBuf>,
+}
+
+/// Before/after content of a single file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalFile {
+    pub path: PathBuf,
+    /// Content before the command (`None` if the command created the file)
+    pub before: Option<String>,
+    /// Content written by the command
+    pub after: String,
+}
+
+/// What `undo` did (or would do, in dry-run mode)
+#[derive(Debug, Serialize)]
+pub struct UndoReport {
+    pub command: String,
+    pub id: String,
+    pub restored: Vec<String>,
+    pub removed: Vec<String>,
+    pub dirs_removed: Vec<String>,
+}
+
+impl JournalEntry {
+    /// Build an entry stamped with the current time
+    pub fn new(command: &str, files: Vec<JournalFile>, dirs_created: Vec<PathBuf>) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            id: format!("{}-{:09}", now.as_secs(), now.subsec_nanos()),
+            command: command.to_string(),
+            timestamp: now.as_secs(),
+            files,
+            dirs_created,
+        }
+    }
+}
+
+/// Choose the directory that owns the journal for a set of touched paths.
+///
+/// Prefers the workspace or project root around `cwd` when it contains every
+/// touched path; otherwise falls back to the deepest common ancestor of the
+/// paths (e.g. the new project directory for `this init`).
+pub fn history_root(touched: &[PathBuf], cwd: &Path) -> PathBuf {
+    let touched: Vec<PathBuf> = touched.iter().map(|p| absolute(p, cwd)).collect();
+
+    let candidates = [
+        super::project::find_workspace_root_from(cwd),
+        super::project::detect_project_root_from(cwd).ok(),
+    ];
+    for root in candidates.into_iter().flatten() {
+        if touched.iter().all(|p| p.starts_with(&root)) {
+            return root;
+        }
+    }
+
+    common_ancestor(&touched).unwrap_or_else(|| cwd.to_path_buf())
+}
+
+/// Find the nearest `.this/history` directory walking up from `start`
+pub fn find_history_root(start: &Path) -> Option<PathBuf> {
+    let mut current = start.to_path_buf();
+    loop {
+        if current.join(HISTORY_DIR).is_dir() {
+            return Some(current);
+        }
+        if !current.pop() {
+            return None;
+        }
+    }
+}
+
+/// Write a journal entry and prune old ones. Returns the journal file path.
+pub fn record(root: &Path, entry: &JournalEntry) -> Result<PathBuf> {
+    let dir = root.join(HISTORY_DIR);
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create history directory: {}", dir.display()))?;
+
+    let path = dir.join(format!("{}.json", entry.id));
+    let json = serde_json::to_string_pretty(entry)?;
+    std::fs::write(&path, json)
+        .with_context(|| format!("Failed to write journal: {}", path.display()))?;
+
+    let entries = list(root)?;
+    if entries.len() > MAX_ENTRIES {
+        for (old, _) in &entries[..entries.len() - MAX_ENTRIES] {
+            let _ = std::fs::remove_file(old);
+        }
+    }
+
+    Ok(path)
+}
+
+/// All journal entries under `root`, oldest first
+pub fn list(root: &Path) -> Result<Vec<(PathBuf, JournalEntry)>> {
+    let dir = root.join(HISTORY_DIR);
+    let Ok(read_dir) = std::fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut paths: Vec<PathBuf> = read_dir
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read journal: {}", path.display()))?;
+            let entry: JournalEntry = serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse journal: {}", path.display()))?;
+            Ok((path, entry))
+        })
+        .collect()
+}
+
+/// Undo the most recent journal entry under `root`.
+///
+/// Refuses when a file was changed after the command wrote it, unless `force`.
+/// With `dry_run`, reports what would be restored without touching anything.
+pub fn undo_latest(root: &Path, force: bool, dry_run: bool) -> Result<UndoReport> {
+    let Some((journal_path, entry)) = list(root)?.pop() else {
+        bail!("Nothing to undo — no history found in {}", root.display());
+    };
+
+    if !force {
+        let changed: Vec<String> = entry
+            .files
+            .iter()
+            .filter(|f| {
+                std::fs::read_to_string(root.join(&f.path)).ok().as_deref() != Some(&f.after)
+            })
+            .map(|f| f.path.display().to_string())
+            .collect();
+        if !changed.is_empty() {
+            bail!(
+                "Cannot undo '{}': these files changed after it ran:\n  {}\nRe-run with --force to restore them anyway.",
+                entry.command,
+                changed.join("\n  ")
+            );
+        }
+    }
+
+    let mut report = UndoReport {
+        command: entry.command.clone(),
+        id: entry.id.clone(),
+        restored: Vec::new(),
+        removed: Vec::new(),
+        dirs_removed: Vec::new(),
+    };
+
+    for file in &entry.files {
+        let path = root.join(&file.path);
+        match &file.before {
+            Some(before) => {
+                if !dry_run {
+                    std::fs::write(&path, before)
+                        .with_context(|| format!("Failed to restore: {}", path.display()))?;
+                }
+                report.restored.push(file.path.display().to_string());
+            }
+            None => {
+                if !dry_run && path.exists() {
+                    std::fs::remove_file(&path)
+                        .with_context(|| format!("Failed to remove: {}", path.display()))?;
+                }
+                report.removed.push(file.path.display().to_string());
+            }
+        }
+    }
+
+    if dry_run {
+        report.dirs_removed = entry
+            .dirs_created
+            .iter()
+            .rev()
+            .map(|d| d.display().to_string())
+            .collect();
+        return Ok(report);
+    }
+
+    std::fs::remove_file(&journal_path)
+        .with_context(|| format!("Failed to remove journal: {}", journal_path.display()))?;
+
+    // Drop the history directory itself if this was the last entry
+    let history_dir = root.join(HISTORY_DIR);
+    let _ = std::fs::remove_dir(&history_dir);
+    if let Some(parent) = history_dir.parent() {
+        let _ = std::fs::remove_dir(parent);
+    }
+
+    // Deepest directories first; only empty ones are removed
+    for dir in entry.dirs_created.iter().rev() {
+        if std::fs::remove_dir(root.join(dir)).is_ok() {
+            report.dirs_removed.push(dir.display().to_string());
+        }
+    }
+
+    Ok(report)
+}
+
+/// Express `path` relative to `root` when it lives under it, absolute otherwise
+pub fn relative_to(path: &Path, root: &Path, cwd: &Path) -> PathBuf {
+    let abs = absolute(path, cwd);
+    abs.strip_prefix(root).map(Path::to_path_buf).unwrap_or(abs)
+}
+
+fn absolute(path: &Path, cwd: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        cwd.join(path)
+    }
+}
+
+fn common_ancestor(paths: &[PathBuf]) -> Option<PathBuf> {
+    let first = paths.first()?;
+    let mut ancestor = first.parent()?.to_path_buf();
+    for path in &paths[1..] {
+        while !path.starts_with(&ancestor) {
+            if !ancestor.pop() {
+                return None;
+            }
+        }
+    }
+    Some(ancestor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(files: Vec<JournalFile>) -> JournalEntry {
+        JournalEntry::new("add entity product", files, vec![])
+    }
+
+    #[test]
+    fn test_record_and_list() {
+        let tmp = TempDir::new().unwrap();
+        let path = record(tmp.path(), &entry(vec![])).unwrap();
+        assert!(path.starts_with(tmp.path().join(HISTORY_DIR)));
+
+        let entries = list(tmp.path()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].1.command, "add entity product");
+    }
+
+    #[test]
+    fn test_record_prunes_old_entries() {
+        let tmp = TempDir::new().unwrap();
+        for i in 0..MAX_ENTRIES + 3 {
+            let mut e = entry(vec![]);
+            e.id = format!("{:05}", i);
+            record(tmp.path(), &e).unwrap();
+        }
+        let entries = list(tmp.path()).unwrap();
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries[0].1.id, "00003");
+    }
+
+    #[test]
+    fn test_undo_restores_and_removes() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::write(root.join("existing.rs"), "after\n").unwrap();
+        std::fs::create_dir_all(root.join("new_dir")).unwrap();
+        std::fs::write(root.join("new_dir/created.rs"), "created\n").unwrap();
+
+        let mut e = entry(vec![
+            JournalFile {
+                path: PathBuf::from("existing.rs"),
+                before: Some("before\n".to_string()),
+                after: "after\n".to_string(),
+            },
+            JournalFile {
+                path: PathBuf::from("new_dir/created.rs"),
+                before: None,
+                after: "created\n".to_string(),
+            },
+        ]);
+        e.dirs_created = vec![PathBuf::from("new_dir")];
+        record(root, &e).unwrap();
+
+        let report = undo_latest(root, false, false).unwrap();
+        assert_eq!(report.restored, vec!["existing.rs"]);
+        assert_eq!(report.removed, vec!["new_dir/created.rs"]);
+        assert_eq!(report.dirs_removed, vec!["new_dir"]);
+
+        assert_eq!(
+            std::fs::read_to_string(root.join("existing.rs")).unwrap(),
+            "before\n"
+        );
+        assert!(!root.join("new_dir").exists());
+        assert!(
+            !root.join(".this").exists(),
+            "empty history should be cleaned up"
+        );
+    }
+
+    #[test]
+    fn test_undo_refuses_when_file_changed_since() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::write(root.join("f.rs"), "user edit\n").unwrap();
+        record(
+            root,
+            &entry(vec![JournalFile {
+                path: PathBuf::from("f.rs"),
+                before: Some("before\n".to_string()),
+                after: "after\n".to_string(),
+            }]),
+        )
+        .unwrap();
+
+        let err = undo_latest(root, false, false).unwrap_err().to_string();
+        assert!(err.contains("f.rs"), "got: {}", err);
+        assert!(err.contains("--force"));
+
+        undo_latest(root, true, false).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("f.rs")).unwrap(),
+            "before\n"
+        );
+    }
+
+    #[test]
+    fn test_undo_dry_run_changes_nothing() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        std::fs::write(root.join("f.rs"), "after\n").unwrap();
+        record(
+            root,
+            &entry(vec![JournalFile {
+                path: PathBuf::from("f.rs"),
+                before: Some("before\n".to_string()),
+                after: "after\n".to_string(),
+            }]),
+        )
+        .unwrap();
+
+        let report = undo_latest(root, false, true).unwrap();
+        assert_eq!(report.restored, vec!["f.rs"]);
+        assert_eq!(
+            std::fs::read_to_string(root.join("f.rs")).unwrap(),
+            "after\n"
+        );
+        assert_eq!(list(root).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_undo_without_history() {
+        let tmp = TempDir::new().unwrap();
+        assert!(undo_latest(tmp.path(), false, false).is_err());
+    }
+
+    #[test]
+    fn test_history_root_falls_back_to_common_ancestor() {
+        let tmp = TempDir::new().unwrap();
+        let project = tmp.path().join("new-project");
+        let touched = vec![project.join("Cargo.toml"), project.join("src/main.rs")];
+        assert_eq!(history_root(&touched, tmp.path()), project);
+    }
+
+    #[test]
+    fn test_find_history_root_walks_up() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join(HISTORY_DIR)).unwrap();
+        std::fs::create_dir_all(tmp.path().join("src/entities")).unwrap();
+        assert_eq!(
+            find_history_root(&tmp.path().join("src/entities")),
+            Some(tmp.path().to_path_buf())
+        );
+    }
+
+    #[test]
+    fn test_relative_to() {
+        let root = Path::new("/proj");
+        assert_eq!(
+            relative_to(Path::new("/proj/src/a.rs"), root, root),
+            PathBuf::from("src/a.rs")
+        );
+        assert_eq!(
+            relative_to(Path::new("/elsewhere/b.rs"), root, root),
+            PathBuf::from("/elsewhere/b.rs")
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use anyhow::Result;
 
+use super::ast::Slot;
+
 /// Insert a line after a marker comment in the content.
 /// The marker is a comment like `// [this:store_fields]`.
 /// The new line is inserted on the next line after the marker, with the same indentation.
@@ -27,6 +29,17 @@ pub fn insert_after_marker(content: &str, marker: &str, line: &str) -> Result<St
     Ok(result)
 }
 
+/// Insert several lines after a marker, keeping their order.
+pub fn insert_lines_after_marker(content: &str, marker: &str, lines: &[String]) -> Result<String> {
+    let mut updated = content.to_string();
+    let mut anchor = marker;
+    for line in lines {
+        updated = insert_after_marker(&updated, anchor, line)?;
+        anchor = line;
+    }
+    Ok(updated)
+}
+
 /// Check if a line already exists after a marker (for idempotence).
 /// Searches all lines between the marker and the end of the file (or next marker).
 pub fn has_line_after_marker(content: &str, marker: &str, needle: &str) -> bool {
@@ -75,6 +88,227 @@ pub fn add_import(content: &str, import_line: &str) -> String {
     result
 }
 
+/// A generator marker and where it belongs in a file.
+///
+/// `anchors` are matched in order against successive lines; the marker
+/// goes right after the line matching the last anchor, one level deeper.
+pub struct MarkerSpec {
+    pub marker: &'static str,
+    pub anchors: &'static [&'static str],
+}
+
+/// Markers used when registering entities in src/module.rs
+pub const MODULE_RS_MARKERS: &[MarkerSpec] = &[
+    MarkerSpec {
+        marker: "[this:entity_types]",
+        anchors: &["fn entity_types(", "vec!["],
+    },
+    MarkerSpec {
+        marker: "[this:register_entities]",
+        anchors: &["fn register_entities("],
+    },
+    MarkerSpec {
+        marker: "[this:entity_fetcher]",
+        anchors: &["fn get_entity_fetcher(", "match "],
+    },
+    MarkerSpec {
+        marker: "[this:entity_creator]",
+        anchors: &["fn get_entity_creator(", "match "],
+    },
+];
+
+/// Markers used when adding in-memory stores to src/stores.rs
+pub const STORES_RS_MARKERS: &[MarkerSpec] = &[
+    MarkerSpec {
+        marker: "[this:store_fields]",
+        anchors: &["pub struct "],
+    },
+    MarkerSpec {
+        marker: "[this:store_init_vars]",
+        anchors: &["fn new_in_memory("],
+    },
+    MarkerSpec {
+        marker: "[this:store_init_fields]",
+        anchors: &["fn new_in_memory(", "Self {"],
+    },
+];
+
+/// A generator marker, the slot it opens and the commands that insert code at it
+pub struct Marker {
+    pub file: &'static str,
+    pub marker: &'static str,
+    pub slot: Slot<'static>,
+    pub used_by: &'static str,
+}
+
+/// Files whose markers `this markers` inspects
+pub const MARKER_FILES: &[&str] = &["src/module.rs", "src/stores.rs", "src/main.rs"];
+
+/// Every marker scaffolded by `this init`
+pub const MARKERS: &[Marker] = &[
+    Marker {
+        file: "src/module.rs",
+        marker: "[this:module_imports]",
+        slot: Slot::Imports,
+        used_by: "",
+    },
+    Marker {
+        file: "src/module.rs",
+        marker: "[this:entity_types]",
+        slot: Slot::EntityTypes,
+        used_by: "add entity, doctor --fix",
+    },
+    Marker {
+        file: "src/module.rs",
+        marker: "[this:register_entities]",
+        slot: Slot::Registrations,
+        used_by: "add entity, doctor --fix",
+    },
+    Marker {
+        file: "src/module.rs",
+        marker: "[this:entity_fetcher]",
+        slot: Slot::MatchArms("get_entity_fetcher"),
+        used_by: "add entity, doctor --fix",
+    },
+    Marker {
+        file: "src/module.rs",
+        marker: "[this:entity_creator]",
+        slot: Slot::MatchArms("get_entity_creator"),
+        used_by: "add entity, doctor --fix",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_fields]",
+        slot: Slot::StoreFields,
+        used_by: "add entity, doctor --fix",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_init_vars]",
+        slot: Slot::ConstructorVars("new_in_memory"),
+        used_by: "add entity, doctor --fix",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_init_fields]",
+        slot: Slot::ConstructorFields("new_in_memory"),
+        used_by: "add entity, doctor --fix",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_pg_init_vars]",
+        slot: Slot::ConstructorVars("new_postgres"),
+        used_by: "add entity --backend postgres",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_pg_init_fields]",
+        slot: Slot::ConstructorFields("new_postgres"),
+        used_by: "add entity --backend postgres",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_mongo_init_vars]",
+        slot: Slot::ConstructorVars("new_mongodb"),
+        used_by: "add entity --backend mongodb",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_mongo_init_fields]",
+        slot: Slot::ConstructorFields("new_mongodb"),
+        used_by: "add entity --backend mongodb",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_neo4j_init_vars]",
+        slot: Slot::ConstructorVars("new_neo4j"),
+        used_by: "add entity --backend neo4j",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_neo4j_init_fields]",
+        slot: Slot::ConstructorFields("new_neo4j"),
+        used_by: "add entity --backend neo4j",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_scylla_init_vars]",
+        slot: Slot::ConstructorVars("new_scylladb"),
+        used_by: "add entity --backend scylladb",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_scylla_init_fields]",
+        slot: Slot::ConstructorFields("new_scylladb"),
+        used_by: "add entity --backend scylladb",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_mysql_init_vars]",
+        slot: Slot::ConstructorVars("new_mysql"),
+        used_by: "add entity --backend mysql",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_mysql_init_fields]",
+        slot: Slot::ConstructorFields("new_mysql"),
+        used_by: "add entity --backend mysql",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_lmdb_init_vars]",
+        slot: Slot::ConstructorVars("new_lmdb"),
+        used_by: "add entity --backend lmdb",
+    },
+    Marker {
+        file: "src/stores.rs",
+        marker: "[this:store_lmdb_init_fields]",
+        slot: Slot::ConstructorFields("new_lmdb"),
+        used_by: "add entity --backend lmdb",
+    },
+];
+
+/// Re-insert a missing marker where it structurally belongs, falling back to
+/// its textual anchors when the file does not parse.
+///
+/// Returns `None` for unknown markers or if no location can be found.
+pub fn restore(content: &str, marker: &str) -> Option<String> {
+    let entry = MARKERS.iter().find(|m| m.marker == marker)?;
+    super::ast::restore_marker(content, entry.slot, marker).or_else(|| {
+        MODULE_RS_MARKERS
+            .iter()
+            .chain(STORES_RS_MARKERS)
+            .find(|spec| spec.marker == marker)
+            .and_then(|spec| restore_marker(content, spec))
+    })
+}
+
+/// Re-insert a missing marker comment at the position described by `spec`.
+///
+/// Returns `None` if the anchors cannot be found.
+pub fn restore_marker(content: &str, spec: &MarkerSpec) -> Option<String> {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut idx = 0;
+    let mut anchor_idx = None;
+    for anchor in spec.anchors {
+        let pos = lines[idx..].iter().position(|l| l.contains(anchor))? + idx;
+        anchor_idx = Some(pos);
+        idx = pos + 1;
+    }
+    let anchor_idx = anchor_idx?;
+
+    let anchor_line = &lines[anchor_idx];
+    let indent = &anchor_line[..anchor_line.len() - anchor_line.trim_start().len()];
+    let marker_line = format!("{}    // {}", indent, spec.marker);
+    lines.insert(anchor_idx + 1, marker_line);
+
+    let mut result = lines.join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    Some(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,4 +426,18 @@ mod tests {
         assert!(product_idx > anyhow_idx);
         assert!(product_idx > arc_idx);
     }
+
+    #[test]
+    fn test_restore_marker_after_anchors() {
+        let content = "    fn get_entity_fetcher(&self) {\n        match entity_type {\n            _ => None,\n        }\n    }\n";
+        let restored = restore_marker(content, &MODULE_RS_MARKERS[2]).unwrap();
+        assert!(restored.contains(
+            "match entity_type {\n            // [this:entity_fetcher]\n            _ => None,"
+        ));
+    }
+
+    #[test]
+    fn test_restore_marker_anchor_missing() {
+        assert!(restore_marker("fn main() {}\n", &STORES_RS_MARKERS[0]).is_none());
+    }
 }
@@ -1,5 +1,9 @@
+pub mod ast;
+pub mod diff;
 pub mod file_writer;
+pub mod history;
 pub mod markers;
 pub mod naming;
 pub mod output;
 pub mod project;
+pub mod watch;
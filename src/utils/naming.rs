@@ -60,6 +60,18 @@ pub fn to_pascal_case(s: &str) -> String {
         .collect()
 }
 
+/// Convert a string to camelCase
+/// "product_category" -> "productCategory"
+/// "product" -> "product"
+pub fn to_camel_case(s: &str) -> String {
+    let pascal = to_pascal_case(s);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
 /// Basic English pluralization
 /// "product" -> "products"
 /// "category" -> "categories"
@@ -118,6 +130,13 @@ mod tests {
         assert_eq!(to_pascal_case("stock_item"), "StockItem");
     }
 
+    #[test]
+    fn test_to_camel_case() {
+        assert_eq!(to_camel_case("product"), "product");
+        assert_eq!(to_camel_case("product_category"), "productCategory");
+        assert_eq!(to_camel_case("stock-item"), "stockItem");
+    }
+
     #[test]
     fn test_pluralize() {
         assert_eq!(pluralize("product"), "products");
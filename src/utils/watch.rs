@@ -0,0 +1,103 @@
+//! Polling file watcher used by `this generate client --watch` and `this dev`.
+//!
+//! Changes are detected by comparing snapshots of modification times and
+//! sizes, which works the same on every platform and needs no OS watcher.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// Interval between two snapshots
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Modification time and size of every file under the watched paths
+pub type Snapshot = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+/// Take a snapshot of `paths`: files are recorded as-is, directories
+/// recursively. Missing paths are skipped, so creating one counts as a change.
+pub fn snapshot(paths: &[PathBuf]) -> Snapshot {
+    let mut files = Snapshot::new();
+    for path in paths {
+        collect(path, &mut files);
+    }
+    files
+}
+
+fn collect(path: &Path, files: &mut Snapshot) {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return;
+    };
+    if metadata.is_dir() {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            collect(&entry.path(), files);
+        }
+    } else {
+        files.insert(
+            path.to_path_buf(),
+            (metadata.modified().ok(), metadata.len()),
+        );
+    }
+}
+
+/// Block until a file under `paths` differs from `previous`, then update it.
+///
+/// Returns `false` without waiting further once `running` is cleared (Ctrl+C).
+pub fn wait_for_change(paths: &[PathBuf], previous: &mut Snapshot, running: &AtomicBool) -> bool {
+    while running.load(Ordering::SeqCst) {
+        std::thread::sleep(POLL_INTERVAL);
+        let current = snapshot(paths);
+        if current != *previous {
+            *previous = current;
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_snapshot_walks_directories_and_skips_missing_paths() {
+        let tmp = TempDir::new().unwrap();
+        let entities = tmp.path().join("src/entities/order");
+        std::fs::create_dir_all(&entities).unwrap();
+        std::fs::write(entities.join("model.rs"), "struct Order;").unwrap();
+
+        let paths = vec![
+            tmp.path().join("src/entities"),
+            tmp.path().join("config/links.yaml"),
+        ];
+        let files = snapshot(&paths);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[&entities.join("model.rs")].1, 13);
+    }
+
+    #[test]
+    fn test_snapshot_detects_new_and_modified_files() {
+        let tmp = TempDir::new().unwrap();
+        let links = tmp.path().join("links.yaml");
+        let paths = vec![links.clone()];
+        let before = snapshot(&paths);
+
+        std::fs::write(&links, "links: []\n").unwrap();
+        let created = snapshot(&paths);
+        assert_ne!(before, created);
+
+        std::fs::write(&links, "links:\n  - {}\n").unwrap();
+        assert_ne!(created, snapshot(&paths));
+    }
+
+    #[test]
+    fn test_wait_for_change_stops_when_not_running() {
+        let running = AtomicBool::new(false);
+        let mut previous = Snapshot::new();
+        assert!(!wait_for_change(&[], &mut previous, &running));
+    }
+}
@@ -481,6 +481,83 @@ fn test_add_entity_outside_project_error() {
     assert!(stderr.contains("Not inside a this-rs project"));
 }
 
+// ============================================================================
+// this undo tests
+// ============================================================================
+
+#[test]
+fn test_undo_reverts_add_entity() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+    let module_before = std::fs::read_to_string(project.join("src/module.rs")).unwrap();
+    let stores_before = std::fs::read_to_string(project.join("src/stores.rs")).unwrap();
+
+    let (success, _, _) = run_this(&["add", "entity", "product"], &project);
+    assert!(success);
+    assert!(
+        project.join(".this/history").is_dir(),
+        "journal should be recorded"
+    );
+
+    let (success, stdout, _) = run_this(&["undo", "--list"], &project);
+    assert!(success);
+    assert!(stdout.contains("add entity product"));
+
+    let (success, stdout, stderr) = run_this(&["undo"], &project);
+    assert!(success, "undo should succeed: {}", stderr);
+    assert!(stdout.contains("Undid 'add entity product'"));
+
+    assert!(!project.join("src/entities/product").exists());
+    assert_eq!(
+        std::fs::read_to_string(project.join("src/module.rs")).unwrap(),
+        module_before
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.join("src/stores.rs")).unwrap(),
+        stores_before
+    );
+}
+
+#[test]
+fn test_undo_refuses_after_manual_edit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+
+    run_this(&["add", "entity", "product"], &project);
+    std::fs::write(project.join("src/module.rs"), "// edited by hand\n").unwrap();
+
+    let (success, _, stderr) = run_this(&["undo"], &project);
+    assert!(!success);
+    assert!(stderr.contains("src/module.rs"));
+    assert!(stderr.contains("--force"));
+}
+
+#[test]
+fn test_failed_command_writes_nothing() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+    let links_before = std::fs::read_to_string(project.join("config/links.yaml")).unwrap();
+
+    let stores_before = std::fs::read_to_string(project.join("src/stores.rs")).unwrap();
+
+    // Make module.rs unreadable so the command fails after stores.rs was updated
+    std::fs::remove_file(project.join("src/module.rs")).unwrap();
+    std::fs::create_dir(project.join("src/module.rs")).unwrap();
+
+    let (success, _, _) = run_this(&["add", "entity", "product"], &project);
+    assert!(!success, "add entity should fail on unreadable module.rs");
+
+    assert!(!project.join("src/entities/product").exists());
+    assert_eq!(
+        std::fs::read_to_string(project.join("src/stores.rs")).unwrap(),
+        stores_before
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.join("config/links.yaml")).unwrap(),
+        links_before
+    );
+}
+
 // ============================================================================
 // this add link tests
 // ============================================================================
@@ -733,6 +810,50 @@ fn test_add_entity_updates_module_rs() {
     );
 }
 
+#[test]
+fn test_add_entity_without_markers() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+
+    // Drop every generator marker and collapse the emptied blocks the way rustfmt would
+    for file in ["src/module.rs", "src/stores.rs"] {
+        let path = project.join(file);
+        let content = std::fs::read_to_string(&path).unwrap();
+        let stripped: String = content
+            .lines()
+            .filter(|line| !line.contains("// [this:"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let collapsed = stripped
+            .replace("vec![\n        ]", "vec![]")
+            .replace("EntityRegistry) {\n    }", "EntityRegistry) {}")
+            .replace("{\n    }\n", "{}\n");
+        std::fs::write(&path, collapsed).unwrap();
+    }
+
+    let (success, _, stderr) = run_this(&["add", "entity", "product"], &project);
+    assert!(success, "add entity failed: {}", stderr);
+    run_this(&["add", "entity", "category"], &project);
+
+    let module = std::fs::read_to_string(project.join("src/module.rs")).unwrap();
+    assert!(
+        module.contains("vec![\n            \"product\",\n            \"category\",\n        ]")
+    );
+    assert!(module.contains("fn register_entities(&self, registry: &mut EntityRegistry) {"));
+    assert!(module.contains(
+        "match entity_type {\n            \"product\" => Some(self.stores.products_entity.clone()),\n            \"category\" => Some(self.stores.categories_entity.clone()),\n            _ => None,"
+    ));
+
+    let stores = std::fs::read_to_string(project.join("src/stores.rs")).unwrap();
+    assert_eq!(stores.matches("products_store:").count(), 2);
+    assert_eq!(stores.matches("categories_store:").count(), 2);
+    assert!(stores.contains("let categories = Arc::new(InMemoryCategoryStore::default());"));
+
+    for content in [&module, &stores] {
+        syn::parse_file(content).expect("edited file should still parse");
+    }
+}
+
 #[test]
 fn test_add_entity_updates_links_yaml() {
     let tmp = tempfile::tempdir().unwrap();
@@ -1021,6 +1142,299 @@ fn test_doctor_detects_invalid_link_entity() {
     // The link references order/invoice which only exist in yaml, not as entity dirs
 }
 
+#[test]
+fn test_doctor_fix_registers_orphan_and_restores_marker() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+    run_this(&["add", "entity", "product"], &project);
+
+    // Simulate a hand edit that dropped the declaration and a marker
+    let mod_rs = project.join("src/entities/mod.rs");
+    let declarations = std::fs::read_to_string(&mod_rs).unwrap();
+    std::fs::write(&mod_rs, declarations.replace("pub mod product;\n", "")).unwrap();
+    let module_rs = project.join("src/module.rs");
+    let module = std::fs::read_to_string(&module_rs).unwrap();
+    std::fs::write(
+        &module_rs,
+        module.replace("        // [this:register_entities]\n", ""),
+    )
+    .unwrap();
+
+    let (success, stdout, _) = run_this(&["--dry-run", "doctor", "--fix"], &project);
+    assert!(success);
+    assert!(stdout.contains("Would fix: Declare 'pub mod product;'"));
+    assert!(
+        !std::fs::read_to_string(&mod_rs)
+            .unwrap()
+            .contains("product")
+    );
+
+    let (success, stdout, _) = run_this(&["doctor", "--fix"], &project);
+    assert!(success);
+    assert!(stdout.contains("Fixed: Restore '// [this:register_entities]' marker"));
+    assert!(
+        std::fs::read_to_string(&mod_rs)
+            .unwrap()
+            .contains("pub mod product;")
+    );
+    assert!(
+        std::fs::read_to_string(&module_rs)
+            .unwrap()
+            .contains("// [this:register_entities]")
+    );
+
+    let (success, stdout, _) = run_this(&["doctor"], &project);
+    assert!(success);
+    assert!(!stdout.contains("[fixable]"), "Nothing left to fix");
+}
+
+#[test]
+fn test_doctor_json_format_and_deny_warnings() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+    std::fs::create_dir_all(project.join("src/entities/ghost")).unwrap();
+
+    let (success, stdout, _) = run_this(&["doctor", "--format", "json"], &project);
+    assert!(success, "warnings alone should not fail");
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("valid JSON");
+    let finding = report["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["level"] == "warn")
+        .unwrap();
+    assert_eq!(finding["rule"], "check_entities");
+    assert_eq!(finding["file"], "src/entities/ghost");
+
+    let (success, stdout, stderr) = run_this(
+        &["doctor", "--format", "sarif", "--deny", "warnings"],
+        &project,
+    );
+    assert!(!success, "--deny warnings should fail the run");
+    assert!(stderr.contains("failing finding"));
+    let sarif: serde_json::Value = serde_json::from_str(&stdout).expect("valid SARIF");
+    assert_eq!(sarif["runs"][0]["results"][0]["level"], "error");
+
+    let (success, _, _) = run_this(
+        &["doctor", "--deny", "warnings", "--allow", "check_entities"],
+        &project,
+    );
+    assert!(success, "allowed rule should not fail the run");
+}
+
+#[test]
+fn test_doctor_custom_rules() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+    let (success, _, _) = run_this(
+        &["add", "entity", "order", "--fields", "amount:f64"],
+        &project,
+    );
+    assert!(success);
+    std::fs::create_dir_all(project.join(".this")).unwrap();
+    std::fs::write(
+        project.join(".this/doctor.yaml"),
+        "rules:\n  - id: entities-validated\n    check: entity_validated\n",
+    )
+    .unwrap();
+
+    let (success, stdout, _) = run_this(&["doctor", "--format", "json"], &project);
+    assert!(success, "custom rules default to warnings");
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("valid JSON");
+    let finding = report["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["rule"] == "entities-validated")
+        .expect("custom rule finding");
+    assert_eq!(finding["level"], "warn");
+    assert_eq!(finding["file"], "src/entities/order/model.rs");
+
+    let (success, _, stderr) = run_this(&["doctor", "--deny", "entities-validated"], &project);
+    assert!(!success, "denied custom rule should fail the run");
+    assert!(stderr.contains("failing finding"));
+}
+
+#[test]
+fn test_upgrade_bumps_this_and_restores_scaffolding() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+    let cargo_path = project.join("Cargo.toml");
+    let cargo = std::fs::read_to_string(&cargo_path).unwrap();
+    std::fs::write(
+        &cargo_path,
+        cargo.replace("version = \"0.0.9\"", "version = \"0.0.8\""),
+    )
+    .unwrap();
+    let stores_path = project.join("src/stores.rs");
+    let stores = std::fs::read_to_string(&stores_path).unwrap();
+    let old_stores = stores.replace("        // [this:store_lmdb_init_vars]\n", "");
+    std::fs::write(&stores_path, &old_stores).unwrap();
+
+    let (success, stdout, _) = run_this(&["upgrade", "--dry-run"], &project);
+    assert!(success);
+    assert!(stdout.contains("this-rs 0.0.8 → 0.0.9"));
+    assert!(stdout.contains("generator-markers"));
+    assert_eq!(std::fs::read_to_string(&stores_path).unwrap(), old_stores);
+
+    let (success, _, _) = run_this(&["upgrade"], &project);
+    assert!(success);
+    assert!(
+        std::fs::read_to_string(&cargo_path)
+            .unwrap()
+            .contains("version = \"0.0.9\"")
+    );
+    assert_eq!(std::fs::read_to_string(&stores_path).unwrap(), stores);
+
+    let (success, stdout, _) = run_this(&["upgrade"], &project);
+    assert!(success);
+    assert!(stdout.contains("Already up to date"));
+}
+
+#[test]
+fn test_markers_list_and_restore() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+    run_this(&["add", "entity", "product"], &project);
+
+    let module_path = project.join("src/module.rs");
+    let module = std::fs::read_to_string(&module_path).unwrap();
+    std::fs::write(
+        &module_path,
+        module.replace("            // [this:entity_fetcher]\n", ""),
+    )
+    .unwrap();
+
+    let (success, stdout, _) = run_this(&["markers", "list"], &project);
+    assert!(success);
+    assert!(stdout.contains("[this:entity_fetcher]"));
+    assert!(stdout.contains("missing"));
+    assert!(stdout.contains("src/main.rs"));
+    assert!(stdout.contains("1 missing"));
+
+    let (success, stdout, _) = run_this(&["markers", "restore"], &project);
+    assert!(success);
+    assert!(stdout.contains("[this:entity_fetcher] → src/module.rs"));
+    assert_eq!(std::fs::read_to_string(&module_path).unwrap(), module);
+
+    let (success, stdout, _) = run_this(&["markers", "restore"], &project);
+    assert!(success);
+    assert!(stdout.contains("nothing to restore"));
+}
+
+#[test]
+fn test_templates_eject_overrides_generated_code() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+
+    let (success, stdout, _) = run_this(&["templates", "list"], &project);
+    assert!(success);
+    assert!(stdout.contains("entity/handlers.rs"));
+    assert!(!stdout.contains("overridden by"));
+
+    let (success, _, _) = run_this(&["templates", "eject", "entity/handlers.rs"], &project);
+    assert!(success);
+    let ejected = project.join(".this/templates/entity/handlers.rs.tera");
+    let template = std::fs::read_to_string(&ejected).unwrap();
+    std::fs::write(
+        &ejected,
+        format!(
+            "// Handlers for {{{{ entity_name }}}} — team logging conventions\n{}",
+            template
+        ),
+    )
+    .unwrap();
+
+    let (success, _, stderr) = run_this(&["templates", "eject", "entity/handlers.rs"], &project);
+    assert!(!success);
+    assert!(stderr.contains("--force"));
+
+    let (success, stdout, _) = run_this(&["templates", "list"], &project);
+    assert!(success);
+    assert!(stdout.contains("overridden by"));
+
+    let (success, _, _) = run_this(&["add", "entity", "product"], &project);
+    assert!(success);
+    let handlers =
+        std::fs::read_to_string(project.join("src/entities/product/handlers.rs")).unwrap();
+    assert!(handlers.starts_with("// Handlers for product — team logging conventions\n"));
+}
+
+#[test]
+fn test_template_pack_extends_add_entity_and_add_link() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+    let pack = project.join(".this/templates/packs/events");
+    std::fs::create_dir_all(&pack).unwrap();
+    std::fs::write(
+        pack.join("pack.yaml"),
+        r#"description: Domain events
+entity:
+  files:
+    - template: events.rs.tera
+      path: "src/entities/{{ entity_name }}/events.rs"
+  inserts:
+    - file: src/module.rs
+      marker: "[this:register_entities]"
+      line: "// events: {{ entity_pascal }}Created"
+link:
+  files:
+    - template: link.md.tera
+      path: "docs/links/{{ link_type }}.md"
+  inserts:
+    - file: config/links.yaml
+      marker: "links:"
+      line: '# events: {{ link_type }}'
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        pack.join("events.rs.tera"),
+        "pub struct {{ entity_pascal }}Created {\n{% for field in fields %}    pub {{ field.name }}: {{ field.rust_type }},\n{% endfor %}}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        pack.join("link.md.tera"),
+        "# {{ source_pascal }} -> {{ target_pascal }} ({{ forward }})\n",
+    )
+    .unwrap();
+
+    let (success, stdout, _) = run_this(&["templates", "list"], &project);
+    assert!(success);
+    assert!(stdout.contains("events"));
+    assert!(stdout.contains("Domain events"));
+
+    let (success, _, stderr) = run_this(
+        &["add", "entity", "order", "--fields", "total:f64"],
+        &project,
+    );
+    assert!(success, "add entity failed: {}", stderr);
+    let events = std::fs::read_to_string(project.join("src/entities/order/events.rs")).unwrap();
+    assert_eq!(
+        events,
+        "pub struct OrderCreated {\n    pub total: f64,\n}\n"
+    );
+    let entity_mod = std::fs::read_to_string(project.join("src/entities/order/mod.rs")).unwrap();
+    assert!(entity_mod.contains("pub mod store;\npub mod events;\n"));
+    let module = std::fs::read_to_string(project.join("src/module.rs")).unwrap();
+    assert_eq!(module.matches("// events: OrderCreated").count(), 1);
+    // The pack insert builds on the entity's own module.rs/stores.rs edits
+    assert!(module.contains("use crate::entities::order::descriptor::OrderDescriptor;"));
+    assert!(module.contains("registry.register(Box::new(OrderDescriptor::new_with_creator("));
+    assert!(module.contains("\"order\" => Some(self.stores.orders_entity.clone()),"));
+    let stores = std::fs::read_to_string(project.join("src/stores.rs")).unwrap();
+    assert!(stores.contains("pub orders_store: Arc<dyn OrderStore>,"));
+
+    let (success, _, stderr) = run_this(&["add", "link", "order", "invoice"], &project);
+    assert!(success, "add link failed: {}", stderr);
+    let link_doc = std::fs::read_to_string(project.join("docs/links/has_invoice.md")).unwrap();
+    assert_eq!(link_doc, "# Order -> Invoice (invoices)\n");
+    let links = std::fs::read_to_string(project.join("config/links.yaml")).unwrap();
+    assert_eq!(links.matches("# events: has_invoice").count(), 1);
+    assert!(links.contains("link_type: has_invoice"));
+    assert!(links.contains("forward_route_name: invoices"));
+}
+
 #[test]
 fn test_doctor_workspace_healthy() {
     let tmp = tempfile::tempdir().unwrap();
@@ -1404,6 +1818,75 @@ fn test_dry_run_add_link_no_files_modified() {
     );
 }
 
+#[test]
+fn test_dry_run_shows_unified_diff_for_updates() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+
+    let (success, stdout, _) = run_this(&["--dry-run", "add", "entity", "widget"], &project);
+
+    assert!(success, "dry-run add entity should succeed");
+    assert!(
+        stdout.contains("--- a/src/stores.rs"),
+        "Should show diff header"
+    );
+    assert!(stdout.contains("@@ -"), "Should show hunk headers");
+    assert!(
+        !stdout.contains("+++ b/src/entities/widget/model.rs"),
+        "New files are only previewed with --dry-run=full"
+    );
+
+    let (success, stdout, _) = run_this(&["--dry-run=full", "add", "entity", "widget"], &project);
+    assert!(success, "--dry-run=full should succeed");
+    assert!(stdout.contains("+++ b/src/entities/widget/model.rs"));
+    assert!(stdout.contains("+impl_data_entity!("));
+    assert!(!project.join("src/entities/widget").exists());
+}
+
+#[test]
+fn test_dry_run_patch_applies_with_git() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+
+    let (success, stdout, _) = run_this(
+        &["--dry-run-format", "patch", "add", "entity", "widget"],
+        &project,
+    );
+    assert!(success, "patch dry-run should succeed");
+    assert!(!project.join("src/entities/widget").exists());
+    assert!(stdout.contains("diff --git a/src/entities/widget/mod.rs"));
+    assert!(!stdout.contains("Would create"));
+
+    let patch_file = tmp.path().join("widget.patch");
+    std::fs::write(&patch_file, &stdout).unwrap();
+    let status = Command::new("git")
+        .args(["apply", patch_file.to_str().unwrap()])
+        .current_dir(&project)
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success(), "git apply should accept the patch");
+
+    // The patched tree matches what a real run produces
+    let real_tmp = tempfile::tempdir().unwrap();
+    let real = setup_project(&real_tmp);
+    let (success, _, _) = run_this(&["add", "entity", "widget"], &real);
+    assert!(success);
+    for file in [
+        "src/entities/mod.rs",
+        "src/stores.rs",
+        "src/module.rs",
+        "src/entities/widget/model.rs",
+        "src/entities/widget/handlers.rs",
+    ] {
+        assert_eq!(
+            std::fs::read_to_string(project.join(file)).unwrap(),
+            std::fs::read_to_string(real.join(file)).unwrap(),
+            "{} differs after git apply",
+            file
+        );
+    }
+}
+
 // ============================================================================
 // Reserved fields filtering
 // ============================================================================
@@ -1736,6 +2219,66 @@ fn test_doctor_websocket_incoherent() {
     );
 }
 
+// ──────────────────────────────────────────────────────────────
+// GraphQL support and `this enable` tests
+// ──────────────────────────────────────────────────────────────
+
+#[test]
+fn test_init_graphql() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (success, _, stderr) = run_this(&["init", "gql", "--graphql", "--no-git"], tmp.path());
+    assert!(success, "init --graphql should succeed: {}", stderr);
+
+    let project = tmp.path().join("gql");
+    let cargo_toml = std::fs::read_to_string(project.join("Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains(r#"features = ["graphql"]"#));
+    let main_rs = std::fs::read_to_string(project.join("src/main.rs")).unwrap();
+    assert!(main_rs.contains("GraphQLExposure::build_router(host.clone())"));
+    syn::parse_file(&main_rs).expect("main.rs should parse");
+
+    let (success, stdout, _) = run_this(&["info"], &project);
+    assert!(success);
+    assert!(stdout.contains("GraphQL:   ✓ enabled"));
+}
+
+#[test]
+fn test_enable_retrofits_features() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+
+    let (success, stdout, stderr) = run_this(&["enable", "graphql", "websocket", "auth"], &project);
+    assert!(success, "enable should succeed: {}", stderr);
+    assert!(stdout.contains("graphql — Cargo.toml, src/main.rs"));
+
+    let cargo_toml = std::fs::read_to_string(project.join("Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains(r#"features = ["graphql", "websocket", "wami"]"#));
+    let main_rs = std::fs::read_to_string(project.join("src/main.rs")).unwrap();
+    syn::parse_file(&main_rs).expect("main.rs should parse");
+    assert!(main_rs.contains(".build_host()?"));
+    assert!(main_rs.contains("GraphQLExposure"));
+    assert!(main_rs.contains("WebSocketExposure"));
+    assert!(main_rs.contains(".with_auth_config_file("));
+    assert!(project.join("config/auth.yaml").exists());
+
+    let (success, stdout, _) = run_this(&["doctor"], &project);
+    assert!(success);
+    assert!(stdout.contains("GraphQLExposure configured"));
+    assert!(stdout.contains("WebSocketExposure configured"));
+
+    let (success, stdout, _) = run_this(&["enable", "graphql"], &project);
+    assert!(success);
+    assert!(stdout.contains("already enabled"));
+
+    let (success, _, _) = run_this(&["undo"], &project);
+    assert!(success);
+    assert!(!project.join("config/auth.yaml").exists());
+    assert!(
+        !std::fs::read_to_string(project.join("src/main.rs"))
+            .unwrap()
+            .contains("GraphQLExposure")
+    );
+}
+
 // ──────────────────────────────────────────────────────────────
 // gRPC support tests
 // ──────────────────────────────────────────────────────────────
@@ -2385,3 +2928,404 @@ fn test_init_all_features_combined() {
     assert!(proj_dir.join("config/events.yaml").exists(), "events.yaml");
     assert!(proj_dir.join("config/links.yaml").exists(), "links.yaml");
 }
+
+#[test]
+fn test_generate_client_with_zod_and_react_query() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (success, _, _) = run_this(&["init", "rq-ws", "--workspace", "--no-git"], tmp.path());
+    assert!(success, "workspace init should succeed");
+    let ws_dir = tmp.path().join("rq-ws");
+
+    for (entity, fields) in [("order", "total:f64"), ("invoice", "amount:f64")] {
+        let (success, _, stderr) =
+            run_this(&["add", "entity", entity, "--fields", fields], &ws_dir);
+        assert!(success, "add entity {} should succeed: {}", entity, stderr);
+    }
+    let (success, _, stderr) = run_this(&["add", "link", "order", "invoice"], &ws_dir);
+    assert!(success, "add link should succeed: {}", stderr);
+
+    let (success, stdout, stderr) = run_this(
+        &[
+            "generate",
+            "client",
+            "--with",
+            "zod",
+            "--with",
+            "react-query",
+        ],
+        &ws_dir,
+    );
+    assert!(success, "generate client should succeed: {}", stderr);
+    assert!(stdout.contains("npm install zod @tanstack/react-query"));
+
+    let ts = std::fs::read_to_string(ws_dir.join("api-client.ts")).unwrap();
+    assert!(ts.contains("import { z } from 'zod';"));
+    assert!(ts.contains("export const InvoiceSchema: z.ZodType<Invoice> = z.object({"));
+    assert!(ts.contains("return fetchParsed(z.array(OrderSchema), '/api/orders', options);"));
+    assert!(ts.contains("export const orderKeys = {"));
+    assert!(ts.contains("export function useOrderInvoices(orderId: string) {"));
+    assert!(ts.contains(
+        "    queryClient.invalidateQueries({ queryKey: [...orderKeys.all, 'invoices'] }),"
+    ));
+
+    // Without extras the client stays dependency-free
+    let (success, _, _) = run_this(&["generate", "client"], &ws_dir);
+    assert!(success);
+    let ts = std::fs::read_to_string(ws_dir.join("api-client.ts")).unwrap();
+    assert!(!ts.contains("import "));
+}
+
+#[test]
+fn test_generate_client_rewrites_only_changed_content() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (success, _, _) = run_this(&["init", "sync-ws", "--workspace", "--no-git"], tmp.path());
+    assert!(success, "workspace init should succeed");
+    let ws_dir = tmp.path().join("sync-ws");
+    let (success, _, stderr) = run_this(
+        &["add", "entity", "order", "--fields", "total:f64"],
+        &ws_dir,
+    );
+    assert!(success, "add entity should succeed: {}", stderr);
+
+    let (success, _, _) = run_this(&["generate", "client"], &ws_dir);
+    assert!(success);
+    let (success, stdout, _) = run_this(&["generate", "client"], &ws_dir);
+    assert!(success);
+    assert!(stdout.contains("api-client.ts is up to date"));
+
+    // --watch writes directly to disk, so it cannot preview
+    let (success, _, stderr) = run_this(&["--dry-run", "generate", "client", "--watch"], &ws_dir);
+    assert!(!success);
+    assert!(stderr.contains("--watch cannot be combined with --dry-run"));
+}
+
+#[test]
+fn test_generate_check_fails_on_stale_files() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (success, _, _) = run_this(&["init", "ci-ws", "--workspace", "--no-git"], tmp.path());
+    assert!(success, "workspace init should succeed");
+    let ws_dir = tmp.path().join("ci-ws");
+    let (success, _, stderr) = run_this(
+        &["add", "entity", "order", "--fields", "total:f64"],
+        &ws_dir,
+    );
+    assert!(success, "add entity should succeed: {}", stderr);
+
+    for kind in ["client", "docs"] {
+        let (success, _, stderr) = run_this(&["generate", kind], &ws_dir);
+        assert!(success, "generate {} should succeed: {}", kind, stderr);
+        let (success, stdout, stderr) = run_this(&["generate", kind, "--check"], &ws_dir);
+        assert!(success, "generate {} --check should pass: {}", kind, stderr);
+        assert!(stdout.contains("Generated files are up to date"));
+        // --check writes nothing, so the generators report nothing as generated
+        assert!(!stdout.contains("✅ Generated API"), "{}", stdout);
+    }
+
+    // A new field without regeneration fails CI with a diff
+    let (success, _, stderr) = run_this(
+        &["add", "entity", "invoice", "--fields", "amount:f64"],
+        &ws_dir,
+    );
+    assert!(success, "add entity should succeed: {}", stderr);
+    let before = std::fs::read_to_string(ws_dir.join("api-client.ts")).unwrap();
+
+    let (success, stdout, stderr) = run_this(&["generate", "--check", "client"], &ws_dir);
+    assert!(!success, "stale client should fail the check");
+    assert!(stdout.contains("+export interface Invoice {"));
+    assert!(stderr.contains("1 generated file(s) out of date"));
+    assert_eq!(
+        std::fs::read_to_string(ws_dir.join("api-client.ts")).unwrap(),
+        before,
+        "--check must not write"
+    );
+}
+
+#[test]
+fn test_generate_dry_run_reports_success_and_hints() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (success, _, _) = run_this(&["init", "dry-ws", "--workspace", "--no-git"], tmp.path());
+    assert!(success, "workspace init should succeed");
+    let ws_dir = tmp.path().join("dry-ws");
+    let (success, _, stderr) = run_this(&["add", "entity", "order"], &ws_dir);
+    assert!(success, "add entity should succeed: {}", stderr);
+
+    let (success, stdout, stderr) = run_this(
+        &["--dry-run", "generate", "client", "--with", "zod"],
+        &ws_dir,
+    );
+    assert!(success, "dry-run generate should succeed: {}", stderr);
+    // The dry-run writer lists the file; the generator does not repeat it
+    assert!(stdout.contains("Would create:"), "{}", stdout);
+    assert!(!stdout.contains("📄"), "{}", stdout);
+    assert!(stdout.contains("✅ Generated API client"), "{}", stdout);
+    assert!(stdout.contains("run `npm install zod`"), "{}", stdout);
+    assert!(!ws_dir.join("api-client.ts").exists());
+
+    let (success, stdout, stderr) = run_this(&["--dry-run", "generate", "json-schema"], &ws_dir);
+    assert!(success, "dry-run json-schema should succeed: {}", stderr);
+    assert!(
+        stdout.contains("✅ Generated 3 entity schemas"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("yaml-language-server"), "{}", stdout);
+}
+
+#[test]
+fn test_generate_client_runtime_follows_auth() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (success, _, _) = run_this(
+        &["init", "auth-ws", "--workspace", "--auth", "--no-git"],
+        tmp.path(),
+    );
+    assert!(success, "workspace init with --auth should succeed");
+    let ws_dir = tmp.path().join("auth-ws");
+    let (success, _, stderr) = run_this(
+        &["add", "entity", "order", "--fields", "total:f64"],
+        &ws_dir,
+    );
+    assert!(success, "add entity should succeed: {}", stderr);
+
+    let (success, stdout, stderr) = run_this(&["generate", "client"], &ws_dir);
+    assert!(success, "generate client should succeed: {}", stderr);
+    assert!(stdout.contains("Auth is enabled"));
+
+    let ts = std::fs::read_to_string(ws_dir.join("api-client.ts")).unwrap();
+    assert!(ts.contains("export function createClient(config: ClientConfig = {}): ApiClient {"));
+    assert!(ts.contains("  getToken?: () => MaybePromise<string | null | undefined>;"));
+    assert!(ts.contains("`${baseUrl}/auth/refresh`"));
+    assert!(ts.contains("export async function listOrders(options?: RequestOptions)"));
+
+    // Without config/auth.yaml the runtime has no token handling
+    std::fs::remove_file(ws_dir.join("api/config/auth.yaml")).unwrap();
+    let (success, stdout, _) = run_this(&["generate", "client"], &ws_dir);
+    assert!(success);
+    assert!(!stdout.contains("Auth is enabled"));
+    let ts = std::fs::read_to_string(ws_dir.join("api-client.ts")).unwrap();
+    assert!(ts.contains("export function configureClient(config: ClientConfig): void {"));
+    assert!(!ts.contains("getToken"));
+}
+
+#[test]
+fn test_generate_graphql_from_workspace() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (success, _, _) = run_this(
+        &["init", "gql-ws", "--workspace", "--graphql", "--no-git"],
+        tmp.path(),
+    );
+    assert!(success, "workspace init should succeed");
+    let ws_dir = tmp.path().join("gql-ws");
+
+    for (entity, fields) in [
+        ("product", "sku:String,price:f64"),
+        ("review", "rating:i32"),
+    ] {
+        let (success, _, stderr) =
+            run_this(&["add", "entity", entity, "--fields", fields], &ws_dir);
+        assert!(success, "add entity {} should succeed: {}", entity, stderr);
+    }
+    let (success, _, stderr) = run_this(&["add", "link", "product", "review"], &ws_dir);
+    assert!(success, "add link should succeed: {}", stderr);
+
+    let (success, stdout, stderr) = run_this(&["generate", "graphql"], &ws_dir);
+    assert!(success, "generate graphql should succeed: {}", stderr);
+    assert!(stdout.contains("Generated GraphQL schema and operations"));
+    assert!(!stdout.contains("this enable graphql"));
+
+    let sdl = std::fs::read_to_string(ws_dir.join("graphql/schema.graphql")).unwrap();
+    assert!(sdl.contains("type Product {"));
+    assert!(sdl.contains("  price: Float!\n"));
+    assert!(sdl.contains("  reviews: ReviewConnection!\n"));
+    assert!(sdl.contains("  createReview(input: CreateReviewInput!): Review!\n"));
+
+    let ops = std::fs::read_to_string(ws_dir.join("graphql/operations.ts")).unwrap();
+    assert!(ops.contains("export async function getProductReviews(productId: string)"));
+}
+
+#[test]
+fn test_generate_proto_from_classic_project() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (success, _, _) = run_this(&["init", "proto-shop", "--grpc", "--no-git"], tmp.path());
+    assert!(success, "init --grpc should succeed");
+    let project = tmp.path().join("proto-shop");
+
+    let (success, _, stderr) = run_this(
+        &[
+            "add",
+            "entity",
+            "product",
+            "--fields",
+            "sku:String,price:f64",
+        ],
+        &project,
+    );
+    assert!(success, "add entity should succeed: {}", stderr);
+
+    let (success, stdout, stderr) = run_this(&["generate", "proto"], &project);
+    assert!(success, "generate proto should succeed: {}", stderr);
+    assert!(stdout.contains("Generated proto package proto_shop.v1"));
+    assert!(!stdout.contains("this enable grpc"));
+
+    let proto =
+        std::fs::read_to_string(project.join("proto/proto_shop/v1/proto_shop.proto")).unwrap();
+    assert!(proto.contains("package proto_shop.v1;"));
+    assert!(proto.contains("service ProductService {"));
+    assert!(proto.contains("  rpc CreateProduct(CreateProductRequest) returns (Product);"));
+}
+
+#[test]
+fn test_generate_diagram_formats() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+
+    let (success, _, stderr) = run_this(
+        &[
+            "add",
+            "entity",
+            "product",
+            "--fields",
+            "sku:String,price:f64",
+        ],
+        &project,
+    );
+    assert!(success, "add entity should succeed: {}", stderr);
+    let (success, _, stderr) = run_this(
+        &[
+            "add",
+            "entity",
+            "invoice",
+            "--fields",
+            "total:f64",
+            "--backend",
+            "postgres",
+        ],
+        &project,
+    );
+    assert!(success, "add entity should succeed: {}", stderr);
+    let (success, _, stderr) = run_this(&["add", "link", "product", "invoice"], &project);
+    assert!(success, "add link should succeed: {}", stderr);
+
+    // Mermaid on stdout, nothing else, so it can be redirected into a doc
+    let (success, stdout, stderr) = run_this(&["generate", "diagram"], &project);
+    assert!(success, "generate diagram should succeed: {}", stderr);
+    assert!(stdout.starts_with("erDiagram\n"));
+    assert!(stdout.contains("        float price\n"));
+    assert!(stdout.contains("Product }o--o{ Invoice : \"has_invoice (invoices / product)\""));
+
+    let (success, stdout, _) = run_this(
+        &[
+            "generate",
+            "diagram",
+            "--format",
+            "dot",
+            "--cluster-by-backend",
+        ],
+        &project,
+    );
+    assert!(success);
+    assert!(stdout.contains("subgraph \"cluster_postgres\""));
+    assert!(stdout.contains("subgraph \"cluster_in-memory\""));
+
+    let (success, stdout, stderr) = run_this(
+        &[
+            "generate",
+            "diagram",
+            "--format",
+            "svg",
+            "--output",
+            "docs/er.svg",
+        ],
+        &project,
+    );
+    assert!(success, "svg output should succeed: {}", stderr);
+    assert!(stdout.contains("Generated diagram"));
+    let svg = std::fs::read_to_string(project.join("docs/er.svg")).unwrap();
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains(">Invoice</text>"));
+}
+
+#[test]
+fn test_generate_docs_markdown_and_html() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+
+    let (success, _, stderr) = run_this(
+        &["add", "entity", "order", "--fields", "total:f64"],
+        &project,
+    );
+    assert!(success, "add entity should succeed: {}", stderr);
+    let (success, _, stderr) = run_this(
+        &["add", "entity", "invoice", "--fields", "amount:f64"],
+        &project,
+    );
+    assert!(success, "add entity should succeed: {}", stderr);
+    let (success, _, stderr) = run_this(&["add", "link", "order", "invoice"], &project);
+    assert!(success, "add link should succeed: {}", stderr);
+
+    let (success, stdout, stderr) = run_this(&["generate", "docs"], &project);
+    assert!(success, "generate docs should succeed: {}", stderr);
+    assert!(stdout.contains("Generated API reference"));
+
+    let docs = project.join("docs/api");
+    let index = std::fs::read_to_string(docs.join("README.md")).unwrap();
+    assert!(index.contains("| [Order](order.md) | `/api/orders` |"));
+    let order = std::fs::read_to_string(docs.join("order.md")).unwrap();
+    assert!(order.contains("| `total` | `f64` | `number` |"));
+    assert!(order.contains("| `GET` | `/api/orders/{id}` | get |"));
+    assert!(order.contains("`GET /api/orders/{id}/invoices` | [Invoice](invoice.md) |"));
+    let invoice = std::fs::read_to_string(docs.join("invoice.md")).unwrap();
+    assert!(invoice.contains("`GET /api/invoices/{id}/order` | [Order](order.md) |"));
+
+    let (success, _, stderr) = run_this(
+        &["generate", "docs", "--format", "html", "--output", "site"],
+        &project,
+    );
+    assert!(success, "html docs should succeed: {}", stderr);
+    let index = std::fs::read_to_string(project.join("site/index.html")).unwrap();
+    assert!(index.contains("<a href=\"invoice.html\">Invoice</a>"));
+    assert!(project.join("site/order.html").exists());
+}
+
+#[test]
+fn test_generate_json_schema_from_validated_entity() {
+    let tmp = tempfile::tempdir().unwrap();
+    let project = setup_project(&tmp);
+
+    let (success, _, stderr) = run_this(
+        &[
+            "add",
+            "entity",
+            "product",
+            "--fields",
+            "sku:String,price:f64",
+            "--validated",
+        ],
+        &project,
+    );
+    assert!(success, "add entity should succeed: {}", stderr);
+
+    let (success, stdout, stderr) = run_this(&["generate", "json-schema"], &project);
+    assert!(success, "generate json-schema should succeed: {}", stderr);
+    assert!(stdout.contains("Generated 3 entity schemas and 4 config schemas"));
+
+    let schemas = project.join("schemas");
+    let create: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(schemas.join("entities/product.create.schema.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(create["properties"]["price"]["exclusiveMinimum"], 0);
+    assert_eq!(
+        create["properties"]["status"]["enum"],
+        serde_json::json!(["active", "inactive"])
+    );
+    assert_eq!(
+        create["required"],
+        serde_json::json!(["sku", "price", "status"])
+    );
+
+    let links: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(schemas.join("config/links.schema.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(links["title"], "links.yaml");
+}
@@ -105,7 +105,7 @@ fn test_mcp_tools_list() {
     assert_eq!(resp["id"], 2);
 
     let tools = resp["result"]["tools"].as_array().unwrap();
-    assert_eq!(tools.len(), 11);
+    assert_eq!(tools.len(), 12);
 
     let tool_names: Vec<&str> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
     assert!(tool_names.contains(&"init_project"));
@@ -773,3 +773,98 @@ fn test_mcp_init_project_grpc_websocket() {
         main_rs
     );
 }
+
+// ============================================================================
+// Streamable HTTP transport tests
+// ============================================================================
+
+/// Send a raw HTTP request and return (status, headers, body)
+fn http_request(
+    port: u16,
+    method: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> (u16, String, String) {
+    use std::io::Read;
+
+    let mut stream = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+    let mut request = format!(
+        "{} /mcp HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Length: {}\r\n",
+        method,
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream.write_all(request.as_bytes()).unwrap();
+
+    let mut raw = String::new();
+    stream.read_to_string(&mut raw).unwrap();
+    let (head, body) = raw.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, head.to_string(), body.to_string())
+}
+
+#[test]
+fn test_mcp_http_session_flow() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let mut child = Command::new(this_bin())
+        .args(["mcp", "--http", &port.to_string(), "--token", "t0ken"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to spawn this mcp --http");
+
+    // Wait for the listener to come up
+    let mut ready = false;
+    for _ in 0..50 {
+        if std::net::TcpStream::connect(("127.0.0.1", port)).is_ok() {
+            ready = true;
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert!(ready, "HTTP server did not start");
+
+    let auth = ("Authorization", "Bearer t0ken");
+    let accept = ("Accept", "application/json, text/event-stream");
+
+    // Missing token is rejected
+    let (status, _, _) = http_request(port, "POST", &[accept], &initialize_msg());
+    assert_eq!(status, 401);
+
+    // initialize opens a session
+    let (status, head, body) = http_request(port, "POST", &[auth, accept], &initialize_msg());
+    assert_eq!(status, 200);
+    let resp: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(resp["result"]["serverInfo"]["name"], "this-cli");
+    let session = head
+        .lines()
+        .find_map(|l| l.strip_prefix("Mcp-Session-Id: "))
+        .expect("Mcp-Session-Id header")
+        .to_string();
+
+    // tools/list within the session
+    let session_header = ("Mcp-Session-Id", session.as_str());
+    let list = json_rpc("tools/list", None, 2);
+    let (status, _, body) = http_request(port, "POST", &[auth, accept, session_header], &list);
+    assert_eq!(status, 200);
+    let resp: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(resp["result"]["tools"].as_array().unwrap().len(), 12);
+
+    // DELETE ends the session; later requests get 404
+    let (status, _, _) = http_request(port, "DELETE", &[auth, session_header], "");
+    assert_eq!(status, 200);
+    let (status, _, _) = http_request(port, "POST", &[auth, accept, session_header], &list);
+    assert_eq!(status, 404);
+
+    child.kill().ok();
+    child.wait().ok();
+}